@@ -4,6 +4,7 @@
 use crate::prelude::*;
 
 use crate::control::callback;
+use crate::frp;
 use crate::system::web;
 
 use wasm_bindgen::prelude::Closure;
@@ -297,4 +298,11 @@ impl DynamicLoop {
     pub fn on_after_frame<F:DynamicLoopCallback>(&self, callback:F) -> callback::Handle {
         self.data.borrow_mut().on_after_frame.add(Box::new(callback))
     }
+
+    /// Tick the FRP clock with the local time of every frame, before running the callbacks
+    /// registered with `on_frame`. The clock drives the time-based FRP nodes, like `debounce`.
+    pub fn drive_frp_clock(&self, clock:&frp::time::Clock) -> callback::Handle {
+        let clock = clock.clone_ref();
+        self.on_before_frame(move |time:TimeInfo| clock.tick(time.local))
+    }
 }
@@ -1,6 +1,7 @@
 //! Application top-level structure definition. Handles views, keyboard shortcuts and more.
 
 pub mod command;
+pub mod command_palette;
 pub mod shortcut;
 pub mod view;
 
@@ -8,6 +9,7 @@ pub use view::View;
 
 use crate::prelude::*;
 
+use crate::control::callback;
 use crate::display;
 use crate::display::world::World;
 use crate::display::style::theme;
@@ -29,6 +31,7 @@ pub struct Application {
     pub shortcuts : shortcut::Registry,
     pub views     : view::Registry,
     pub themes    : theme::Manager,
+    text_editing  : callback::Handle,
 }
 
 impl Application {
@@ -37,10 +40,15 @@ impl Application {
         let logger    = Logger::new("Application");
         let display   = World::new(dom);
         let commands  = command::Registry::create(&logger);
-        let shortcuts = shortcut::Registry::new(&logger, &commands);
+        let shortcuts = shortcut::Registry::new(&logger,&commands,&display.scene().frp.clock);
         let views     = view::Registry::create(&logger,&display,&commands,&shortcuts);
         let themes    = theme::Manager::from(&display.scene().style_sheet);
-        Self {logger,display,commands,shortcuts,views,themes}
+        let context   = shortcuts.context().clone_ref();
+        let focus     = display.text_field_focus_manager();
+        let text_editing = focus.on_focus_change(move |focused| {
+            context.set(shortcut::TEXT_EDITING_FLAG,*focused)
+        });
+        Self {logger,display,commands,shortcuts,views,themes,text_editing}
     }
 }
 
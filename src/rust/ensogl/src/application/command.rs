@@ -122,7 +122,7 @@ pub struct FrpEndpointDefinition<Frp> {
 }
 
 /// A pair of label and caption for a particular FRP endpoint.
-#[derive(Debug)]
+#[derive(Clone,Debug)]
 #[allow(missing_docs)]
 pub struct EndpointDocs {
     pub label   : String,
@@ -157,20 +157,26 @@ impl EndpointDocs {
 
 /// A command registry. Allows registering command `Providers` and corresponding
 /// `ProviderInstance`s. See docs of `Provider` to learn more.
+///
+/// The registry also tracks the focused `ProviderInstance`, which is the target of commands
+/// invoked by the user directly, for example from the command palette.
 #[derive(Debug,Clone,CloneRef)]
 #[allow(missing_docs)]
 pub struct Registry {
-    pub logger    : Logger,
-    pub instances : Rc<RefCell<HashMap<String,Vec<ProviderInstance>>>>,
-    // todo docs
+    pub logger       : Logger,
+    pub instances    : Rc<RefCell<HashMap<String,Vec<ProviderInstance>>>>,
+    pub command_docs : Rc<RefCell<HashMap<String,Vec<EndpointDocs>>>>,
+    pub focused      : Rc<RefCell<Option<(String,frp::WeakNetwork)>>>,
 }
 
 impl Registry {
     /// Constructor.
     pub fn create(logger:impl AnyLogger) -> Self {
-        let logger    = Logger::sub(logger,"views");
-        let instances = default();
-        Self {logger,instances}
+        let logger       = Logger::sub(logger,"views");
+        let instances    = default();
+        let command_docs = default();
+        let focused      = default();
+        Self {logger,instances,command_docs,focused}
     }
 
     /// Registers the command `Provider`.
@@ -181,6 +187,7 @@ impl Registry {
             warning!(&self.logger, "The view '{label}' was already registered.")
         } else {
             self.instances.borrow_mut().insert(label.into(),default());
+            self.command_docs.borrow_mut().insert(label.into(),V::command_api_docs());
         }
     }
 
@@ -219,6 +226,49 @@ impl Registry {
         };
         self.instances.borrow_mut().get_mut(label).unwrap().push(instance);
     }
+
+    /// Marks the given `ProviderInstance` as focused. The focused instance is the target of
+    /// commands emitted by `invoke`.
+    pub fn set_focus<T:Provider>(&self, target:&T) {
+        let label   = T::label().to_string();
+        let network = T::network(target).downgrade();
+        *self.focused.borrow_mut() = Some((label,network));
+    }
+
+    /// Checks if any alive `ProviderInstance` is focused.
+    pub fn has_focus(&self) -> bool {
+        let focused = self.focused.borrow();
+        focused.as_ref().map_or(false,|(_,network)| network.upgrade().is_some())
+    }
+
+    /// Emits the command of the given provider. The command is emitted on the focused instance if
+    /// it is an instance of the provider, or on the most recently registered alive instance
+    /// otherwise. Returns `false` if no instance was able to handle the command.
+    pub fn invoke(&self, provider:&str, command:&str) -> bool {
+        let target = {
+            let focused   = self.focused.borrow();
+            let instances = self.instances.borrow();
+            let alive     = instances.get(provider).into_iter().flatten();
+            let alive     = alive.filter(|instance| instance.check_alive()).collect_vec();
+            let focused   = focused.as_ref().filter(|(label,_)| label == provider);
+            let focused   = focused.and_then(|(_,network)| {
+                alive.iter().find(|instance| instance.network.ptr_eq(network))
+            });
+            let instance  = focused.or_else(|| alive.last());
+            instance.and_then(|instance| instance.command_map.get(command))
+                .map(|endpoint| endpoint.frp.clone_ref())
+        };
+        match target {
+            Some(frp) => {
+                frp.emit(());
+                true
+            }
+            None => {
+                warning!(&self.logger,"Command {command} was not found on {provider}.");
+                false
+            }
+        }
+    }
 }
 
 
@@ -237,6 +287,7 @@ macro_rules! def_status_api {
         ),* $(,)?
     ) => {
         #[derive(Debug,Clone,CloneRef)]
+        #[allow(missing_docs)]
         pub struct $name {
             $(pub $field : frp::Sampler<bool>),*
         }
@@ -263,6 +314,7 @@ macro_rules! def_command_api {
         ),* $(,)?
     ) => {
         #[derive(Debug,Clone,CloneRef)]
+        #[allow(missing_docs)]
         pub struct $name {
             $(pub $field : frp::Source),*
         }
@@ -0,0 +1,451 @@
+//! Command palette: a searchable list of all commands exposed by the registered command
+//! `Provider`s. The selected command is invoked on the focused instance of its provider.
+
+use crate::prelude::*;
+
+use crate::application;
+use crate::application::Application;
+use crate::application::command;
+use crate::application::shortcut;
+use crate::application::shortcut::DefaultShortcutProvider;
+use crate::data::color;
+use crate::display::shape::text::glyph::font;
+use crate::display::shape::text::text_field::TextField;
+use crate::display::shape::text::text_field::TextFieldProperties;
+use crate::display;
+use crate::frp;
+use crate::frp::io::keyboard::Key;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The maximum number of commands displayed at once.
+pub const MAX_VISIBLE_ENTRIES:usize = 10;
+
+/// The height of a single line of the palette, in pixels.
+const LINE_HEIGHT:f32 = 16.0;
+
+/// The width of the palette, in pixels.
+const WIDTH:f32 = 600.0;
+
+/// The shortcut context flag set while the palette is visible.
+pub const OPEN_FLAG:&str = "command_palette_open";
+
+
+
+// =============
+// === Fuzzy ===
+// =============
+
+/// Matches the `pattern` against the `text` fuzzily: all pattern characters must appear in the
+/// text in the same order, ignoring case. Returns `None` if the text does not match, or a score
+/// otherwise. The score is higher when matched characters are adjacent or start words.
+pub fn fuzzy_score(pattern:&str, text:&str) -> Option<usize> {
+    let mut score      = 0;
+    let mut text_chars = text.chars().flat_map(char::to_lowercase).enumerate();
+    let mut previous   = None;
+    let mut prev_char  = None;
+    for pattern_char in pattern.chars().flat_map(char::to_lowercase) {
+        if pattern_char.is_whitespace() {
+            continue;
+        }
+        loop {
+            let (index,text_char) = text_chars.next()?;
+            let is_word_start     = prev_char.map_or(true,|c:char| !c.is_alphanumeric());
+            prev_char             = Some(text_char);
+            if text_char == pattern_char {
+                let is_adjacent = previous.map_or(false,|previous| previous + 1 == index);
+                score    += 1 + if is_adjacent {2} else {0} + if is_word_start {3} else {0};
+                previous  = Some(index);
+                break;
+            }
+        }
+    }
+    Some(score)
+}
+
+
+
+// =============
+// === Entry ===
+// =============
+
+/// A single command listed in the palette.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct Entry {
+    /// Label of the command provider, like `GraphEditor`.
+    pub provider  : String,
+    /// Label of the command, like `toggle_node_inverse_select`.
+    pub command   : String,
+    /// Human readable description of the command.
+    pub caption   : String,
+    /// Shortcuts currently bound to the command.
+    pub shortcuts : Vec<String>,
+}
+
+impl Entry {
+    /// The fuzzy match score of this entry, see `fuzzy_score`. Both the caption and the command
+    /// label are matched, the better result is returned.
+    pub fn score(&self, query:&str) -> Option<usize> {
+        let label = self.command.replace('_'," ");
+        std::cmp::max(fuzzy_score(query,&self.caption),fuzzy_score(query,&label))
+    }
+
+    /// A line describing this entry in the palette.
+    pub fn description(&self) -> String {
+        let shortcuts = self.shortcuts.join(", ");
+        iformat!("{self.caption}  [{self.provider}.{self.command}]  {shortcuts}")
+    }
+}
+
+/// Returns the entries matching the query, best matches first. Entries with equal score keep
+/// their order.
+pub fn filter(entries:&[Entry], query:&str) -> Vec<Entry> {
+    let scored = entries.iter().filter_map(|entry| entry.score(query).map(|score| (score,entry)));
+    let mut scored = scored.collect_vec();
+    scored.sort_by(|(left,_),(right,_)| right.cmp(left));
+    scored.into_iter().map(|(_,entry)| entry.clone()).collect()
+}
+
+
+
+// =============
+// === Model ===
+// =============
+
+/// Internal representation of `CommandPalette`.
+#[derive(Debug)]
+pub struct Model {
+    logger         : Logger,
+    display_object : display::object::Instance,
+    query_field    : TextField,
+    list_field     : TextField,
+    commands       : command::Registry,
+    shortcuts      : shortcut::Registry,
+    all_entries    : RefCell<Vec<Entry>>,
+    entries        : RefCell<Vec<Entry>>,
+    query          : RefCell<String>,
+    selected       : Cell<usize>,
+    visible        : Cell<bool>,
+}
+
+impl Model {
+    fn new(app:&Application, fonts:&mut font::Registry) -> Self {
+        let logger         = Logger::sub(&app.logger,"CommandPalette");
+        let display_object = display::object::Instance::new(&logger);
+        let mut properties = |height| TextFieldProperties {
+            font       : fonts.get_or_load_embedded_font("DejaVuSansMono").unwrap(),
+            text_size  : LINE_HEIGHT,
+            base_color : color::Rgba::new(1.0,1.0,1.0,0.7),
+            size       : Vector2::new(WIDTH,height),
+        };
+        let query_field = TextField::new(&app.display,properties(LINE_HEIGHT));
+        let list_height = LINE_HEIGHT * MAX_VISIBLE_ENTRIES as f32;
+        let list_field  = TextField::new(&app.display,properties(list_height));
+        list_field.set_position(Vector3::new(0.0,-LINE_HEIGHT,0.0));
+        let commands    = app.commands.clone_ref();
+        let shortcuts   = app.shortcuts.clone_ref();
+        let all_entries = default();
+        let entries     = default();
+        let query       = default();
+        let selected    = default();
+        let visible     = default();
+        Self {logger,display_object,query_field,list_field,commands,shortcuts,all_entries,entries
+             ,query,selected,visible}
+    }
+
+    /// Collects entries for all commands of all registered providers, except the palette itself.
+    fn collect_entries(&self) -> Vec<Entry> {
+        let command_docs  = self.commands.command_docs.borrow();
+        let providers     = command_docs.keys().filter(|label| *label != CommandPalette::label());
+        let mut providers = providers.cloned().collect_vec();
+        providers.sort();
+        providers.into_iter().flat_map(|provider| {
+            command_docs[&provider].iter().map(|docs| {
+                let actions   = self.shortcuts.actions_for(&provider,&docs.label);
+                let chords    = self.shortcuts.chords_for(&provider,&docs.label);
+                let actions   = actions.iter().map(ToString::to_string);
+                let shortcuts = actions.chain(chords.iter().map(ToString::to_string)).collect();
+                let command   = docs.label.clone();
+                let caption   = docs.caption.clone();
+                Entry {provider:provider.clone(),command,caption,shortcuts}
+            }).collect_vec()
+        }).collect()
+    }
+
+    /// Filters the entries by the query typed in the palette. The selection is reset only when
+    /// the query has actually changed, so a new line typed when accepting the selected command
+    /// does not affect it.
+    fn update_entries(&self) {
+        let query:String = self.query_field.get_content().lines().collect();
+        if *self.query.borrow() != query {
+            let entries = filter(&self.all_entries.borrow(),&query);
+            *self.entries.borrow_mut() = entries;
+            *self.query.borrow_mut()   = query;
+            self.selected.set(0);
+            self.redraw_list();
+        }
+    }
+
+    fn redraw_list(&self) {
+        let selected = self.selected.get();
+        let first    = (selected + 1).saturating_sub(MAX_VISIBLE_ENTRIES);
+        let entries  = self.entries.borrow();
+        let visible  = entries.iter().enumerate().skip(first).take(MAX_VISIBLE_ENTRIES);
+        let lines    = visible.map(|(index,entry)| {
+            let marker = if index == selected {"> "} else {"  "};
+            iformat!("{marker}{entry.description()}")
+        });
+        self.list_field.set_content(&lines.collect_vec().join("\n"));
+    }
+
+    fn show(&self) {
+        *self.all_entries.borrow_mut() = self.collect_entries();
+        self.query_field.clear_content();
+        self.display_object.add_child(&self.query_field.display_object());
+        self.display_object.add_child(&self.list_field.display_object());
+        self.query_field.set_focus();
+        self.visible.set(true);
+        *self.query.borrow_mut()   = default();
+        *self.entries.borrow_mut() = self.all_entries.borrow().clone();
+        self.selected.set(0);
+        self.redraw_list();
+    }
+
+    fn hide(&self) {
+        self.query_field.clear_content();
+        self.query_field.lose_focus();
+        self.display_object.remove_child(&self.query_field.display_object());
+        self.display_object.remove_child(&self.list_field.display_object());
+        self.visible.set(false);
+    }
+
+    /// Shows the palette if it was hidden and hides it otherwise. Returns the new visibility.
+    fn toggle(&self) -> bool {
+        if self.visible.get() { self.hide() } else { self.show() }
+        self.visible.get()
+    }
+
+    fn select_next(&self) {
+        let count = self.entries.borrow().len();
+        if self.selected.get() + 1 < count {
+            self.selected.set(self.selected.get() + 1);
+            self.redraw_list();
+        }
+    }
+
+    fn select_previous(&self) {
+        if self.selected.get() > 0 {
+            self.selected.set(self.selected.get() - 1);
+            self.redraw_list();
+        }
+    }
+
+    /// Hides the palette and invokes the selected command, if any.
+    fn invoke_selected(&self) {
+        let entry = self.entries.borrow().get(self.selected.get()).cloned();
+        self.hide();
+        match entry {
+            Some(entry) => { self.commands.invoke(&entry.provider,&entry.command); }
+            None        => info!(self.logger,"No command matches the query."),
+        }
+    }
+}
+
+
+
+// ===========
+// === FRP ===
+// ===========
+
+crate::def_command_api! { Commands
+    /// Show the command palette, or hide it if it is visible.
+    toggle,
+    /// Hide the command palette.
+    hide,
+    /// Select the next command on the list.
+    select_next,
+    /// Select the previous command on the list.
+    select_previous,
+    /// Invoke the selected command and hide the command palette.
+    invoke_selected,
+}
+
+crate::def_status_api! { FrpStatus
+    /// Checks whether the command palette is visible.
+    is_visible,
+}
+
+
+
+// ======================
+// === CommandPalette ===
+// ======================
+
+/// The command palette component. It lists commands of all registered command providers together
+/// with their shortcuts and filters them fuzzily by the typed query.
+///
+/// The palette registers itself as a command provider, so it can be opened with a shortcut
+/// (`ctrl shift p` by default). The default shortcuts are removed when the palette is dropped.
+#[derive(Clone,CloneRef,Debug)]
+#[allow(missing_docs)]
+pub struct CommandPalette {
+    model            : Rc<Model>,
+    network          : frp::Network,
+    pub commands     : Commands,
+    pub status       : FrpStatus,
+    shortcut_handles : Rc<Vec<shortcut::Handle>>,
+}
+
+impl CommandPalette {
+    /// Constructor. Registers the palette in the application's command and shortcut registries.
+    pub fn new(app:&Application, fonts:&mut font::Registry) -> Self {
+        let model   = Rc::new(Model::new(app,fonts));
+        let network = frp::Network::new();
+        frp::extend! { network
+            def toggle          = source();
+            def hide            = source();
+            def select_next     = source();
+            def select_previous = source();
+            def invoke_selected = source();
+
+            shown_or_hidden <- toggle.map(f_!(model.toggle()));
+            hidden          <- any(hide,invoke_selected).constant(false);
+            eval_ hide            (model.hide());
+            eval_ invoke_selected (model.invoke_selected());
+            eval_ select_next     (model.select_next());
+            eval_ select_previous (model.select_previous());
+            visibility      <- any(shown_or_hidden,hidden);
+            def is_visible = visibility.sampler();
+        }
+        app.shortcuts.context().bind(&network,OPEN_FLAG,&visibility);
+        let commands = Commands {toggle,hide,select_next,select_previous,invoke_selected};
+        let status   = FrpStatus {is_visible};
+        let shortcut_handles = Rc::new(Self::default_shortcuts().into_iter().map(|shortcut| {
+            &app.shortcuts + shortcut
+        }).collect());
+        let palette = Self {model,network,commands,status,shortcut_handles};
+        app.commands.register::<Self>();
+        app.commands.register_instance(&palette);
+        palette.init()
+    }
+
+    fn init(self) -> Self {
+        let model = Rc::downgrade(&self.model);
+        self.model.query_field.set_text_edit_callback(move |_| {
+            if let Some(model) = model.upgrade() {
+                model.update_entries()
+            }
+        });
+        self
+    }
+
+    /// The entries matching the current query, best matches first.
+    pub fn entries(&self) -> Vec<Entry> {
+        self.model.entries.borrow().clone()
+    }
+}
+
+impl display::Object for CommandPalette {
+    fn display_object(&self) -> &display::object::Instance {
+        &self.model.display_object
+    }
+}
+
+impl command::FrpNetworkProvider for CommandPalette {
+    fn network(&self) -> &frp::Network {
+        &self.network
+    }
+}
+
+impl command::CommandApi for CommandPalette {
+    fn command_api_docs() -> Vec<command::EndpointDocs> {
+        Commands::command_api_docs()
+    }
+
+    fn command_api(&self) -> Vec<command::CommandEndpoint> {
+        self.commands.command_api()
+    }
+}
+
+impl command::StatusApi for CommandPalette {
+    fn status_api_docs() -> Vec<command::EndpointDocs> {
+        FrpStatus::status_api_docs()
+    }
+
+    fn status_api(&self) -> Vec<command::StatusEndpoint> {
+        self.status.status_api()
+    }
+}
+
+impl command::Provider for CommandPalette {
+    fn label() -> &'static str {
+        "CommandPalette"
+    }
+}
+
+impl shortcut::DefaultShortcutProvider for CommandPalette {
+    fn default_shortcuts() -> Vec<shortcut::Shortcut> {
+        use shortcut::Action;
+        use shortcut::Condition;
+        let visible  = || Condition::Simple("is_visible".into());
+        let toggle   = Action::press(&[Key::Control,Key::Shift,Key::Character("p".into())]);
+        let hide     = Action::press(&[Key::Escape]);
+        let next     = Action::press(&[Key::ArrowDown]);
+        let previous = Action::press(&[Key::ArrowUp]);
+        let invoke   = Action::press(&[Key::Enter]);
+        vec! [ Self::self_shortcut      (toggle  ,"toggle")
+             , Self::self_shortcut_when (hide    ,"hide"           ,visible())
+             , Self::self_shortcut_when (next    ,"select_next"    ,visible())
+             , Self::self_shortcut_when (previous,"select_previous",visible())
+             , Self::self_shortcut_when (invoke  ,"invoke_selected",visible())
+             ]
+    }
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(command:&str, caption:&str) -> Entry {
+        let provider  = "GraphEditor".into();
+        let command   = command.into();
+        let caption   = caption.into();
+        let shortcuts = default();
+        Entry {provider,command,caption,shortcuts}
+    }
+
+    #[test]
+    fn fuzzy_matching() {
+        assert!(fuzzy_score("tnis","toggle node inverse select").is_some());
+        assert!(fuzzy_score("TNIS","toggle node inverse select").is_some());
+        assert!(fuzzy_score("select toggle","toggle node inverse select").is_none());
+        assert!(fuzzy_score("","anything").is_some());
+        let word_starts = fuzzy_score("ns","node select").unwrap();
+        let inside_word = fuzzy_score("ns","nodes").unwrap();
+        assert!(word_starts > inside_word);
+    }
+
+    #[test]
+    fn filtering_entries() {
+        let entries = vec!
+            [ entry("add_node"                  ,"Add a new node.")
+            , entry("toggle_node_inverse_select","Inverse the selection of nodes.")
+            , entry("remove_selected_nodes"     ,"Remove the selected nodes.")
+            ];
+        let commands = |query| filter(&entries,query).into_iter().map(|e| e.command).collect_vec();
+        assert_eq!(commands("inverse"), vec!["toggle_node_inverse_select"]);
+        assert_eq!(commands("rem sel")[0], "remove_selected_nodes");
+        assert_eq!(commands("").len(), 3);
+        assert!(commands("xyz").is_empty());
+    }
+}
@@ -1,10 +1,15 @@
 //! Keyboard shortcut management.
 
+pub mod keymap;
+
+pub use keymap::Keymap;
+
 use crate::prelude::*;
 
 use super::command;
 
 use crate::control::io::keyboard::listener::KeyboardFrpBindings;
+use crate::frp::data::bitfield::BitField;
 use crate::frp::io::keyboard::KeyMask;
 use crate::frp::io::keyboard::Keyboard;
 use crate::frp;
@@ -18,19 +23,31 @@ use crate::system::web;
 
 type RuleMap   = HashMap<KeyMask,Vec<WeakHandle>>;
 type ActionMap = HashMap<ActionType,RuleMap>;
+type ChordMap  = HashMap<Vec<KeyMask>,RuleMap>;
+
+/// The default time in milliseconds to wait for the next step of a chord, like `ctrl+c` after
+/// pressing `ctrl+k` in the `ctrl+k ctrl+c` chord.
+pub const DEFAULT_CHORD_TIMEOUT_MS:f64 = 1500.0;
 
 /// Keyboard shortcut registry. You can add new shortcuts by using the `add` method and get a
 /// `Handle` back. When `Handle` is dropped, the shortcut will be lazily removed. This is useful
 /// when defining shortcuts by GUI components. When a component is unloaded, all its default
 /// shortcuts should be removed as well.
 ///
+/// Shortcuts may also be chords, sequences of key presses like `ctrl+k ctrl+c` (see
+/// `Shortcut::after`). While a chord is being typed, the presses are not processed as regular
+/// actions. The chord is cancelled when a key not continuing it is pressed, or when the next step
+/// is not pressed within the chord timeout.
+///
 /// Note: we should probably handle user shortcuts in a slightly different way. User shortcuts
 /// should persist and should probably not return handles. Alternatively, there should be an user
 /// shortcut manager which will own and manage the handles.
 #[derive(Clone,CloneRef,Debug)]
 pub struct Registry {
-    model   : RegistryModel,
-    network : frp::Network,
+    model             : RegistryModel,
+    network           : frp::Network,
+    /// The steps of the chord being typed, if any. Views may use it to display a hint.
+    pub pending_chord : frp::Sampler<Chord>,
 }
 
 /// Internal representation of `Registry`.
@@ -41,6 +58,21 @@ pub struct RegistryModel {
     keyboard_bindings : Rc<KeyboardFrpBindings>,
     command_registry  : command::Registry,
     action_map        : Rc<RefCell<ActionMap>>,
+    chord_map         : Rc<RefCell<ChordMap>>,
+    chord             : Rc<RefCell<ChordState>>,
+    chord_timeout_ms  : Rc<Cell<f64>>,
+    clock             : frp::time::Clock,
+    context           : Context,
+    keymap            : Rc<RefCell<KeymapState>>,
+}
+
+/// The user keymap installed in the `Registry`.
+#[derive(Debug,Default)]
+struct KeymapState {
+    /// Handles of shortcuts defined in the keymap.
+    handles    : Vec<Handle>,
+    /// Commands whose default shortcuts are replaced by the keymap, as (target,command) pairs.
+    overridden : HashSet<(String,String)>,
 }
 
 impl Deref for Registry {
@@ -52,21 +84,29 @@ impl Deref for Registry {
 
 impl RegistryModel {
     /// Constructor.
-    pub fn new(logger:impl AnyLogger, command_registry:&command::Registry) -> Self {
+    pub fn new
+    (logger:impl AnyLogger, command_registry:&command::Registry, clock:&frp::time::Clock) -> Self {
         let logger            = Logger::sub(logger,"ShortcutRegistry");
         let keyboard          = Keyboard::default();
         let keyboard_bindings = Rc::new(KeyboardFrpBindings::new(&logger,&keyboard));
         let command_registry  = command_registry.clone_ref();
         let action_map        = default();
-
-        Self {logger,keyboard,keyboard_bindings,command_registry,action_map}
+        let chord_map         = default();
+        let chord             = default();
+        let chord_timeout_ms  = Rc::new(Cell::new(DEFAULT_CHORD_TIMEOUT_MS));
+        let clock             = clock.clone_ref();
+        let context           = default();
+        let keymap            = default();
+        Self {logger,keyboard,keyboard_bindings,command_registry,action_map,chord_map,chord
+            ,chord_timeout_ms,clock,context,keymap}
     }
 }
 
 impl Registry {
-    /// Constructor.
-    pub fn new(logger:&Logger, command_registry:&command::Registry) -> Self {
-        let model = RegistryModel::new(logger,command_registry);
+    /// Constructor. The chord timeout is measured by the `clock`, usually the scene's one.
+    pub fn new
+    (logger:&Logger, command_registry:&command::Registry, clock:&frp::time::Clock) -> Self {
+        let model = RegistryModel::new(logger,command_registry,clock);
 
         // TODO move to theme configuration.
         let double_press_threshold_ms = 300.0;
@@ -76,7 +116,17 @@ impl Registry {
             nothing_pressed_prev <- nothing_pressed.previous();
             press                <- key_mask.gate_not(&nothing_pressed);
             single_press         <- press.gate(&nothing_pressed_prev);
-            eval press ((m) model.process_action(ActionType::Press,m));
+
+            // The pending chord is checked once per clock tick until it is completed or expired.
+            def chord_poll = source::<()>();
+            chord_changed      <- press.map(f!((m) model.process_press(m))).unwrap();
+            chord_update       <- any_(chord_changed,chord_poll);
+            chord_check        <- chord_update.per_frame(&model.clock);
+            chord_expired      <- chord_check.map(f_!(model.expire_chord())).unwrap();
+            pending_chord      <- any(chord_changed,chord_expired).sampler();
+            eval_ chord_check ([model,chord_poll] if model.is_chord_pending() {
+                chord_poll.emit(())
+            });
 
             single_press_prev  <- single_press.previous();
             press_time         <- single_press.map(|_| web::performance().now());
@@ -92,69 +142,259 @@ impl Registry {
             release            <- prev_key.gate_not(&the_same_key);
             eval release      ((m) model.process_action(ActionType::Release,m));
         }
-        Self {model,network}
+        Self {model,network,pending_chord}
     }
 }
 
 impl RegistryModel {
 
     fn process_action(&self, action_type:ActionType, key_mask:&KeyMask) {
-        let action_map_mut = &mut self.action_map.borrow_mut();
-        if let Some(rule_map) = action_map_mut.get_mut(&action_type) {
-            if let Some(rules) = rule_map.get_mut(key_mask) {
-                self.process_rules(rules)
-            }
+        if self.chord.borrow().consumed == Some(*key_mask) {
+            return
+        }
+        let targets = {
+            let action_map_mut = &mut self.action_map.borrow_mut();
+            let rule_map       = action_map_mut.get_mut(&action_type);
+            let rules          = rule_map.and_then(|rule_map| rule_map.get_mut(key_mask));
+            rules.map(|rules| self.matching_commands(rules)).unwrap_or_default()
+        };
+        for target in targets {
+            target.emit(())
         }
     }
 
-    fn process_rules(&self, rules:&mut Vec<WeakHandle>) {
-        let mut targets = Vec::new();
-        {
-            let borrowed_command_map = self.command_registry.instances.borrow();
-            rules.retain(|weak_rule| {
-                weak_rule.upgrade().map(|rule| {
-                    let target = &rule.target;
-                    borrowed_command_map.get(target).for_each(|commands| {
-                        for command in commands {
-                            if Self::condition_checker(&rule.when,&command.status_map) {
-                                let command_name = &rule.command.name;
-                                match command.command_map.get(command_name){
-                                    Some(t) => targets.push(t.frp.clone_ref()),
-                                    None    => warning!(&self.logger,
-                                        "Command {command_name} was not found on {target}."),
-                                }
-                            }
-                        }
-                    })
-                }).is_some()
-            })
+    /// Handles the key press, which either is a step of a chord or is processed as a regular
+    /// press action. Returns the new chord if the chord state has changed.
+    fn process_press(&self, key_mask:&KeyMask) -> Option<Chord> {
+        let mut targets  = Vec::new();
+        let time         = self.clock.now() as f64;
+        let timeout      = self.chord_timeout_ms.get();
+        let is_prefix    = |steps:&[KeyMask]| self.is_chord_prefix(steps);
+        let try_complete = |prefix:&[KeyMask], key_mask:&KeyMask| {
+            let chord_map = &mut self.chord_map.borrow_mut();
+            let rules     = chord_map.get_mut(prefix).and_then(|map| map.get_mut(key_mask));
+            targets       = rules.map(|rules| self.matching_commands(rules)).unwrap_or_default();
+            !targets.is_empty()
+        };
+        let (step,chord) = {
+            let mut state = self.chord.borrow_mut();
+            let old_chord = state.chord();
+            let step      = state.step(key_mask,time,timeout,try_complete,is_prefix);
+            let chord     = state.chord();
+            (step,(chord != old_chord).as_some(chord))
+        };
+        match step {
+            ChordStep::Ignored   => self.process_action(ActionType::Press,key_mask),
+            ChordStep::Cancelled => {
+                let keys = key_names(key_mask).join("+");
+                info!(self.logger,"Chord cancelled by {keys}.")
+            }
+            _ => {}
         }
         for target in targets {
             target.emit(())
         }
+        chord
     }
 
+    /// Checks if the steps are the beginning of some chord applicable in the current context.
+    fn is_chord_prefix(&self, steps:&[KeyMask]) -> bool {
+        let chord_map = self.chord_map.borrow();
+        let prefixed  = chord_map.iter().filter(|(prefix,_)| prefix.starts_with(steps));
+        prefixed.any(|(_,rule_map)| {
+            rule_map.values().flatten().filter_map(WeakHandle::upgrade).any(|rule| {
+                let mut targets = Vec::new();
+                self.push_rule_commands(&rule,&mut targets);
+                !targets.is_empty()
+            })
+        })
+    }
+
+    /// Checks whether a chord is being typed.
+    fn is_chord_pending(&self) -> bool {
+        !self.chord.borrow().pending.is_empty()
+    }
 
+    /// Cancels the pending chord if its next step was not pressed within the chord timeout.
+    /// Returns the new, empty chord if the chord was cancelled.
+    fn expire_chord(&self) -> Option<Chord> {
+        let mut state = self.chord.borrow_mut();
+        let elapsed   = self.clock.now() as f64 - state.last_step;
+        let expired   = !state.pending.is_empty() && elapsed > self.chord_timeout_ms.get();
+        expired.as_some_from(|| {
+            state.pending.clear();
+            state.chord()
+        })
+    }
+
+    /// Sets the time in milliseconds to wait for the next step of a chord.
+    pub fn set_chord_timeout_ms(&self, timeout_ms:f64) {
+        self.chord_timeout_ms.set(timeout_ms)
+    }
+
+    /// Finds commands which should be triggered by the given rules, according to their conditions
+    /// and the registered command provider instances. Rules whose handles were dropped are removed.
+    fn matching_commands(&self, rules:&mut Vec<WeakHandle>) -> Vec<frp::Source> {
+        let mut targets = Vec::new();
+        rules.retain(|weak_rule| {
+            weak_rule.upgrade().map(|rule| self.push_rule_commands(&rule,&mut targets)).is_some()
+        });
+        targets
+    }
+
+    /// Pushes commands which should be triggered by the rule to `targets`.
+    fn push_rule_commands(&self, rule:&Rule, targets:&mut Vec<frp::Source>) {
+        let borrowed_command_map = self.command_registry.instances.borrow();
+        let target  = &rule.target;
+        let enabled = self.is_rule_enabled(rule);
+        borrowed_command_map.get(target).filter(|_| enabled).for_each(|commands| {
+            for command in commands {
+                if self.condition_checker(&rule.when,&command.status_map) {
+                    let command_name = &rule.command.name;
+                    match command.command_map.get(command_name){
+                        Some(t) => targets.push(t.frp.clone_ref()),
+                        None    => warning!(&self.logger,
+                            "Command {command_name} was not found on {target}."),
+                    }
+                }
+            }
+        })
+    }
+
+    /// All keyboard actions bound to the given command of the given target. Shortcuts whose
+    /// handles were dropped are skipped.
+    pub fn actions_for(&self, target:&str, command:&str) -> Vec<Action> {
+        let action_map = self.action_map.borrow();
+        let mut actions = Vec::new();
+        for (tp,rule_map) in action_map.iter() {
+            for (key_mask,rules) in rule_map {
+                let matches = rules.iter().filter_map(|rule| rule.upgrade()).any(|rule| {
+                    rule.target == target && rule.command.name == command
+                        && self.is_rule_enabled(&rule)
+                });
+                if matches {
+                    actions.push(Action::new(*tp,*key_mask))
+                }
+            }
+        }
+        actions
+    }
+
+    /// All chords bound to the given command of the given target.
+    pub fn chords_for(&self, target:&str, command:&str) -> Vec<Chord> {
+        let chord_map  = self.chord_map.borrow();
+        let mut chords = Vec::new();
+        for (prefix,rule_map) in chord_map.iter() {
+            for (key_mask,rules) in rule_map {
+                let matches = rules.iter().filter_map(|rule| rule.upgrade()).any(|rule| {
+                    rule.target == target && rule.command.name == command
+                        && self.is_rule_enabled(&rule)
+                });
+                if matches {
+                    let steps = prefix.iter().chain(std::iter::once(key_mask)).copied().collect();
+                    chords.push(Chord {steps})
+                }
+            }
+        }
+        chords
+    }
+
+    /// Checks whether the condition is satisfied for the command provider instance with the given
+    /// status endpoints.
     fn condition_checker
-    (condition:&Condition, status_map:&HashMap<String,command::Status>) -> bool {
-        match condition {
-            Condition::Ok           => true,
-            Condition::Simple(name) => status_map.get(name).map(|t| t.frp.value()).unwrap_or(false)
+    (&self, condition:&Condition, status_map:&HashMap<String,command::Status>) -> bool {
+        condition.check(&|name| {
+            let status = status_map.get(name).map(|t| t.frp.value());
+            status.unwrap_or_else(|| self.is_flag_set(name))
+        })
+    }
+
+    /// Checks if the context flag is set. The focus flags are checked against the focused command
+    /// provider. No provider is considered focused while a text field takes the keyboard input
+    /// (see `TEXT_EDITING_FLAG`).
+    pub fn is_flag_set(&self, name:&str) -> bool {
+        if name.starts_with(FOCUS_FLAG_PREFIX) {
+            let provider = &name[FOCUS_FLAG_PREFIX.len()..];
+            let focused  = self.command_registry.focused.borrow();
+            let editing  = self.context.is_set(TEXT_EDITING_FLAG);
+            !editing && focused.as_ref().map_or(false,|(label,network)| {
+                label == provider && network.upgrade().is_some()
+            })
+        } else {
+            self.context.is_set(name)
         }
     }
-}
 
-impl Add<Shortcut> for &Registry {
-    type Output = Handle;
-    fn add(self, shortcut:Shortcut) -> Handle {
+    /// The context flags used to evaluate shortcut conditions.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Checks whether the rule is in effect. Default rules are disabled for commands which are
+    /// bound or unbound by the user keymap.
+    fn is_rule_enabled(&self, rule:&Rule) -> bool {
+        let key = (rule.target.clone(),rule.command.name.clone());
+        rule.from_keymap || !self.keymap.borrow().overridden.contains(&key)
+    }
+
+    fn add_shortcut(&self, shortcut:Shortcut) -> Handle {
         let handle     = Handle::new(shortcut.rule);
         let instance   = handle.downgrade();
         let action_map = &mut self.action_map.borrow_mut();
-        let rule_map   = action_map.entry(shortcut.action.tp).or_default();
+        let chord_map  = &mut self.chord_map.borrow_mut();
+        let rule_map   = if shortcut.prefix.is_empty() {
+            action_map.entry(shortcut.action.tp).or_default()
+        } else {
+            chord_map.entry(shortcut.prefix).or_default()
+        };
         let rules      = rule_map.entry(shortcut.action.key_mask).or_default();
         rules.push(instance);
         handle
     }
+
+    /// Installs the user keymap, replacing the previously installed one. The default shortcuts of
+    /// commands mentioned in the keymap are disabled.
+    pub fn set_keymap(&self, keymap:&Keymap) {
+        let bindings   = keymap.bindings();
+        let handles    = bindings.map(|binding| self.add_shortcut(binding.to_shortcut())).collect();
+        let overridden = keymap.commands();
+        *self.keymap.borrow_mut() = KeymapState {handles,overridden};
+    }
+
+    /// The keymap describing all shortcuts currently in effect, both the defaults and the ones
+    /// defined by the user keymap.
+    pub fn effective_keymap(&self) -> Keymap {
+        let action_map = self.action_map.borrow();
+        let chord_map  = self.chord_map.borrow();
+        let actions    = action_map.iter().flat_map(|(tp,rule_map)| {
+            rule_map.iter().map(move |(key_mask,rules)| (vec![],Action::new(*tp,*key_mask),rules))
+        });
+        let chords     = chord_map.iter().flat_map(|(prefix,rule_map)| {
+            rule_map.iter().map(move |(key_mask,rules)| {
+                (prefix.clone(),Action::press(*key_mask),rules)
+            })
+        });
+        let mut bindings = Vec::new();
+        for (prefix,action,rules) in actions.chain(chords) {
+            for rule in rules.iter().filter_map(WeakHandle::upgrade) {
+                if self.is_rule_enabled(&rule) {
+                    let prefix    = prefix.clone();
+                    let target    = rule.target.clone();
+                    let command   = rule.command.name.clone();
+                    let condition = rule.when.clone();
+                    bindings.push(keymap::Binding {prefix,action,target,command,condition});
+                }
+            }
+        }
+        Keymap::from_bindings(bindings)
+    }
+}
+
+impl Add<Shortcut> for &Registry {
+    type Output = Handle;
+    fn add(self, shortcut:Shortcut) -> Handle {
+        self.add_shortcut(shortcut)
+    }
 }
 
 
@@ -212,7 +452,7 @@ pub enum ActionType {
 /// the release action happens as soon as the key mask is no longer valid. So for example, after
 /// pressing key "n", and then pressing key "a" (while holding "n"), the release event of the key
 /// "n" will be emitted.
-#[derive(Clone,Copy,Debug)]
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
 #[allow(missing_docs)]
 pub struct Action {
     pub tp       : ActionType,
@@ -243,35 +483,194 @@ impl Action {
     }
 }
 
+/// Legacy keycodes of the Ctrl, Shift, Alt and Meta keys.
+const MODIFIER_KEYCODES:[usize;4] = [17,16,18,91];
+
+/// Checks if the mask contains modifier keys only, like while holding `ctrl` between chord steps.
+fn is_modifier_only(key_mask:&KeyMask) -> bool {
+    let mut others = *key_mask;
+    for code in &MODIFIER_KEYCODES {
+        others.set_bit(*code,false);
+    }
+    others == default()
+}
+
+/// Human readable name of a key with the given legacy keycode, as used by `KeyMask`.
+fn key_name(code:usize) -> Option<String> {
+    let name = match code {
+        8                 => "Backspace",
+        9                 => "Tab",
+        13                => "Enter",
+        16                => "Shift",
+        17                => "Ctrl",
+        18                => "Alt",
+        27                => "Escape",
+        32                => "Space",
+        37                => "Left",
+        38                => "Up",
+        39                => "Right",
+        40                => "Down",
+        46                => "Delete",
+        91                => "Meta",
+        48..=57 | 65..=90 => return std::char::from_u32(code as u32).map(|c| c.to_string()),
+        _                 => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Names of all keys in the mask, modifiers first. Keys without a known name are described by their
+/// legacy keycode, like `#186`.
+fn key_names(key_mask:&KeyMask) -> Vec<String> {
+    let modifiers = MODIFIER_KEYCODES;
+    let is_set    = |code:&usize| key_mask.get_bit(*code);
+    let others    = (0..256).filter(|code| !modifiers.contains(code));
+    let codes     = modifiers.iter().copied().filter(is_set).chain(others.filter(is_set));
+    codes.map(|code| key_name(code).unwrap_or_else(|| iformat!("#{code}"))).collect()
+}
+
+impl Display for Action {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys = key_names(&self.key_mask).join("+");
+        match self.tp {
+            ActionType::Press       => write!(f,"{}",keys),
+            ActionType::Release     => write!(f,"{} (release)",keys),
+            ActionType::DoublePress => write!(f,"{} (double)",keys),
+        }
+    }
+}
+
+
+
+// =============
+// === Chord ===
+// =============
+
+/// A sequence of key presses triggering a command, like `ctrl+k ctrl+c`. Used also to describe a
+/// chord being typed, in which case only the already pressed steps are present.
+#[derive(Clone,Debug,Default,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub struct Chord {
+    pub steps : Vec<KeyMask>,
+}
+
+impl Display for Chord {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps = self.steps.iter().map(|step| key_names(step).join("+")).collect_vec();
+        write!(f,"{}",steps.join(" "))
+    }
+}
+
+/// The result of processing a key press by `ChordState`.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+enum ChordStep {
+    /// The press is not a part of any chord and should be processed as a regular action.
+    Ignored,
+    /// The press started or continued a chord, which is waiting for the next step.
+    Pending,
+    /// The press completed a chord.
+    Completed,
+    /// The press does not continue the pending chord, which was cancelled.
+    Cancelled,
+}
+
+/// The state of the chord being typed.
+#[derive(Debug,Default)]
+struct ChordState {
+    /// The steps pressed so far.
+    pending   : Vec<KeyMask>,
+    /// The time of the last step, in milliseconds.
+    last_step : f64,
+    /// The key mask of the last press, if it was handled as a chord step. Release and double press
+    /// actions of such key mask are not processed.
+    consumed  : Option<KeyMask>,
+}
+
+impl ChordState {
+    fn chord(&self) -> Chord {
+        Chord {steps:self.pending.clone()}
+    }
+
+    /// Processes the key press. The `try_complete` function is called with the pending steps and
+    /// the pressed key mask, and should trigger the chord's commands if there is such chord. The
+    /// `is_prefix` function should check if the steps are the beginning of some chord.
+    fn step
+    ( &mut self
+    , key_mask     : &KeyMask
+    , time         : f64
+    , timeout      : f64
+    , try_complete : impl FnOnce(&[KeyMask],&KeyMask) -> bool
+    , is_prefix    : impl FnOnce(&[KeyMask]) -> bool
+    ) -> ChordStep {
+        if !self.pending.is_empty() && time - self.last_step > timeout {
+            self.pending.clear();
+        }
+        let step = if self.pending.is_empty() && is_modifier_only(key_mask) {
+            ChordStep::Ignored
+        } else if is_modifier_only(key_mask) {
+            return ChordStep::Pending
+        } else if !self.pending.is_empty() && try_complete(&self.pending,key_mask) {
+            self.pending.clear();
+            ChordStep::Completed
+        } else {
+            let mut steps = self.pending.clone();
+            steps.push(*key_mask);
+            if is_prefix(&steps) {
+                self.pending   = steps;
+                self.last_step = time;
+                ChordStep::Pending
+            } else if !self.pending.is_empty() {
+                self.pending.clear();
+                ChordStep::Cancelled
+            } else {
+                ChordStep::Ignored
+            }
+        };
+        self.consumed = (step != ChordStep::Ignored).as_some(*key_mask);
+        step
+    }
+}
+
 
 
 // ================
 // === Shortcut ===
 // ================
 
-/// A keyboard shortcut, an `Action` associated with a `Rule`.
+/// A keyboard shortcut, an `Action` associated with a `Rule`. If the `prefix` is not empty, the
+/// shortcut is a chord, and the action is its last step.
 #[derive(Clone,Debug,Shrinkwrap)]
 pub struct Shortcut {
     #[shrinkwrap(main_field)]
     rule   : Rule,
     action : Action,
+    prefix : Vec<KeyMask>,
 }
 
 impl Shortcut {
     /// Constructor. Version without condition checker.
     pub fn new<A,T,C>(action:A, target:T, command:C) -> Self
     where A:Into<Action>, T:Into<String>, C:Into<Command> {
-        let rule     = Rule::new(target,command);
+        let rule   = Rule::new(target,command);
         let action = action.into();
-        Self {rule,action}
+        let prefix = default();
+        Self {rule,action,prefix}
     }
 
     /// Constructor.
     pub fn new_when<A,T,C>(action:A, target:T, command:C, condition:Condition) -> Self
     where A:Into<Action>, T:Into<String>, C:Into<Command> {
-        let rule     = Rule::new_when(target,command,condition);
+        let rule   = Rule::new_when(target,command,condition);
         let action = action.into();
-        Self {rule,action}
+        let prefix = default();
+        Self {rule,action,prefix}
+    }
+
+    /// Makes the shortcut a chord: its action triggers only after pressing the given key masks in
+    /// sequence, like `ctrl+k` in the `ctrl+k ctrl+c` chord. The last step of a chord is always a
+    /// press, so the action type is ignored for chords.
+    pub fn after(mut self, prefix:Vec<KeyMask>) -> Self {
+        self.prefix = prefix;
+        self
     }
 }
 
@@ -286,9 +685,11 @@ impl Shortcut {
 /// to be executed.
 #[derive(Clone,Debug)]
 pub struct Rule {
-    target  : String,
-    command : Command,
-    when    : Condition,
+    target      : String,
+    command     : Command,
+    when        : Condition,
+    /// Whether the rule comes from the user keymap rather than from component defaults.
+    from_keymap : bool,
 }
 
 impl Rule {
@@ -301,9 +702,10 @@ impl Rule {
     /// Constructor.
     pub fn new_when<T,C>(target:T, command:C, when:Condition) -> Self
     where T:Into<String>, C:Into<Command> {
-        let target  = target.into();
-        let command = command.into();
-        Self {target,when,command}
+        let target      = target.into();
+        let command     = command.into();
+        let from_keymap = false;
+        Self {target,when,command,from_keymap}
     }
 }
 
@@ -331,15 +733,272 @@ impl From<&str> for Command {
 // === Condition ===
 // =================
 
-// TODO: Uncomment and handle more complex cases. Left here to show the intention of future dev.
-/// Condition expression.
-#[derive(Clone,Debug)]
+/// Condition expression. Conditions are built of named flags combined with `&` (and), `|` (or)
+/// and `!` (not). A flag is set if the target command provider instance has a status endpoint
+/// with this name and it is currently `true`, or if the flag is set in the registry's `Context`.
+///
+/// Conditions can be parsed from their textual form, like `!searcher_open & (a | b)`. The `true`
+/// literal denotes the condition which is always satisfied.
+#[derive(Clone,Debug,Eq,PartialEq)]
 #[allow(missing_docs)]
 pub enum Condition {
     Ok,
     Simple (String),
-    // Or     (Box<Condition>, Box<Condition>),
-    // And    (Box<Condition>, Box<Condition>),
+    Not    (Box<Condition>),
+    Or     (Box<Condition>, Box<Condition>),
+    And    (Box<Condition>, Box<Condition>),
+}
+
+impl Condition {
+    /// Condition satisfied when the flag of the given name is set.
+    pub fn flag(name:impl Into<String>) -> Self {
+        Self::Simple(name.into())
+    }
+
+    /// Evaluates the condition. The `is_set` function tells if the flag of a given name is set.
+    pub fn check(&self, is_set:&impl Fn(&str) -> bool) -> bool {
+        match self {
+            Self::Ok              => true,
+            Self::Simple(name)    => is_set(name),
+            Self::Not(cond)       => !cond.check(is_set),
+            Self::Or(left,right)  => left.check(is_set) || right.check(is_set),
+            Self::And(left,right) => left.check(is_set) && right.check(is_set),
+        }
+    }
+
+    /// Writes the condition, putting it in parentheses if the operator binds weaker than the
+    /// surrounding one.
+    fn fmt_with_precedence(&self, f:&mut fmt::Formatter<'_>, outer:usize) -> fmt::Result {
+        let precedence = match self {
+            Self::Or  (..) => 1,
+            Self::And (..) => 2,
+            _              => 3,
+        };
+        let parenthesize = precedence < outer;
+        if parenthesize { write!(f,"(")?; }
+        match self {
+            Self::Ok           => write!(f,"true")?,
+            Self::Simple(name) => write!(f,"{}",name)?,
+            Self::Not(cond)    => {
+                write!(f,"!")?;
+                cond.fmt_with_precedence(f,precedence)?;
+            }
+            Self::Or(left,right) | Self::And(left,right) => {
+                let operator = if precedence == 1 {"|"} else {"&"};
+                left.fmt_with_precedence(f,precedence)?;
+                write!(f," {} ",operator)?;
+                right.fmt_with_precedence(f,precedence+1)?;
+            }
+        }
+        if parenthesize { write!(f,")")?; }
+        Ok(())
+    }
+}
+
+impl Default for Condition {
+    fn default() -> Self {
+        Self::Ok
+    }
+}
+
+impl std::ops::Not for Condition {
+    type Output = Condition;
+    fn not(self) -> Self::Output {
+        Self::Not(Box::new(self))
+    }
+}
+
+impl std::ops::BitAnd for Condition {
+    type Output = Condition;
+    fn bitand(self, other:Condition) -> Self::Output {
+        Self::And(Box::new(self),Box::new(other))
+    }
+}
+
+impl std::ops::BitOr for Condition {
+    type Output = Condition;
+    fn bitor(self, other:Condition) -> Self::Output {
+        Self::Or(Box::new(self),Box::new(other))
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with_precedence(f,0)
+    }
+}
+
+
+// === Parsing ===
+
+/// Error returned when the condition text is malformed.
+#[derive(Clone,Debug,Fail)]
+#[fail(display="Invalid shortcut condition \"{}\": {}.",input,reason)]
+#[allow(missing_docs)]
+pub struct ConditionParseError {
+    pub input  : String,
+    pub reason : String,
+}
+
+/// A token of the condition text.
+#[derive(Clone,Debug,PartialEq)]
+enum Token { Flag(String), Not, And, Or, Open, Close }
+
+/// Checks if the character may be a part of flag name, like `focus:GraphEditor`.
+fn is_flag_char(c:char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == ':' || c == '.' || c == '-'
+}
+
+/// A recursive descent parser of conditions. The operator precedence is: `!`, `&`, `|`.
+struct ConditionParser<'a> {
+    input  : &'a str,
+    tokens : Vec<Token>,
+    next   : usize,
+}
+
+impl<'a> ConditionParser<'a> {
+    fn new(input:&'a str) -> Result<Self,ConditionParseError> {
+        let mut tokens = Vec::new();
+        let mut chars  = input.chars().peekable();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '!' => Token::Not,
+                '&' => Token::And,
+                '|' => Token::Or,
+                '(' => Token::Open,
+                ')' => Token::Close,
+                c if c.is_whitespace() => continue,
+                c if is_flag_char(c)   => {
+                    let mut name = c.to_string();
+                    while let Some(c) = chars.peek().copied().filter(|c| is_flag_char(*c)) {
+                        name.push(c);
+                        chars.next();
+                    }
+                    Token::Flag(name)
+                }
+                c => return Err(Self::error(input,iformat!("unexpected character '{c}'"))),
+            };
+            tokens.push(token);
+        }
+        Ok(Self {input,tokens,next:0})
+    }
+
+    fn error(input:&str, reason:impl Into<String>) -> ConditionParseError {
+        let input  = input.into();
+        let reason = reason.into();
+        ConditionParseError {input,reason}
+    }
+
+    fn parse(mut self) -> Result<Condition,ConditionParseError> {
+        let condition = self.parse_or()?;
+        match self.tokens.get(self.next) {
+            None        => Ok(condition),
+            Some(token) => Err(Self::error(self.input,iformat!("unexpected {token:?}"))),
+        }
+    }
+
+    fn take_if(&mut self, token:Token) -> bool {
+        let matches = self.tokens.get(self.next) == Some(&token);
+        if matches { self.next += 1 }
+        matches
+    }
+
+    fn parse_or(&mut self) -> Result<Condition,ConditionParseError> {
+        let mut condition = self.parse_and()?;
+        while self.take_if(Token::Or) {
+            condition = condition | self.parse_and()?;
+        }
+        Ok(condition)
+    }
+
+    fn parse_and(&mut self) -> Result<Condition,ConditionParseError> {
+        let mut condition = self.parse_not()?;
+        while self.take_if(Token::And) {
+            condition = condition & self.parse_not()?;
+        }
+        Ok(condition)
+    }
+
+    fn parse_not(&mut self) -> Result<Condition,ConditionParseError> {
+        if self.take_if(Token::Not) {
+            Ok(!self.parse_not()?)
+        } else {
+            self.parse_atom()
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Condition,ConditionParseError> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        match token {
+            Some(Token::Flag(name)) if name == "true" => Ok(Condition::Ok),
+            Some(Token::Flag(name)) => Ok(Condition::Simple(name)),
+            Some(Token::Open)       => {
+                let condition = self.parse_or()?;
+                if self.take_if(Token::Close) {
+                    Ok(condition)
+                } else {
+                    Err(Self::error(self.input,"missing closing parenthesis"))
+                }
+            }
+            Some(token) => Err(Self::error(self.input,iformat!("unexpected {token:?}"))),
+            None        => Err(Self::error(self.input,"unexpected end of condition")),
+        }
+    }
+}
+
+impl std::str::FromStr for Condition {
+    type Err = ConditionParseError;
+    fn from_str(s:&str) -> Result<Self,Self::Err> {
+        ConditionParser::new(s)?.parse()
+    }
+}
+
+
+
+// ===============
+// === Context ===
+// ===============
+
+/// Prefix of flags telling which command provider is focused, like `focus:GraphEditor`. These
+/// flags are derived from the focus tracked by the `command::Registry`.
+pub const FOCUS_FLAG_PREFIX:&str = "focus:";
+
+/// The flag set while some text field is focused and receives the typed characters, for example
+/// when the user edits code in the text editor or types in the searcher.
+pub const TEXT_EDITING_FLAG:&str = "text_editing";
+
+/// A set of named flags describing the current state of the application, like `searcher_open`.
+/// The flags are published by views and are used to evaluate shortcut `Condition`s.
+#[derive(Clone,CloneRef,Debug,Default)]
+pub struct Context {
+    flags : Rc<RefCell<HashSet<String>>>,
+}
+
+impl Context {
+    /// Sets or unsets the flag.
+    pub fn set(&self, name:impl Into<String>, value:bool) {
+        let name = name.into();
+        if value {
+            self.flags.borrow_mut().insert(name);
+        } else {
+            self.flags.borrow_mut().remove(&name);
+        }
+    }
+
+    /// Checks if the flag is set.
+    pub fn is_set(&self, name:&str) -> bool {
+        self.flags.borrow().contains(name)
+    }
+
+    /// Keeps the flag in sync with the given FRP stream, as long as the network is alive.
+    pub fn bind(&self, network:&frp::Network, name:impl Into<String>, value:&frp::Stream<bool>) {
+        let context = self.clone_ref();
+        let name    = name.into();
+        frp::extend! { network
+            eval value ((value) context.set(name.clone(),*value));
+        }
+    }
 }
 
 
@@ -376,3 +1035,92 @@ impl<T:command::Provider> DefaultShortcutProvider for T {
         default()
     }
 }
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::frp::io::keyboard::Key;
+    use std::str::FromStr;
+
+    fn flag(name:&str) -> Condition {
+        Condition::flag(name)
+    }
+
+    #[test]
+    fn parsing_conditions() {
+        let parse = |text| Condition::from_str(text).unwrap();
+        assert_eq!(parse("true"), Condition::Ok);
+        assert_eq!(parse("focus:GraphEditor"), flag("focus:GraphEditor"));
+        assert_eq!(parse("!a & b | c"), (!flag("a") & flag("b")) | flag("c"));
+        assert_eq!(parse("a & !(b | c)"), flag("a") & !(flag("b") | flag("c")));
+        assert_eq!(parse("a | b | c"), (flag("a") | flag("b")) | flag("c"));
+    }
+
+    #[test]
+    fn printing_conditions() {
+        let texts = ["true","a","!a & b | c","a & !(b | c)","a | (b | c)","!!a"];
+        for text in &texts {
+            let condition = Condition::from_str(text).unwrap();
+            assert_eq!(&condition.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn invalid_conditions() {
+        for text in &["","a &","(a | b","a b","a # b","!"] {
+            assert!(Condition::from_str(text).is_err(), "{} should be invalid", text);
+        }
+    }
+
+    #[test]
+    fn checking_conditions() {
+        let condition = Condition::from_str("!searcher_open & (is_active | focus:GraphEditor)");
+        let condition = condition.unwrap();
+        let check     = |flags:&[&str]| condition.check(&|name| flags.contains(&name));
+        assert!(check(&["is_active"]));
+        assert!(check(&["focus:GraphEditor"]));
+        assert!(!check(&["is_active","searcher_open"]));
+        assert!(!check(&[]));
+    }
+
+    #[test]
+    fn typing_chords() {
+        let ctrl_k       = KeyMask::control_plus('k');
+        let ctrl_c       = KeyMask::control_plus('c');
+        let ctrl         = KeyMask::from_vec(vec![Key::Control]);
+        let x            = KeyMask::from_vec(vec![Key::Character("x".into())]);
+        let chord        = [ctrl_k,ctrl_c];
+        let is_prefix    = |steps:&[KeyMask]| steps.len() < chord.len() && chord.starts_with(steps);
+        let try_complete = |prefix:&[KeyMask], key_mask:&KeyMask| {
+            prefix == &chord[..1] && *key_mask == chord[1]
+        };
+        let timeout   = 1000.0;
+        let mut state = ChordState::default();
+        let mut press = |key_mask:&KeyMask, time:f64| {
+            state.step(key_mask,time,timeout,&try_complete,&is_prefix)
+        };
+        assert_eq!(press(&ctrl,0.0)      , ChordStep::Ignored);
+        assert_eq!(press(&ctrl_k,0.0)    , ChordStep::Pending);
+        assert_eq!(press(&ctrl,100.0)    , ChordStep::Pending);
+        assert_eq!(press(&ctrl_c,200.0)  , ChordStep::Completed);
+        assert_eq!(press(&x,300.0)       , ChordStep::Ignored);
+        assert_eq!(press(&ctrl_k,400.0)  , ChordStep::Pending);
+        assert_eq!(press(&ctrl_c,2000.0) , ChordStep::Ignored);
+        assert_eq!(press(&ctrl_k,3000.0) , ChordStep::Pending);
+        assert_eq!(press(&x,3100.0)      , ChordStep::Cancelled);
+        assert_eq!(state.chord()         , Chord::default());
+    }
+
+    #[test]
+    fn printing_chords() {
+        let steps = vec![KeyMask::control_plus('k'),KeyMask::from_vec(vec![Key::Enter])];
+        assert_eq!(Chord {steps}.to_string(), "Ctrl+K Enter");
+    }
+}
@@ -0,0 +1,554 @@
+//! User-defined key bindings. A keymap is a text with one entry per line:
+//!
+//! ```text
+//! # Lines starting with a hash are comments.
+//! press ctrl+shift+p      : CommandPalette.toggle
+//! press backspace         : GraphEditor.remove_selected_nodes when !searcher_open
+//! double_press ctrl+space : GraphEditor.double_press_visualization_visibility
+//! press ctrl+k ctrl+l     : GraphEditor.arrange_selected_nodes
+//! unbind GraphEditor.add_node_at_cursor
+//! ```
+//!
+//! A binding consists of the action type (`press`, `release` or `double_press`), keys joined with
+//! `+`, the target command provider and its command, and an optional `Condition`. Keys are given
+//! by their names (like `ctrl`, `enter`, `up` or `a`), or by their keycodes (like `#186`), which
+//! allows binding keys of non-US keyboard layouts. Press bindings may be chords: sequences of
+//! key combinations separated by spaces.
+//!
+//! Binding a command in the keymap replaces all its default shortcuts. The `unbind` entry removes
+//! the default shortcuts of the command without defining new ones.
+
+use crate::prelude::*;
+
+use super::Action;
+use super::ActionType;
+use super::Condition;
+use super::Rule;
+use super::Shortcut;
+use super::key_names;
+
+use crate::application::command;
+use crate::frp::data::bitfield::BitField;
+use crate::frp::io::keyboard::Key;
+use crate::frp::io::keyboard::KeyMask;
+
+use std::str::FromStr;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The maximum number of distinct flags in a pair of conditions for which the conflict detection
+/// checks all flag combinations. Bindings with more flags are reported as conflicting.
+const MAX_CONFLICT_CHECK_FLAGS:usize = 12;
+
+
+
+// ==============
+// === Errors ===
+// ==============
+
+/// Error of parsing the keymap text.
+#[derive(Clone,Debug,Fail)]
+#[fail(display="Invalid keymap entry in line {}: {}.",line,reason)]
+#[allow(missing_docs)]
+pub struct ParseError {
+    pub line   : usize,
+    pub reason : String,
+}
+
+/// Error of a keymap entry which does not match any registered command.
+#[derive(Clone,Debug,Fail)]
+#[allow(missing_docs)]
+pub enum ValidationError {
+    #[fail(display="Unknown command provider \"{}\".",target)]
+    UnknownTarget {target:String},
+    #[fail(display="The command provider \"{}\" has no command \"{}\".",target,command)]
+    UnknownCommand {target:String, command:String},
+}
+
+/// Two bindings which may be triggered by the same keyboard action at the same time.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Conflict {
+    pub first  : Binding,
+    pub second : Binding,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"Conflicting bindings: \"{}\" and \"{}\".",self.first,self.second)
+    }
+}
+
+
+
+// ===========
+// === Key ===
+// ===========
+
+/// Finds the key by its name used in keymaps. Single characters denote the character keys.
+fn key_from_name(name:&str) -> Option<Key> {
+    let key = match name {
+        "ctrl" | "control" => Key::Control,
+        "shift"            => Key::Shift,
+        "alt"              => Key::Alt,
+        "meta" | "cmd"     => Key::Meta,
+        "enter"            => Key::Enter,
+        "escape" | "esc"   => Key::Escape,
+        "backspace"        => Key::Backspace,
+        "delete"           => Key::Delete,
+        "tab"              => Key::Tab,
+        "space"            => Key::Character(" ".into()),
+        "up"               => Key::ArrowUp,
+        "down"             => Key::ArrowDown,
+        "left"             => Key::ArrowLeft,
+        "right"            => Key::ArrowRight,
+        _ if name.chars().count() == 1 => Key::Character(name.to_lowercase()),
+        _                  => return None,
+    };
+    Some(key)
+}
+
+/// Parses the chord steps separated by whitespace, like `ctrl+k ctrl+c`.
+fn parse_steps(text:&str) -> Result<Vec<KeyMask>,String> {
+    let steps = text.split_whitespace().map(parse_keys).collect::<Result<Vec<_>,_>>()?;
+    (!steps.is_empty()).as_result(steps,"missing keys".into())
+}
+
+/// Parses keys joined with `+`, like `ctrl+shift+p`.
+fn parse_keys(text:&str) -> Result<KeyMask,String> {
+    let mut key_mask = KeyMask::default();
+    for name in text.split('+') {
+        let name = name.trim().to_lowercase();
+        if name.starts_with('#') && name.len() > 1 {
+            let code = name[1..].parse::<usize>().ok().filter(|code| *code < 256);
+            let code = code.ok_or_else(|| iformat!("invalid keycode \"{name}\""))?;
+            key_mask.set_bit(code,true);
+        } else {
+            let key = key_from_name(&name).ok_or_else(|| iformat!("unknown key \"{name}\""))?;
+            key_mask.set(&key,true);
+        }
+    }
+    Ok(key_mask)
+}
+
+fn action_type_name(tp:ActionType) -> &'static str {
+    match tp {
+        ActionType::Press       => "press",
+        ActionType::Release     => "release",
+        ActionType::DoublePress => "double_press",
+    }
+}
+
+fn action_type_from_name(name:&str) -> Option<ActionType> {
+    match name {
+        "press"        => Some(ActionType::Press),
+        "release"      => Some(ActionType::Release),
+        "double_press" => Some(ActionType::DoublePress),
+        _              => None,
+    }
+}
+
+/// Splits the `Target.command` reference.
+fn parse_command_reference(text:&str) -> Result<(String,String),String> {
+    let mut parts = text.splitn(2,'.');
+    let target    = parts.next().filter(|t| !t.is_empty());
+    let command   = parts.next().filter(|c| !c.is_empty());
+    match (target,command) {
+        (Some(target),Some(command)) => Ok((target.into(),command.into())),
+        _ => Err(iformat!("expected a command in form \"Target.command\", got \"{text}\"")),
+    }
+}
+
+
+
+// ===============
+// === Binding ===
+// ===============
+
+/// A single key binding defined in the keymap.
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub struct Binding {
+    /// The chord steps preceding the action. Empty if the binding is not a chord.
+    pub prefix    : Vec<KeyMask>,
+    pub action    : Action,
+    pub target    : String,
+    pub command   : String,
+    pub condition : Condition,
+}
+
+impl Binding {
+    /// Converts the binding to a shortcut which can be registered in the shortcut `Registry`.
+    pub fn to_shortcut(&self) -> Shortcut {
+        let condition    = self.condition.clone();
+        let mut rule     = Rule::new_when(&self.target,self.command.clone(),condition);
+        rule.from_keymap = true;
+        let action       = self.action;
+        let prefix       = self.prefix.clone();
+        Shortcut {rule,action,prefix}
+    }
+
+    /// Checks if both bindings may be triggered by the same keyboard action at the same time. It
+    /// happens when there is a set of flags satisfying both conditions.
+    pub fn conflicts_with(&self, other:&Binding) -> bool {
+        let same_command = self.target == other.target && self.command == other.command;
+        let same_action  = self.action == other.action && self.prefix == other.prefix;
+        !same_command && same_action && {
+            let mut flags = Vec::new();
+            self.condition.collect_flags(&mut flags);
+            other.condition.collect_flags(&mut flags);
+            flags.sort();
+            flags.dedup();
+            flags.len() > MAX_CONFLICT_CHECK_FLAGS || (0..1usize << flags.len()).any(|bits| {
+                let is_set = |name:&str| {
+                    let index = flags.iter().position(|flag| flag == name);
+                    index.map_or(false, |index| bits & (1 << index) != 0)
+                };
+                self.condition.check(&is_set) && other.condition.check(&is_set)
+            })
+        }
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let tp    = action_type_name(self.action.tp);
+        let steps = self.prefix.iter().chain(std::iter::once(&self.action.key_mask));
+        let keys  = steps.map(|step| key_names(step).join("+").to_lowercase()).collect_vec();
+        write!(f,"{} {} : {}.{}",tp,keys.join(" "),self.target,self.command)?;
+        if self.condition != Condition::Ok {
+            write!(f," when {}",self.condition)?;
+        }
+        Ok(())
+    }
+}
+
+impl Condition {
+    /// Collects names of all flags used in the condition.
+    fn collect_flags(&self, flags:&mut Vec<String>) {
+        match self {
+            Self::Ok              => {}
+            Self::Simple(name)    => flags.push(name.clone()),
+            Self::Not(cond)       => cond.collect_flags(flags),
+            Self::Or(left,right)  |
+            Self::And(left,right) => {
+                left.collect_flags(flags);
+                right.collect_flags(flags);
+            }
+        }
+    }
+}
+
+
+
+// =============
+// === Entry ===
+// =============
+
+/// A keymap entry.
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub enum Entry {
+    Bind   (Binding),
+    Unbind {target:String, command:String},
+}
+
+impl Entry {
+    /// The command affected by this entry, as a (target,command) pair.
+    pub fn command(&self) -> (&str,&str) {
+        match self {
+            Self::Bind(binding)          => (&binding.target,&binding.command),
+            Self::Unbind{target,command} => (target,command),
+        }
+    }
+
+    fn parse(line:&str) -> Result<Self,String> {
+        let mut words = line.split_whitespace();
+        let first     = words.next().unwrap_or_default();
+        if first == "unbind" {
+            let reference        = words.next().unwrap_or_default();
+            let (target,command) = parse_command_reference(reference)?;
+            match words.next() {
+                Some(word) => Err(iformat!("unexpected \"{word}\" after the command")),
+                None       => Ok(Self::Unbind {target,command}),
+            }
+        } else {
+            let tp        = action_type_from_name(first);
+            let tp        = tp.ok_or_else(|| iformat!("unknown action type \"{first}\""))?;
+            let rest      = &line[line.find(first).unwrap_or_default() + first.len()..];
+            let mut parts = rest.splitn(2,':');
+            let keys      = parts.next().unwrap_or_default();
+            let target    = parts.next().ok_or_else(|| "missing \":\" after the keys".to_string())?;
+            let mut steps = parse_steps(keys)?;
+            let key_mask  = steps.pop().unwrap_or_default();
+            let prefix    = steps;
+            if !prefix.is_empty() && tp != ActionType::Press {
+                return Err("only press bindings may be chords".into())
+            }
+            let action           = Action::new(tp,key_mask);
+            let mut target_words = target.split_whitespace();
+            let reference        = target_words.next().unwrap_or_default();
+            let (target,command) = parse_command_reference(reference)?;
+            let condition = match target_words.next() {
+                None         => Condition::Ok,
+                Some("when") => {
+                    let text = target_words.collect_vec().join(" ");
+                    Condition::from_str(&text).map_err(|err| err.reason)?
+                }
+                Some(word)   => return Err(iformat!("expected \"when\", got \"{word}\"")),
+            };
+            Ok(Self::Bind(Binding {prefix,action,target,command,condition}))
+        }
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bind(binding)          => write!(f,"{}",binding),
+            Self::Unbind{target,command} => write!(f,"unbind {}.{}",target,command),
+        }
+    }
+}
+
+
+
+// ==============
+// === Keymap ===
+// ==============
+
+/// A set of user-defined key bindings. See the module docs to learn about the text format.
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub struct Keymap {
+    pub entries : Vec<Entry>,
+}
+
+impl Keymap {
+    /// Creates a keymap with the given bindings, ordered by their commands.
+    pub fn from_bindings(mut bindings:Vec<Binding>) -> Self {
+        bindings.sort_by(|a,b| {
+            (&a.target,&a.command,a.to_string()).cmp(&(&b.target,&b.command,b.to_string()))
+        });
+        let entries = bindings.into_iter().map(Entry::Bind).collect();
+        Self {entries}
+    }
+
+    /// All bindings defined in the keymap.
+    pub fn bindings(&self) -> impl Iterator<Item=&Binding> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Bind(binding) => Some(binding),
+            _                    => None,
+        })
+    }
+
+    /// All commands bound or unbound in this keymap, as (target,command) pairs.
+    pub fn commands(&self) -> HashSet<(String,String)> {
+        self.entries.iter().map(|entry| {
+            let (target,command) = entry.command();
+            (target.to_string(),command.to_string())
+        }).collect()
+    }
+
+    /// Returns the keymap with entries of the `other` keymap taking precedence: all entries of
+    /// commands mentioned in `other` are replaced. Useful for layering a project keymap over the
+    /// global one.
+    pub fn overridden_by(self, other:Keymap) -> Keymap {
+        let overridden = other.commands();
+        let is_kept    = |entry:&Entry| {
+            let (target,command) = entry.command();
+            !overridden.contains(&(target.to_string(),command.to_string()))
+        };
+        let mut entries = self.entries.into_iter().filter(is_kept).collect_vec();
+        entries.extend(other.entries);
+        Self {entries}
+    }
+
+    /// Checks if all entries refer to commands registered in the command `Registry`.
+    pub fn validate(&self, registry:&command::Registry) -> Vec<ValidationError> {
+        let command_docs = registry.command_docs.borrow();
+        self.entries.iter().filter_map(|entry| Self::validate_entry(entry,&command_docs)).collect()
+    }
+
+    /// Removes the entries which do not refer to commands registered in the command `Registry`,
+    /// returning the errors describing the removed entries.
+    pub fn retain_valid(&mut self, registry:&command::Registry) -> Vec<ValidationError> {
+        let command_docs = registry.command_docs.borrow();
+        let mut errors   = Vec::new();
+        self.entries.retain(|entry| match Self::validate_entry(entry,&command_docs) {
+            Some(error) => { errors.push(error); false }
+            None        => true,
+        });
+        errors
+    }
+
+    fn validate_entry
+    (entry:&Entry, command_docs:&HashMap<String,Vec<command::EndpointDocs>>)
+    -> Option<ValidationError> {
+        let (target,command) = entry.command();
+        match command_docs.get(target) {
+            None       => Some(ValidationError::UnknownTarget {target:target.into()}),
+            Some(docs) => (!docs.iter().any(|doc| doc.label == command)).as_some_from(|| {
+                let target  = target.into();
+                let command = command.into();
+                ValidationError::UnknownCommand {target,command}
+            }),
+        }
+    }
+
+    /// Finds pairs of bindings which may be triggered by the same keyboard action at once.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let bindings = self.bindings().collect_vec();
+        let mut conflicts = Vec::new();
+        for (index,first) in bindings.iter().enumerate() {
+            for second in &bindings[index+1..] {
+                if first.conflicts_with(second) {
+                    let first  = (*first).clone();
+                    let second = (*second).clone();
+                    conflicts.push(Conflict {first,second});
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+impl FromStr for Keymap {
+    type Err = ParseError;
+    fn from_str(text:&str) -> Result<Self,Self::Err> {
+        let lines   = text.lines().enumerate().map(|(index,line)| (index+1,line.trim()));
+        let lines   = lines.filter(|(_,line)| !line.is_empty() && !line.starts_with('#'));
+        let entries = lines.map(|(line,text)| {
+            Entry::parse(text).map_err(|reason| ParseError {line,reason})
+        });
+        let entries = entries.collect::<Result<_,_>>()?;
+        Ok(Self {entries})
+    }
+}
+
+impl Display for Keymap {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f,"{}",entry)?;
+        }
+        Ok(())
+    }
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYMAP:&str = "
+        # Comment.
+        press ctrl+shift+p      : CommandPalette.toggle
+        press backspace         : GraphEditor.remove_selected_nodes when !searcher_open
+        double_press ctrl+space : GraphEditor.double_press_visualization_visibility
+        press #186              : GraphEditor.add_node_at_cursor
+        unbind GraphEditor.cycle_visualization_for_selected_node
+    ";
+
+    fn binding(keys:&str, target:&str, command:&str, condition:&str) -> Binding {
+        let action    = Action::press(parse_keys(keys).unwrap());
+        let target    = target.into();
+        let command   = command.into();
+        let condition = Condition::from_str(condition).unwrap();
+        let prefix    = default();
+        Binding {prefix,action,target,command,condition}
+    }
+
+    #[test]
+    fn parsing_keymap() {
+        let keymap = Keymap::from_str(KEYMAP).unwrap();
+        assert_eq!(keymap.entries.len(), 5);
+        let bindings = keymap.bindings().collect_vec();
+        let ctrl_shift_p = [Key::Control,Key::Shift,Key::Character("p".into())];
+        assert_eq!(bindings[0].action, Action::press(&ctrl_shift_p));
+        assert_eq!(bindings[1].condition, !Condition::flag("searcher_open"));
+        assert_eq!(bindings[2].action.tp, ActionType::DoublePress);
+        assert!(bindings[3].action.key_mask.get_bit(186));
+        let unbind = Entry::Unbind {
+            target  : "GraphEditor".into(),
+            command : "cycle_visualization_for_selected_node".into(),
+        };
+        assert_eq!(keymap.entries[4], unbind);
+    }
+
+    #[test]
+    fn parsing_chords() {
+        let keymap   = Keymap::from_str("press ctrl+k ctrl+c : A.b").unwrap();
+        let bindings = keymap.bindings().collect_vec();
+        assert_eq!(bindings[0].prefix, vec![KeyMask::control_plus('k')]);
+        assert_eq!(bindings[0].action, Action::press(KeyMask::control_plus('c')));
+        assert_eq!(keymap.to_string(), "press ctrl+k ctrl+c : A.b\n");
+        assert!(Keymap::from_str("release ctrl+k ctrl+c : A.b").is_err());
+        let single = binding("ctrl+c","C","c","true");
+        assert!(!bindings[0].conflicts_with(&single));
+    }
+
+    #[test]
+    fn printing_keymap() {
+        let keymap  = Keymap::from_str(KEYMAP).unwrap();
+        let printed = keymap.to_string();
+        assert!(printed.contains("press ctrl+shift+p : CommandPalette.toggle\n"));
+        assert!(printed.contains("when !searcher_open\n"));
+        assert_eq!(Keymap::from_str(&printed).unwrap(), keymap);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Keymap::from_str("press ctrl+p : A.b\npress hyper+p : A.b").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert!(Keymap::from_str("hold ctrl : A.b").is_err());
+        assert!(Keymap::from_str("press ctrl A.b").is_err());
+        assert!(Keymap::from_str("press ctrl : A").is_err());
+        assert!(Keymap::from_str("press ctrl : A.b if x").is_err());
+        assert!(Keymap::from_str("press ctrl : A.b when (x").is_err());
+    }
+
+    #[test]
+    fn detecting_conflicts() {
+        let always    = binding("ctrl+p","A","a","true");
+        let searching = binding("ctrl+p","B","b","searching");
+        let idle      = binding("ctrl+p","C","c","!searching");
+        let other_key = binding("ctrl+o","D","d","true");
+        assert!(always.conflicts_with(&searching));
+        assert!(always.conflicts_with(&idle));
+        assert!(!searching.conflicts_with(&idle));
+        assert!(!always.conflicts_with(&other_key));
+        let keymap = Keymap::from_bindings(vec![always,searching,idle,other_key]);
+        assert_eq!(keymap.conflicts().len(), 2);
+    }
+
+    #[test]
+    fn skipping_unknown_commands() {
+        let registry = command::Registry::create(Logger::new("test"));
+        let docs     = vec![command::EndpointDocs::new("a","Command A")];
+        registry.command_docs.borrow_mut().insert("A".into(),docs);
+        let mut keymap = Keymap::from_str("press a : A.a\npress b : A.b\nunbind B.a").unwrap();
+        assert_eq!(keymap.validate(&registry).len(), 2);
+        let errors = keymap.retain_valid(&registry);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(keymap.to_string(), "press a : A.a\n");
+        assert!(keymap.validate(&registry).is_empty());
+    }
+
+    #[test]
+    fn overriding_keymap() {
+        let global  = Keymap::from_str("press a : A.a\npress b : A.b").unwrap();
+        let project = Keymap::from_str("press c : A.b\nunbind A.c").unwrap();
+        let merged  = global.overridden_by(project);
+        let printed = merged.to_string();
+        assert_eq!(printed, "press a : A.a\npress c : A.b\nunbind A.c\n");
+    }
+}
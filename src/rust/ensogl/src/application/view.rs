@@ -75,7 +75,8 @@ impl Registry {
         self.command_registry.register::<V>();
     }
 
-    /// New view constructor.
+    /// New view constructor. The created view becomes the focused instance of its command
+    /// provider, unless some other alive view is focused already.
     #[allow(clippy::new_ret_no_self)]
     pub fn new<V:View>(&self) -> V {
         let label          = V::label();
@@ -89,6 +90,9 @@ impl Registry {
         }
         let view = V::new(&self.display);
         self.command_registry.register_instance(&view);
+        if !self.command_registry.has_focus() {
+            self.command_registry.set_focus(&view);
+        }
         view
     }
 }
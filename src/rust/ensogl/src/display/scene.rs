@@ -749,13 +749,15 @@ impl Views {
 // === FRP ===
 // ===========
 
-/// FRP Scene interface.
+/// FRP Scene interface. The `clock` is ticked on every frame and should be used to drive the
+/// time-based FRP nodes, like `debounce` or `per_frame`.
 #[derive(Clone,CloneRef,Debug)]
 pub struct Frp {
     pub network        : frp::Network,
     pub shape          : frp::Sampler<Shape>,
     pub camera_changed : frp::Stream,
     pub frame_time     : frp::Stream<f32>,
+    pub clock          : frp::time::Clock,
 
     camera_changed_source : frp::Source,
     frame_time_source     : frp::Source<f32>,
@@ -771,7 +773,9 @@ impl Frp {
         let shape          = shape.clone_ref();
         let camera_changed = camera_changed_source.clone_ref().into();
         let frame_time     = frame_time_source.clone_ref().into();
-        Self {network,shape,camera_changed,frame_time,camera_changed_source,frame_time_source}
+        let clock          = default();
+        Self {network,shape,camera_changed,frame_time,clock,camera_changed_source
+             ,frame_time_source}
     }
 }
 
@@ -185,9 +185,10 @@ impl StyleWatch {
         *self.handles.borrow_mut() = default();
     }
 
-    /// Queries style sheet value for a value.
+    /// Queries style sheet value for a value. The value is read through a static var, so it is
+    /// not animated when switching themes (see `style::theme`).
     pub fn get(&self, path:&str) -> Option<style::Data> {
-        let var      = self.sheet.var(path);
+        let var      = self.sheet.static_var(path);
         let value    = var.value();
         let callback = self.callback.clone_ref();
         var.on_change(move |_:&Option<style::Data>| (callback.borrow())());
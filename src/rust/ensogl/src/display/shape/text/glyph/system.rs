@@ -86,12 +86,16 @@ impl display::Object for Glyph {
 /// changing text, and for best performance it re-uses the created Glyphs (what means the specific
 /// buffer space). Therefore you can set a cap for line length by using the `set_fixed_capacity`
 /// method.
+///
+/// Each glyph is displayed in the line's font color, unless a specific color was set for it with
+/// `set_text_with_colors`.
 #[derive(Clone,CloneRef,Debug)]
 pub struct Line {
     display_object : display::object::Instance,
     glyph_system   : GlyphSystem,
     content        : Rc<RefCell<String>>,
     glyphs         : Rc<RefCell<Vec<Glyph>>>,
+    glyph_colors   : Rc<RefCell<Vec<Option<color::Rgba>>>>,
     font_color     : Rc<Cell<color::Rgba>>,
     font_size      : Rc<Cell<f32>>,
     fixed_capacity : Rc<Cell<Option<usize>>>,
@@ -107,13 +111,22 @@ impl Line {
         let font_color     = Rc::new(Cell::new(color::Rgba::new(0.0,0.0,0.0,1.0)));
         let content        = default();
         let glyphs         = default();
+        let glyph_colors   = default();
         let fixed_capacity = default();
-        Line {display_object,glyph_system,glyphs,font_size,font_color,content,fixed_capacity}
+        Line {display_object,glyph_system,glyphs,glyph_colors,font_size,font_color,content
+             ,fixed_capacity}
     }
 
     /// Replace currently visible text.
     pub fn set_text<S:Into<String>>(&self, content:S) {
-        *self.content.borrow_mut() = content.into();
+        self.set_text_with_colors(content,default());
+    }
+
+    /// Replace currently visible text, setting a color for each character. Characters with `None`
+    /// color, or without any color specified, are displayed in the font color.
+    pub fn set_text_with_colors<S:Into<String>>(&self, content:S, colors:Vec<Option<color::Rgba>>) {
+        *self.content.borrow_mut()      = content.into();
+        *self.glyph_colors.borrow_mut() = colors;
         self.redraw();
     }
 }
@@ -124,10 +137,12 @@ impl Line {
 #[allow(missing_docs)]
 impl Line {
     pub fn set_font_color<C:Into<color::Rgba>>(&self, color:C) {
-        let color = color.into();
+        let color        = color.into();
+        let glyph_colors = self.glyph_colors.borrow();
         self.font_color.set(color);
-        for glyph in &*self.glyphs.borrow() {
-            glyph.color().set(color.into());
+        for (index,glyph) in self.glyphs.borrow().iter().enumerate() {
+            let glyph_color = glyph_colors.get(index).copied().flatten().unwrap_or(color);
+            glyph.color().set(glyph_color.into());
         }
     }
 
@@ -202,17 +217,19 @@ impl Line {
         let chars       = content.chars();
         let pen         = PenIterator::new(font_size,chars,font);
         let content_len = content.len();
-        let color       = self.font_color.get().into();
+        let font_color  = self.font_color.get();
+        let colors      = self.glyph_colors.borrow();
 
-        for (glyph,(chr,x_offset)) in self.glyphs.borrow().iter().zip(pen) {
+        for (index,(glyph,(chr,x_offset))) in self.glyphs.borrow().iter().zip(pen).enumerate() {
             let glyph_info   = self.glyph_system.font.get_glyph_info(chr);
             let size         = glyph_info.scale.scale(font_size);
             let glyph_offset = glyph_info.offset.scale(font_size);
             let glyph_x      = x_offset + glyph_offset.x;
             let glyph_y      = glyph_offset.y;
             glyph.set_position(Vector3::new(glyph_x,glyph_y,0.0));
+            let color        = colors.get(index).copied().flatten().unwrap_or(font_color);
             glyph.set_glyph(chr);
-            glyph.color().set(color);
+            glyph.color().set(color.into());
             glyph.size.set(size);
         }
 
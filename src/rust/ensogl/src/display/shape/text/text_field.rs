@@ -5,15 +5,20 @@
 pub mod content;
 pub mod cursor;
 pub mod frp;
+pub mod highlight;
+pub mod history;
 pub mod render;
+pub mod search;
 pub mod word_occurrence;
 
 use crate::prelude::*;
 
+use crate::control::callback;
 use crate::data::color;
 use crate::display::object::traits::*;
 use crate::display::shape::text::glyph::font;
 use crate::display::shape::text::text_field::content::location::TextLocationChange;
+use crate::display::shape::text::text_field::content::DirtyLines;
 use crate::display::shape::text::text_field::content::TextFieldContent;
 use crate::display::shape::text::text_field::cursor::Cursor;
 use crate::display::shape::text::text_field::cursor::CursorId;
@@ -21,17 +26,26 @@ use crate::display::shape::text::text_field::cursor::CursorNavigation;
 use crate::display::shape::text::text_field::cursor::Cursors;
 use crate::display::shape::text::text_field::cursor::Step;
 use crate::display::shape::text::text_field::frp::TextFieldFrp;
+use crate::display::shape::text::text_field::highlight::Highlighting;
+use crate::display::shape::text::text_field::history::Edit;
+use crate::display::shape::text::text_field::history::EditKind;
+use crate::display::shape::text::text_field::history::History;
 use crate::display::shape::text::text_field::render::assignment::GlyphLinesAssignmentUpdate;
 use crate::display::shape::text::text_field::render::TextFieldSprites;
+use crate::display::shape::text::text_field::search::InvalidPattern;
+use crate::display::shape::text::text_field::search::Query;
+use crate::display::shape::text::text_field::search::Search;
 use crate::display::shape::text::text_field::word_occurrence::WordOccurrences;
 use crate::display::world::World;
 use crate::display;
+use crate::system::web;
 use crate::system::web::text_input::KeyboardBinding;
 
 use data::text::TextChange;
 use data::text::TextLocation;
 use nalgebra::Vector2;
 use nalgebra::Vector3;
+use std::ops::Range;
 
 
 
@@ -39,17 +53,20 @@ use nalgebra::Vector3;
 // === Focus Manager ===
 // =====================
 
+/// Tracks which TextField receives the keyboard input. At most one field is focused at a time.
 #[derive(Clone,CloneRef,Debug)]
 pub struct FocusManager {
-    binding    : Rc<RefCell<KeyboardBinding>>,
-    focused_on : Rc<CloneCell<Option<WeakTextField>>>,
+    binding         : Rc<RefCell<KeyboardBinding>>,
+    focused_on      : Rc<CloneCell<Option<WeakTextField>>>,
+    on_focus_change : callback::SharedRegistryMut1<bool>,
 }
 
 impl FocusManager {
     pub fn new_with_js_handlers() -> Self {
         FocusManager {
-            binding    : Rc::new(RefCell::new(KeyboardBinding::create())),
-            focused_on : default()
+            binding         : Rc::new(RefCell::new(KeyboardBinding::create())),
+            focused_on      : default(),
+            on_focus_change : default(),
         }
     }
 
@@ -57,13 +74,39 @@ impl FocusManager {
         let current = self.focused_on.get().and_then(|ptr| ptr.upgrade());
         let already_focused = current.as_ref().map_or(false, |ptr| (ptr.identity_equals(text_field)));
         if !already_focused {
+            let had_focus = current.is_some();
             current.for_each(|current| current.on_defocus());
             let tf_ref = text_field.rc.borrow();
             let frp    = &tf_ref.frp.as_ref().unwrap().keyboard;
             frp.bind_frp_to_js_text_input_actions(&mut self.binding.borrow_mut());
-            self.focused_on.set(Some(text_field.downgrade()))
+            self.focused_on.set(Some(text_field.downgrade()));
+            if !had_focus {
+                self.on_focus_change.run_all(&true);
+            }
+        }
+    }
+
+    /// Removes the focus from the given field, if it is focused. No field receives the keyboard
+    /// input afterwards.
+    pub fn clear_focus_of(&self, text_field:&TextField) {
+        let current = self.focused_on.get().and_then(|ptr| ptr.upgrade());
+        if current.map_or(false, |ptr| ptr.identity_equals(text_field)) {
+            text_field.on_defocus();
+            self.binding.borrow_mut().clear_text_input_handlers();
+            self.focused_on.set(None);
+            self.on_focus_change.run_all(&false);
         }
+    }
 
+    /// Checks if any TextField is focused.
+    pub fn is_any_focused(&self) -> bool {
+        self.focused_on.get().and_then(|ptr| ptr.upgrade()).is_some()
+    }
+
+    /// Registers a callback called with `true` when some field gains the focus while none was
+    /// focused, and with `false` when the focus is cleared.
+    pub fn on_focus_change<F:FnMut(&bool)+'static>(&self, callback:F) -> callback::Handle {
+        self.on_focus_change.add(callback)
     }
 }
 
@@ -114,10 +157,12 @@ shared! { TextField
         properties       : TextFieldProperties,
         content          : TextFieldContent,
         cursors          : Cursors,
+        history          : History,
         rendered         : TextFieldSprites,
         display_object   : display::object::Instance,
         frp              : Option<TextFieldFrp>,
         word_occurrences : Option<WordOccurrences>,
+        search           : Option<Search>,
         #[derivative(Debug="ignore")]
         text_change_callback : Option<Box<dyn FnMut(TextChange)>>,
         focus_manager        : FocusManager,
@@ -233,7 +278,7 @@ shared! { TextField
             let last_cursor      = self.cursors.last_cursor();
             let scroll_y         = self.scroll_position().y;
             let view_size        = self.size();
-            let current_line     = last_cursor.current_line(&mut self.content);
+            let current_line     = last_cursor.current_line(&self.content);
             let current_line_pos = current_line.y_position();
             let next_line_pos    = current_line_pos + current_line.height;
             let y_scrolling      = (scroll_y - next_line_pos + view_size.y).min(0.0);
@@ -260,8 +305,10 @@ shared! { TextField
         /// Whenever possible, tries to maintain cursor positions.
         pub fn set_content(&mut self, text:&str) {
             self.clear_word_occurrences();
+            self.history.clear();
             self.content.set_content(text);
             self.cursors.recalculate_positions(&self.content);
+            self.update_search_matches();
             self.assignment_update().update_after_text_edit();
             self.rendered.update_glyphs(&mut self.content);
             self.rendered.update_cursor_sprites(&self.cursors, &mut self.content,self.focused);
@@ -324,6 +371,49 @@ shared! { TextField
             }
         }
 
+        /// Start searching for the given query, highlighting all its matches. Returns the number
+        /// of matches found.
+        pub fn search(&mut self, query:Query) -> Result<usize,InvalidPattern> {
+            let search  = Search::new(query,&self.content)?;
+            let count   = search.matches().len();
+            self.search = Some(search);
+            self.redraw_search_matches();
+            Ok(count)
+        }
+
+        /// Stop searching, removing the highlights of matches.
+        pub fn clear_search(&mut self) {
+            self.search = None;
+            self.redraw_search_matches();
+        }
+
+        /// All matches of the current search.
+        pub fn search_matches(&self) -> Vec<Range<TextLocation>> {
+            self.search.as_ref().map_or(default(), |search| search.matches().to_vec())
+        }
+
+        /// Select the next match of the current search after the last cursor.
+        pub fn find_next(&mut self) {
+            let location = self.cursors.last_cursor().selection_range().end;
+            let found    = self.search.as_mut().and_then(|search| search.next_after(location));
+            found.for_each(|found| self.select_found(&found));
+        }
+
+        /// Select the previous match of the current search before the last cursor.
+        pub fn find_previous(&mut self) {
+            let location = self.cursors.last_cursor().selection_range().start;
+            let found    = self.search.as_mut().and_then(|search| search.previous_before(location));
+            found.for_each(|found| self.select_found(&found));
+        }
+
+        fn select_found(&mut self, found:&Range<TextLocation>) {
+            self.clear_word_occurrences();
+            self.cursors.finish_multicursor_mode();
+            self.cursors.last_cursor_mut().select_range(found);
+            self.adjust_view();
+            self.rendered.update_cursor_sprites(&self.cursors,&mut self.content,self.focused);
+        }
+
         /// Check if given point on screen is inside this TextField.
         pub fn is_inside(&self, point:Vector2<f32>) -> bool {
             let position = self.display_object.global_position();
@@ -333,15 +423,37 @@ shared! { TextField
             x_range.contains(&point.x) && y_range.contains(&point.y)
         }
 
+        /// Check if there is any edit which can be undone.
+        pub fn can_undo(&self) -> bool {
+            self.history.can_undo()
+        }
+
+        /// Check if there is any undone edit which can be redone.
+        pub fn can_redo(&self) -> bool {
+            self.history.can_redo()
+        }
+
+        /// Prevent the next edit from being grouped with the previous one in the undo history.
+        pub fn close_undo_group(&mut self) {
+            self.history.close_group();
+        }
+
         /// Set text edit callback.
         ///
         /// This callback will be called once per `write` function call and all functions using it.
-        /// That's include all edits being an effect of keyboard or mouse event.
+        /// That's include all edits being an effect of keyboard or mouse event, and also undoing
+        /// and redoing them.
         pub fn set_text_edit_callback<Callback:FnMut(TextChange) + 'static>
         (&mut self, callback:Callback) {
             self.text_change_callback = Some(Box::new(callback))
         }
 
+        /// Set the syntax highlighting of the content, or disable it by passing `None`.
+        pub fn set_highlighting(&mut self, highlighting:Option<Highlighting>) {
+            self.content.set_highlighting(highlighting);
+            self.rendered.update_glyphs(&mut self.content);
+        }
+
         fn on_defocus(&mut self) {
             self.focused = false;
             self.rendered.update_cursor_sprites(&self.cursors,&mut self.content,self.focused);
@@ -374,6 +486,12 @@ impl TextField {
         focus_manager.set_focus_on(&self);
         self.with_borrowed(|data| data.focused = true);
     }
+
+    /// Removes the focus from this field, so it no longer receives the keyboard input.
+    pub fn lose_focus(&self) {
+        let focus_manager = self.with_borrowed(|data| data.focus_manager.clone_ref());
+        focus_manager.clear_focus_of(&self);
+    }
 }
 
 
@@ -392,22 +510,85 @@ impl TextField {
         // content. Such situation we call here Line Per Cursor Edit.
         let is_line_per_cursor_edit = trimmed.contains('\n') && cursor_ids.len() > 1;
 
+        let kind           = self.with_borrowed(|this| this.edit_kind(text));
+        let cursors_before = self.with_borrowed(|this| this.cursors.cursors.clone());
+
         if is_line_per_cursor_edit {
             let cursor_with_line = cursor_ids.iter().cloned().zip(trimmed.split('\n'));
-            self.write_per_cursor(cursor_with_line);
+            self.write_per_cursor(cursor_with_line,kind,cursors_before);
         } else {
             let cursor_with_line = cursor_ids.iter().map(|cursor_id| (*cursor_id,text));
-            self.write_per_cursor(cursor_with_line);
+            self.write_per_cursor(cursor_with_line,kind,cursors_before);
         };
-        self.with_borrowed(|this| {
-            this.clear_word_occurrences();
-            // TODO[ao] updates should be done only in one place and only once per frame
-            // see https://github.com/luna/ide/issues/178
-            this.assignment_update().update_after_text_edit();
-            this.adjust_view();
-            this.rendered.update_glyphs(&mut this.content);
-            this.rendered.update_cursor_sprites(&this.cursors, &mut this.content, this.focused);
+        self.with_borrowed(|this| this.update_after_text_edit());
+    }
+
+    /// Replace the match of the current search selected by the cursor, and select the next one.
+    ///
+    /// For regex searches, the `replacement` may refer to the captured groups, see
+    /// `Search::replacement_for`.
+    pub fn replace_current(&self, replacement:&str) {
+        let text = self.with_borrowed(|this| {
+            let single_cursor = this.cursors.cursors.len() == 1;
+            let selection     = this.cursors.last_cursor().selection_range();
+            let search        = this.search.as_ref().filter(|s| s.is_match(&selection));
+            let search        = search.filter(|_| single_cursor);
+            search.map(|search| search.replacement_for(&this.content,&selection,replacement))
+        });
+        if let Some(text) = text {
+            self.write(&text);
+        }
+        self.find_next();
+    }
+
+    /// Replace all matches of the current search. All replacements are a single step in the undo
+    /// history.
+    pub fn replace_all(&self, replacement:&str) {
+        let changes = self.with_borrowed(|this| {
+            let search  = this.search.as_ref();
+            let matches = search.map_or(default(), |search| search.matches().to_vec());
+            // The replacements are applied from the content end, so the locations of the
+            // remaining matches stay valid.
+            matches.into_iter().rev().map(|found| {
+                let text = search.map_or(default(), |s| {
+                    s.replacement_for(&this.content,&found,replacement)
+                });
+                content::Change::replace(found,&text)
+            }).collect_vec()
+        });
+        if let Some(first) = changes.last().map(|change| change.replaced.start) {
+            let cursors_before = self.with_borrowed(|this| this.cursors.cursors.clone());
+            let mut edit       = Edit::new(EditKind::Other,cursors_before,web::performance().now());
+            self.apply_changes_notifying(changes, |this,change| {
+                this.apply_recorded_change(&mut edit,change)
+            });
+            self.with_borrowed(|this| {
+                this.cursors.set_cursor(first);
+                edit.cursors_after = this.cursors.cursors.clone();
+                this.history.record(edit);
+                this.update_after_text_edit();
+            });
+        }
+    }
+
+    /// Revert the last edit (or group of consecutive typing), restoring cursors from before it.
+    pub fn undo(&self) {
+        let step = self.with_borrowed(|this| {
+            this.history.undo().map(|edit| (edit.inverse.clone(),edit.cursors_before.clone()))
+        });
+        if let Some((changes,cursors)) = step {
+            self.apply_history_step(changes,cursors);
+        }
+    }
+
+    /// Make again the last undone edit, restoring cursors from after it.
+    pub fn redo(&self) {
+        let step = self.with_borrowed(|this| {
+            this.history.redo().map(|edit| (edit.changes.clone(),edit.cursors_after.clone()))
         });
+        if let Some((changes,cursors)) = step {
+            self.apply_history_step(changes,cursors);
+        }
     }
 
     /// Remove all text selected by all cursors.
@@ -419,16 +600,24 @@ impl TextField {
     ///
     /// For cursors with selection it will just remove the selected text. For the rest, it will
     /// remove all content covered by `step`.
+    ///
+    /// The cursors are recorded in history as they were before selecting the removed content, so
+    /// consecutive deletions are grouped into one undo step.
     pub fn do_delete_operation(&self, step:Step) {
         let text_field_size = self.size();
-        self.with_borrowed(|this| {
+        let cursors_before  = self.with_borrowed(|this| {
+            let cursors_before    = this.cursors.cursors.clone();
             let content           = &mut this.content;
             let selecting         = true;
             let mut navigation    = CursorNavigation{selecting,content,text_field_size};
             let without_selection = |c:&Cursor| !c.has_selection();
             this.cursors.navigate_cursors(&mut navigation,step,without_selection);
+            cursors_before
         });
-        self.remove_selection();
+        let cursor_ids = self.with_borrowed(|this| this.cursors.sorted_cursor_indices());
+        let removals   = cursor_ids.iter().map(|cursor_id| (*cursor_id,""));
+        self.write_per_cursor(removals,EditKind::Deleting,cursors_before);
+        self.with_borrowed(|this| this.update_after_text_edit());
     }
 }
 
@@ -437,14 +626,36 @@ impl TextField {
 
 impl TextField {
 
-    fn write_per_cursor<'a,It>(&self, text_per_cursor:It)
+    fn write_per_cursor<'a,It>(&self, text_per_cursor:It, kind:EditKind, cursors_before:Vec<Cursor>)
         where It : Iterator<Item=(CursorId,&'a str)> {
         let mut location_change = TextLocationChange::default();
-        let mut opt_callback    = self.with_borrowed(|this| std::mem::take(&mut this.text_change_callback));
-        for (cursor_id,to_insert) in text_per_cursor {
-            let notification = self.with_borrowed(|this| {
-                this.apply_one_cursor_change(&mut location_change,cursor_id,to_insert)
-            });
+        let mut edit            = Edit::new(kind,cursors_before,web::performance().now());
+        self.apply_changes_notifying(text_per_cursor, |this,(cursor_id,to_insert)| {
+            this.apply_one_cursor_change(&mut location_change,&mut edit,cursor_id,to_insert)
+        });
+        self.with_borrowed(|this| {
+            edit.cursors_after = this.cursors.cursors.clone();
+            this.history.record(edit);
+        });
+    }
+
+    fn apply_history_step(&self, changes:Vec<content::Change>, cursors:Vec<Cursor>) {
+        self.apply_changes_notifying(changes, |this,change| this.apply_history_change(change));
+        self.with_borrowed(|this| {
+            this.cursors.cursors = cursors;
+            this.history.close_group();
+            this.update_after_text_edit();
+        });
+    }
+
+    /// Apply changes one by one, passing the notification returned by `apply` for each of them to
+    /// the `text_change_callback`. The callback is called without the TextField being borrowed.
+    fn apply_changes_notifying<Items,F>(&self, items:Items, mut apply:F)
+    where Items : IntoIterator,
+          F     : FnMut(&mut TextFieldData, Items::Item) -> TextChange {
+        let mut opt_callback = self.with_borrowed(|this| this.text_change_callback.take());
+        for item in items {
+            let notification = self.with_borrowed(|this| apply(this,item));
             if let Some(callback) = opt_callback.as_mut() {
                 callback(notification);
             }
@@ -463,16 +674,18 @@ impl TextFieldData {
         let display_object       = display::object::Instance::new(logger);
         let content              = TextFieldContent::new(initial_content,&properties);
         let cursors              = Cursors::default();
+        let history              = History::default();
         let rendered             = TextFieldSprites::new(world,&properties);
         let frp                  = None;
         let word_occurrences     = None;
+        let search               = None;
         let text_change_callback = None;
         let focus_manager        = world.text_field_focus_manager().clone_ref();
         let focused              = false;
         display_object.add_child(&rendered);
 
-        Self {properties,content,cursors,rendered,display_object,frp,word_occurrences,
-              text_change_callback,focus_manager,focused}.initialize()
+        Self {properties,content,cursors,history,rendered,display_object,frp,word_occurrences,
+              search,text_change_callback,focus_manager,focused}.initialize()
     }
 
     fn initialize(mut self) -> Self {
@@ -491,21 +704,88 @@ impl TextFieldData {
         }
     }
 
-    /// Applies change for one cursor, updating its position, and returns struct which should be
-    /// passed to `text_change_callback`.
+    fn update_after_text_edit(&mut self) {
+        self.clear_word_occurrences();
+        self.update_search_matches();
+        // TODO[ao] updates should be done only in one place and only once per frame
+        // see https://github.com/luna/ide/issues/178
+        self.assignment_update().update_after_text_edit();
+        self.adjust_view();
+        self.rendered.update_glyphs(&mut self.content);
+        self.rendered.update_cursor_sprites(&self.cursors, &mut self.content, self.focused);
+    }
+
+    /// Find again the search matches in the lines modified since the last rendering.
+    fn update_search_matches(&mut self) {
+        let dirty = std::mem::take(&mut self.content.dirty_lines);
+        if let Some(search) = &mut self.search {
+            search.update(&self.content,&dirty);
+        }
+        let matches = self.search.as_ref().map_or(&[][..], |search| search.matches());
+        self.rendered.update_match_sprites(matches,&dirty,&mut self.content);
+        self.content.dirty_lines = dirty;
+    }
+
+    /// Draw again the highlights of all search matches.
+    fn redraw_search_matches(&mut self) {
+        let matches = self.search.as_ref().map_or(&[][..], |search| search.matches());
+        self.rendered.update_match_sprites(matches,&DirtyLines::all(),&mut self.content);
+    }
+
+    /// The kind of edit made by writing `text`, used for grouping edits in history.
+    fn edit_kind(&self, text:&str) -> EditKind {
+        let mut chars      = text.chars();
+        let is_single_char = chars.next().map_or(false, |c| c != '\n') && chars.next().is_none();
+        let has_selection  = self.cursors.cursors.iter().any(|cursor| cursor.has_selection());
+        if text.is_empty() {
+            EditKind::Deleting
+        } else if is_single_char && !has_selection {
+            EditKind::Typing
+        } else {
+            EditKind::Other
+        }
+    }
+
+    /// Applies change for one cursor, updating its position and recording it in `edit`, and
+    /// returns struct which should be passed to `text_change_callback`.
     fn apply_one_cursor_change
-    (&mut self, location_change:&mut TextLocationChange, cursor_id:CursorId, to_insert:&str)
-    -> TextChange {
+    ( &mut self
+    , location_change : &mut TextLocationChange
+    , edit            : &mut Edit
+    , cursor_id       : CursorId
+    , to_insert       : &str
+    ) -> TextChange {
         let CursorId(id)   = cursor_id;
         let cursor         = &mut self.cursors.cursors[id];
         let replaced       = location_change.apply_to_range(cursor.selection_range());
         let replaced_chars = self.content.convert_location_range_to_char_index(&replaced);
+        let replaced_text  = self.content.copy_fragment(replaced.clone());
         let change         = content::Change::replace(replaced,to_insert);
+        let inverse        = content::Change::replace(change.inserted_text_range(),&replaced_text);
         location_change.add_change(&change);
         *cursor = Cursor::new(change.inserted_text_range().end);
+        edit.push(change.clone(),inverse);
         self.content.apply_change(change);
         TextChange::replace(replaced_chars, to_insert.to_string())
     }
+
+    /// Applies change, recording it in `edit` together with its inverse, and returns struct which
+    /// should be passed to `text_change_callback`.
+    fn apply_recorded_change(&mut self, edit:&mut Edit, change:content::Change) -> TextChange {
+        let replaced_text = self.content.copy_fragment(change.replaced.clone());
+        let inverse       = content::Change::replace(change.inserted_text_range(),&replaced_text);
+        edit.push(change.clone(),inverse);
+        self.apply_history_change(change)
+    }
+
+    /// Applies change from the edit history and returns struct which should be passed to
+    /// `text_change_callback`. Cursors are not updated, as they are restored from history.
+    fn apply_history_change(&mut self, change:content::Change) -> TextChange {
+        let replaced_chars = self.content.convert_location_range_to_char_index(&change.replaced);
+        let inserted       = change.inserted_string();
+        self.content.apply_change(change);
+        TextChange::replace(replaced_chars,inserted)
+    }
 }
 
 // === Display Object ===
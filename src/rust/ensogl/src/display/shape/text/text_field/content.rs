@@ -1,12 +1,16 @@
 //! Module with all structures describing the content of the TextField.
 pub mod line;
 pub mod location;
+pub mod rope;
 
 use crate::prelude::*;
 
+use crate::data::color;
 use crate::display::shape::text::glyph::font;
 use crate::display::shape::text::text_field::content::line::Line;
 use crate::display::shape::text::text_field::content::line::LineFullInfo;
+use crate::display::shape::text::text_field::content::rope::Rope;
+use crate::display::shape::text::text_field::highlight::Highlighting;
 use crate::display::shape::text::text_field::TextFieldProperties;
 
 use data::text;
@@ -46,6 +50,13 @@ impl Default for DirtyLines {
 }
 
 impl DirtyLines {
+    /// `DirtyLines` where all lines are dirty.
+    pub fn all() -> Self {
+        let single_lines = HashSet::new();
+        let range        = Some(0..);
+        Self {single_lines,range}
+    }
+
     /// Mark single line as dirty.
     pub fn add_single_line(&mut self, index:usize) {
         self.single_lines.insert(index);
@@ -94,7 +105,7 @@ pub enum ChangeType {
 }
 
 /// A type representing change applied on TextFieldContent.
-#[derive(Debug,Shrinkwrap)]
+#[derive(Clone,Debug,Shrinkwrap)]
 #[shrinkwrap(mutable)]
 pub struct Change(pub TextChangeTemplate<TextLocation,Vec<Vec<char>>>);
 
@@ -166,9 +177,9 @@ pub struct TextFieldContent {
     /// Line height in pixels, being a distance between baselines of consecutive lines.
     pub line_height: f32,
     /// Lines being the actual content.
-    lines: Vec<Line>,
-    /// This field caches the position of beginning of each line.
-    line_offsets: Vec<usize>,
+    lines: Rope,
+    /// Syntax highlighting of lines, if enabled.
+    highlighting: Option<Highlighting>,
 }
 
 impl TextFieldContent {
@@ -181,7 +192,7 @@ impl TextFieldContent {
             lines        : split_to_lines(text).map(Line::new).collect(),
             dirty_lines  : DirtyLines::default(),
             font         : properties.font.clone_ref(),
-            line_offsets : Vec::new(),
+            highlighting : None,
         }
     }
 
@@ -204,7 +215,7 @@ impl TextFieldContent {
             output.extend(first_line.chars()[fragment.start.column..].iter().cloned());
             output.extend(std::iter::once('\n'));
             let whole_lines = (fragment.start.line+1)..fragment.end.line;
-            for line in &self.lines[whole_lines] {
+            for line in whole_lines.map(|index| &self.lines[index]) {
                 output.extend(line.chars().iter().cloned());
                 output.extend(std::iter::once('\n'));
             }
@@ -217,23 +228,24 @@ impl TextFieldContent {
     ///
     /// It's a little bit quicker than getting specific line by `line` method, because it don't
     /// need to copy any additional data.
-    pub fn lines(&self) -> &[Line] {
-        self.lines.as_slice()
+    pub fn lines(&self) -> &Rope {
+        &self.lines
     }
 
-    /// A mutablie `lines` accessor.
+    /// A mutable `lines` accessor.
     ///
-    /// Please be noted, that this invalidate some cached data.
-    pub fn lines_mut(&mut self) -> &mut Vec<Line> {
-        self.line_offsets.clear();
+    /// The rope keeps its cached summaries up to date on every modification, but marking the
+    /// changed lines as dirty is up to the caller.
+    pub fn lines_mut(&mut self) -> &mut Rope {
         &mut self.lines
     }
 
     /// Get a handy wrapper for line under index.
-    pub fn line(&mut self, index:usize) -> LineFullInfo {
+    pub fn line(&self, index:usize) -> LineFullInfo {
+        let line = self.lines.get(index);
         LineFullInfo {
             height  : self.line_height,
-            line    : &mut self.lines[index],
+            line    : line.unwrap_or_else(|| panic!("Line index {} out of bounds.",index)),
             line_id : index,
             font    : self.font.clone_ref(),
         }
@@ -242,12 +254,36 @@ impl TextFieldContent {
     /// Replaces content with a new text. This marks all lines as dirty.
     pub fn set_content(&mut self, text:&str) {
         self.lines = split_to_lines(text).map(Line::new).collect();
-        self.line_offsets.clear();
         self.dirty_lines.add_lines_range_from(0..);
     }
 
+    /// Set the syntax highlighting. This marks all lines as dirty.
+    pub fn set_highlighting(&mut self, highlighting:Option<Highlighting>) {
+        self.highlighting = highlighting;
+        for index in 0..self.lines.len() {
+            self.lines.update(index,|line| line.set_spans(None));
+        }
+        self.dirty_lines.add_lines_range_from(0..);
+    }
+
+    /// Get colors of characters in given columns of line, or `None` if highlighting is disabled.
+    ///
+    /// The line is highlighted again if it was modified since the last call.
+    pub fn line_colors(&mut self, index:usize, columns:Range<usize>)
+    -> Option<Vec<Option<color::Rgba>>> {
+        let highlighting = self.highlighting.as_ref()?;
+        if self.lines.get(index)?.spans().is_none() {
+            self.lines.update(index,|line| {
+                let spans = highlighting.highlighter.highlight_line(line.chars());
+                line.set_spans(Some(spans));
+            });
+        }
+        let spans = self.lines.get(index)?.spans()?;
+        Some(highlighting.colors(spans,columns))
+    }
+
     /// Get the nearest line from the point on the screen.
-    pub fn line_location_at_point(&mut self, point:Vector2<f32>) -> usize {
+    pub fn line_location_at_point(&self, point:Vector2<f32>) -> usize {
         let line_opt = self.line_at_y_position(point.y);
         let line     = match line_opt {
             Some(line)             => line,
@@ -258,8 +294,8 @@ impl TextFieldContent {
     }
 
     /// Get the nearest column from te point on the screen.
-    pub fn column_location_at_point(&mut self, line:usize, point:Vector2<f32>) -> usize {
-        let mut line   = self.line(line);
+    pub fn column_location_at_point(&self, line:usize, point:Vector2<f32>) -> usize {
+        let line       = self.line(line);
         let column_opt = line.find_char_at_x_position(point.x);
         match column_opt {
             Some(column)           => column,
@@ -269,14 +305,14 @@ impl TextFieldContent {
     }
 
     /// Get the nearest text location from the point on the screen.
-    pub fn location_at_point(&mut self, point:Vector2<f32>) -> TextLocation {
+    pub fn location_at_point(&self, point:Vector2<f32>) -> TextLocation {
         let line   = self.line_location_at_point(point);
         let column = self.column_location_at_point(line,point);
         TextLocation{line,column}
     }
 
     /// Get the index of line which is displayed at given y screen coordinate.
-    pub fn line_at_y_position(&mut self, y:f32) -> Option<LineFullInfo> {
+    pub fn line_at_y_position(&self, y:f32) -> Option<LineFullInfo> {
         let index    = -(y / self.line_height).ceil();
         let is_valid = index >= 0.0 && index < self.lines.len() as f32;
         let index    = is_valid.and_option_from(|| Some(index as usize));
@@ -284,25 +320,20 @@ impl TextFieldContent {
     }
 
     /// Converts location in this text represented by `row:column` pair to absolute char's position
-    /// from document begin. Panics if location is invalid for current content.
-    pub fn convert_location_to_char_index(&mut self, location:TextLocation) -> text::Index {
-        if self.line_offsets.is_empty() {
-            self.line_offsets.push(0);
-        }
-        if self.line_offsets.len() <= location.line {
-            let required_len           = location.line + 1;
-            let mut current_char_index = *self.line_offsets.last().unwrap();
-            for line in self.lines.iter().take(required_len).skip(self.line_offsets.len()-1) {
-                current_char_index += line.len() + 1;
-                self.line_offsets.push(current_char_index);
-            }
-        }
-        text::Index::new(self.line_offsets[location.line] + location.column)
+    /// from document begin.
+    pub fn convert_location_to_char_index(&self, location:TextLocation) -> text::Index {
+        text::Index::new(self.lines.char_index_of_line(location.line) + location.column)
+    }
+
+    /// Converts absolute char's position from document begin to location in this text represented
+    /// by `row:column` pair. Positions past the end of text are converted to the end location.
+    pub fn convert_char_index_to_location(&self, index:text::Index) -> TextLocation {
+        self.lines.location_at_char_index(index.value)
     }
 
     /// Converts range of locations in this text represented by `row:column` pair to absolute
     /// char's position from document begin.
-    pub fn convert_location_range_to_char_index(&mut self, range:&Range<TextLocation>)
+    pub fn convert_location_range_to_char_index(&self, range:&Range<TextLocation>)
     -> Range<text::Index> {
         let start = self.convert_location_to_char_index(range.start);
         let end   = self.convert_location_to_char_index(range.end);
@@ -316,14 +347,10 @@ impl TextFieldContent {
 impl TextFieldContent {
     /// Apply change to content.
     pub fn apply_change(&mut self, change:Change) {
-        let first_modified_line = change.replaced.start.line;
         match change.change_type() {
             ChangeType::SingleLine => self.make_simple_change(change),
             ChangeType::MultiLine  => self.make_multiline_change(change),
         }
-        // Invalidate lines offsets
-        let first_offset_invalidated = first_modified_line + 1;
-        self.line_offsets.resize(first_offset_invalidated,default());
     }
 
     /// Apply many changes to content.
@@ -338,7 +365,9 @@ impl TextFieldContent {
         let empty_line  = default();
         let new_content = change.inserted.first().unwrap_or(&empty_line);
         let range       = change.replaced.start.column..change.replaced.end.column;
-        self.lines[line_index].modify().splice(range,new_content.iter().cloned());
+        self.lines.update(line_index,|line| {
+            line.modify().splice(range,new_content.iter().cloned());
+        });
         self.dirty_lines.add_single_line(line_index);
     }
 
@@ -349,7 +378,7 @@ impl TextFieldContent {
         let replaced_lines_count = end_line - start_line + 1;
         let inserted_lines_count = change.inserted.len();
         let inserted_lines       = change.inserted.drain(0..inserted_lines_count).map(Line::new_raw);
-        self.lines.splice(start_line..=end_line,inserted_lines);
+        self.lines.splice(start_line..end_line+1,inserted_lines);
         if replaced_lines_count != inserted_lines_count {
             self.dirty_lines.add_lines_range_from(start_line..);
         } else {
@@ -373,7 +402,7 @@ impl TextFieldContent {
     fn mix_first_edited_line_into_change(&self, change:&mut Change) {
         let first_line   = change.replaced.start.line;
         let replace_from = change.replaced.start.column;
-        let first_edited = &self.lines[first_line].chars();
+        let first_edited = self.lines[first_line].chars();
         let prefix       = &first_edited[..replace_from];
         change.inserted.first_mut().unwrap().splice(0..0, prefix.iter().cloned());
     }
@@ -381,7 +410,7 @@ impl TextFieldContent {
     fn mix_last_edited_line_into_change(&mut self, change:&mut Change) {
         let last_line    = change.replaced.end.line;
         let replace_to   = change.replaced.end.column;
-        let last_edited  = &self.lines[last_line].chars();
+        let last_edited  = self.lines[last_line].chars();
         let suffix       = &last_edited[replace_to..];
         change.inserted.last_mut().unwrap().extend_from_slice(suffix);
     }
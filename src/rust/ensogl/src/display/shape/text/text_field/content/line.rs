@@ -3,6 +3,7 @@ use crate::prelude::*;
 
 use crate::display::shape::text::glyph::font;
 use crate::display::shape::text::glyph::pen::PenIterator;
+use crate::display::shape::text::text_field::highlight::Span;
 
 use nalgebra::Vector2;
 use std::ops::Range;
@@ -18,11 +19,14 @@ use std::ops::Range;
 /// access to a concrete fragment of the line. Additionally, this structure keeps cache of x
 /// position of the char in a _text space_ (where value of 1.0 is equal to lines height). The cache
 /// is initially empty and is load on demand - so the `char_x_position` vector will be often shorter
-/// than number of characters in line.
+/// than number of characters in line. Similarly, the highlighted spans are computed on demand and
+/// dropped once the line is modified. The x position cache is filled through shared references,
+/// so the line may be measured without being borrowed mutably.
 #[derive(Clone,Debug)]
 pub struct Line {
     chars            : Vec<char>,
-    char_x_positions : Vec<f32>,
+    char_x_positions : RefCell<Vec<f32>>,
+    spans            : Option<Vec<Span>>,
 }
 
 impl Line {
@@ -34,7 +38,8 @@ impl Line {
     /// Create line from given characters vector.
     pub fn new_raw(chars:Vec<char>) -> Self {
         Line {chars,
-            char_x_positions : Vec::new(),
+            char_x_positions : default(),
+            spans            : None,
         }
     }
 
@@ -42,7 +47,8 @@ impl Line {
     pub fn empty() -> Self {
         Line{
             chars            : Vec::new(),
-            char_x_positions : Vec::new(),
+            char_x_positions : default(),
+            spans            : None,
         }
     }
 
@@ -62,11 +68,22 @@ impl Line {
     }
 
     /// Get the mutable reference to characters. Because we're allowing for modifications here,
-    /// the `chars_x_position` cache is cleared.
+    /// the `chars_x_position` cache and highlighted spans are cleared.
     pub fn modify(&mut self) -> &mut Vec<char> {
-        self.char_x_positions.clear();
+        self.char_x_positions.borrow_mut().clear();
+        self.spans = None;
         &mut self.chars
     }
+
+    /// Highlighted spans of this line, if they were computed since the last modification.
+    pub fn spans(&self) -> Option<&[Span]> {
+        self.spans.as_deref()
+    }
+
+    /// Set the highlighted spans of this line.
+    pub fn set_spans(&mut self, spans:Option<Vec<Span>>) {
+        self.spans = spans;
+    }
 }
 
 
@@ -87,11 +104,10 @@ impl ToString for Line {
 /// A structure wrapping line reference with information about line number and font. These
 /// information allows to get more information from line about chars position in rendered text.
 #[derive(Debug,Shrinkwrap)]
-#[shrinkwrap(mutable)]
 #[allow(missing_docs)]
 pub struct LineFullInfo<'a> {
     #[shrinkwrap(main_field)]
-    pub line    : &'a mut Line,
+    pub line    : &'a Line,
     pub line_id : usize,
     pub font    : font::Handle,
     pub height  : f32,
@@ -111,13 +127,13 @@ impl<'a> LineFullInfo<'a> {
     }
 
     /// Get x position of character with given index. The position is in _text space_.
-    pub fn get_char_x_position(&mut self, index:usize) -> f32 {
+    pub fn get_char_x_position(&self, index:usize) -> f32 {
         self.fill_chars_x_position_up_to(index);
-        self.char_x_positions[index]
+        self.char_x_positions.borrow()[index]
     }
 
     /// Get range of x coordinates containing the given character.
-    pub fn get_char_x_range(&mut self, index:usize) -> Range<f32> {
+    pub fn get_char_x_range(&self, index:usize) -> Range<f32> {
         let start   = self.get_char_x_position(index);
         let advance = self.font.get_glyph_info(self.chars[index]).advance * self.height;
         start..(start + advance)
@@ -126,7 +142,7 @@ impl<'a> LineFullInfo<'a> {
     /// Find the character occupying the given `x_position` in a _text space_. If there are two
     /// characters under this x coordinate (e.g. due to a kerning) the char on the left will be
     /// returned.
-    pub fn find_char_at_x_position(&mut self, x_position:f32)
+    pub fn find_char_at_x_position(&self, x_position:f32)
     -> Option<usize> {
         if self.chars.is_empty() {
             None
@@ -134,8 +150,8 @@ impl<'a> LineFullInfo<'a> {
             let comparator   = |f:&f32| f.partial_cmp(&x_position).unwrap();
             self.fill_chars_x_position_up_to_value(x_position);
             let last_index   = self.len() - 1;
-            let found        = self.char_x_positions.binary_search_by(comparator);
-            let mut in_range = || self.get_char_x_range(last_index).end >= x_position;
+            let found        = self.char_x_positions.borrow().binary_search_by(comparator);
+            let in_range     = || self.get_char_x_range(last_index).end >= x_position;
             match found {
                 Ok(index)                        => Some(index),
                 Err(0)                           => None,
@@ -147,31 +163,32 @@ impl<'a> LineFullInfo<'a> {
 
     /// Fill the `chars_x_position` cache so it will contain information about character with given
     /// index.
-    pub fn fill_chars_x_position_up_to(&mut self, index:usize) {
-        let baseline_start = self.baseline_start();
-        let new_len        = index + 1;
-        let from_index     = self.char_x_positions.len().saturating_sub(1);
-        let to_fill        = new_len.saturating_sub(self.char_x_positions.len());
-        let x_position     = self.char_x_positions.last().cloned().unwrap_or(baseline_start.x);
-        let line           = &mut self.line;
-        let chars          = line.chars[from_index..].iter().cloned();
-        let to_skip        = if line.char_x_positions.is_empty() {0} else {1};
-        let pen            = PenIterator::new(self.height,chars,self.font.clone_ref());
+    pub fn fill_chars_x_position_up_to(&self, index:usize) {
+        let baseline_start  = self.baseline_start();
+        let mut x_positions = self.char_x_positions.borrow_mut();
+        let new_len         = index + 1;
+        let from_index      = x_positions.len().saturating_sub(1);
+        let to_fill         = new_len.saturating_sub(x_positions.len());
+        let x_position      = x_positions.last().cloned().unwrap_or(baseline_start.x);
+        let chars           = self.chars[from_index..].iter().cloned();
+        let to_skip         = if x_positions.is_empty() {0} else {1};
+        let pen             = PenIterator::new(self.height,chars,self.font.clone_ref());
 
         for (_,x_offset) in pen.skip(to_skip).take(to_fill) {
-            line.char_x_positions.push(x_position + x_offset);
+            x_positions.push(x_position + x_offset);
         }
     }
 
     /// Fill the `chars_x_position` cache so it will contain information about character being
     /// under given `x_position`.
-    pub fn fill_chars_x_position_up_to_value(&mut self, x_position:f32) {
-        let last_cached    = self.char_x_positions.last();
-        let already_filled = last_cached.map_or(false, |cached| *cached >= x_position);
+    pub fn fill_chars_x_position_up_to_value(&self, x_position:f32) {
+        let last_cached    = self.char_x_positions.borrow().last().cloned();
+        let already_filled = last_cached.map_or(false, |cached| cached >= x_position);
         if !already_filled {
-            for index in self.char_x_positions.len()..self.chars.len() {
+            let cached_len = self.char_x_positions.borrow().len();
+            for index in cached_len..self.chars.len() {
                 self.fill_chars_x_position_up_to(index);
-                let current = self.char_x_positions[index];
+                let current = self.char_x_positions.borrow()[index];
                 if current >= x_position {
                     break;
                 }
@@ -192,19 +209,19 @@ mod test {
     #[wasm_bindgen_test(async)]
     async fn getting_chars_x_position() {
         ensogl_core_msdf_sys::initialized().await;
-        let mut line     = Line::new("ABA");
-        let mut line_ref = LineFullInfo {
-            line    : &mut line,
+        let line         = Line::new("ABA");
+        let line_ref     = LineFullInfo {
+            line    : &line,
             font    : prepare_font_with_ab(),
             line_id : 0,
             height  : 1.0,
         };
 
-        assert_eq!(0, line_ref.char_x_positions.len());
+        assert_eq!(0, line_ref.char_x_positions.borrow().len());
         let first_pos = line_ref.get_char_x_position(0);
-        assert_eq!(1, line_ref.char_x_positions.len());
+        assert_eq!(1, line_ref.char_x_positions.borrow().len());
         let third_pos = line_ref.get_char_x_position(2);
-        assert_eq!(3, line_ref.char_x_positions.len());
+        assert_eq!(3, line_ref.char_x_positions.borrow().len());
 
         assert_eq!(0.0, first_pos);
         assert_eq!(2.5, third_pos);
@@ -213,24 +230,24 @@ mod test {
     #[wasm_bindgen_test(async)]
     async fn finding_char_by_x_position() {
         ensogl_core_msdf_sys::initialized().await;
-        let mut line     = Line::new("ABBA");
-        let mut line_ref = LineFullInfo {
-            line    : &mut line,
+        let line         = Line::new("ABBA");
+        let line_ref     = LineFullInfo {
+            line    : &line,
             font    : prepare_font_with_ab(),
             line_id : 0,
             height  : 1.0,
         };
 
         let before_first       = line_ref.find_char_at_x_position(-0.1);
-        assert_eq!(1, line_ref.char_x_positions.len());
+        assert_eq!(1, line_ref.char_x_positions.borrow().len());
         let first              = line_ref.find_char_at_x_position(0.5);
-        assert_eq!(2, line_ref.char_x_positions.len());
+        assert_eq!(2, line_ref.char_x_positions.borrow().len());
         let first_again        = line_ref.find_char_at_x_position(0.5);
-        assert_eq!(2, line_ref.char_x_positions.len());
+        assert_eq!(2, line_ref.char_x_positions.borrow().len());
         let third              = line_ref.find_char_at_x_position(3.0);
-        assert_eq!(4, line_ref.char_x_positions.len());
+        assert_eq!(4, line_ref.char_x_positions.borrow().len());
         let last               = line_ref.find_char_at_x_position(4.5);
-        assert_eq!(4, line_ref.char_x_positions.len());
+        assert_eq!(4, line_ref.char_x_positions.borrow().len());
         let after_last         = line_ref.find_char_at_x_position(5.5);
         let third_again        = line_ref.find_char_at_x_position(3.0);
         let before_first_again = line_ref.find_char_at_x_position(-0.5);
@@ -248,9 +265,9 @@ mod test {
     #[wasm_bindgen_test(async)]
     async fn finding_char_by_x_position_in_empty_line() {
         ensogl_core_msdf_sys::initialized().await;
-        let mut line     = Line::new("");
-        let mut line_ref = LineFullInfo {
-            line    : &mut line,
+        let line         = Line::new("");
+        let line_ref     = LineFullInfo {
+            line    : &line,
             font    : prepare_font_with_ab(),
             line_id : 0,
             height  : 1.0,
@@ -265,16 +282,23 @@ mod test {
     async fn modifying_line() {
         ensogl_core_msdf_sys::initialized().await;
         let mut line     = Line::new("AB");
-        let mut line_ref = LineFullInfo {
-            line    : &mut line,
-            font    : prepare_font_with_ab(),
+        let font         = prepare_font_with_ab();
+        let line_ref     = LineFullInfo {
+            line    : &line,
+            font    : font.clone_ref(),
             line_id : 0,
             height  : 1.0,
         };
         let before_edit = line_ref.get_char_x_position(1);
-        assert_eq!(2, line_ref.char_x_positions.len());
-        line_ref.modify().insert(0, 'B');
-        assert_eq!(0, line_ref.char_x_positions.len());
+        assert_eq!(2, line_ref.char_x_positions.borrow().len());
+        line.modify().insert(0, 'B');
+        assert_eq!(0, line.char_x_positions.borrow().len());
+        let line_ref = LineFullInfo {
+            line    : &line,
+            font,
+            line_id : 0,
+            height  : 1.0,
+        };
         let after_edit = line_ref.get_char_x_position(1);
 
         assert_eq!(1.0, before_edit);
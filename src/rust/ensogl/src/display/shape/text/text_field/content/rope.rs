@@ -0,0 +1,529 @@
+//! A rope of text lines: a balanced binary tree whose leaves are chunks of lines. Each node caches
+//! the number of lines, characters and bytes below it, so getting a line, converting between text
+//! locations and char indices, and replacing a range of lines all take logarithmic time.
+use crate::prelude::*;
+
+use crate::display::shape::text::text_field::content::line::Line;
+
+use data::text::TextLocation;
+use std::ops::Add;
+use std::ops::Index;
+use std::ops::Range;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The maximum number of lines kept in a single leaf.
+const MAX_CHUNK_LINES:usize = 64;
+
+
+
+// ===============
+// === Summary ===
+// ===============
+
+/// The cached information about a sequence of lines.
+#[derive(Clone,Copy,Debug,Default,Eq,PartialEq)]
+pub struct Summary {
+    /// The number of lines.
+    pub lines : usize,
+    /// The number of characters, not counting the line separators.
+    pub chars : usize,
+    /// The number of bytes in UTF-8 encoding, not counting the line separators.
+    pub bytes : usize,
+}
+
+impl Summary {
+    fn of_line(line:&Line) -> Self {
+        let chars = line.len();
+        let bytes = line.chars().iter().map(|c| c.len_utf8()).sum();
+        Self {lines:1,chars,bytes}
+    }
+
+    fn of_lines<'a>(lines:impl IntoIterator<Item=&'a Line>) -> Self {
+        lines.into_iter().map(Self::of_line).fold(default(),Add::add)
+    }
+
+    /// The number of characters, including the separators between the lines.
+    pub fn chars_with_separators(&self) -> usize {
+        self.chars + self.lines.saturating_sub(1)
+    }
+}
+
+impl Add for Summary {
+    type Output = Self;
+    fn add(self, other:Self) -> Self {
+        let lines = self.lines + other.lines;
+        let chars = self.chars + other.chars;
+        let bytes = self.bytes + other.bytes;
+        Self {lines,chars,bytes}
+    }
+}
+
+
+
+// ============
+// === Node ===
+// ============
+
+/// A node of the rope tree. Branches are kept height-balanced (AVL) and never have empty children.
+#[derive(Clone,Debug)]
+enum Node {
+    Leaf   {summary:Summary, lines:Vec<Line>},
+    Branch {summary:Summary, height:usize, left:Box<Node>, right:Box<Node>},
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self::leaf(default())
+    }
+}
+
+impl Node {
+    fn leaf(lines:Vec<Line>) -> Self {
+        let summary = Summary::of_lines(&lines);
+        Self::Leaf {summary,lines}
+    }
+
+    fn branch(left:Node, right:Node) -> Self {
+        let summary = left.summary() + right.summary();
+        let height  = max(left.height(),right.height()) + 1;
+        let left    = Box::new(left);
+        let right   = Box::new(right);
+        Self::Branch {summary,height,left,right}
+    }
+
+    /// Builds a balanced tree of the given lines.
+    fn from_lines(mut lines:Vec<Line>) -> Self {
+        if lines.len() <= MAX_CHUNK_LINES {
+            Self::leaf(lines)
+        } else {
+            let right = lines.split_off(lines.len() / 2);
+            Self::branch(Self::from_lines(lines),Self::from_lines(right))
+        }
+    }
+
+    fn summary(&self) -> Summary {
+        match self {
+            Self::Leaf   {summary,..} => *summary,
+            Self::Branch {summary,..} => *summary,
+        }
+    }
+
+    fn height(&self) -> usize {
+        match self {
+            Self::Leaf   {..}        => 1,
+            Self::Branch {height,..} => *height,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.summary().lines == 0
+    }
+
+    fn into_children(self) -> (Node,Node) {
+        match self {
+            Self::Branch {left,right,..} => (*left,*right),
+            Self::Leaf   {..}            => panic!("Rope leaves have no children."),
+        }
+    }
+
+    fn into_lines(self) -> Vec<Line> {
+        match self {
+            Self::Leaf   {lines,..}      => lines,
+            Self::Branch {left,right,..} => {
+                let mut lines = left.into_lines();
+                lines.extend(right.into_lines());
+                lines
+            }
+        }
+    }
+
+    /// Concatenates two trees, keeping the result balanced.
+    fn join(left:Node, right:Node) -> Node {
+        let left_height  = left.height();
+        let right_height = right.height();
+        if right.is_empty() {
+            left
+        } else if left.is_empty() {
+            right
+        } else if left_height > right_height + 1 {
+            let (left_left,left_right) = left.into_children();
+            Self::balance(left_left,Self::join(left_right,right))
+        } else if right_height > left_height + 1 {
+            let (right_left,right_right) = right.into_children();
+            Self::balance(Self::join(left,right_left),right_right)
+        } else {
+            match (left,right) {
+                (Self::Leaf{lines:mut left,..}, Self::Leaf{lines:right,..})
+                if left.len() + right.len() <= MAX_CHUNK_LINES => {
+                    left.extend(right);
+                    Self::leaf(left)
+                }
+                (left,right) => Self::branch(left,right),
+            }
+        }
+    }
+
+    /// Creates a branch of two trees whose heights differ by at most 2, rotating it if needed.
+    fn balance(left:Node, right:Node) -> Node {
+        let left_height  = left.height();
+        let right_height = right.height();
+        if left_height > right_height + 1 {
+            let (left_left,left_right) = left.into_children();
+            if left_left.height() >= left_right.height() {
+                Self::branch(left_left,Self::branch(left_right,right))
+            } else {
+                let (middle_left,middle_right) = left_right.into_children();
+                Self::branch(Self::branch(left_left,middle_left),Self::branch(middle_right,right))
+            }
+        } else if right_height > left_height + 1 {
+            let (right_left,right_right) = right.into_children();
+            if right_right.height() >= right_left.height() {
+                Self::branch(Self::branch(left,right_left),right_right)
+            } else {
+                let (middle_left,middle_right) = right_left.into_children();
+                Self::branch(Self::branch(left,middle_left),Self::branch(middle_right,right_right))
+            }
+        } else {
+            Self::branch(left,right)
+        }
+    }
+
+    /// Splits the tree into the lines before the given index and the rest.
+    fn split(self, at:usize) -> (Node,Node) {
+        match self {
+            Self::Leaf {mut lines,..} => {
+                let right = lines.split_off(min(at,lines.len()));
+                (Self::leaf(lines),Self::leaf(right))
+            }
+            Self::Branch {left,right,..} => {
+                let left_lines = left.summary().lines;
+                if at == left_lines {
+                    (*left,*right)
+                } else if at < left_lines {
+                    let (left_left,left_right) = left.split(at);
+                    (left_left,Self::join(left_right,*right))
+                } else {
+                    let (right_left,right_right) = right.split(at - left_lines);
+                    (Self::join(*left,right_left),right_right)
+                }
+            }
+        }
+    }
+
+    fn get(&self, index:usize) -> Option<&Line> {
+        match self {
+            Self::Leaf   {lines,..}      => lines.get(index),
+            Self::Branch {left,right,..} => {
+                let left_lines = left.summary().lines;
+                if index < left_lines {left.get(index)} else {right.get(index - left_lines)}
+            }
+        }
+    }
+
+    fn update<R>(&mut self, index:usize, f:impl FnOnce(&mut Line) -> R) -> Option<R> {
+        match self {
+            Self::Leaf {summary,lines} => {
+                let result = lines.get_mut(index).map(f);
+                *summary   = Summary::of_lines(lines.iter());
+                result
+            }
+            Self::Branch {summary,left,right,..} => {
+                let left_lines = left.summary().lines;
+                let result     = if index < left_lines {
+                    left.update(index,f)
+                } else {
+                    right.update(index - left_lines,f)
+                };
+                *summary = left.summary() + right.summary();
+                result
+            }
+        }
+    }
+
+    /// The summary of lines before the given index.
+    fn summary_before(&self, index:usize) -> Summary {
+        match self {
+            Self::Leaf   {lines,..}      => Summary::of_lines(&lines[..min(index,lines.len())]),
+            Self::Branch {left,right,..} => {
+                let left_summary = left.summary();
+                if index <= left_summary.lines {
+                    left.summary_before(index)
+                } else {
+                    left_summary + right.summary_before(index - left_summary.lines)
+                }
+            }
+        }
+    }
+
+    /// Finds the line and column of the char with given index, counting line separators.
+    fn location_at_char_index(&self, index:usize) -> TextLocation {
+        match self {
+            Self::Leaf {lines,..} => {
+                let mut index = index;
+                for (line,chars) in lines.iter().enumerate() {
+                    if index <= chars.len() {
+                        return TextLocation {line,column:index}
+                    }
+                    index -= chars.len() + 1;
+                }
+                let line   = lines.len().saturating_sub(1);
+                let column = lines.last().map_or(0,|last| last.len());
+                TextLocation {line,column}
+            }
+            Self::Branch {left,right,..} => {
+                let left_summary = left.summary();
+                let left_chars   = left_summary.chars + left_summary.lines;
+                if index < left_chars {
+                    left.location_at_char_index(index)
+                } else {
+                    let location = right.location_at_char_index(index - left_chars);
+                    let line     = location.line + left_summary.lines;
+                    TextLocation {line,..location}
+                }
+            }
+        }
+    }
+}
+
+
+
+// ============
+// === Rope ===
+// ============
+
+/// A sequence of text lines with logarithmic access and modification.
+#[derive(Clone,Debug,Default)]
+pub struct Rope {
+    root : Node,
+}
+
+impl Rope {
+    /// Create a rope with the given lines.
+    pub fn new(lines:Vec<Line>) -> Self {
+        let root = Node::from_lines(lines);
+        Self {root}
+    }
+
+    /// The number of lines.
+    pub fn len(&self) -> usize {
+        self.root.summary().lines
+    }
+
+    /// Check if there are no lines.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of lines, chars and bytes in the rope.
+    pub fn summary(&self) -> Summary {
+        self.root.summary()
+    }
+
+    /// Get the line with given index.
+    pub fn get(&self, index:usize) -> Option<&Line> {
+        self.root.get(index)
+    }
+
+    /// Modify the line with given index. The cached summaries are recomputed afterwards, so this is
+    /// the only way of changing a line in place.
+    pub fn update<R>(&mut self, index:usize, f:impl FnOnce(&mut Line) -> R) -> Option<R> {
+        self.root.update(index,f)
+    }
+
+    /// Get the last line.
+    pub fn last(&self) -> Option<&Line> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Iterate over all lines.
+    pub fn iter(&self) -> Iter {
+        let stack = vec![&self.root];
+        let lines = [].iter();
+        Iter {stack,lines}
+    }
+
+    /// Replace the lines in given range with new ones.
+    pub fn splice(&mut self, range:Range<usize>, lines:impl IntoIterator<Item=Line>) {
+        let root          = std::mem::take(&mut self.root);
+        let (left,rest)   = root.split(range.start);
+        let (_,right)     = rest.split(range.end.saturating_sub(range.start));
+        let inserted      = Node::from_lines(lines.into_iter().collect());
+        self.root         = Node::join(Node::join(left,inserted),right);
+    }
+
+    /// Append a line at the end.
+    pub fn push(&mut self, line:Line) {
+        let len = self.len();
+        self.splice(len..len,std::iter::once(line));
+    }
+
+    /// Remove the last line and return it.
+    pub fn pop(&mut self) -> Option<Line> {
+        let index         = self.len().checked_sub(1)?;
+        let root          = std::mem::take(&mut self.root);
+        let (left,right)  = root.split(index);
+        self.root         = left;
+        right.into_lines().pop()
+    }
+
+    /// The index of the first char of the given line, counting the line separators.
+    pub fn char_index_of_line(&self, line:usize) -> usize {
+        let before = self.root.summary_before(line);
+        before.chars + before.lines
+    }
+
+    /// The offset in bytes of the first char of the given line, counting the line separators.
+    pub fn byte_offset_of_line(&self, line:usize) -> usize {
+        let before = self.root.summary_before(line);
+        before.bytes + before.lines
+    }
+
+    /// The location of the char with given index, counting the line separators. Indices past the
+    /// end of text are mapped to the end of the last line.
+    pub fn location_at_char_index(&self, index:usize) -> TextLocation {
+        self.root.location_at_char_index(index)
+    }
+}
+
+impl Index<usize> for Rope {
+    type Output = Line;
+    fn index(&self, index:usize) -> &Line {
+        self.get(index).unwrap_or_else(|| panic!("Line index {} out of bounds.",index))
+    }
+}
+
+impl FromIterator<Line> for Rope {
+    fn from_iter<T:IntoIterator<Item=Line>>(iter:T) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for &'a Rope {
+    type Item     = &'a Line;
+    type IntoIter = Iter<'a>;
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+
+// === Iter ===
+
+/// Iterator over lines of the `Rope`.
+#[derive(Clone,Debug)]
+pub struct Iter<'a> {
+    stack : Vec<&'a Node>,
+    lines : std::slice::Iter<'a,Line>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Line;
+    fn next(&mut self) -> Option<&'a Line> {
+        loop {
+            if let Some(line) = self.lines.next() {
+                return Some(line)
+            }
+            match self.stack.pop()? {
+                Node::Leaf   {lines,..}      => self.lines = lines.iter(),
+                Node::Branch {left,right,..} => {
+                    self.stack.push(right);
+                    self.stack.push(left);
+                }
+            }
+        }
+    }
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lines(count:usize) -> Vec<Line> {
+        (0..count).map(|index| Line::new(iformat!("line {index}"))).collect()
+    }
+
+    fn strings(rope:&Rope) -> Vec<String> {
+        rope.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn accessing_lines() {
+        let rope = Rope::new(lines(1000));
+        assert_eq!(rope.len(), 1000);
+        assert_eq!(rope[0].to_string(), "line 0");
+        assert_eq!(rope[567].to_string(), "line 567");
+        assert_eq!(rope.last().unwrap().to_string(), "line 999");
+        assert!(rope.get(1000).is_none());
+        assert_eq!(strings(&rope), lines(1000).iter().map(|l| l.to_string()).collect_vec());
+    }
+
+    #[test]
+    fn splicing_keeps_tree_balanced() {
+        let mut rope     = Rope::new(lines(500));
+        let mut expected = lines(500).iter().map(|l| l.to_string()).collect_vec();
+        let mut seed     = 7usize;
+        let mut random   = |bound:usize| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345) % (1 << 31);
+            seed % bound.max(1)
+        };
+        for step in 0..300 {
+            let start    = random(expected.len() + 1);
+            let end      = start + random(min(20,expected.len() - start + 1));
+            let inserted = (0..random(100)).map(|i| iformat!("step {step} {i}")).collect_vec();
+            rope.splice(start..end,inserted.iter().map(Line::new));
+            expected.splice(start..end,inserted);
+            assert_eq!(rope.len(), expected.len());
+        }
+        assert_eq!(strings(&rope), expected);
+        let max_height = 1.45 * ((expected.len() + 2) as f32).log2();
+        assert!(rope.root.height() as f32 <= max_height + 2.0);
+    }
+
+    #[test]
+    fn pushing_and_popping() {
+        let mut rope = Rope::default();
+        for line in lines(200) {
+            rope.push(line);
+        }
+        assert_eq!(rope.len(), 200);
+        assert_eq!(rope.pop().unwrap().to_string(), "line 199");
+        assert_eq!(rope.len(), 199);
+        assert_eq!(rope.last().unwrap().to_string(), "line 198");
+    }
+
+    #[test]
+    fn converting_char_indices() {
+        let lines = vec![Line::new("ab"),Line::new("ąę€"),Line::new(""),Line::new("c")];
+        let rope  = Rope::new(lines);
+        assert_eq!(rope.char_index_of_line(0), 0);
+        assert_eq!(rope.char_index_of_line(1), 3);
+        assert_eq!(rope.char_index_of_line(2), 7);
+        assert_eq!(rope.char_index_of_line(3), 8);
+        assert_eq!(rope.byte_offset_of_line(2), 2 + 7 + 2);
+        assert_eq!(rope.location_at_char_index(2), TextLocation {line:0, column:2});
+        assert_eq!(rope.location_at_char_index(3), TextLocation {line:1, column:0});
+        assert_eq!(rope.location_at_char_index(7), TextLocation {line:2, column:0});
+        assert_eq!(rope.location_at_char_index(9), TextLocation {line:3, column:1});
+        assert_eq!(rope.location_at_char_index(50), TextLocation {line:3, column:1});
+        assert_eq!(rope.summary().chars_with_separators(), 9);
+    }
+
+    #[test]
+    fn updating_line() {
+        let mut rope = Rope::new(lines(300));
+        rope.update(150,|line| line.modify().extend("abc".chars()));
+        assert_eq!(rope[150].to_string(), "line 150abc");
+        assert_eq!(rope.char_index_of_line(151), rope.char_index_of_line(150) + 12);
+    }
+}
@@ -91,7 +91,7 @@ impl Cursor {
     }
 
     /// Get `LineFullInfo` object of this cursor's line.
-    pub fn current_line<'a>(&self, content:&'a mut TextFieldContent)
+    pub fn current_line<'a>(&self, content:&'a TextFieldContent)
     -> LineFullInfo<'a> {
         content.line(self.position.line)
     }
@@ -104,16 +104,16 @@ impl Cursor {
     ///  (https://www.freetype.org/freetype2/docs/glyphs/glyphs-3.html#section-1).
     pub fn render_position(position:&TextLocation, content:&mut TextFieldContent) -> Vector2<f32> {
         let line_height = content.line_height;
-        let mut line    = content.line(position.line);
+        let line        = content.line(position.line);
         // TODO[ao] this value should be read from font information, but msdf_sys library does
         // not provide it yet.
         let descender = line.baseline_start().y - 0.15 * line_height;
-        let x         = Self::x_position_of_cursor_at(position.column,&mut line);
+        let x         = Self::x_position_of_cursor_at(position.column,&line);
         let y         = descender + line_height / 2.0;
         Vector2::new(x,y)
     }
 
-    fn x_position_of_cursor_at(column:usize, line:&mut LineFullInfo) -> f32 {
+    fn x_position_of_cursor_at(column:usize, line:&LineFullInfo) -> f32 {
         if column > 0 {
             let char_index = column - 1;
             line.get_char_x_range(char_index).end
@@ -302,8 +302,8 @@ impl<'a> CursorNavigation<'a> {
     /// displayed cursor on the screen will be nearest the current value.
     fn near_same_x_in_another_line(&mut self, position:&TextLocation, line_index:usize)
     -> TextLocation {
-        let mut line   = self.content.line(position.line);
-        let x_position = Cursor::x_position_of_cursor_at(position.column,&mut line);
+        let line       = self.content.line(position.line);
+        let x_position = Cursor::x_position_of_cursor_at(position.column,&line);
         let column     = self.column_near_x(line_index,x_position);
         TextLocation {line:line_index, column}
     }
@@ -312,11 +312,11 @@ impl<'a> CursorNavigation<'a> {
     /// `x_position` in _text space_. See `display::shape::text::content::line::Line`
     /// documentation for details about _text space_.
     fn column_near_x(&mut self, line_index:usize, x_position:f32) -> usize {
-        let mut line                = self.content.line(line_index);
+        let line                    = self.content.line(line_index);
         let x                       = x_position;
         let char_at_x               = line.find_char_at_x_position(x);
         let nearer_to_end           = |range:Range<f32>| range.end - x < x - range.start;
-        let mut nearer_to_chars_end = |index| nearer_to_end(line.get_char_x_range(index));
+        let nearer_to_chars_end     = |index| nearer_to_end(line.get_char_x_range(index));
         match char_at_x {
             Some(index) if nearer_to_chars_end(index) => index + 1,
             Some(index)                               => index,
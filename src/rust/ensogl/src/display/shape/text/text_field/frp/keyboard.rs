@@ -178,6 +178,11 @@ impl TextFieldKeyboardFrp {
         setter.set_action(&[Delete],                     |t| t.do_delete_operation(Step::Right));
         setter.set_action(&[Backspace],                  |t| t.do_delete_operation(Step::Left));
         setter.set_action(&[Escape],                     |t| t.finish_multicursor_mode());
+        setter.set_action(&undo_keys(),                  |t| t.undo());
+        setter.set_action(&redo_keys(),                  |t| t.redo());
+        setter.set_action(&redo_alt_keys(),              |t| t.redo());
+        setter.set_action(&[F3],                         |t| t.find_next());
+        setter.set_action(&[Shift,F3],                   |t| t.find_previous());
     }
 }
 
@@ -232,6 +237,26 @@ fn right_word_keys() -> Vec<keyboard::Key> {
     }
 }
 
+fn undo_keys() -> Vec<keyboard::Key> {
+    vec![edit_modifier(),keyboard::Key::Character("z".into())]
+}
+
+fn redo_keys() -> Vec<keyboard::Key> {
+    vec![edit_modifier(),keyboard::Key::Shift,keyboard::Key::Character("z".into())]
+}
+
+fn redo_alt_keys() -> Vec<keyboard::Key> {
+    vec![edit_modifier(),keyboard::Key::Character("y".into())]
+}
+
+fn edit_modifier() -> keyboard::Key {
+    if let Platform::MacOS = Platform::query() {
+        keyboard::Key::Meta
+    } else {
+        keyboard::Key::Control
+    }
+}
+
 
 // === Private Utilities ===
 
@@ -30,6 +30,8 @@ pub struct TextFieldMouseFrp {
     pub set_cursor_action: frp::Stream,
     /// A node modifying selection on mouse drag.
     pub select_action: frp::Stream,
+    /// A node removing focus from TextField after mouse click outside of it.
+    pub lose_focus_action: frp::Stream,
 }
 
 impl TextFieldMouseFrp {
@@ -44,7 +46,9 @@ impl TextFieldMouseFrp {
         let is_block_selection  = |mask:&keyboard::KeyMask| mask == &[Alt].iter().collect();
         let loc_text_field_ptr  = text_field_ptr.clone();
         let set_cursor_action   = move |p,m| Self::set_cursor(&loc_text_field_ptr,p,m);
-        let select_action       = move |p,s| Self::select(&text_field_ptr,p,s);
+        let loc_text_field_ptr  = text_field_ptr.clone();
+        let select_action       = move |p,s| Self::select(&loc_text_field_ptr,p,s);
+        let lose_focus_action   = move || Self::lose_focus(&text_field_ptr);
         frp::new_network! { text_field
             is_inside         <- mouse.position.map(move |t|is_inside(*t));
             click_in          <- mouse.down.gate(&is_inside);
@@ -57,9 +61,12 @@ impl TextFieldMouseFrp {
             select_pos        <- mouse.position.gate(&selecting);
             set_cursor_action <- click_in_pos.map2(&multicursor,move|p,m|set_cursor_action(*p,*m));
             select_action     <- select_pos.map2(&block_selection,move|p,s|select_action(*p,*s));
+            click_out         <- mouse.down.gate_not(&is_inside);
+            lose_focus_action <- click_out.map(move |_| lose_focus_action());
         }
         let network = text_field;
-        Self {mouse,network,click_in,selecting,multicursor,set_cursor_action,select_action}
+        Self {mouse,network,click_in,selecting,multicursor,set_cursor_action,select_action
+             ,lose_focus_action}
     }
 
     /// Bind this FRP graph to js events.
@@ -87,6 +94,12 @@ impl TextFieldMouseFrp {
         }
     }
 
+    fn lose_focus(text_field:&WeakTextField) {
+        if let Some(text_field) = text_field.upgrade() {
+            text_field.lose_focus();
+        }
+    }
+
     fn select(text_field:&WeakTextField,position:Vector2<f32>,block_selection:bool) {
         if let Some(text_field) = text_field.upgrade() {
             text_field.set_focus();
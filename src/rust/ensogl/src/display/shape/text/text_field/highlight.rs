@@ -0,0 +1,180 @@
+//! Syntax highlighting of the TextField content.
+//!
+//! The TextField does not know any language itself. Instead, a `Highlighter` is plugged in, which
+//! classifies the spans of each line. The spans are cached in content lines and computed again
+//! only for the lines which were modified. Then, while rendering dirty lines (see
+//! `content::DirtyLines`), they are translated to glyph colors using the `Palette`.
+
+use crate::prelude::*;
+
+use crate::data::color;
+
+use std::ops::Range;
+
+
+
+// ============
+// === Span ===
+// ============
+
+/// A kind of highlighted span.
+#[derive(Copy,Clone,Debug,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub enum SpanKind {
+    Identifier,
+    Constructor,
+    Operator,
+    Number,
+    Text,
+    Comment,
+    Invalid,
+}
+
+/// A classified fragment of a single line.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct Span {
+    /// Columns of the line covered by this span.
+    pub columns : Range<usize>,
+    /// The span kind, deciding about its color.
+    pub kind : SpanKind,
+}
+
+impl Span {
+    /// Constructor.
+    pub fn new(columns:Range<usize>, kind:SpanKind) -> Self {
+        Span {columns,kind}
+    }
+}
+
+
+
+// ===================
+// === Highlighter ===
+// ===================
+
+/// A language-specific classifier of the text.
+///
+/// Highlighting is done line by line, so only the modified lines are processed again. The
+/// returned spans should be sorted and must not overlap. Characters not covered by any span are
+/// displayed in the TextField's base color.
+pub trait Highlighter : Debug {
+    /// Classify the fragments of the given line.
+    fn highlight_line(&self, line:&[char]) -> Vec<Span>;
+}
+
+
+
+// ===============
+// === Palette ===
+// ===============
+
+/// Colors used for each span kind.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Palette {
+    pub identifier  : color::Rgba,
+    pub constructor : color::Rgba,
+    pub operator    : color::Rgba,
+    pub number      : color::Rgba,
+    pub text        : color::Rgba,
+    pub comment     : color::Rgba,
+    pub invalid     : color::Rgba,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            identifier  : color::Rgba::new(1.0 ,1.0 ,1.0 ,0.7),
+            constructor : color::Rgba::new(0.55,0.75,1.0 ,0.9),
+            operator    : color::Rgba::new(1.0 ,0.75,0.4 ,0.9),
+            number      : color::Rgba::new(0.7 ,0.55,1.0 ,0.9),
+            text        : color::Rgba::new(0.6 ,0.85,0.5 ,0.9),
+            comment     : color::Rgba::new(1.0 ,1.0 ,1.0 ,0.35),
+            invalid     : color::Rgba::new(1.0 ,0.35,0.35,1.0),
+        }
+    }
+}
+
+impl Palette {
+    /// Get color of the given span kind.
+    pub fn color(&self, kind:SpanKind) -> color::Rgba {
+        match kind {
+            SpanKind::Identifier  => self.identifier,
+            SpanKind::Constructor => self.constructor,
+            SpanKind::Operator    => self.operator,
+            SpanKind::Number      => self.number,
+            SpanKind::Text        => self.text,
+            SpanKind::Comment     => self.comment,
+            SpanKind::Invalid     => self.invalid,
+        }
+    }
+}
+
+
+
+// ====================
+// === Highlighting ===
+// ====================
+
+/// A highlighter together with colors used for displaying its spans.
+#[derive(Clone,Debug)]
+pub struct Highlighting {
+    /// The language-specific highlighter.
+    pub highlighter : Rc<dyn Highlighter>,
+    /// Colors of the spans.
+    pub palette : Palette,
+}
+
+impl Highlighting {
+    /// Constructor.
+    pub fn new(highlighter:impl Highlighter + 'static, palette:Palette) -> Self {
+        let highlighter = Rc::new(highlighter);
+        Highlighting {highlighter,palette}
+    }
+
+    /// Get colors of characters in given columns range, based on the line's spans. Characters
+    /// outside any span have `None` color.
+    pub fn colors(&self, spans:&[Span], columns:Range<usize>) -> Vec<Option<color::Rgba>> {
+        let mut colors = vec![None; columns.len()];
+        for span in spans {
+            let start = span.columns.start.max(columns.start);
+            let end   = span.columns.end.min(columns.end);
+            let color = self.palette.color(span.kind);
+            for column in start..end {
+                colors[column - columns.start] = Some(color);
+            }
+        }
+        colors
+    }
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn computing_colors() {
+        let highlighting = Highlighting {
+            highlighter : Rc::new(NoHighlighter),
+            palette     : default(),
+        };
+        let palette    = &highlighting.palette;
+        let identifier = Span::new(0..3,SpanKind::Identifier);
+        let operator   = Span::new(4..5,SpanKind::Operator);
+        let spans      = vec![identifier,operator];
+        let colors     = highlighting.colors(&spans,2..6);
+        let expected   = vec![Some(palette.identifier),None,Some(palette.operator),None];
+        assert_eq!(colors, expected);
+    }
+
+    #[derive(Debug)]
+    struct NoHighlighter;
+
+    impl Highlighter for NoHighlighter {
+        fn highlight_line(&self, _line:&[char]) -> Vec<Span> {
+            default()
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! A module with the edit history of the TextField, used for undo and redo.
+//!
+//! Every edit is stored together with its inverse, so undoing is just applying the inverse
+//! changes. The changes of one edit are applied one after another (each cursor's change is
+//! expressed in locations valid after applying the previous ones), so the inverse changes are
+//! kept in the reversed order.
+
+use crate::prelude::*;
+
+use crate::display::shape::text::text_field::content::Change;
+use crate::display::shape::text::text_field::cursor::Cursor;
+
+
+
+// ================
+// === EditKind ===
+// ================
+
+/// A kind of edit, deciding if consecutive edits may be grouped into one undo step.
+#[derive(Copy,Clone,Debug,Eq,PartialEq)]
+pub enum EditKind {
+    /// Typing a single character without any selection.
+    Typing,
+    /// Removing text, e.g. with backspace or delete.
+    Deleting,
+    /// Any other edit, like pasting or cutting. Such edits are never grouped.
+    Other,
+}
+
+impl EditKind {
+    /// Check if edits of this kind may be grouped with consecutive edits of the same kind.
+    pub fn is_groupable(self) -> bool {
+        self != EditKind::Other
+    }
+}
+
+
+
+// ============
+// === Edit ===
+// ============
+
+/// A single undo step, being one or many changes made to the TextField content.
+#[derive(Clone,Debug)]
+pub struct Edit {
+    /// The kind of this edit.
+    pub kind : EditKind,
+    /// Changes made by this edit, in order of applying.
+    pub changes : Vec<Change>,
+    /// Changes reverting this edit, in order of applying.
+    pub inverse : Vec<Change>,
+    /// Cursors before making the edit. They are restored when undoing.
+    pub cursors_before : Vec<Cursor>,
+    /// Cursors after making the edit. They are restored when redoing.
+    pub cursors_after : Vec<Cursor>,
+    /// Time of the last change in this edit, in milliseconds.
+    pub time : f64,
+}
+
+impl Edit {
+    /// Create an edit without any changes yet.
+    pub fn new(kind:EditKind, cursors_before:Vec<Cursor>, time:f64) -> Self {
+        let changes       = default();
+        let inverse       = default();
+        let cursors_after = cursors_before.clone();
+        Edit {kind,changes,inverse,cursors_before,cursors_after,time}
+    }
+
+    /// Add a change to this edit together with the change reverting it.
+    ///
+    /// The `inverse` must be expressed in locations valid just after applying `change`.
+    pub fn push(&mut self, change:Change, inverse:Change) {
+        self.changes.push(change);
+        self.inverse.insert(0,inverse);
+    }
+
+    /// Check if the edit actually changes anything.
+    pub fn is_empty(&self) -> bool {
+        self.changes.iter().all(|change| {
+            let nothing_replaced = change.replaced.start == change.replaced.end;
+            let nothing_inserted = change.inserted.iter().all(|line| line.is_empty());
+            nothing_replaced && nothing_inserted
+        })
+    }
+
+    /// Check if the `next` edit continues this one and both should be undone in one step.
+    ///
+    /// This is the case when both are of the same groupable kind, there were no cursor moves
+    /// between them, and the `next` was made not later than `timeout` after this one.
+    pub fn is_continued_by(&self, next:&Edit, timeout:f64) -> bool {
+        let same_kind   = self.kind == next.kind && self.kind.is_groupable();
+        let no_moves    = self.cursors_after == next.cursors_before;
+        let not_delayed = next.time - self.time <= timeout;
+        same_kind && no_moves && not_delayed
+    }
+
+    /// Extend this edit with the `next` one, so they will be undone in one step.
+    pub fn merge(&mut self, next:Edit) {
+        let mut inverse = next.inverse;
+        inverse.extend(std::mem::take(&mut self.inverse));
+        self.changes.extend(next.changes);
+        self.inverse       = inverse;
+        self.cursors_after = next.cursors_after;
+        self.time          = next.time;
+    }
+}
+
+
+
+// ===============
+// === History ===
+// ===============
+
+/// The edit history of one TextField, with separate undo and redo stacks.
+///
+/// Recording a new edit clears the redo stack. Consecutive typing or deleting is grouped into
+/// a single undo step, see `Edit::is_continued_by`.
+#[derive(Debug)]
+pub struct History {
+    undo_stack : Vec<Edit>,
+    redo_stack : Vec<Edit>,
+    /// The maximum time between edits in milliseconds for them to be grouped.
+    pub group_timeout : f64,
+    /// The maximum number of stored undo steps. The oldest steps are dropped first.
+    pub max_depth : usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            undo_stack    : default(),
+            redo_stack    : default(),
+            group_timeout : Self::DEFAULT_GROUP_TIMEOUT,
+            max_depth     : Self::DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
+impl History {
+    /// Default value of `group_timeout`.
+    pub const DEFAULT_GROUP_TIMEOUT:f64 = 1000.0;
+    /// Default value of `max_depth`.
+    pub const DEFAULT_MAX_DEPTH:usize = 1000;
+
+    /// Record a new edit. Empty edits are ignored.
+    pub fn record(&mut self, edit:Edit) {
+        if !edit.is_empty() {
+            self.redo_stack.clear();
+            let timeout = self.group_timeout;
+            let last    = self.undo_stack.last_mut();
+            let grouped = last.filter(|last| last.is_continued_by(&edit,timeout));
+            if let Some(last) = grouped {
+                last.merge(edit)
+            } else {
+                self.undo_stack.push(edit);
+                let overflow = self.undo_stack.len().saturating_sub(self.max_depth);
+                self.undo_stack.drain(..overflow);
+            }
+        }
+    }
+
+    /// Move the last edit to the redo stack and return it. The caller should apply its `inverse`
+    /// changes and restore `cursors_before`.
+    pub fn undo(&mut self) -> Option<&Edit> {
+        let edit = self.undo_stack.pop()?;
+        self.redo_stack.push(edit);
+        self.redo_stack.last()
+    }
+
+    /// Move the last undone edit back to the undo stack and return it. The caller should apply
+    /// its `changes` and restore `cursors_after`.
+    pub fn redo(&mut self) -> Option<&Edit> {
+        let edit = self.redo_stack.pop()?;
+        self.undo_stack.push(edit);
+        self.undo_stack.last()
+    }
+
+    /// Prevent the next recorded edit from being grouped with the last one.
+    pub fn close_group(&mut self) {
+        if let Some(last) = self.undo_stack.last_mut() {
+            last.time = f64::NEG_INFINITY;
+        }
+    }
+
+    /// Check if there is anything to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Check if there is anything to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Forget all the recorded edits.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use data::text::TextLocation;
+
+    fn typing(column:usize, text:&str, time:f64) -> Edit {
+        let at       = TextLocation{line:0,column};
+        let after    = TextLocation{line:0,column:column+text.len()};
+        let mut edit = Edit::new(EditKind::Typing,vec![Cursor::new(at)],time);
+        edit.push(Change::insert(at,text),Change::delete(at..after));
+        edit.cursors_after = vec![Cursor::new(after)];
+        edit
+    }
+
+    #[test]
+    fn grouping_consecutive_typing() {
+        let mut history = History::default();
+        history.record(typing(0,"a",0.0));
+        history.record(typing(1,"b",100.0));
+        history.record(typing(2,"c",200.0));
+        let edit = history.undo().unwrap();
+        assert_eq!(edit.changes.len(), 3);
+        assert_eq!(edit.inverse[0].replaced.start.column, 2);
+        assert_eq!(edit.inverse[2].replaced.start.column, 0);
+        assert_eq!(edit.cursors_before, vec![Cursor::new(TextLocation{line:0,column:0})]);
+        assert!(!history.can_undo());
+    }
+
+    fn deleting(column:usize, time:f64) -> Edit {
+        let at       = TextLocation{line:0,column};
+        let before   = TextLocation{line:0,column:column-1};
+        let mut edit = Edit::new(EditKind::Deleting,vec![Cursor::new(at)],time);
+        edit.push(Change::delete(before..at),Change::insert(before,"x"));
+        edit.cursors_after = vec![Cursor::new(before)];
+        edit
+    }
+
+    #[test]
+    fn grouping_consecutive_deleting() {
+        let mut history = History::default();
+        history.record(deleting(3,0.0));
+        history.record(deleting(2,100.0));
+        history.record(deleting(1,200.0));
+        let edit = history.undo().unwrap();
+        assert_eq!(edit.changes.len(), 3);
+        assert_eq!(edit.cursors_before, vec![Cursor::new(TextLocation{line:0,column:3})]);
+        assert_eq!(edit.cursors_after , vec![Cursor::new(TextLocation{line:0,column:0})]);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn breaking_groups() {
+        let mut history = History::default();
+        history.record(typing(0,"a",0.0));
+        // Too late.
+        history.record(typing(1,"b",5000.0));
+        // Cursor was moved in the meantime.
+        history.record(typing(5,"c",5100.0));
+        history.close_group();
+        history.record(typing(6,"d",5200.0));
+        let mut undone = 0;
+        while history.undo().is_some() { undone += 1; }
+        assert_eq!(undone, 4);
+    }
+
+    #[test]
+    fn undoing_and_redoing() {
+        let mut history = History::default();
+        history.record(typing(0,"a",0.0));
+        assert!(history.undo().is_some());
+        assert!(history.can_redo());
+        assert!(history.redo().is_some());
+        assert!(history.undo().is_some());
+        history.record(typing(0,"b",0.0));
+        assert!(!history.can_redo());
+        assert!(history.undo().is_some());
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn limiting_depth() {
+        let mut history = History::default();
+        history.max_depth = 2;
+        for i in 0..5 {
+            let mut edit = typing(i,"x",0.0);
+            edit.kind    = EditKind::Other;
+            history.record(edit);
+        }
+        let last = history.undo().unwrap();
+        assert_eq!(last.changes[0].replaced.start.column, 4);
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_none());
+    }
+}
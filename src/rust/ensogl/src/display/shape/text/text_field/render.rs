@@ -10,6 +10,7 @@ use crate::display;
 use crate::display::object::traits::*;
 use crate::display::shape::text::glyph::font;
 use crate::display::shape::text::glyph::system::GlyphSystem;
+use crate::display::shape::text::text_field::content::DirtyLines;
 use crate::display::shape::text::text_field::content::TextFieldContent;
 use crate::display::shape::text::text_field::cursor::Cursor;
 use crate::display::shape::text::text_field::cursor::Cursors;
@@ -22,10 +23,12 @@ use crate::display::shape::primitive::system::ShapeSystem;
 use crate::display::symbol::geometry::compound::sprite::Sprite;
 use crate::display::world::World;
 
+use data::text::TextLocation;
 use nalgebra::{Vector2, zero};
 use nalgebra::Vector3;
 use crate::math::topology::unit::PixelDistance;
 use crate::display::Glsl;
+use std::ops::Range;
 
 
 
@@ -55,10 +58,14 @@ pub struct TextFieldSprites {
     pub cursor_system: ShapeSystem,
     /// System used for rendering selections.
     pub selection_system: ShapeSystem,
+    /// System used for rendering highlights of search matches.
+    pub match_system: ShapeSystem,
     /// All drawn glyph lines.
     pub glyph_lines: Vec<GlyphLine>,
     /// All drawn cursors..
     pub cursors: Vec<CursorSprites>,
+    /// All drawn highlights of search matches, with the indices of lines they are in.
+    pub matches: Vec<(usize,Sprite)>,
     /// Current assignment of glyph lines to actual lines of text.
     pub assignment: GlyphLinesAssignment,
     /// Line height in pixels.
@@ -78,18 +85,21 @@ impl TextFieldSprites {
         let line_height       = properties.text_size;
         let color             = properties.base_color;
         let selection_system  = Self::create_selection_system(world);
+        let match_system      = Self::create_match_system(world);
         let cursor_system     = Self::create_cursor_system(world,line_height,color);
         let cursors           = Vec::new();
+        let matches           = Vec::new();
         let glyph_system      = GlyphSystem::new(world,font.clone_ref());
         let display_object    = display::object::Instance::new(Logger::new("RenderedContent"));
+        display_object.add_child(&match_system);
         display_object.add_child(&selection_system);
         display_object.add_child(&glyph_system);
         display_object.add_child(&cursor_system);
 
         let assignment  = default();
         let glyph_lines = default();
-        TextFieldSprites {glyph_system,cursor_system,selection_system,glyph_lines,cursors,
-            line_height,display_object,assignment}.initialize(properties)
+        TextFieldSprites {glyph_system,cursor_system,selection_system,match_system,glyph_lines,
+            cursors,matches,line_height,display_object,assignment}.initialize(properties)
     }
 
     fn initialize(mut self, properties:&TextFieldProperties) -> Self {
@@ -140,6 +150,12 @@ impl TextFieldSprites {
         ShapeSystem::new(world,&selection_definition)
     }
 
+    fn create_match_system(world:&World) -> ShapeSystem {
+        let color            = color::Rgba::new(1.0,0.8,0.3,0.25);
+        let match_definition = Rect(("input_size.x","input_size.y")).fill(color);
+        ShapeSystem::new(world,&match_definition)
+    }
+
     fn create_assignment_structure
     ( window_size : Vector2<f32>
     , line_height : f32
@@ -204,14 +220,36 @@ impl TextFieldSprites {
         }
     }
 
+    /// Update the highlights of search matches in the `dirty` lines.
+    pub fn update_match_sprites
+    ( &mut self
+    , matches : &[Range<TextLocation>]
+    , dirty   : &DirtyLines
+    , content : &mut TextFieldContent
+    ) {
+        let line_height   = self.line_height;
+        let system        = &self.match_system;
+        let mut generator = SelectionSpritesGenerator {content,line_height,system};
+        let to_draw       = matches.iter().filter(|m| dirty.is_dirty(m.start.line));
+        self.matches.retain(|(line,_)| !dirty.is_dirty(*line));
+        for found in to_draw {
+            let line = found.start.line;
+            self.matches.extend(generator.generate(found).into_iter().map(|s| (line,s)));
+        }
+    }
+
     fn update_glyph_line
     (glyph_line:&mut GlyphLine, fragment:&LineFragment, content:&mut TextFieldContent) {
         let bsl_start       = Self::baseline_start_for_fragment(fragment,content);
+        let colors          = content.line_colors(fragment.line_index,fragment.chars_range.clone());
         let line            = &content.lines()[fragment.line_index];
         let chars           = &line.chars()[fragment.chars_range.clone()];
         let string : String = chars.iter().collect();
         glyph_line.set_position(Vector3::new(bsl_start.x,bsl_start.y,0.0));
-        glyph_line.set_text(string);
+        match colors {
+            Some(colors) => glyph_line.set_text_with_colors(string,colors),
+            None         => glyph_line.set_text(string),
+        }
     }
 
     /// The baseline start for given line's fragment.
@@ -221,7 +259,7 @@ impl TextFieldSprites {
     /// position of first char of fragment.
     fn baseline_start_for_fragment(fragment:&LineFragment, content:&mut TextFieldContent)
     -> Vector2<f32> {
-        let mut line = content.line(fragment.line_index);
+        let line = content.line(fragment.line_index);
         if fragment.chars_range.start >= line.chars().len() {
             line.baseline_start()
         } else {
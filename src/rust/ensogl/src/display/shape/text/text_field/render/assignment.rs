@@ -29,7 +29,7 @@ impl LineFragment {
     /// Tells if rendering this line's fragment will cover the x range.
     pub fn covers_displayed_range
     (&self, displayed_range:&RangeInclusive<f32>, content:&mut TextFieldContent) -> bool {
-        let mut line       = content.line(self.line_index);
+        let line           = content.line(self.line_index);
         let front_rendered = self.chars_range.start == 0;
         let back_rendered  = self.chars_range.end == line.len();
         let x_range_start  = line.get_char_x_position(self.chars_range.start);
@@ -217,7 +217,7 @@ impl GlyphLinesAssignment {
 impl<'a,'b> GlyphLinesAssignmentUpdate<'a,'b> {
     /// Returns LineFragment of specific line which is currently visible.
     fn displayed_fragment(&mut self, line_id:usize) -> LineFragment {
-        let mut line             = self.content.line(line_id);
+        let line                 = self.content.line(line_id);
         let max_index            = line.len().saturating_sub(self.assignment.max_glyphs_in_line);
         let displayed_from_x     = self.scroll_offset.x - self.assignment.x_margin;
         let first_displayed      = line.find_char_at_x_position(displayed_from_x);
@@ -0,0 +1,297 @@
+//! This module contains the search and replace facility of TextField.
+//!
+//! Both literal and regex queries are compiled to a `Regex`. Matching is done line by line, so
+//! a match never spans many lines.
+
+use crate::prelude::*;
+
+use crate::display::shape::text::text_field::content::DirtyLines;
+use crate::display::shape::text::text_field::content::TextFieldContent;
+
+use data::text::TextLocation;
+use regex::Regex;
+use regex::RegexBuilder;
+use std::ops::Range;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// Error of compiling the search query.
+#[derive(Clone,Debug,Fail)]
+#[fail(display="Invalid search pattern \"{}\": {}",pattern,reason)]
+#[allow(missing_docs)]
+pub struct InvalidPattern {
+    pub pattern : String,
+    pub reason  : String,
+}
+
+
+
+// =============
+// === Query ===
+// =============
+
+/// The searched phrase with options.
+#[derive(Clone,Debug,Default,Eq,PartialEq)]
+pub struct Query {
+    /// The searched text or regular expression.
+    pub pattern : String,
+    /// If set, the `pattern` is a regular expression. Otherwise it is matched literally.
+    pub regex : bool,
+    /// If not set, letters are matched regardless of their case.
+    pub case_sensitive : bool,
+    /// If set, only matches not being a part of a bigger word are found.
+    pub whole_word : bool,
+}
+
+impl Query {
+    /// A case sensitive query matching the given text literally.
+    pub fn literal(pattern:impl Str) -> Self {
+        let pattern        = pattern.into();
+        let case_sensitive = true;
+        Query {pattern,case_sensitive,..default()}
+    }
+
+    /// A case sensitive query matching the given regular expression.
+    pub fn regex(pattern:impl Str) -> Self {
+        Query {regex:true, ..Self::literal(pattern)}
+    }
+
+    /// Compile the query to regex.
+    pub fn compile(&self) -> Result<Regex,InvalidPattern> {
+        let pattern = if self.regex { self.pattern.clone() } else { regex::escape(&self.pattern) };
+        let pattern = if self.whole_word { iformat!("\\b(?:{pattern})\\b") } else { pattern };
+        let builder = RegexBuilder::new(&pattern).case_insensitive(!self.case_sensitive).build();
+        builder.map_err(|error| {
+            let pattern = self.pattern.clone();
+            let reason  = error.to_string();
+            InvalidPattern {pattern,reason}
+        })
+    }
+}
+
+
+
+// ==============
+// === Search ===
+// ==============
+
+/// An active search in TextField content, keeping all the found matches.
+///
+/// The matches are not updated automatically - after each content change the `update` method
+/// should be called with the lines modified since the last update.
+#[derive(Clone,Debug)]
+pub struct Search {
+    query   : Query,
+    regex   : Regex,
+    matches : Vec<Range<TextLocation>>,
+    current : Option<usize>,
+}
+
+impl Search {
+    /// Compile query and find all its matches in the content.
+    pub fn new(query:Query, content:&TextFieldContent) -> Result<Self,InvalidPattern> {
+        let regex   = query.compile()?;
+        let matches = default();
+        let current = None;
+        let mut search = Search {query,regex,matches,current};
+        search.update(content,&DirtyLines::all());
+        Ok(search)
+    }
+
+    /// The searched query.
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// All matches, sorted by their location.
+    pub fn matches(&self) -> &[Range<TextLocation>] {
+        &self.matches
+    }
+
+    /// The match selected by the last `next_after` or `previous_before` call.
+    pub fn current(&self) -> Option<&Range<TextLocation>> {
+        self.current.and_then(|index| self.matches.get(index))
+    }
+
+    /// Find the matches again in the `dirty` lines, after the content was modified. The matches
+    /// in other lines are kept.
+    pub fn update(&mut self, content:&TextFieldContent, dirty:&DirtyLines) {
+        let lines_count = content.lines().len();
+        let range_start = dirty.range.as_ref().map_or(lines_count, |r| r.start.min(lines_count));
+        let singles     = dirty.single_lines.iter().cloned().filter(|line| *line < range_start);
+        let to_scan     = singles.chain(range_start..lines_count);
+        let regex       = &self.regex;
+        self.matches.retain(|m| !dirty.is_dirty(m.start.line));
+        for line in to_scan {
+            let found = find_in_line(regex,content.lines()[line].chars());
+            self.matches.extend(found.into_iter().map(|columns| {
+                let start = TextLocation {line, column:columns.start};
+                let end   = TextLocation {line, column:columns.end};
+                start..end
+            }));
+        }
+        self.matches.sort_by_key(|m| m.start);
+        self.current = None;
+    }
+
+    /// Select the first match starting at or after `location`, wrapping around the content end.
+    pub fn next_after(&mut self, location:TextLocation) -> Option<Range<TextLocation>> {
+        let next     = self.matches.iter().position(|m| m.start >= location);
+        let first    = (!self.matches.is_empty()).as_some(0);
+        self.current = next.or(first);
+        self.current().cloned()
+    }
+
+    /// Select the last match starting before `location`, wrapping around the content begin.
+    pub fn previous_before(&mut self, location:TextLocation) -> Option<Range<TextLocation>> {
+        let previous = self.matches.iter().rposition(|m| m.start < location);
+        let last     = self.matches.len().checked_sub(1);
+        self.current = previous.or(last);
+        self.current().cloned()
+    }
+
+    /// Check if the given range is one of the matches.
+    pub fn is_match(&self, range:&Range<TextLocation>) -> bool {
+        self.matches.binary_search_by_key(&range.start, |m| m.start).map_or(false, |index| {
+            self.matches[index].end == range.end
+        })
+    }
+
+    /// Get the text which should replace the given match.
+    ///
+    /// For regex queries, the `$name` and `${name}` references in `replacement` are substituted
+    /// with the captured groups, as described in `regex::Captures::expand` docs.
+    pub fn replacement_for
+    (&self, content:&TextFieldContent, found:&Range<TextLocation>, replacement:&str) -> String {
+        if self.query.regex {
+            let line     = content.lines()[found.start.line].to_string();
+            let start    = byte_offset(&line,found.start.column);
+            let captures = self.regex.captures_iter(&line).find(|captures| {
+                captures.get(0).map_or(false, |m| m.start() == start)
+            });
+            let mut output = String::new();
+            captures.for_each(|captures| captures.expand(replacement,&mut output));
+            output
+        } else {
+            replacement.to_string()
+        }
+    }
+}
+
+
+
+// =============
+// === Utils ===
+// =============
+
+/// Find all non-empty matches of regex in line, returning their columns ranges.
+fn find_in_line(regex:&Regex, line:&[char]) -> Vec<Range<usize>> {
+    let text = String::from_iter(line.iter());
+    let mut matches = Vec::new();
+    let mut chars   = text.char_indices().map(|(byte,_)| byte).enumerate().peekable();
+    for found in regex.find_iter(&text).filter(|m| m.start() < m.end()) {
+        let mut column_of = |byte| {
+            while chars.peek().map_or(false, |(_,b)| *b < byte) { chars.next(); }
+            chars.peek().map_or(line.len(), |(column,_)| *column)
+        };
+        let start = column_of(found.start());
+        let end   = column_of(found.end());
+        matches.push(start..end);
+    }
+    matches
+}
+
+fn byte_offset(text:&str, column:usize) -> usize {
+    text.char_indices().nth(column).map_or(text.len(), |(byte,_)| byte)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::display::shape::text::text_field::content::Change;
+    use crate::display::shape::text::text_field::content::test::mock_properties;
+
+    use ensogl_core_msdf_sys as msdf_sys;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn find(query:Query, line:&str) -> Vec<Range<usize>> {
+        let chars = line.chars().collect_vec();
+        find_in_line(&query.compile().unwrap(),&chars)
+    }
+
+    #[test]
+    fn finding_in_line() {
+        let line = "foo.bar(Foo) żółw foo_baz foo";
+        assert_eq!(find(Query::literal("foo"),line), vec![0..3,18..21,26..29]);
+        assert_eq!(find(Query::literal("."),line), vec![3..4]);
+        assert_eq!(find(Query::literal("ółw"),line), vec![14..17]);
+        let case_insensitive = Query {case_sensitive:false, ..Query::literal("foo")};
+        assert_eq!(find(case_insensitive,line), vec![0..3,8..11,18..21,26..29]);
+        let whole_word = Query {whole_word:true, ..Query::literal("foo")};
+        assert_eq!(find(whole_word,line), vec![0..3,26..29]);
+        assert_eq!(find(Query::regex("[a-z]+_[a-z]+"),line), vec![18..25]);
+        assert_eq!(find(Query::regex("x*"),line), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(Query::regex("(unclosed").compile().is_err());
+        assert!(Query::literal("(unclosed").compile().is_ok());
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn stepping_through_matches() {
+        msdf_sys::initialized().await;
+        let content    = "let a = 1\nlet b = a\nlet c = b";
+        let content    = TextFieldContent::new(content,&mock_properties());
+        let mut search = Search::new(Query::literal("let"),&content).unwrap();
+        let location   = |line,column| TextLocation {line,column};
+        assert_eq!(search.matches().len(), 3);
+        let found = search.next_after(location(0,1)).unwrap();
+        assert_eq!(found, location(1,0)..location(1,3));
+        let found = search.next_after(location(2,1)).unwrap();
+        assert_eq!(found, location(0,0)..location(0,3));
+        let found = search.previous_before(location(0,0)).unwrap();
+        assert_eq!(found, location(2,0)..location(2,3));
+        assert!(search.is_match(&found));
+        assert!(!search.is_match(&(location(2,0)..location(2,2))));
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn updating_only_dirty_lines() {
+        msdf_sys::initialized().await;
+        let mut content = TextFieldContent::new("let a\nlet b\nlet c",&mock_properties());
+        let mut search  = Search::new(Query::literal("let"),&content).unwrap();
+        let location    = |line,column| TextLocation {line,column};
+        content.apply_change(Change::insert(location(1,0),"let "));
+        search.update(&content,&content.dirty_lines);
+        let starts = search.matches().iter().map(|m| m.start).collect_vec();
+        assert_eq!(starts, vec![location(0,0),location(1,0),location(1,4),location(2,0)]);
+        content.dirty_lines = default();
+        content.apply_change(Change::delete(location(0,5)..location(1,4)));
+        search.update(&content,&content.dirty_lines);
+        let starts = search.matches().iter().map(|m| m.start).collect_vec();
+        assert_eq!(starts, vec![location(0,0),location(0,5),location(1,0)]);
+    }
+
+    #[wasm_bindgen_test(async)]
+    async fn expanding_replacement() {
+        msdf_sys::initialized().await;
+        let content = "foo = bar";
+        let content = TextFieldContent::new(content,&mock_properties());
+        let search  = Search::new(Query::regex("(\\w+) = (\\w+)"),&content).unwrap();
+        let found   = search.matches()[0].clone();
+        assert_eq!(search.replacement_for(&content,&found,"$2 = $1"), "bar = foo");
+    }
+}
@@ -4,6 +4,8 @@ use crate::prelude::*;
 
 use crate::data::color;
 
+use std::str::FromStr;
+
 
 
 // ============
@@ -55,6 +57,77 @@ impl Display for Data {
 }
 
 
+// === Interpolation ===
+
+impl Data {
+    /// Interpolate between this and the `other` value. The `weight` of 0.0 gives this value, and
+    /// the `weight` of 1.0 gives the `other` one. Colors are mixed in the linear RGB space. Returns
+    /// `None` if the values are not of the same, interpolable type.
+    pub fn mix(&self, other:&Data, weight:f32) -> Option<Data> {
+        match (self,other) {
+            (Data::Number(start),Data::Number(end)) => {
+                Some(Data::Number(start + (end - start) * weight))
+            }
+            (Data::Color(start),Data::Color(end)) => {
+                let start = color::LinearRgba::from(*start);
+                let end   = color::LinearRgba::from(*end);
+                let mixed = start * (1.0 - weight) + end * weight;
+                Some(Data::Color(color::Lcha::from(mixed)))
+            }
+            _ => None
+        }
+    }
+}
+
+
+// === Text Format ===
+
+impl Data {
+    /// Formats the value in the text format used by theme files and style sheet dumps, like `7.5`
+    /// or `lcha(0.5,0.1,0.2,1)`. Invalid values cannot be formatted and return `None`.
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            Self::Invalid(_) => None,
+            Self::Number(t)  => Some(t.to_string()),
+            Self::Color(t)   => {
+                let (l,c,h,a) = (t.lightness,t.chroma,t.hue,t.alpha);
+                Some(format!("lcha({},{},{},{})",l,c,h,a))
+            }
+        }
+    }
+}
+
+/// Parses a number or a color, given either as `rgba(r,g,b,a)` or `lcha(l,c,h,a)`. The alpha
+/// component may be omitted (`rgb(r,g,b)` and `lch(l,c,h)`), defaulting to 1.0.
+impl FromStr for Data {
+    type Err = String;
+    fn from_str(text:&str) -> Result<Self,Self::Err> {
+        let invalid_number = |text:&str| format!("invalid number \"{}\"",text);
+        match text.find('(') {
+            None => text.parse::<f32>().map(Data::from).map_err(|_| invalid_number(text)),
+            Some(open) => {
+                let space = &text[..open];
+                let args  = text.ends_with(')').as_some_from(|| &text[open+1..text.len()-1]);
+                let args  = args.ok_or_else(|| "missing ')'".to_string())?;
+                let args  = args.split(',').map(|arg| {
+                    let arg = arg.trim();
+                    arg.parse::<f32>().map_err(|_| invalid_number(arg))
+                }).collect::<Result<Vec<_>,_>>()?;
+                let alpha = match (space,args.len()) {
+                    ("rgb",3) | ("lch",3)   => 1.0,
+                    ("rgba",4) | ("lcha",4) => args[3],
+                    _ => return Err(format!("invalid color \"{}\"",text)),
+                };
+                match space {
+                    "rgb" | "rgba" => Ok(color::Rgba::new(args[0],args[1],args[2],alpha).into()),
+                    _              => Ok(color::Lcha::new(args[0],args[1],args[2],alpha).into()),
+                }
+            }
+        }
+    }
+}
+
+
 // FIXME: Make use of this macro and allow themes to modify colors, including:
 // lighten,darken,saturate,desaturate,with_hue,shift_hue, etc.
 macro_rules! _define_color_transform {
@@ -42,6 +42,12 @@ impl Path {
 
 impl AsRef<Path> for Path { fn as_ref(&self) -> &Path { self } }
 
+impl Display for Path {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"{}",self.rev_segments.iter().rev().join("."))
+    }
+}
+
 impls! {              From<&str>   for Path { |t| Self::from_rev_segments(t.rsplit('.')) } }
 impls! {              From<&&str>  for Path { |t| (*t).into() }}
 impls! {              From<&Path>  for Path { |t| t.clone() }}
@@ -28,7 +28,9 @@ pub use super::path::Path;
 /// and is remembered (`binding`). Moreover, each query keeps list of all sheet nodes which use this
 /// query in their expressions (`users`). A query is considered unused and can be safely removed
 /// from the style sheet graph if no sheet nodes use it in their expressions and it is not
-/// referred by an external, user code (`external_count`).
+/// referred by an external, user code (`external_count`). Some of the external variables are
+/// static (`static_count`), which means their values are baked into something expensive to
+/// rebuild, like a shader, so they should not be changed continuously.
 #[derive(Debug)]
 pub struct Query {
     path           : Path,
@@ -37,6 +39,7 @@ pub struct Query {
     binding        : Option<Index<SheetNode>>,
     users          : HashSet<Index<SheetNode>>,
     external_count : usize,
+    static_count   : usize,
 }
 
 impl Query {
@@ -46,7 +49,8 @@ impl Query {
         let binding        = default();
         let users          = default();
         let external_count = default();
-        Self {path,index,matches,binding,users,external_count}
+        let static_count   = default();
+        Self {path,index,matches,binding,users,external_count,static_count}
     }
 
     /// Checks whether the variable is being used. Please note that all external variables are
@@ -69,6 +73,21 @@ impl Query {
     fn dec_external_count(&mut self) {
         self.external_count -= 1;
     }
+
+    /// Checks whether this query is used by one or more static variables. See `Sheet::static_var`.
+    pub fn is_static(&self) -> bool {
+        self.static_count > 0
+    }
+
+    /// Increments the counter of static users of this query.
+    fn inc_static_count(&mut self) {
+        self.static_count += 1;
+    }
+
+    /// Decrements the counter of static users of this query.
+    fn dec_static_count(&mut self) {
+        self.static_count -= 1;
+    }
 }
 
 
@@ -245,6 +264,128 @@ impl Change {
 
 
 
+// ===================
+// === Text Format ===
+// ===================
+
+/// Error of parsing the style sheet text format.
+#[derive(Clone,Debug,Fail)]
+#[fail(display="Invalid style sheet entry in line {}: {}.",line,reason)]
+#[allow(missing_docs)]
+pub struct ParseError {
+    pub line   : usize,
+    pub reason : String,
+}
+
+/// Parses a single `path = value` entry. The value format is described in `Data`'s `FromStr`
+/// implementation.
+pub fn parse_entry(text:&str) -> Result<(Path,Data),String> {
+    let mut parts = text.splitn(2,'=');
+    let path      = parts.next().unwrap_or_default().trim();
+    let value     = parts.next().ok_or_else(|| "missing value".to_string())?;
+    let value     = value.trim().parse::<Data>()?;
+    if path.is_empty() { Err("missing style path".into()) } else { Ok((path.into(),value)) }
+}
+
+/// Parses the style sheet text format: `path = value` entries, one per line. Empty lines and
+/// lines starting with a hash are ignored. See `Sheet::to_text`.
+pub fn parse_text(text:&str) -> Result<Vec<Change>,ParseError> {
+    let lines = text.lines().enumerate().map(|(index,line)| (index+1,line.trim()));
+    let lines = lines.filter(|(_,line)| !line.is_empty() && !line.starts_with('#'));
+    lines.map(|(line,text)| {
+        let (path,value) = parse_entry(text).map_err(|reason| ParseError {line,reason})?;
+        Ok(Change::new(path,Some(value.into())))
+    }).collect()
+}
+
+
+
+// ==================
+// === Resolution ===
+// ==================
+
+/// The role of a sheet node in resolving a path. See `Resolution`.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum CandidateStatus {
+    /// The node has no value, so it is skipped.
+    Undefined,
+    /// The node is the most specific one with a value, so it provides the resolved value.
+    Bound,
+    /// The node has a value, but a more specific node is bound instead.
+    Overridden,
+}
+
+/// A sheet node which may provide the value of the resolved path.
+#[derive(Clone,Debug,PartialEq)]
+#[allow(missing_docs)]
+pub struct Candidate {
+    pub path   : Path,
+    pub value  : Option<Data>,
+    pub status : CandidateStatus,
+    /// Paths of the arguments of the expression computing the value, if the node was assigned
+    /// with an expression.
+    pub expression_args : Option<Vec<Path>>,
+}
+
+/// The state of the query of the resolved path, if any `Var` or expression uses it.
+#[derive(Clone,Debug,PartialEq)]
+pub struct QueryState {
+    /// The path of the sheet node the query is bound to. It should always be the path of the
+    /// `Bound` candidate.
+    pub binding : Option<Path>,
+    /// The number of `Var`s using the query.
+    pub external_count : usize,
+    /// Paths of the sheet nodes whose expressions use the query.
+    pub users : Vec<Path>,
+}
+
+/// The report of resolving a path in the style sheet, as a `Var` of this path would be resolved.
+/// Returned by `Sheet::inspect`.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Resolution {
+    /// The inspected path.
+    pub path : Path,
+    /// The resolved value.
+    pub value : Option<Data>,
+    /// All sheet nodes matching the path, from the most to the least specific one. For example,
+    /// for "panel.button.size" these are "panel.button.size", "button.size", and "size".
+    pub candidates : Vec<Candidate>,
+    /// The state of the query of this path, if it exists.
+    pub query : Option<QueryState>,
+}
+
+impl Display for Resolution {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let text_of = |value:&Option<Data>| {
+            value.as_ref().map_or("undefined".into(),|value| value.to_string())
+        };
+        writeln!(f,"{} = {}",self.path,text_of(&self.value))?;
+        for candidate in &self.candidates {
+            let status = match candidate.status {
+                CandidateStatus::Undefined  => "undefined",
+                CandidateStatus::Bound      => "bound",
+                CandidateStatus::Overridden => "overridden",
+            };
+            write!(f,"  [{}] {} = {}",status,candidate.path,text_of(&candidate.value))?;
+            if let Some(args) = &candidate.expression_args {
+                write!(f," (expression of {})",args.iter().join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        if let Some(query) = &self.query {
+            let binding = query.binding.as_ref().map_or("nothing".into(),|path| path.to_string());
+            write!(f,"  query bound to {}, used by {} vars",binding,query.external_count)?;
+            if !query.users.is_empty() {
+                write!(f," and expressions of {}",query.users.iter().join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+
+
 // =================
 // === SheetData ===
 // =================
@@ -346,11 +487,8 @@ impl SheetData {
     pub fn query<P>(&self, path:P) -> Option<&Data>
     where P:Into<Path> {
         let path = path.into();
-        while path.rev_segments.is_empty() {
-            let value = self.value(&path);
-            if value.is_some() { return value }
-        }
-        None
+        let segs = &path.rev_segments;
+        (1..=segs.len()).rev().find_map(|len| self.value_by_rev_segments(&segs[..len]))
     }
 
     /// Reads the value of the style sheet by the exact path provided. If you want to read a value
@@ -358,8 +496,35 @@ impl SheetData {
     pub fn value<P>(&self, path:P) -> Option<&Data>
     where P:Into<Path> {
         let path = path.into();
-        let segs = &path.rev_segments;
-        self.node_map.get_node(segs).and_then(|t| self.nodes[t.value].value.as_ref())
+        self.value_by_rev_segments(&path.rev_segments)
+    }
+
+    fn value_by_rev_segments(&self, rev_segments:&[String]) -> Option<&Data> {
+        self.sheet_node_by_rev_segments(rev_segments).and_then(|node| node.value.as_ref())
+    }
+
+    fn sheet_node_by_rev_segments(&self, rev_segments:&[String]) -> Option<&SheetNode> {
+        self.node_map.get_node(rev_segments).map(|t| &self.nodes[t.value])
+    }
+
+    /// Checks whether the value of the given path is used by a static variable, either directly
+    /// or through expressions. See `Sheet::static_var`.
+    pub fn is_used_statically<P>(&self, path:P) -> bool
+    where P:Into<Path> {
+        let path        = path.into();
+        let node        = self.node_map.get_node(&path.rev_segments).map(|t| t.value);
+        let mut stack   = node.into_iter().collect_vec();
+        let mut visited = HashSet::new();
+        while let Some(sheet_node_index) = stack.pop() {
+            if visited.insert(sheet_node_index) {
+                for query_index in &self.nodes[sheet_node_index].bindings {
+                    let query = &self.queries[*query_index];
+                    if query.is_static() { return true }
+                    stack.extend(query.users.iter().cloned());
+                }
+            }
+        }
+        false
     }
 
     /// Returns the amount of queries used.
@@ -641,6 +806,64 @@ impl SheetData {
         format!("digraph G {{\nnode [shape=box style=rounded]\n{}\n}}",dot)
     }
 
+    /// Explains how the path is resolved: which sheet nodes match it, which one provides the
+    /// value, and which ones are overridden. See `Resolution`.
+    pub fn inspect<P>(&self, path:P) -> Resolution
+    where P:Into<Path> {
+        let path       = path.into();
+        let segs       = &path.rev_segments;
+        let mut bound  = None;
+        let candidates = (1..=segs.len()).rev().map(|len| {
+            let node   = self.sheet_node_by_rev_segments(&segs[..len]);
+            let value  = node.and_then(|node| node.value.clone());
+            let status = match (value.is_some(),bound.is_some()) {
+                (false,_)    => CandidateStatus::Undefined,
+                (true,false) => { bound = value.clone(); CandidateStatus::Bound }
+                (true,true)  => CandidateStatus::Overridden,
+            };
+            let expression_args = node.and_then(|node| node.expr.as_ref()).map(|expr| {
+                expr.args.iter().map(|query| self.queries[*query].path.clone()).collect()
+            });
+            let path = Path::from_rev_segments(&segs[..len]);
+            Candidate {path,value,status,expression_args}
+        }).collect();
+        let query = self.query_map.get(segs).and_then(|t| *t).map(|query_index| {
+            let query          = &self.queries[query_index];
+            let path_of        = |node:Index<SheetNode>| self.nodes[node].path.clone();
+            let binding        = query.binding.map(path_of);
+            let external_count = query.external_count;
+            let mut users      = query.users.iter().map(|node| path_of(*node)).collect_vec();
+            users.sort_by_key(|path| path.to_string());
+            QueryState {binding,external_count,users}
+        });
+        Resolution {path,value:bound,candidates,query}
+    }
+
+    /// Serializes all the values of the style sheet to the text format, with one `path = value`
+    /// entry per line, sorted by paths. The values computed by expressions are written as they
+    /// are, preceded by a comment with the expression arguments. The invalid values are written
+    /// as comments. Use `parse_text` or `Sheet::from_text` to read it back.
+    pub fn to_text(&self) -> String {
+        let mut entries = self.nodes.iter().filter_map(|node| {
+            let value = node.value.as_ref()?;
+            let path  = node.path.to_string();
+            let entry = match value.to_text() {
+                Some(text) => iformat!("{path} = {text}"),
+                None       => iformat!("# {path} = {value}"),
+            };
+            let entry = match &node.expr {
+                None       => entry,
+                Some(expr) => {
+                    let args = expr.args.iter().map(|query| &self.queries[*query].path).join(", ");
+                    iformat!("# Computed from: {args}.\n{entry}")
+                }
+            };
+            Some((path,entry))
+        }).collect_vec();
+        entries.sort_by(|(path1,_),(path2,_)| path1.cmp(path2));
+        entries.into_iter().map(|(_,entry)| entry + "\n").collect()
+    }
+
     fn sheet_node_map_to_graphviz(&self, dot:&mut String, node_map:&NodeMap) {
         let sheet_node_index = node_map.value;
         let sheet_node = &self.nodes[sheet_node_index];
@@ -718,15 +941,20 @@ pub struct VarData {
     sheet       : Sheet,
     query_index : Index<Query>,
     callbacks   : CallbackRegistry,
+    is_static   : bool,
 }
 
 impl VarData {
     /// Constructor.
-    pub fn new<R>(sheet:R, query_index:Index<Query>, callbacks:CallbackRegistry) -> Self
-    where R:Into<Sheet> {
-        let sheet = sheet.into();
-        sheet.rc.borrow_mut().queries[query_index].inc_external_count();
-        Self {sheet,query_index,callbacks}
+    pub fn new<R>(sheet:R, query_index:Index<Query>, callbacks:CallbackRegistry, is_static:bool)
+    -> Self where R:Into<Sheet> {
+        let sheet          = sheet.into();
+        let mut sheet_data = sheet.rc.borrow_mut();
+        let query          = &mut sheet_data.queries[query_index];
+        query.inc_external_count();
+        if is_static { query.inc_static_count() }
+        drop(sheet_data);
+        Self {sheet,query_index,callbacks,is_static}
     }
 
     /// Adds a new callback used when value changes. Returns handle to the callback. As soon as the
@@ -746,16 +974,18 @@ impl Drop for VarData {
     fn drop(&mut self) {
         self.sheet.callbacks.borrow_mut().remove(&self.query_index);
         let sheet_data = &mut *self.sheet.rc.borrow_mut();
-        sheet_data.queries[self.query_index].dec_external_count();
+        let query      = &mut sheet_data.queries[self.query_index];
+        query.dec_external_count();
+        if self.is_static { query.dec_static_count() }
         sheet_data.drop_query_if_unused(self.query_index);
     }
 }
 
 impl Var {
     /// Constructor.
-    pub fn new<R>(sheet:R, query_index:Index<Query>, callbacks:CallbackRegistry) -> Self
-    where R:Into<Sheet> {
-        let rc = Rc::new(VarData::new(sheet,query_index,callbacks));
+    pub fn new<R>(sheet:R, query_index:Index<Query>, callbacks:CallbackRegistry, is_static:bool)
+    -> Self where R:Into<Sheet> {
+        let rc = Rc::new(VarData::new(sheet,query_index,callbacks,is_static));
         Self {rc}
     }
 }
@@ -788,10 +1018,22 @@ impl Sheet {
     /// Creates a new style sheet `Var`.
     pub fn var<P>(&self, path:P) -> Var
     where P:Into<Path> {
-        let query_index          = self.rc.borrow_mut().unmanaged_query(path);
-        let callback_registry = callback::SharedRegistryMut1::<Option<Data>>::default();
-        self.callbacks.borrow_mut().insert(query_index,callback_registry.clone_ref());
-        Var::new(self,query_index,callback_registry)
+        self.new_var(path,false)
+    }
+
+    /// Creates a new static style sheet `Var`. Static vars are used where the value is baked into
+    /// something expensive to rebuild, like a shader. The values used by static vars are never
+    /// animated, see `theme::Manager`.
+    pub fn static_var<P>(&self, path:P) -> Var
+    where P:Into<Path> {
+        self.new_var(path,true)
+    }
+
+    /// Checks whether the value of the given path is used by a static variable, either directly
+    /// or through expressions.
+    pub fn is_used_statically<P>(&self, path:P) -> bool
+    where P:Into<Path> {
+        self.rc.borrow().is_used_statically(path)
     }
 
     /// Sets the value by the given path.
@@ -847,12 +1089,38 @@ impl Sheet {
     pub fn to_graphviz(&self) -> String {
         self.rc.borrow().to_graphviz()
     }
+
+    /// Explains how the path is resolved. See `Resolution`.
+    pub fn inspect<P>(&self, path:P) -> Resolution
+    where P:Into<Path> {
+        self.rc.borrow().inspect(path)
+    }
+
+    /// Serializes all the values of the style sheet to the text format. See `SheetData::to_text`.
+    pub fn to_text(&self) -> String {
+        self.rc.borrow().to_text()
+    }
+
+    /// Creates a style sheet with the values read from the text format. See `to_text`.
+    pub fn from_text(text:&str) -> Result<Self,ParseError> {
+        let sheet = Self::new();
+        sheet.apply_changes(parse_text(text)?);
+        Ok(sheet)
+    }
 }
 
 
 // === Private ===
 
 impl Sheet {
+    fn new_var<P>(&self, path:P, is_static:bool) -> Var
+    where P:Into<Path> {
+        let query_index       = self.rc.borrow_mut().unmanaged_query(path);
+        let callback_registry = callback::SharedRegistryMut1::<Option<Data>>::default();
+        self.callbacks.borrow_mut().insert(query_index,callback_registry.clone_ref());
+        Var::new(self,query_index,callback_registry,is_static)
+    }
+
     /// Runs callbacks registered for the given variable id.
     fn run_callbacks_for(&self, query_index:Index<Query>) {
         if let Some(callbacks) = self.callbacks.borrow().get(&query_index).map(|t| t.clone_ref()) {
@@ -971,6 +1239,25 @@ mod tests {
         assert_query_sheet_count(&sheet,0,0);
     }
 
+    #[test]
+    pub fn static_variables() {
+        let sheet = Sheet::new();
+        sheet.set("size",data(1.0));
+        sheet.set("button.size",data(2.0));
+        sheet.set("circle.radius",Expression::new(&["size"], |args| args[0] + &data(10.0)));
+        let _var = sheet.var("panel.size");
+        assert!(!sheet.is_used_statically("button.size"));
+        assert!(!sheet.is_used_statically("size"));
+        let radius = sheet.static_var("circle.radius");
+        assert!(sheet.is_used_statically("circle.radius"));
+        assert!(sheet.is_used_statically("size"));
+        assert!(!sheet.is_used_statically("button.size"));
+        drop(radius);
+        assert!(!sheet.is_used_statically("size"));
+        let _size = sheet.static_var("button.size");
+        assert!(sheet.is_used_statically("button.size"));
+    }
+
     #[test]
     pub fn variable_unbind() {
         let sheet   = Sheet::new();
@@ -1058,4 +1345,60 @@ mod tests {
         assert!(style.value("a").is_none());
         assert!(style.value("b").is_none());
     }
+
+    #[test]
+    pub fn querying_paths() {
+        let sheet = Sheet::new();
+        sheet.set("size",data(1.0));
+        sheet.set("button.size",data(2.0));
+        assert_eq!(sheet.query("panel.button.size"),Some(data(2.0)));
+        assert_eq!(sheet.query("panel.size"),Some(data(1.0)));
+        assert_eq!(sheet.query("panel.width"),None);
+        assert_eq!(sheet.value("panel.size"),None);
+    }
+
+    #[test]
+    pub fn inspecting_resolution() {
+        let sheet = Sheet::new();
+        let _var  = sheet.var("panel.button.size");
+        sheet.set("size",data(1.0));
+        sheet.set("button.size",data(2.0));
+        sheet.set("circle.radius",Expression::new(&["panel.button.size"],|args| args[0].clone()));
+        let resolution = sheet.inspect("panel.button.size");
+        assert_eq!(resolution.value, Some(data(2.0)));
+        let statuses = resolution.candidates.iter().map(|c| (c.path.to_string(),c.status));
+        let expected = vec!
+            [ ("panel.button.size".to_string() , CandidateStatus::Undefined)
+            , ("button.size".to_string()       , CandidateStatus::Bound)
+            , ("size".to_string()              , CandidateStatus::Overridden)
+            ];
+        assert_eq!(statuses.collect_vec(), expected);
+        let query = resolution.query.unwrap();
+        assert_eq!(query.binding, Some(Path::from("button.size")));
+        assert_eq!(query.external_count, 1);
+        assert_eq!(query.users, vec![Path::from("circle.radius")]);
+
+        let resolution = sheet.inspect("circle.radius");
+        let args       = resolution.candidates[0].expression_args.clone();
+        assert_eq!(args, Some(vec![Path::from("panel.button.size")]));
+        assert!(resolution.query.is_none());
+    }
+
+    #[test]
+    pub fn text_format_round_trip() {
+        let sheet = Sheet::new();
+        sheet.set("size",data(1.5));
+        sheet.set("button.color",data(crate::data::color::Lcha::new(0.5,0.25,0.75,1.0)));
+        sheet.set("button.size",Expression::new(&["size"],|args| args[0] + &data(1.0)));
+        let text     = sheet.to_text();
+        let expected = "button.color = lcha(0.5,0.25,0.75,1)\n\
+                        # Computed from: size.\n\
+                        button.size = 2.5\n\
+                        size = 1.5\n";
+        assert_eq!(text, expected);
+        let loaded = Sheet::from_text(&text).unwrap();
+        assert_eq!(loaded.to_text(), text.replace("# Computed from: size.\n",""));
+        assert_eq!(Sheet::from_text("size = 1\nsize = x").unwrap_err().line, 2);
+        assert_eq!(Sheet::from_text("\n = 1").unwrap_err().line, 2);
+    }
 }
@@ -1,10 +1,31 @@
 //! Defines `Theme`, a smart style manager on top of style sheets.
+//!
+//! Themes can be defined in code, or loaded from theme files. A theme file may define many themes,
+//! each starting with its name in square brackets, followed by style values, one per line:
+//!
+//! ```text
+//! # Lines starting with a hash are comments.
+//! [light]
+//! application.background.color       = lcha(0.96,0.013,0.18,1.0)
+//! graph_editor.node.selection.size   = 7.0
+//! graph_editor.node.selection.color  = rgba(0.9,0.6,0.2,1.0)
+//! ```
+//!
+//! Values are numbers or colors, in the format described in `Data`'s `FromStr` implementation.
+//!
+//! Switching themes animates only the dynamic styles, i.e. the ones read through style sheet vars
+//! (`Sheet::var`) and applied on change, like the background or the port label colors. The styles
+//! read through `StyleWatch::get` are baked into shape shaders, which are rebuilt on each change,
+//! so they switch to the new value at once, when the transition starts. A style which should be
+//! animated must be read as a dynamic var and passed to the shape as a parameter.
 
 use crate::prelude::*;
 
+use crate::animation::easing;
 use crate::data::HashMapTree;
 use crate::data::color;
 
+use super::data::Data;
 use super::sheet::Path;
 use super::sheet::Change;
 use super::sheet::Value;
@@ -47,18 +68,95 @@ impl PartialSemigroup<&Theme> for Theme {
 
 
 
+// ==================
+// === Theme File ===
+// ==================
+
+/// Error of parsing the theme file.
+#[derive(Clone,Debug,Fail)]
+#[fail(display="Invalid theme definition in line {}: {}.",line,reason)]
+#[allow(missing_docs)]
+pub struct ParseError {
+    pub line   : usize,
+    pub reason : String,
+}
+
+/// Parses the theme file, returning the defined themes with their names in order of definition.
+/// See the module docs to learn about the file format.
+pub fn parse(text:&str) -> Result<Vec<(String,Theme)>,ParseError> {
+    let mut themes = Vec::<(String,Theme)>::new();
+    let lines      = text.lines().enumerate().map(|(index,line)| (index+1,line.trim()));
+    let lines      = lines.filter(|(_,line)| !line.is_empty() && !line.starts_with('#'));
+    for (line,text) in lines {
+        let error = |reason:String| ParseError {line,reason};
+        if text.starts_with('[') {
+            let name = text.ends_with(']').as_some_from(|| text[1..text.len()-1].trim());
+            let name = name.filter(|name| !name.is_empty());
+            let name = name.ok_or_else(|| error("malformed theme name".into()))?;
+            themes.push((name.into(),Theme::new()));
+        } else {
+            let (path,value) = style::parse_entry(text).map_err(error)?;
+            let (_,theme)    = themes.last_mut().ok_or_else(|| error("missing theme name".into()))?;
+            theme.insert(path,value);
+        }
+    }
+    Ok(themes)
+}
+
+
 
 // ===============
 // === Manager ===
 // ===============
 
+/// The default duration of the animated transition between themes, in milliseconds.
+pub const DEFAULT_TRANSITION_DURATION:f32 = 300.0;
+
+/// A style value animated during the transition between themes.
+#[derive(Clone,Debug)]
+struct AnimatedValue {
+    path  : Path,
+    start : Data,
+    end   : Data,
+}
+
+impl AnimatedValue {
+    /// The style sheet change setting the value at the given transition progress.
+    fn change(&self, weight:f32) -> Change {
+        let mixed = (weight < 1.0).and_option_from(|| self.start.mix(&self.end,weight));
+        let value = mixed.unwrap_or_else(|| self.end.clone());
+        Change::new(&self.path,Some(value.into()))
+    }
+}
+
+/// A running transition between themes.
+#[derive(Debug)]
+struct Transition {
+    values   : Rc<Vec<AnimatedValue>>,
+    animator : easing::DynAnimator<f32,easing::QuadInOut>,
+}
+
 /// Internal data used by the `Manager`.
-#[derive(Debug,Default)]
+#[derive(Debug)]
 pub struct ManagerData {
-    all         : HashMap<String,Theme>,
-    enabled     : Vec<String>,
-    combined    : Theme,
-    style_sheet : style::Sheet,
+    all                 : HashMap<String,Theme>,
+    enabled             : Vec<String>,
+    combined            : Theme,
+    style_sheet         : style::Sheet,
+    transition          : Option<Transition>,
+    transition_duration : f32,
+}
+
+impl Default for ManagerData {
+    fn default() -> Self {
+        let all                 = default();
+        let enabled             = default();
+        let combined            = default();
+        let style_sheet         = default();
+        let transition          = default();
+        let transition_duration = DEFAULT_TRANSITION_DURATION;
+        Self {all,enabled,combined,style_sheet,transition,transition_duration}
+    }
 }
 
 impl ManagerData {
@@ -72,6 +170,19 @@ impl ManagerData {
         &self.enabled
     }
 
+    /// Returns names of all registered themes, sorted alphabetically.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.all.keys().cloned().collect_vec();
+        names.sort();
+        names
+    }
+
+    /// Sets the duration of the animated transition between themes, in milliseconds. The value
+    /// of 0.0 disables the animation.
+    pub fn set_transition_duration(&mut self, duration:f32) {
+        self.transition_duration = duration;
+    }
+
     /// Sets a new set of enabled themes.
     pub fn set_enabled<N>(&mut self, names:N)
     where N:IntoIterator, N::Item:ToString {
@@ -83,8 +194,9 @@ impl ManagerData {
             }
         };
 
-        let mut changes = Vec::<Change>::new();
-        let diff        = self.combined.tree.zip_clone(&combined.tree);
+        let mut changes  = Vec::<Change>::new();
+        let mut animated = Vec::<AnimatedValue>::new();
+        let diff         = self.combined.tree.zip_clone(&combined.tree);
         for (segments,values) in &diff {
             let path   = Path::from_rev_segments(segments);
             let first  = values.first().and_then(|t|t.as_ref());
@@ -93,12 +205,55 @@ impl ManagerData {
                 match (first,second) {
                     (None,None)     => {}
                     (Some(_),None)  => changes.push(Change::new(path,None)),
-                    (_,Some(value)) => changes.push(Change::new(path,Some(value.clone()))),
+                    (_,Some(value)) => match self.animated_value(&path,value) {
+                        Some(value) => animated.push(value),
+                        None        => changes.push(Change::new(path,Some(value.clone()))),
+                    }
                 }
             }
         }
         self.combined = combined;
         self.style_sheet.apply_changes(changes);
+        self.start_transition(animated);
+    }
+
+    /// Checks if the change of the style value to `target` can be animated, and if so, returns
+    /// the description of the animation. Only the data values which are currently set in the style
+    /// sheet to a value of the same type can be animated. The values used by static vars (e.g.
+    /// baked into shaders) are never animated, as each change would rebuild their users.
+    fn animated_value(&self, path:&Path, target:&Value) -> Option<AnimatedValue> {
+        let enabled = self.transition_duration > 0.0;
+        let enabled = enabled && !self.style_sheet.is_used_statically(path);
+        let end     = match target { Value::Data(data) => Some(data.clone()), _ => None };
+        let end     = end.filter(|_| enabled)?;
+        let start   = self.style_sheet.value(path)?;
+        let path    = path.clone();
+        start.mix(&end,0.0).map(|_| AnimatedValue {path,start,end})
+    }
+
+    /// Starts the animation of the given values. The values animated by the previous transition,
+    /// which are not animated by the new one, are immediately set to their target values.
+    fn start_transition(&mut self, values:Vec<AnimatedValue>) {
+        if let Some(old) = self.transition.take() {
+            old.animator.stop();
+            let is_animated = |path:&Path| values.iter().any(|value| &value.path == path);
+            let finished    = old.values.iter().filter(|value| !is_animated(&value.path));
+            self.style_sheet.apply_changes(finished.map(|value| value.change(1.0)).collect_vec());
+        }
+        if !values.is_empty() {
+            let values      = Rc::new(values);
+            let style_sheet = self.style_sheet.clone_ref();
+            let step_values = values.clone_ref();
+            let callback    = Box::new(move |weight:f32| {
+                let changes = step_values.iter().map(|value| value.change(weight)).collect_vec();
+                style_sheet.apply_changes(changes);
+            });
+            let easing   = easing::quad_in_out();
+            let animator = easing::DynAnimator::new_not_started(0.0,1.0,easing,callback);
+            animator.set_duration(self.transition_duration);
+            animator.start();
+            self.transition = Some(Transition {values,animator});
+        }
     }
 
     /// Registers a new theme.
@@ -113,6 +268,18 @@ impl ManagerData {
         let name = name.as_ref();
         self.all.remove(name);
     }
+
+    /// Registers all themes defined in the theme file, returning their names. If a theme of the
+    /// same name was already registered, it is replaced. See the module docs to learn about the
+    /// file format.
+    pub fn load(&mut self, text:&str) -> Result<Vec<String>,ParseError> {
+        let themes = parse(text)?;
+        let names  = themes.iter().map(|(name,_)| name.clone()).collect();
+        for (name,theme) in themes {
+            self.register(name,theme);
+        }
+        Ok(names)
+    }
 }
 
 impl From<&style::Sheet> for ManagerData {
@@ -145,11 +312,35 @@ impl Manager {
         self.rc.borrow_mut().register(name,theme)
     }
 
-    /// Sets a new set of enabled themes.
+    /// Registers all themes defined in the theme file, returning their names. See the module
+    /// docs to learn about the file format.
+    pub fn load(&self, text:&str) -> Result<Vec<String>,ParseError> {
+        self.rc.borrow_mut().load(text)
+    }
+
+    /// Sets a new set of enabled themes. The changed dynamic styles are animated, see
+    /// `set_transition_duration`, while the styles baked into shaders are switched at once. See
+    /// the module docs for details.
     pub fn set_enabled<N>(&self, names:N)
     where N:IntoIterator, N::Item:ToString {
         self.rc.borrow_mut().set_enabled(names)
     }
+
+    /// Returns names of all enabled themes.
+    pub fn enabled(&self) -> Vec<String> {
+        self.rc.borrow().enabled().clone()
+    }
+
+    /// Returns names of all registered themes, sorted alphabetically.
+    pub fn names(&self) -> Vec<String> {
+        self.rc.borrow().names()
+    }
+
+    /// Sets the duration of the animated transition between themes, in milliseconds. The value
+    /// of 0.0 disables the animation.
+    pub fn set_transition_duration(&self, duration:f32) {
+        self.rc.borrow_mut().set_transition_duration(duration)
+    }
 }
 
 impl From<&style::Sheet> for Manager {
@@ -190,3 +381,69 @@ pub fn test() {
     println!("-------------------");
     theme_manager.set_enabled(&["theme1","theme2"]);
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::display::style::data::DataMatch;
+
+    #[test]
+    fn parsing_theme_file() {
+        let text = "
+            # Comment.
+            [dark]
+            application.background.color = lcha(0.13,0.013,0.18,1.0)
+            graph_editor.node.selection.size = 7
+
+            [light]
+            application.background.color = rgb(1.0, 1.0, 1.0)
+        ";
+        let themes = parse(text).unwrap();
+        let names  = themes.iter().map(|(name,_)| name.as_str()).collect_vec();
+        assert_eq!(names, vec!["dark","light"]);
+        let value = |theme:&Theme, path:&str| {
+            let path = Path::from(path);
+            match theme.tree.get(&path.rev_segments) {
+                Some(Some(Value::Data(data))) => Some(data.clone()),
+                _                             => None,
+            }
+        };
+        let (_,dark)  = &themes[0];
+        let (_,light) = &themes[1];
+        let dark_bg   = value(dark,"application.background.color").color();
+        let light_bg  = value(light,"application.background.color").color();
+        assert_eq!(dark_bg, Some(color::Lcha::new(0.13,0.013,0.18,1.0)));
+        assert_eq!(light_bg, Some(color::Rgba::new(1.0,1.0,1.0,1.0).into()));
+        assert_eq!(value(dark,"graph_editor.node.selection.size").number(), Some(7.0));
+        assert_eq!(value(light,"graph_editor.node.selection.size"), None);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let line = |text| parse(text).unwrap_err().line;
+        assert_eq!(line("a.b = 1"), 1);
+        assert_eq!(line("[dark]\n\na.b = lcha(1,2,3)"), 3);
+        assert_eq!(line("[dark]\na.b = rgba(1,2,x,1)"), 2);
+        assert_eq!(line("[dark]\na.b"), 2);
+        assert_eq!(line("[dark\n"), 1);
+    }
+
+    #[test]
+    fn mixing_values() {
+        let start = Data::Number(1.0);
+        let end   = Data::Number(3.0);
+        assert_eq!(start.mix(&end,0.5), Some(Data::Number(2.0)));
+        assert_eq!(start.mix(&Data::Color(color::Lcha::new(0.0,0.0,0.0,1.0)),0.5), None);
+        let black = Data::from(color::Rgba::new(0.0,0.0,0.0,0.0));
+        let white = Data::from(color::Rgba::new(1.0,1.0,1.0,1.0));
+        let alpha = black.mix(&white,0.25).color().map(|color| color.alpha);
+        assert_eq!(alpha, Some(0.25));
+    }
+}
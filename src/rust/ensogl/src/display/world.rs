@@ -33,6 +33,7 @@ use wasm_bindgen::prelude::Closure;
 #[derive(Clone,CloneRef,Debug)]
 pub struct CallbackHandles {
     on_before_frame : callback::Handle,
+    on_clock_tick   : callback::Handle,
     on_frame        : callback::Handle,
     on_after_frame  : callback::Handle,
 }
@@ -96,6 +97,7 @@ impl World {
         let focus_manager   = text_field::FocusManager::new_with_js_handlers();
 
         let on_before_frame = main_loop.on_before_frame (f_!(stats_monitor.begin()));
+        let on_clock_tick   = main_loop.drive_frp_clock(&scene.frp.clock);
         let on_after_frame  = main_loop.on_after_frame  (f_!(stats_monitor.end()));
         let on_frame        = main_loop.on_frame(
             f!([uniforms,scene_dirty,scene] (t:animation::TimeInfo) {
@@ -106,7 +108,8 @@ impl World {
             })
         );
 
-        let callback_handles = CallbackHandles {on_before_frame,on_frame,on_after_frame};
+        let callback_handles =
+            CallbackHandles {on_before_frame,on_clock_tick,on_frame,on_after_frame};
         Self {scene,scene_dirty,logger,main_loop,uniforms,callback_handles,stats,stats_monitor
              ,focus_manager} . init()
     }
@@ -136,6 +136,15 @@ impl KeyboardBinding {
         self.js_handlers.set_event_handler("keyup", &handler_js);
         self.key_up_handler = Some(handler_js);
     }
+
+    /// Replace the key and clipboard handlers with ones ignoring all events. The window defocus
+    /// handler is kept.
+    pub fn clear_text_input_handlers(&mut self) {
+        self.set_copy_handler(|_| String::new());
+        self.set_paste_handler(|_| {});
+        self.set_key_down_handler(|_| {});
+        self.set_key_up_handler(|_| {});
+    }
 }
 
 impl Drop for KeyboardBinding {
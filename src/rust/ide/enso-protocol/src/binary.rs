@@ -3,6 +3,7 @@
 pub mod client;
 pub mod connection;
 pub mod message;
+pub mod replay;
 pub mod serialization;
 pub mod uuid;
 
@@ -12,3 +13,4 @@ pub use client::Event;
 pub use client::Notification;
 pub use client::MockAPI as MockClient;
 pub use connection::Connection;
+pub use replay::MessageIds;
@@ -14,6 +14,7 @@ use crate::binary::message::VisualisationContext;
 use crate::language_server::types::Path;
 
 use json_rpc::error::RpcError;
+use json_rpc::inspector::Inspector;
 use json_rpc::TransportEvent;
 use json_rpc::Transport;
 use mockall::automock;
@@ -84,8 +85,9 @@ pub trait API {
 #[derive(Clone,Derivative)]
 #[derivative(Debug)]
 pub struct Client {
-    handler : Handler<Uuid, FromServerPayloadOwned,Notification>,
-    logger  : Logger,
+    handler   : Handler<Uuid, FromServerPayloadOwned,Notification>,
+    inspector : Inspector,
+    logger    : Logger,
 }
 
 impl Client {
@@ -101,7 +103,7 @@ impl Client {
     /// Function that does early processing of the peer's message and decides how it shall be
     /// handled. Returns a function so that it may be passed to the `Handler`.
     fn processor
-    (logger:Logger)
+    (logger:Logger, inspector:Inspector)
     -> impl FnMut(TransportEvent) -> Disposition<Uuid, FromServerPayloadOwned,Notification> + 'static {
         move |event:TransportEvent| {
             let binary_data = match event {
@@ -114,6 +116,9 @@ impl Client {
             };
             debug!(logger, "Deserialized incoming binary message: {message:?}");
             let correlation_id = message.correlation_id;
+            let id             = correlation_id.map(|id| id.to_string());
+            let method         = message.payload.method_name().map(ToString::to_string);
+            inspector.incoming(id,method,|| message.payload.summary());
             match message.0.payload {
                 FromServerPayloadOwned::VisualizationUpdate {context,data} =>
                     Disposition::notify(Notification::VisualizationUpdate {data,context}),
@@ -136,13 +141,20 @@ impl Client {
     /// * `init` must be called or it needs to be wrapped into `Connection`.
     pub fn new(parent:impl AnyLogger, transport:impl Transport + 'static) -> Client {
         let logger    = Logger::sub(parent,"binary-protocol-client");
-        let processor = Self::processor(logger.clone_ref());
+        let inspector = Inspector::default();
+        let processor = Self::processor(logger.clone_ref(),inspector.clone_ref());
         Client {
-            logger  : logger.clone_ref(),
-            handler : Handler::new(transport, logger, processor),
+            logger    : logger.clone_ref(),
+            handler   : Handler::new(transport, logger, processor),
+            inspector,
         }
     }
 
+    /// The hook observing the messages passed between the client and the server.
+    pub fn inspector(&self) -> Inspector {
+        self.inspector.clone_ref()
+    }
+
     /// Starts a new request, described by the given payload.
     /// Function `f` serves to retrieve the request's result from the more general `Reply` type.
     pub fn make_request<F,R>(&self, payload:ToServerPayload, f:F) -> LocalBoxFuture<FallibleResult<R>>
@@ -163,6 +175,8 @@ impl Client {
             }
         };
 
+        let method = Some(message.payload.method_name().to_string());
+        self.inspector.outgoing(Some(id.to_string()),method,|| message.payload.summary());
         let fut = self.handler.make_request(&message, completer);
         Box::pin(fut)
     }
@@ -360,4 +374,43 @@ mod tests {
         }
         tail.boxed_local().expect_pending();
     }
+
+
+
+    // =========================
+    // === Testing Inspector ===
+    // =========================
+
+    #[test]
+    fn test_inspecting_traffic() {
+        let mut fixture = ClientFixture::new();
+        let observed    = Rc::new(RefCell::new(Vec::new()));
+        let sink        = observed.clone();
+        let inspector   = fixture.client.inspector();
+        inspector.set_clock(|| 0.0);
+        inspector.set_observer(move |traffic| sink.borrow_mut().push(traffic.clone()));
+
+        let root_id   = Uuid::new_v4();
+        let path      = Path::new(root_id, &["Main.enso"]);
+        let mut fut   = fixture.client.read_file(&path);
+        let request   = fixture.transport.expect_binary_message();
+        let request   = MessageToServerOwned::deserialize(&request).unwrap();
+        let contents  = Vec::from("hello".as_bytes());
+        let payload   = FromServerPayloadOwned::FileContentsReply {contents};
+        let mut reply = MessageFromServer::new(payload);
+        reply.correlation_id = Some(request.message_id);
+        reply.with_serialized(|data| fixture.transport.mock_peer_binary_message(data));
+        fixture.executor.run_until_stalled();
+        fut.expect_ok();
+
+        let observed = observed.borrow();
+        let id       = Some(request.message_id.to_string());
+        assert_eq!(observed.len(), 2);
+        assert_eq!(observed[0].id      , id);
+        assert_eq!(observed[0].method  , Some("readFile".into()));
+        assert_eq!(observed[1].id      , id);
+        assert_eq!(observed[1].method  , Some("readFile".into()));
+        assert_eq!(observed[1].latency , Some(0.0));
+        assert_eq!(observed[1].summary , "5 bytes");
+    }
 }
@@ -110,6 +110,51 @@ pub enum FromServerPayload<'a> {
     FileContentsReply {contents:&'a [u8]},
 }
 
+impl<'a> ToServerPayload<'a> {
+    /// The name of the request, as it would appear in the JSON protocol.
+    pub fn method_name(&self) -> &'static str {
+        match self {
+            ToServerPayload::InitSession {..} => "initSession",
+            ToServerPayload::WriteFile   {..} => "writeFile",
+            ToServerPayload::ReadFile    {..} => "readFile",
+        }
+    }
+
+    /// Short description of the payload, omitting the binary data.
+    pub fn summary(&self) -> String {
+        match self {
+            ToServerPayload::InitSession {client_id}     => format!("client id {}",client_id),
+            ToServerPayload::WriteFile   {path,contents} =>
+                format!("{} ({} bytes)",path,contents.len()),
+            ToServerPayload::ReadFile    {path}          => path.to_string(),
+        }
+    }
+}
+
+impl FromServerPayloadOwned {
+    /// The name of the notification, as it would appear in the JSON protocol. Replies have no
+    /// name.
+    pub fn method_name(&self) -> Option<&'static str> {
+        match self {
+            FromServerPayloadOwned::VisualizationUpdate {..} => Some("visualizationUpdate"),
+            _                                                => None,
+        }
+    }
+
+    /// Short description of the payload, omitting the binary data.
+    pub fn summary(&self) -> String {
+        match self {
+            FromServerPayloadOwned::Error {code,message} =>
+                format!("error {}: {}",code,message),
+            FromServerPayloadOwned::Success {} => "success".into(),
+            FromServerPayloadOwned::VisualizationUpdate {context,data} =>
+                format!("{:?} ({} bytes)",context,data.len()),
+            FromServerPayloadOwned::FileContentsReply {contents} =>
+                format!("{} bytes",contents.len()),
+        }
+    }
+}
+
 
 
 // ===============
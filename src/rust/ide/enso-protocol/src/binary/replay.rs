@@ -0,0 +1,103 @@
+//! Support for replaying the recorded sessions of the binary protocol.
+
+use crate::prelude::*;
+
+use crate::binary::message::MessageFromServerOwned;
+use crate::binary::message::MessageToServerOwned;
+
+use json_rpc::trace::BinaryIds;
+
+
+
+// ==================
+// === MessageIds ===
+// ==================
+
+/// Gives the `ReplayTransport` access to the ids of binary protocol messages, so the replayed
+/// replies refer to the requests sent in the replaying session.
+///
+/// The requests are matched with the recorded ones regardless of their `message_id`, and the
+/// `correlation_id` of replies is rewritten to the id of the matched request.
+#[derive(Clone,Copy,Debug,Default)]
+pub struct MessageIds;
+
+impl BinaryIds for MessageIds {
+    fn strip_id(&self, message:&[u8]) -> Option<(Vec<u8>,Vec<u8>)> {
+        let mut message    = MessageToServerOwned::deserialize(message).ok()?;
+        let id             = message.message_id.as_bytes().to_vec();
+        message.message_id = Uuid::nil();
+        Some((message.with_serialized(|data| data.to_vec()),id))
+    }
+
+    fn reply_id(&self, message:&[u8]) -> Option<Vec<u8>> {
+        let message = MessageFromServerOwned::deserialize(message).ok()?;
+        Some(message.correlation_id?.as_bytes().to_vec())
+    }
+
+    fn rewrite_reply_id
+    (&self, message:&[u8], live_id:&dyn Fn(&[u8]) -> Option<Vec<u8>>) -> Option<Vec<u8>> {
+        let mut message        = MessageFromServerOwned::deserialize(message).ok()?;
+        let recorded_id        = message.correlation_id?;
+        let live_id            = live_id(recorded_id.as_bytes())?;
+        message.correlation_id = Some(Uuid::from_slice(&live_id).ok()?);
+        Some(message.with_serialized(|data| data.to_vec()))
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::binary::Client;
+    use crate::binary::message::FromServerPayloadOwned;
+    use crate::binary::message::MessageFromServer;
+    use crate::language_server::types::Path;
+
+    use futures::task::LocalSpawnExt;
+    use json_rpc::test_util::transport::mock::MockTransport;
+    use json_rpc::trace::RecordingTransport;
+    use json_rpc::trace::ReplayTransport;
+    use utils::test::traits::*;
+
+    #[test]
+    fn replaying_binary_session() {
+        let path     = Path::new(Uuid::new_v4(), &["Main.enso"]);
+        let contents = Vec::from("hello".as_bytes());
+
+        // Record the session with a mocked peer.
+        let mut peer      = MockTransport::new();
+        let mut recording = RecordingTransport::new(peer.clone());
+        let recorder      = recording.recorder();
+        let mut pool      = futures::executor::LocalPool::new();
+        pool.spawner().spawn_local(recording.runner()).unwrap();
+        let client        = Client::new(&Logger::default(),recording);
+        pool.spawner().spawn_local(client.runner()).unwrap();
+        let mut fut       = client.read_file(&path);
+        let request       = peer.expect_binary_message();
+        let request       = MessageToServerOwned::deserialize(&request).unwrap();
+        let payload       = FromServerPayloadOwned::FileContentsReply {contents:contents.clone()};
+        let mut reply     = MessageFromServer::new(payload);
+        reply.correlation_id = Some(request.message_id);
+        reply.with_serialized(|data| peer.mock_peer_binary_message(data));
+        pool.run_until_stalled();
+        assert_eq!(fut.expect_ok(), contents);
+
+        // Replay it, with the request having a different id than recorded.
+        let transport = ReplayTransport::new(recorder.trace()).with_binary_ids(MessageIds);
+        let mut pool  = futures::executor::LocalPool::new();
+        let client    = Client::new(&Logger::default(),transport);
+        pool.spawner().spawn_local(client.runner()).unwrap();
+        let mut fut   = client.read_file(&path);
+        pool.run_until_stalled();
+        assert_eq!(fut.expect_ok(), contents);
+
+        let other_path = Path::new(Uuid::new_v4(), &["Other.enso"]);
+        client.read_file(&other_path).expect_err();
+    }
+}
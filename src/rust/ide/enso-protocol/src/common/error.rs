@@ -2,6 +2,9 @@
 
 use crate::prelude::*;
 
+use json_rpc::error::RpcError;
+use serde::de::DeserializeOwned;
+
 /// When trying to parse a line, not a single line was produced.
 #[derive(Debug,Fail,Clone,Copy)]
 #[fail(display = "No active request by id {}", _0)]
@@ -16,3 +19,37 @@ pub struct DeserializationError(pub String);
 #[derive(Debug,Fail,Clone,Copy)]
 #[fail(display = "Received a message that is neither a response nor a notification")]
 pub struct UnexpectedMessage;
+
+/// An error reported by the remote peer in its JSON-RPC error reply, like the Language Server's
+/// or the Project Manager's errors. The implementors define the conversion from the reply, and
+/// get the common ways of extracting the typed error from the call results.
+pub trait RemoteError : Clone + Fail + From<json_rpc::messages::Error> {
+    /// Get the typed error from the remote peer's reply, if the call failed because of it.
+    fn from_rpc_error(error:&RpcError) -> Option<Self> {
+        match error {
+            RpcError::RemoteError(error) => Some(error.clone().into()),
+            _                            => None,
+        }
+    }
+
+    /// Get the typed error from the failure, if it was caused by the remote peer's reply.
+    fn from_failure(error:&failure::Error) -> Option<Self> {
+        let typed = error.downcast_ref::<Self>().cloned();
+        typed.or_else(|| error.downcast_ref::<RpcError>().and_then(Self::from_rpc_error))
+    }
+
+    /// Convert the call error to failure, replacing the remote peer's reply with the typed error,
+    /// so it is displayed with a meaningful message.
+    fn typed(error:RpcError) -> failure::Error {
+        match Self::from_rpc_error(&error) {
+            Some(typed) => typed.into(),
+            None        => error.into(),
+        }
+    }
+}
+
+/// Decode the additional information attached to the error reply of the remote peer. Returns
+/// `None` if there is no payload or it does not match the expected type.
+pub fn decode_payload<T:DeserializeOwned>(data:&Option<serde_json::Value>) -> Option<T> {
+    data.as_ref().and_then(|data| serde_json::from_value(data.clone()).ok())
+}
@@ -7,6 +7,7 @@
 //! This file tries to follow the scheme of the protocol specification.
 
 pub mod connection;
+pub mod error;
 pub mod response;
 #[cfg(test)]
 mod tests;
@@ -14,6 +15,7 @@ pub mod types;
 
 pub use types::*;
 pub use connection::Connection;
+pub use error::Error;
 
 use crate::prelude::*;
 
@@ -35,6 +37,10 @@ use uuid::Uuid;
 // === API & Client ===
 // ====================
 
+/// Timeout of requests which may trigger evaluation of the program, like entering a node. They
+/// may take much longer than the other requests on heavy code.
+pub const EXECUTION_TIMEOUT_SECS:u64 = 60;
+
 make_rpc_methods! {
 /// An interface containing all the available file management operations.
 trait API {
@@ -113,11 +119,13 @@ trait API {
     fn destroy_execution_context(&self, context_id:ContextId) -> ();
 
     /// Move the execution context to a new location deeper down the stack.
-    #[MethodInput=PushToExecutionContextInput,rpc_name="executionContext/push"]
+    #[MethodInput=PushToExecutionContextInput,rpc_name="executionContext/push",
+      timeout_secs=EXECUTION_TIMEOUT_SECS]
     fn push_to_execution_context(&self, context_id:ContextId, stack_item:StackItem) -> ();
 
     /// Move the execution context up the stack.
-    #[MethodInput=PopFromExecutionContextInput,rpc_name="executionContext/pop"]
+    #[MethodInput=PopFromExecutionContextInput,rpc_name="executionContext/pop",
+      timeout_secs=EXECUTION_TIMEOUT_SECS]
     fn pop_from_execution_context(&self, context_id:ContextId) -> ();
 
     /// Attach a visualisation, potentially preprocessed by some arbitrary Enso code, to a given
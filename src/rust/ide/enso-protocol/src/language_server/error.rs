@@ -0,0 +1,272 @@
+//! The errors reported by the Language Server, as described in the protocol specification.
+//!
+//! The Language Server replies with JSON-RPC errors having numeric codes. This module maps them
+//! to the typed `Error`, decoding the additional payloads, so callers can react to specific
+//! failures and display meaningful messages.
+
+use crate::prelude::*;
+
+use crate::common::error::decode_payload;
+use crate::types::Sha3_224;
+
+use serde::Deserialize;
+
+
+
+// =============
+// === Codes ===
+// =============
+
+/// Codes of the errors defined by the Language Server protocol.
+#[allow(missing_docs)]
+pub mod code {
+    pub const ACCESS_DENIED               : i64 = 100;
+    pub const FILE_SYSTEM                 : i64 = 1000;
+    pub const CONTENT_ROOT_NOT_FOUND      : i64 = 1001;
+    pub const FILE_NOT_FOUND              : i64 = 1003;
+    pub const FILE_EXISTS                 : i64 = 1004;
+    pub const OPERATION_TIMEOUT           : i64 = 1005;
+    pub const NOT_DIRECTORY               : i64 = 1006;
+    pub const STACK_ITEM_NOT_FOUND        : i64 = 2001;
+    pub const CONTEXT_NOT_FOUND           : i64 = 2002;
+    pub const EMPTY_STACK                 : i64 = 2003;
+    pub const INVALID_STACK_ITEM          : i64 = 2004;
+    pub const MODULE_NOT_FOUND            : i64 = 2005;
+    pub const VISUALISATION_NOT_FOUND     : i64 = 2006;
+    pub const VISUALISATION_EXPRESSION    : i64 = 2007;
+    pub const FILE_NOT_OPENED             : i64 = 3001;
+    pub const TEXT_EDIT_VALIDATION        : i64 = 3002;
+    pub const INVALID_VERSION             : i64 = 3003;
+    pub const WRITE_DENIED                : i64 = 3004;
+    pub const CAPABILITY_NOT_ACQUIRED     : i64 = 5001;
+    pub const SESSION_NOT_INITIALISED     : i64 = 6001;
+    pub const SESSION_ALREADY_INITIALISED : i64 = 6002;
+    pub const RESOURCES_INITIALIZATION    : i64 = 6003;
+}
+
+
+
+// ================
+// === Payloads ===
+// ================
+
+#[derive(Clone,Debug,Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModuleNotFoundPayload {
+    module_name : String,
+}
+
+#[derive(Clone,Debug,Deserialize)]
+struct VisualisationExpressionPayload {
+    message : String,
+}
+
+#[derive(Clone,Debug,Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvalidVersionPayload {
+    client_version : Sha3_224,
+    server_version : Sha3_224,
+}
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// An error reported by the Language Server.
+#[derive(Clone,Debug,Fail,PartialEq)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[fail(display="Access denied.")]
+    AccessDenied,
+    #[fail(display="File system error: {}.",message)]
+    FileSystem {message:String},
+    #[fail(display="Content root not found.")]
+    ContentRootNotFound,
+    #[fail(display="File not found.")]
+    FileNotFound,
+    #[fail(display="File already exists.")]
+    FileExists,
+    #[fail(display="The IO operation timed out.")]
+    OperationTimeout,
+    #[fail(display="The path is not a directory.")]
+    NotDirectory,
+    #[fail(display="Stack item not found.")]
+    StackItemNotFound,
+    #[fail(display="Execution context not found.")]
+    ContextNotFound,
+    #[fail(display="The execution context stack is empty.")]
+    EmptyStack,
+    #[fail(display="Invalid stack item.")]
+    InvalidStackItem,
+    #[fail(display="Module {} not found.",module_name)]
+    ModuleNotFound {module_name:String},
+    #[fail(display="Visualisation not found.")]
+    VisualisationNotFound,
+    #[fail(display="Evaluation of the visualisation expression failed: {}.",message)]
+    VisualisationExpression {message:String},
+    #[fail(display="The file is not opened.")]
+    FileNotOpened,
+    #[fail(display="Invalid text edit: {}.",message)]
+    TextEditValidation {message:String},
+    #[fail(display="Invalid file version: the client has {:?}, while the server has {:?}.",
+        client_version,server_version)]
+    InvalidVersion {client_version:Sha3_224, server_version:Sha3_224},
+    #[fail(display="The client does not hold the write lock.")]
+    WriteDenied,
+    #[fail(display="The capability is not acquired.")]
+    CapabilityNotAcquired,
+    #[fail(display="The session is not initialised.")]
+    SessionNotInitialised,
+    #[fail(display="The session is already initialised.")]
+    SessionAlreadyInitialised,
+    #[fail(display="Failed to initialize the Language Server resources.")]
+    ResourcesInitialization,
+    /// An error not described by the protocol, or having an invalid payload.
+    #[fail(display="Language Server error {}: {}.",code,message)]
+    Other {code:i64, message:String},
+}
+
+impl Error {
+    /// The protocol code of this error.
+    pub fn code(&self) -> i64 {
+        match self {
+            Error::AccessDenied                 => code::ACCESS_DENIED,
+            Error::FileSystem {..}              => code::FILE_SYSTEM,
+            Error::ContentRootNotFound          => code::CONTENT_ROOT_NOT_FOUND,
+            Error::FileNotFound                 => code::FILE_NOT_FOUND,
+            Error::FileExists                   => code::FILE_EXISTS,
+            Error::OperationTimeout             => code::OPERATION_TIMEOUT,
+            Error::NotDirectory                 => code::NOT_DIRECTORY,
+            Error::StackItemNotFound            => code::STACK_ITEM_NOT_FOUND,
+            Error::ContextNotFound              => code::CONTEXT_NOT_FOUND,
+            Error::EmptyStack                   => code::EMPTY_STACK,
+            Error::InvalidStackItem             => code::INVALID_STACK_ITEM,
+            Error::ModuleNotFound {..}          => code::MODULE_NOT_FOUND,
+            Error::VisualisationNotFound        => code::VISUALISATION_NOT_FOUND,
+            Error::VisualisationExpression {..} => code::VISUALISATION_EXPRESSION,
+            Error::FileNotOpened                => code::FILE_NOT_OPENED,
+            Error::TextEditValidation {..}      => code::TEXT_EDIT_VALIDATION,
+            Error::InvalidVersion {..}          => code::INVALID_VERSION,
+            Error::WriteDenied                  => code::WRITE_DENIED,
+            Error::CapabilityNotAcquired        => code::CAPABILITY_NOT_ACQUIRED,
+            Error::SessionNotInitialised        => code::SESSION_NOT_INITIALISED,
+            Error::SessionAlreadyInitialised    => code::SESSION_ALREADY_INITIALISED,
+            Error::ResourcesInitialization      => code::RESOURCES_INITIALIZATION,
+            Error::Other {code,..}              => *code,
+        }
+    }
+}
+
+impl RemoteError for Error {}
+
+impl From<json_rpc::messages::Error> for Error {
+    fn from(error:json_rpc::messages::Error) -> Self {
+        let json_rpc::messages::Error {code,message,data} = error;
+        let payload = || match code {
+            code::MODULE_NOT_FOUND => decode_payload(&data).map(|p:ModuleNotFoundPayload| {
+                Error::ModuleNotFound {module_name:p.module_name}
+            }),
+            code::VISUALISATION_EXPRESSION =>
+                decode_payload(&data).map(|p:VisualisationExpressionPayload| {
+                    Error::VisualisationExpression {message:p.message}
+                }),
+            code::INVALID_VERSION => decode_payload(&data).map(|p:InvalidVersionPayload| {
+                let client_version = p.client_version;
+                let server_version = p.server_version;
+                Error::InvalidVersion {client_version,server_version}
+            }),
+            _ => None,
+        };
+        let simple = match code {
+            code::ACCESS_DENIED               => Some(Error::AccessDenied),
+            code::FILE_SYSTEM                 => Some(Error::FileSystem {message:message.clone()}),
+            code::CONTENT_ROOT_NOT_FOUND      => Some(Error::ContentRootNotFound),
+            code::FILE_NOT_FOUND              => Some(Error::FileNotFound),
+            code::FILE_EXISTS                 => Some(Error::FileExists),
+            code::OPERATION_TIMEOUT           => Some(Error::OperationTimeout),
+            code::NOT_DIRECTORY               => Some(Error::NotDirectory),
+            code::STACK_ITEM_NOT_FOUND        => Some(Error::StackItemNotFound),
+            code::CONTEXT_NOT_FOUND           => Some(Error::ContextNotFound),
+            code::EMPTY_STACK                 => Some(Error::EmptyStack),
+            code::INVALID_STACK_ITEM          => Some(Error::InvalidStackItem),
+            code::VISUALISATION_NOT_FOUND     => Some(Error::VisualisationNotFound),
+            code::FILE_NOT_OPENED             => Some(Error::FileNotOpened),
+            code::TEXT_EDIT_VALIDATION        =>
+                Some(Error::TextEditValidation {message:message.clone()}),
+            code::WRITE_DENIED                => Some(Error::WriteDenied),
+            code::CAPABILITY_NOT_ACQUIRED     => Some(Error::CapabilityNotAcquired),
+            code::SESSION_NOT_INITIALISED     => Some(Error::SessionNotInitialised),
+            code::SESSION_ALREADY_INITIALISED => Some(Error::SessionAlreadyInitialised),
+            code::RESOURCES_INITIALIZATION    => Some(Error::ResourcesInitialization),
+            _                                 => None,
+        };
+        simple.or_else(payload).unwrap_or(Error::Other {code,message})
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use json_rpc::error::RpcError;
+
+    fn remote_error(code:i64, data:Option<serde_json::Value>) -> RpcError {
+        let message = "Error message".to_string();
+        RpcError::RemoteError(json_rpc::messages::Error {code,message,data})
+    }
+
+    #[test]
+    fn mapping_codes() {
+        let error = remote_error(code::FILE_NOT_FOUND,None);
+        assert_eq!(Error::from_rpc_error(&error), Some(Error::FileNotFound));
+        let error = remote_error(code::CONTEXT_NOT_FOUND,None);
+        assert_eq!(Error::from_rpc_error(&error), Some(Error::ContextNotFound));
+        let error = remote_error(42,None);
+        let other = Error::Other {code:42, message:"Error message".into()};
+        assert_eq!(Error::from_rpc_error(&error), Some(other));
+        assert_eq!(Error::from_rpc_error(&RpcError::LostConnection), None);
+        for code in &[code::ACCESS_DENIED,code::WRITE_DENIED,code::RESOURCES_INITIALIZATION] {
+            assert_eq!(Error::from_rpc_error(&remote_error(*code,None)).unwrap().code(), *code);
+        }
+    }
+
+    #[test]
+    fn decoding_payloads() {
+        let data  = serde_json::json!({"moduleName":"Main"});
+        let error = remote_error(code::MODULE_NOT_FOUND,Some(data));
+        let expected = Error::ModuleNotFound {module_name:"Main".into()};
+        assert_eq!(Error::from_rpc_error(&error), Some(expected));
+
+        let data  = serde_json::json!({"clientVersion":"abc","serverVersion":"def"});
+        let error = remote_error(code::INVALID_VERSION,Some(data));
+        match Error::from_rpc_error(&error) {
+            Some(Error::InvalidVersion {client_version,server_version}) => {
+                assert_eq!(client_version.as_str(), "abc");
+                assert_eq!(server_version.as_str(), "def");
+            }
+            other => panic!("Unexpected error {:?}", other),
+        }
+
+        let error = remote_error(code::MODULE_NOT_FOUND,None);
+        assert_eq!(Error::from_rpc_error(&error).unwrap().code(), code::MODULE_NOT_FOUND);
+    }
+
+    #[test]
+    fn getting_from_failure() {
+        let error:failure::Error = remote_error(code::WRITE_DENIED,None).into();
+        assert_eq!(Error::from_failure(&error), Some(Error::WriteDenied));
+        let error = Error::typed(remote_error(code::FILE_EXISTS,None));
+        assert_eq!(Error::from_failure(&error), Some(Error::FileExists));
+        assert_eq!(error.to_string(), "File already exists.");
+        let error:failure::Error = RpcError::LostConnection.into();
+        assert_eq!(Error::from_failure(&error), None);
+    }
+}
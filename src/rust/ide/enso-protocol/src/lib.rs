@@ -56,6 +56,7 @@ pub mod traits {
     pub use crate::binary::serialization::DeserializableRoot;
     pub use crate::binary::serialization::SerializableRoot;
     pub use crate::binary::serialization::SerializableUnion;
+    pub use crate::common::error::RemoteError;
 
     pub use crate::language_server::API as TRAIT_LanguageServerAPI;
     pub use crate::project_manager::API as TRAIT_ProjectManagerAPI;
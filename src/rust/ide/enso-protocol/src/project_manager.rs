@@ -6,6 +6,7 @@
 use crate::prelude::*;
 
 use crate::types::UTCDateTime;
+
 use json_rpc::api::Result;
 use json_rpc::Handler;
 use json_rpc::make_rpc_methods;
@@ -66,6 +67,91 @@ trait API {
 
 
 
+// =============
+// === Error ===
+// =============
+
+/// Codes of the errors defined by the Project Manager protocol.
+#[allow(missing_docs)]
+pub mod code {
+    pub const PROJECT_NAME_VALIDATION    : i64 = 4001;
+    pub const PROJECT_DATA_STORE         : i64 = 4002;
+    pub const PROJECT_EXISTS             : i64 = 4003;
+    pub const PROJECT_NOT_FOUND          : i64 = 4004;
+    pub const PROJECT_OPEN               : i64 = 4005;
+    pub const PROJECT_NOT_OPEN           : i64 = 4006;
+    pub const PROJECT_OPEN_BY_OTHERS     : i64 = 4007;
+    pub const CANNOT_REMOVE_OPEN_PROJECT : i64 = 4008;
+    pub const PROJECT_CLOSE              : i64 = 4009;
+}
+
+/// An error reported by the Project Manager.
+#[derive(Clone,Debug,Fail,PartialEq)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[fail(display="Invalid project name: {}.",message)]
+    ProjectNameValidation {message:String},
+    #[fail(display="Failed to access the project data store: {}.",message)]
+    ProjectDataStore {message:String},
+    #[fail(display="Project with the given name already exists.")]
+    ProjectExists,
+    #[fail(display="Project not found.")]
+    ProjectNotFound,
+    #[fail(display="Failed to open the project: {}.",message)]
+    ProjectOpen {message:String},
+    #[fail(display="The project is not open.")]
+    ProjectNotOpen,
+    #[fail(display="The project is open by other clients.")]
+    ProjectOpenByOthers,
+    #[fail(display="Cannot remove an open project.")]
+    CannotRemoveOpenProject,
+    #[fail(display="Failed to close the project: {}.",message)]
+    ProjectClose {message:String},
+    /// An error not described by the protocol.
+    #[fail(display="Project Manager error {}: {}.",code,message)]
+    Other {code:i64, message:String},
+}
+
+impl Error {
+    /// The protocol code of this error.
+    pub fn code(&self) -> i64 {
+        match self {
+            Error::ProjectNameValidation {..} => code::PROJECT_NAME_VALIDATION,
+            Error::ProjectDataStore {..}      => code::PROJECT_DATA_STORE,
+            Error::ProjectExists              => code::PROJECT_EXISTS,
+            Error::ProjectNotFound            => code::PROJECT_NOT_FOUND,
+            Error::ProjectOpen {..}           => code::PROJECT_OPEN,
+            Error::ProjectNotOpen             => code::PROJECT_NOT_OPEN,
+            Error::ProjectOpenByOthers        => code::PROJECT_OPEN_BY_OTHERS,
+            Error::CannotRemoveOpenProject    => code::CANNOT_REMOVE_OPEN_PROJECT,
+            Error::ProjectClose {..}          => code::PROJECT_CLOSE,
+            Error::Other {code,..}            => *code,
+        }
+    }
+}
+
+impl RemoteError for Error {}
+
+impl From<json_rpc::messages::Error> for Error {
+    fn from(error:json_rpc::messages::Error) -> Self {
+        let json_rpc::messages::Error {code,message,..} = error;
+        match code {
+            code::PROJECT_NAME_VALIDATION    => Error::ProjectNameValidation {message},
+            code::PROJECT_DATA_STORE         => Error::ProjectDataStore {message},
+            code::PROJECT_EXISTS             => Error::ProjectExists,
+            code::PROJECT_NOT_FOUND          => Error::ProjectNotFound,
+            code::PROJECT_OPEN               => Error::ProjectOpen {message},
+            code::PROJECT_NOT_OPEN           => Error::ProjectNotOpen,
+            code::PROJECT_OPEN_BY_OTHERS     => Error::ProjectOpenByOthers,
+            code::CANNOT_REMOVE_OPEN_PROJECT => Error::CannotRemoveOpenProject,
+            code::PROJECT_CLOSE              => Error::ProjectClose {message},
+            _                                => Error::Other {code,message},
+        }
+    }
+}
+
+
+
 // =============
 // === Types ===
 // =============
@@ -396,3 +482,50 @@ mod remote_client_tests {
         );
     }
 }
+
+
+
+// ===================
+// === Error tests ===
+// ===================
+
+#[cfg(test)]
+mod error_tests {
+    use super::*;
+
+    use json_rpc::error::RpcError;
+
+    fn remote_error(code:i64) -> RpcError {
+        let message = "Error message".to_string();
+        let data    = None;
+        RpcError::RemoteError(json_rpc::messages::Error {code,message,data})
+    }
+
+    #[test]
+    fn mapping_codes() {
+        let error = remote_error(code::PROJECT_NOT_FOUND);
+        assert_eq!(Error::from_rpc_error(&error), Some(Error::ProjectNotFound));
+        let error    = remote_error(code::PROJECT_OPEN);
+        let expected = Error::ProjectOpen {message:"Error message".into()};
+        assert_eq!(Error::from_rpc_error(&error), Some(expected));
+        let error = remote_error(42);
+        let other = Error::Other {code:42, message:"Error message".into()};
+        assert_eq!(Error::from_rpc_error(&error), Some(other));
+        assert_eq!(Error::from_rpc_error(&RpcError::LostConnection), None);
+        let codes = &[code::PROJECT_NAME_VALIDATION,code::PROJECT_EXISTS,code::PROJECT_CLOSE];
+        for code in codes {
+            assert_eq!(Error::from_rpc_error(&remote_error(*code)).unwrap().code(), *code);
+        }
+    }
+
+    #[test]
+    fn getting_from_failure() {
+        let error:failure::Error = remote_error(code::PROJECT_NOT_OPEN).into();
+        assert_eq!(Error::from_failure(&error), Some(Error::ProjectNotOpen));
+        let error = Error::typed(remote_error(code::CANNOT_REMOVE_OPEN_PROJECT));
+        assert_eq!(Error::from_failure(&error), Some(Error::CannotRemoveOpenProject));
+        assert_eq!(error.to_string(), "Cannot remove an open project.");
+        let error:failure::Error = RpcError::LostConnection.into();
+        assert_eq!(Error::from_failure(&error), None);
+    }
+}
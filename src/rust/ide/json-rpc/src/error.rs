@@ -4,6 +4,7 @@
 use crate::prelude::*;
 
 use crate::messages::Error;
+use crate::messages::Id;
 use crate::messages::Response;
 
 use futures::channel::oneshot::Canceled;
@@ -90,4 +91,16 @@ pub enum HandlingError {
     /// decode it.
     #[fail(display = "Failed to decode a notification: {}.", _0)]
     InvalidNotification(#[cause] serde_json::Error),
+
+    /// The reply to a request made by server could not be sent.
+    #[fail(display = "Failed to send a reply to the request id={}: {}.", _0, _1)]
+    ReplyNotSent(Id,String),
+
+    /// The reply to an invalid message sent by server could not be sent.
+    #[fail(display = "Failed to send a reply to an invalid request: {}.", _0)]
+    InvalidRequestReplyNotSent(String),
+
+    /// The notification about cancelling the request could not be sent.
+    #[fail(display = "Failed to notify about cancelling the request id={}: {}.", _0, _1)]
+    CancelNotSent(Id,String),
 }
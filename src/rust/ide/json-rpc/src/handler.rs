@@ -8,6 +8,7 @@ use crate::ensogl::sleep;
 use crate::ensogl::Duration;
 use crate::error::HandlingError;
 use crate::error::RpcError;
+use crate::inspector::Inspector;
 use crate::messages;
 use crate::messages::Id;
 use crate::transport::Transport;
@@ -20,6 +21,7 @@ use futures::Stream;
 use futures::channel::mpsc::unbounded;
 use futures::channel::mpsc::UnboundedSender;
 use futures::channel::oneshot;
+use serde::Serialize;
 use serde::de::DeserializeOwned;
 use std::future::Future;
 use utils::channel;
@@ -104,6 +106,160 @@ pub enum Event<N> {
 
 
 
+// =================
+// === Responder ===
+// =================
+
+/// Result of handling a request made by the peer, being either the returned value or an error.
+pub type RequestResult = std::result::Result<serde_json::Value,messages::Error>;
+
+/// A handle used to reply to a single request made by the peer.
+///
+/// The reply does not need to be sent immediately, e.g. the user may be asked for confirmation
+/// first. If the responder is dropped without replying, the peer gets an internal error.
+///
+/// The replies to requests of an incoming batch are collected and sent in a single batch message.
+#[derive(Debug)]
+pub struct Responder<Notification> {
+    id      : Id,
+    handler : WeakHandler<Notification>,
+    batch   : Option<Rc<BatchReplies<Notification>>>,
+    replied : bool,
+}
+
+impl<Notification> Responder<Notification> {
+    fn new
+    (id:Id, handler:WeakHandler<Notification>, batch:Option<Rc<BatchReplies<Notification>>>)
+    -> Self {
+        let replied = false;
+        Responder {id,handler,batch,replied}
+    }
+
+    /// The id of the request being replied.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Send the reply to the peer.
+    pub fn reply(mut self, result:RequestResult) {
+        self.send(result)
+    }
+
+    fn send(&mut self, result:RequestResult) {
+        self.replied = true;
+        if let Some(batch) = &self.batch {
+            batch.push(BatchReply::Response(reply_message(self.id,result)))
+        } else if let Some(handler) = self.handler.upgrade() {
+            handler.send_reply(self.id,result)
+        }
+    }
+}
+
+impl<Notification> Drop for Responder<Notification> {
+    fn drop(&mut self) {
+        if !self.replied {
+            let code = messages::error_code::INTERNAL_ERROR;
+            self.send(Err(messages::Error::new(code,"The request was not handled.")))
+        }
+    }
+}
+
+fn reply_message(id:Id, result:RequestResult) -> messages::ResponseMessage<serde_json::Value> {
+    let result = match result {
+        Ok(value)  => messages::Result::new_success(value),
+        Err(error) => messages::Result::Error {error},
+    };
+    messages::Message::new(messages::Response {id,result})
+}
+
+fn invalid_request_message(reason:impl Display) -> messages::InvalidRequestMessage {
+    let message = format!("Invalid Request: {}.", reason);
+    messages::Message::new(messages::InvalidRequestResponse::new(message))
+}
+
+
+
+// ====================
+// === BatchReplies ===
+// ====================
+
+/// A single reply in the batch sent to the peer.
+#[derive(Debug,Serialize)]
+#[serde(untagged)]
+enum BatchReply {
+    Response       (messages::ResponseMessage<serde_json::Value>),
+    InvalidRequest (messages::InvalidRequestMessage),
+}
+
+/// Collects the replies to the requests of a single incoming batch. It is shared by the batch's
+/// responders and sends all the replies in a single batch message when the last of them is gone.
+#[derive(Debug)]
+struct BatchReplies<Notification> {
+    handler : WeakHandler<Notification>,
+    replies : RefCell<Vec<BatchReply>>,
+}
+
+impl<Notification> BatchReplies<Notification> {
+    fn new(handler:WeakHandler<Notification>) -> Self {
+        let replies = default();
+        BatchReplies {handler,replies}
+    }
+
+    fn push(&self, reply:BatchReply) {
+        self.replies.borrow_mut().push(reply)
+    }
+}
+
+impl<Notification> Drop for BatchReplies<Notification> {
+    fn drop(&mut self) {
+        let replies = std::mem::take(self.replies.get_mut());
+        if let Some(handler) = self.handler.upgrade().filter(|_| !replies.is_empty()) {
+            handler.send_batch_reply(replies)
+        }
+    }
+}
+
+
+
+// ======================
+// === RequestHandler ===
+// ======================
+
+/// A function handling requests made by the peer for a single method. It gets the request
+/// parameters and the `Responder` which should be used to send the reply.
+#[derive(CloneRef)]
+pub struct RequestHandler<Notification> {
+    function : Rc<dyn Fn(serde_json::Value,Responder<Notification>)>,
+}
+
+impl<Notification> Clone for RequestHandler<Notification> {
+    fn clone(&self) -> Self {
+        let function = self.function.clone();
+        RequestHandler {function}
+    }
+}
+
+impl<Notification> Debug for RequestHandler<Notification> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"RequestHandler")
+    }
+}
+
+impl<Notification> RequestHandler<Notification> {
+    /// Constructor.
+    pub fn new(function:impl Fn(serde_json::Value,Responder<Notification>) + 'static) -> Self {
+        let function = Rc::new(function);
+        RequestHandler {function}
+    }
+
+    /// Handle the request.
+    pub fn call(&self, params:serde_json::Value, responder:Responder<Notification>) {
+        (self.function)(params,responder)
+    }
+}
+
+
+
 // ===================
 // === HandlerData ===
 // ===================
@@ -113,6 +269,9 @@ pub enum Event<N> {
 /// from this container.
 pub type OngoingCalls = HashMap<Id,oneshot::Sender<ReplyMessage>>;
 
+/// Handlers of the requests made by the peer, by method name.
+pub type RequestHandlers<Notification> = HashMap<String,RequestHandler<Notification>>;
+
 
 
 
@@ -126,7 +285,12 @@ pub type OngoingCalls = HashMap<Id,oneshot::Sender<ReplyMessage>>;
 /// It allows making request, where method calls are described by values
 /// implementing `RemoteMethodCall`. The response is returned as a `Future`.
 ///
-/// Notifications and internal messages are emitted using the `events` stream.
+/// Notifications and internal messages are emitted using the `events` stream. The requests made
+/// by the peer are passed to the handlers registered with `set_request_handler`.
+///
+/// Many requests and notifications may be sent in a single message using `batch`. Messages of
+/// incoming batches are handled separately, but the replies to their requests are sent back in a
+/// single batch message.
 ///
 /// `Notification` is a type for notifications. It should implement
 /// `DeserializeOwned` and deserialize from JSON maps with `method` and `params`
@@ -140,15 +304,23 @@ shared! { Handler
 #[derive(Debug)]
 pub struct HandlerData<Notification> {
     /// Timeout for futures.
-    timeout         : Duration,
+    timeout          : Duration,
+    /// Timeouts overriding `timeout` for requests of specific methods.
+    method_timeouts  : HashMap<String,Duration>,
+    /// Method of the notification sent to the peer when a request is cancelled.
+    cancel_method    : Option<String>,
     /// Ongoing calls.
-    ongoing_calls   : OngoingCalls,
+    ongoing_calls    : OngoingCalls,
     /// Handle to send outgoing events.
-    outgoing_events : Option<UnboundedSender<Event<Notification>>>,
+    outgoing_events  : Option<UnboundedSender<Event<Notification>>>,
     /// Provides identifiers for requests.
-    id_generator    : IdGenerator,
+    id_generator     : IdGenerator,
+    /// Handlers of the requests made by the peer.
+    request_handlers : RequestHandlers<Notification>,
+    /// Observes the sent and received messages.
+    inspector        : Inspector,
     /// Transports text messages between this handler and the peer.
-    transport       : Box<dyn Transport>,
+    transport        : Box<dyn Transport>,
 }
 
 
@@ -174,6 +346,23 @@ impl<Notification> {
         self.ongoing_calls.clear()
     }
 
+    /// Registers the handler of requests with given method made by the peer, replacing the
+    /// previous one.
+    pub fn insert_request_handler(&mut self, method:String, handler:RequestHandler<Notification>) {
+        self.request_handlers.insert(method,handler);
+    }
+
+    /// Removes the handler of requests with given method. Such requests will be replied with the
+    /// "method not found" error.
+    pub fn remove_request_handler(&mut self, method:&str) {
+        self.request_handlers.remove(method);
+    }
+
+    /// Gets the handler of requests with given method.
+    pub fn request_handler(&self, method:&str) -> Option<RequestHandler<Notification>> {
+        self.request_handlers.get(method).cloned()
+    }
+
     /// Obtains an id for a new request to be made.
     pub fn generate_new_id(&mut self) -> Id {
         self.id_generator.generate()
@@ -181,7 +370,15 @@ impl<Notification> {
 
     /// Sends a text message to the peer.
     pub fn send_text_message(&mut self, text:&str) -> std::result::Result<(), failure::Error> {
-        self.transport.send_text(text)
+        self.transport.send_text(text)?;
+        self.inspector.outgoing_json(text);
+        Ok(())
+    }
+
+    /// The hook observing the messages sent and received by this handler. It does nothing until
+    /// its observer is set.
+    pub fn inspector(&self) -> Inspector {
+        self.inspector.clone_ref()
     }
 
     /// Creates a new stream with events from this handler.
@@ -210,6 +407,30 @@ impl<Notification> {
     pub fn set_timeout(&mut self, timeout:Duration) {
         self.timeout = timeout;
     }
+
+    /// A `Duration` after which requests of given method are timed out.
+    pub fn method_timeout(&self, method:&str) -> Duration {
+        self.method_timeouts.get(method).copied().unwrap_or(self.timeout)
+    }
+
+    /// Set new timeout for future requests of given method, overriding the timeout set by
+    /// `set_timeout`. Pending requests are not affected.
+    pub fn set_method_timeout(&mut self, method:String, timeout:Duration) {
+        self.method_timeouts.insert(method,timeout);
+    }
+
+    /// Set the method of notification sent to the peer when a request is cancelled, either by
+    /// dropping its future or because of timeout. The notification has the request's `id` as
+    /// the only parameter, like `$/cancelRequest` in the Language Server Protocol. If `None`,
+    /// the peer is not notified.
+    pub fn set_cancel_method(&mut self, method:Option<String>) {
+        self.cancel_method = method;
+    }
+
+    /// The method of notification sent to the peer when a request is cancelled.
+    pub fn cancel_method(&self) -> Option<String> {
+        self.cancel_method.clone()
+    }
 }
 } // shared!
 
@@ -237,11 +458,15 @@ impl<Notification> Handler<Notification> {
     /// `Transport` must be functional (e.g. not in the process of opening).
     pub fn new(transport:impl Transport + 'static) -> Handler<Notification> {
         let data = HandlerData {
-            timeout         : crate::constants::TIMEOUT,
-            ongoing_calls   : default(),
-            id_generator    : IdGenerator::new(),
-            transport       : Box::new(transport),
-            outgoing_events : None,
+            timeout          : crate::constants::TIMEOUT,
+            method_timeouts  : default(),
+            cancel_method    : None,
+            ongoing_calls    : default(),
+            id_generator     : IdGenerator::new(),
+            request_handlers : default(),
+            inspector        : default(),
+            transport        : Box::new(transport),
+            outgoing_events  : None,
         };
         Handler {rc: Rc::new(RefCell::new(data))}
     }
@@ -251,9 +476,10 @@ impl<Notification> Handler<Notification> {
     pub fn open_request<In:api::RemoteMethodCall>
     (&self, input:In) -> impl Future<Output = Result<In::Returned>> {
         let id      = self.generate_new_id();
+        let timeout = self.method_timeout(In::NAME);
         let message = api::into_request_message(input,id);
         let serialized_message = serde_json::to_string(&message).unwrap();
-        self.open_request_with_message(id,&serialized_message)
+        self.send_request(id,&serialized_message,timeout)
     }
 
     /// Sends a request to the peer and returns a `Future` that shall yield a reply message.
@@ -270,9 +496,10 @@ impl<Notification> Handler<Notification> {
     pub fn open_request_with_json<Returned:DeserializeOwned>
     (&self, method_name:&str, input:&serde_json::Value) -> impl Future<Output = Result<Returned>> {
         let id      = self.generate_new_id();
+        let timeout = self.method_timeout(method_name);
         let message = crate::messages::Message::new_request(id,method_name,input);
         let serialized_message = serde_json::to_string(&message).unwrap();
-        self.open_request_with_message(id,&serialized_message)
+        self.send_request(id,&serialized_message,timeout)
     }
 
     /// Sends a request to the peer and returns a `Future` that shall yield a reply message.
@@ -281,25 +508,75 @@ impl<Notification> Handler<Notification> {
     /// `open_request_with_json` docstring for more information.
     pub fn open_request_with_message<Returned:DeserializeOwned>
     (&self, id:Id, message_json:&str) -> impl Future<Output = Result<Returned>> {
+        let message = serde_json::from_str::<serde_json::Value>(message_json).unwrap_or_default();
+        let timeout = self.request_timeout(&message);
+        self.send_request(id,message_json,timeout)
+    }
+
+    /// The timeout for the given request message: the one set for its method, or the default.
+    fn request_timeout(&self, message:&serde_json::Value) -> Duration {
+        let method = message.get("method").and_then(|method| method.as_str());
+        method.map_or_else(|| self.timeout(), |method| self.method_timeout(method))
+    }
+
+    fn send_request<Returned:DeserializeOwned>
+    (&self, id:Id, message_json:&str, timeout:Duration) -> impl Future<Output = Result<Returned>> {
+        let reply = self.expect_reply(id,timeout);
+        if self.send_text_message(message_json).is_err() {
+            // If message cannot be send, future ret must be cancelled.
+            self.remove_ongoing_request(id);
+        }
+        reply
+    }
+
+    /// Starts a batch of requests and notifications, to be sent to the peer in a single message.
+    pub fn batch(&self) -> Batch<Notification> {
+        Batch::new(self.clone_ref())
+    }
+
+    /// Registers the ongoing request with given id and returns a `Future` that shall yield its
+    /// decoded reply, or fail if no reply comes before the timeout.
+    ///
+    /// If the future is dropped or timed out before receiving the reply, the request is cancelled:
+    /// it is removed from ongoing requests and the peer is notified (see `set_cancel_method`).
+    fn expect_reply<Returned:DeserializeOwned>
+    (&self, id:Id, timeout:Duration) -> impl Future<Output = Result<Returned>> {
         let (sender, receiver) = oneshot::channel::<ReplyMessage>();
         let ret                = receiver.map(|result_or_cancel| {
             let result = result_or_cancel?;
             decode_result(result)
         });
-
         self.insert_ongoing_request(id,sender);
-        if self.send_text_message(message_json).is_err() {
-            // If message cannot be send, future ret must be cancelled.
-            self.remove_ongoing_request(id);
-        }
 
-        let millis = self.timeout().as_millis();
-        future::select(ret, sleep(self.timeout()).boxed_local()).map(move |either|
+        let millis = timeout.as_millis();
+        let reply  = future::select(ret, sleep(timeout).boxed_local()).map(move |either|
             match either {
                 future::Either::Left ((x, _)) => x,
                 future::Either::Right((_, _)) => Err(RpcError::TimeoutError{millis}),
             }
-        )
+        );
+        let guard = CancelGuard {id, handler:self.downgrade()};
+        async move {
+            let result = reply.await;
+            drop(guard);
+            result
+        }
+    }
+
+    /// Cancels the request if it is still ongoing. Returns `true` if it was.
+    pub fn cancel_request(&self, id:Id) -> bool {
+        let ongoing = self.remove_ongoing_request(id).is_some();
+        if let Some(method) = self.cancel_method().filter(|_| ongoing) {
+            let params  = serde_json::json!({"id":id});
+            let call    = messages::MethodCall {method,params};
+            let message = messages::Message::new(messages::Notification(call));
+            let sent    = serde_json::to_string(&message).map_err(failure::Error::from);
+            let sent    = sent.and_then(|text| self.send_text_message(&text));
+            if let Err(error) = sent {
+                self.error_occurred(HandlingError::CancelNotSent(id,error.to_string()));
+            }
+        }
+        ongoing
     }
 
     /// Deal with `Response` message from the peer.
@@ -316,6 +593,80 @@ impl<Notification> Handler<Notification> {
         }
     }
 
+    /// Sets the handler of requests with given method made by the peer.
+    ///
+    /// The handler gets the request parameters and a `Responder` used to reply. Requests of
+    /// methods without registered handler are replied with the "method not found" error.
+    pub fn set_request_handler<F>(&self, method:impl Str, handler:F)
+    where F : Fn(serde_json::Value,Responder<Notification>) + 'static {
+        self.insert_request_handler(method.into(),RequestHandler::new(handler))
+    }
+
+    /// Deal with `Request` message from the peer.
+    ///
+    /// The request is passed to the handler registered for its method. If there is none, the
+    /// peer is replied with an error.
+    pub fn process_request
+    (&self, message:messages::Request<messages::MethodCall<serde_json::Value>>) {
+        self.process_request_in_batch(message,None)
+    }
+
+    /// Deal with `Request` message from the peer, being a part of the incoming batch whose
+    /// replies are collected by `batch`.
+    fn process_request_in_batch
+    ( &self
+    , message : messages::Request<messages::MethodCall<serde_json::Value>>
+    , batch   : Option<Rc<BatchReplies<Notification>>>
+    ) {
+        let responder = Responder::new(message.id,self.downgrade(),batch);
+        let call      = message.call;
+        match self.request_handler(&call.method) {
+            Some(handler) => handler.call(call.params,responder),
+            None          => {
+                let code    = messages::error_code::METHOD_NOT_FOUND;
+                let message = format!("Method not found: {}.", call.method);
+                responder.reply(Err(messages::Error::new(code,message)))
+            }
+        }
+    }
+
+    /// Sends the reply to the request made by the peer.
+    fn send_reply(&self, id:Id, result:RequestResult) {
+        let message = reply_message(id,result);
+        let sent    = serde_json::to_string(&message).map_err(failure::Error::from);
+        let sent    = sent.and_then(|text| self.send_text_message(&text));
+        if let Err(error) = sent {
+            self.error_occurred(HandlingError::ReplyNotSent(id,error.to_string()));
+        }
+    }
+
+    /// Sends the replies to the requests of an incoming batch in a single batch message.
+    fn send_batch_reply(&self, replies:Vec<BatchReply>) {
+        let sent = serde_json::to_string(&replies).map_err(failure::Error::from);
+        let sent = sent.and_then(|text| self.send_text_message(&text));
+        if let Err(error) = sent {
+            for reply in replies {
+                let reason = error.to_string();
+                let error  = match reply {
+                    BatchReply::Response(reply) => HandlingError::ReplyNotSent(reply.id,reason),
+                    BatchReply::InvalidRequest(_) =>
+                        HandlingError::InvalidRequestReplyNotSent(reason),
+                };
+                self.error_occurred(error);
+            }
+        }
+    }
+
+    /// Replies to the message which is not a valid request.
+    fn send_invalid_request_reply(&self, reason:impl Display) {
+        let message = invalid_request_message(reason);
+        let sent    = serde_json::to_string(&message).map_err(failure::Error::from);
+        let sent    = sent.and_then(|text| self.send_text_message(&text));
+        if let Err(error) = sent {
+            self.error_occurred(HandlingError::InvalidRequestReplyNotSent(error.to_string()));
+        }
+    }
+
     /// Deal with `Notification` message from the peer.
     ///
     /// If possible, emits a message with notification. In case of failure,
@@ -337,17 +688,48 @@ impl<Notification> Handler<Notification> {
 
     /// Deal with incoming text message from the peer.
     ///
-    /// The message must conform either to the `Response` or to the
-    /// `Notification` JSON-serialized format. Otherwise, an error is raised.
+    /// The message must conform either to the `Response`, `Request` or to the
+    /// `Notification` JSON-serialized format, or be a batch of such messages.
+    /// Otherwise, an error is raised.
+    ///
+    /// The replies to the requests of a batch are sent in a single batch message, once all of
+    /// them are given. Each invalid message of the batch is replied with the "invalid request"
+    /// error in that message, and an empty batch gets a single "invalid request" reply.
     pub fn process_incoming_message(&self, message:String)
     where Notification: DeserializeOwned {
-        match messages::decode_incoming_message(&message) {
-            Ok(messages::IncomingMessage::Response(response)) =>
+        self.inspector().incoming_json(&message);
+        let is_batch = message.trim_start().starts_with('[');
+        let batch    = if is_batch {Some(Rc::new(BatchReplies::new(self.downgrade())))} else {None};
+        match messages::decode_incoming_messages(&message) {
+            Ok(messages) if is_batch && messages.is_empty() =>
+                self.send_invalid_request_reply("empty batch"),
+            Ok(messages) => for message in messages {
+                match message {
+                    Ok(messages::IncomingMessage::Request(request)) =>
+                        self.process_request_in_batch(request,batch.clone()),
+                    Ok(message) => self.process_decoded_message(message),
+                    Err(err)    => {
+                        if let Some(batch) = &batch {
+                            batch.push(BatchReply::InvalidRequest(invalid_request_message(&err)));
+                        }
+                        self.error_occurred(HandlingError::InvalidMessage(err))
+                    }
+                }
+            },
+            Err(err) => self.error_occurred(HandlingError::InvalidMessage(err)),
+        }
+    }
+
+    /// Deal with a single decoded message from the peer.
+    pub fn process_decoded_message(&self, message:messages::IncomingMessage)
+    where Notification: DeserializeOwned {
+        match message {
+            messages::IncomingMessage::Response(response) =>
                 self.process_response(response),
-            Ok(messages::IncomingMessage::Notification(notification)) =>
+            messages::IncomingMessage::Request(request) =>
+                self.process_request(request),
+            messages::IncomingMessage::Notification(notification) =>
                 self.process_notification(notification),
-            Err(err) =>
-                self.error_occurred(HandlingError::InvalidMessage(err)),
         }
     }
 
@@ -402,3 +784,110 @@ impl<Notification> Handler<Notification> {
         })
     }
 }
+
+
+
+// ===================
+// === CancelGuard ===
+// ===================
+
+/// Cancels the ongoing request when dropped. It is a part of the request's future, so the request
+/// is cancelled when the future is dropped or finishes without receiving a reply.
+#[derive(Debug)]
+struct CancelGuard<Notification> {
+    id      : Id,
+    handler : WeakHandler<Notification>,
+}
+
+impl<Notification> Drop for CancelGuard<Notification> {
+    fn drop(&mut self) {
+        if let Some(handler) = self.handler.upgrade() {
+            handler.cancel_request(self.id);
+        }
+    }
+}
+
+
+
+// =============
+// === Batch ===
+// =============
+
+/// Requests and notifications to be sent to the peer in a single batch message.
+///
+/// The futures returned for requests yield replies only after the batch is sent. If the batch is
+/// dropped without sending, they fail as if the connection was lost.
+#[derive(Debug)]
+pub struct Batch<Notification> {
+    handler  : Handler<Notification>,
+    ids      : Vec<Id>,
+    messages : Vec<serde_json::Value>,
+}
+
+impl<Notification> Batch<Notification> {
+    fn new(handler:Handler<Notification>) -> Self {
+        let ids      = default();
+        let messages = default();
+        Batch {handler,ids,messages}
+    }
+
+    /// Adds a request to the batch and returns a `Future` that shall yield its reply.
+    pub fn request<In:api::RemoteMethodCall>
+    (&mut self, input:In) -> impl Future<Output = Result<In::Returned>> {
+        let id      = self.handler.generate_new_id();
+        let message = api::into_request_message(input,id);
+        self.push_request(id,serde_json::to_value(&message).unwrap())
+    }
+
+    /// Adds a request to the batch and returns a `Future` that shall yield its reply.
+    ///
+    /// See `Handler::open_request_with_json` for the reason why this method exists.
+    pub fn request_with_json<Returned:DeserializeOwned>
+    (&mut self, method_name:&str, input:&serde_json::Value)
+    -> impl Future<Output = Result<Returned>> {
+        let id      = self.handler.generate_new_id();
+        let message = messages::Message::new_request(id,method_name,input);
+        self.push_request(id,serde_json::to_value(&message).unwrap())
+    }
+
+    /// Adds a notification to the batch.
+    pub fn notification(&mut self, method_name:&str, params:&serde_json::Value) {
+        let call    = messages::MethodCall {method:method_name.into(),params};
+        let message = messages::Message::new(messages::Notification(call));
+        self.messages.push(serde_json::to_value(&message).unwrap());
+    }
+
+    fn push_request<Returned:DeserializeOwned>
+    (&mut self, id:Id, message:serde_json::Value) -> impl Future<Output = Result<Returned>> {
+        let timeout = self.handler.request_timeout(&message);
+        self.ids.push(id);
+        self.messages.push(message);
+        self.handler.expect_reply(id,timeout)
+    }
+
+    /// Checks if nothing was added to the batch.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Sends all the requests and notifications to the peer. Empty batch is not sent at all.
+    pub fn send(mut self) -> std::result::Result<(),failure::Error> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            let messages = std::mem::take(&mut self.messages);
+            let text     = serde_json::to_string(&messages)?;
+            self.handler.send_text_message(&text)?;
+            self.ids.clear();
+            Ok(())
+        }
+    }
+}
+
+impl<Notification> Drop for Batch<Notification> {
+    fn drop(&mut self) {
+        for id in std::mem::take(&mut self.ids) {
+            self.handler.remove_ongoing_request(id);
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Observing the traffic passing through the protocol handlers, for debugging purposes.
+//!
+//! The `Inspector` is a hook shared by the handler and its user. Until the observer is set, it does
+//! nothing, so the messages are not even summarized. Once set, the observer gets a `Traffic` entry
+//! for each message sent or received, with the timestamp, the request id and the method name.
+//! The replies also get the latency of the request they answer.
+
+use crate::prelude::*;
+
+use serde_json::Value;
+
+
+
+// ===============
+// === Traffic ===
+// ===============
+
+/// Direction in which the message was passed.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+#[allow(missing_docs)]
+pub enum Direction {Outgoing,Incoming}
+
+/// A single message observed by the `Inspector`.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Traffic {
+    /// Whether the message was sent or received.
+    pub direction : Direction,
+    /// The time of passing the message, in milliseconds.
+    pub timestamp : f64,
+    /// The id of the request or of the request being replied.
+    pub id        : Option<String>,
+    /// The method of the request or notification. For replies, the method of the request.
+    pub method    : Option<String>,
+    /// For replies, the milliseconds passed since sending the request.
+    pub latency   : Option<f64>,
+    /// The message contents, or their short description.
+    pub summary   : String,
+}
+
+impl Display for Traffic {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        let arrow = match self.direction {
+            Direction::Outgoing => "-->",
+            Direction::Incoming => "<--",
+        };
+        write!(f,"[{:.1}ms] {}",self.timestamp,arrow)?;
+        if let Some(id)      = &self.id      { write!(f," #{}",id)?; }
+        if let Some(method)  = &self.method  { write!(f," {}",method)?; }
+        if let Some(latency) = &self.latency { write!(f," (after {:.1}ms)",latency)?; }
+        write!(f,": {}",self.summary)
+    }
+}
+
+
+
+// =================
+// === Inspector ===
+// =================
+
+/// The function getting the current time in milliseconds.
+pub type Clock = Rc<dyn Fn() -> f64>;
+
+/// The function called for each observed message.
+pub type Observer = Rc<dyn Fn(&Traffic)>;
+
+/// The default clock: `performance.now()` in the browser, time since the clock creation otherwise.
+pub fn default_clock() -> Clock {
+    #[cfg(target_arch = "wasm32")] {
+        Rc::new(|| crate::ensogl::performance().now())
+    }
+    #[cfg(not(target_arch = "wasm32"))] {
+        let origin = std::time::Instant::now();
+        Rc::new(move || origin.elapsed().as_secs_f64() * 1000.0)
+    }
+}
+
+/// A request sent to the peer, awaiting the reply.
+#[derive(Clone,Debug)]
+struct PendingRequest {
+    timestamp : f64,
+    method    : Option<String>,
+}
+
+struct InspectorData {
+    observer : Option<Observer>,
+    clock    : Clock,
+    pending  : HashMap<String,PendingRequest>,
+}
+
+impl Default for InspectorData {
+    fn default() -> Self {
+        let observer = None;
+        let clock    = default_clock();
+        let pending  = default();
+        InspectorData {observer,clock,pending}
+    }
+}
+
+/// A hook observing the messages passed by a protocol handler. See the module docs.
+#[derive(Clone,CloneRef,Default)]
+pub struct Inspector {
+    data : Rc<RefCell<InspectorData>>,
+}
+
+impl Debug for Inspector {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        write!(f,"Inspector(enabled: {})",self.is_enabled())
+    }
+}
+
+impl Inspector {
+    /// Set the function called for each observed message. It must not pass messages through the
+    /// inspected handler.
+    pub fn set_observer(&self, observer:impl Fn(&Traffic) + 'static) {
+        self.data.borrow_mut().observer = Some(Rc::new(observer));
+    }
+
+    /// Stop observing the messages.
+    pub fn unset_observer(&self) {
+        let mut data = self.data.borrow_mut();
+        data.observer = None;
+        data.pending.clear();
+    }
+
+    /// Replace the clock used to get the timestamps.
+    pub fn set_clock(&self, clock:impl Fn() -> f64 + 'static) {
+        self.data.borrow_mut().clock = Rc::new(clock);
+    }
+
+    /// Check if the observer is set.
+    pub fn is_enabled(&self) -> bool {
+        self.data.borrow().observer.is_some()
+    }
+
+    /// Observe a sent message. The messages having both `id` and `method` are considered requests,
+    /// and their replies will get the latency.
+    pub fn outgoing
+    (&self, id:Option<String>, method:Option<String>, summary:impl FnOnce() -> String) {
+        self.observe(Direction::Outgoing,id,method,summary)
+    }
+
+    /// Observe a received message. The messages having `id` but no `method` are considered replies
+    /// to the requests observed by `outgoing`.
+    pub fn incoming
+    (&self, id:Option<String>, method:Option<String>, summary:impl FnOnce() -> String) {
+        self.observe(Direction::Incoming,id,method,summary)
+    }
+
+    /// Observe a sent JSON-RPC message, or each message of a batch.
+    pub fn outgoing_json(&self, text:&str) {
+        self.observe_json(Direction::Outgoing,text)
+    }
+
+    /// Observe a received JSON-RPC message, or each message of a batch.
+    pub fn incoming_json(&self, text:&str) {
+        self.observe_json(Direction::Incoming,text)
+    }
+
+    fn observe_json(&self, direction:Direction, text:&str) {
+        if self.is_enabled() {
+            match serde_json::from_str::<Value>(text) {
+                Ok(Value::Array(batch)) => for message in batch {
+                    self.observe_json_value(direction,message)
+                },
+                Ok(message) => self.observe_json_value(direction,message),
+                Err(_)      => self.observe(direction,None,None,|| text.to_string()),
+            }
+        }
+    }
+
+    fn observe_json_value(&self, direction:Direction, message:Value) {
+        let id     = message.get("id").map(|id| id.to_string());
+        let method = message.get("method").and_then(Value::as_str).map(ToString::to_string);
+        self.observe(direction,id,method,|| message.to_string())
+    }
+
+    fn observe
+    ( &self
+    , direction : Direction
+    , id        : Option<String>
+    , method    : Option<String>
+    , summary   : impl FnOnce() -> String) {
+        let observer_and_traffic = with(self.data.borrow_mut(), |mut data| {
+            let observer    = data.observer.clone()?;
+            let timestamp   = (data.clock)();
+            let has_method  = method.is_some();
+            let mut latency = None;
+            let mut method  = method;
+            match (direction,id.clone(),has_method) {
+                (Direction::Outgoing,Some(id),true) => {
+                    let request = PendingRequest {timestamp,method:method.clone()};
+                    data.pending.insert(id,request);
+                }
+                (Direction::Incoming,Some(id),false) => {
+                    if let Some(request) = data.pending.remove(&id) {
+                        latency = Some(timestamp - request.timestamp);
+                        method  = request.method;
+                    }
+                }
+                _ => {}
+            }
+            let summary = default();
+            Some((observer,Traffic {direction,timestamp,id,method,latency,summary}))
+        });
+        // The observer is called without borrowing the data, so it may use the inspector.
+        if let Some((observer,mut traffic)) = observer_and_traffic {
+            traffic.summary = summary();
+            observer(&traffic);
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observing_json_traffic() {
+        let inspector = Inspector::default();
+        let time      = Rc::new(Cell::new(0.0));
+        let observed  = Rc::new(RefCell::new(Vec::new()));
+        inspector.outgoing_json(r#"{"jsonrpc":"2.0","id":0,"method":"pow","params":{"i":3}}"#);
+        let clock_time = time.clone();
+        let sink       = observed.clone();
+        inspector.set_clock(move || clock_time.get());
+        inspector.set_observer(move |traffic| sink.borrow_mut().push(traffic.clone()));
+
+        time.set(10.0);
+        inspector.outgoing_json(r#"{"jsonrpc":"2.0","id":1,"method":"pow","params":{"i":4}}"#);
+        time.set(25.0);
+        let batch = r#"[{"jsonrpc":"2.0","id":1,"result":16},{"jsonrpc":"2.0","method":"meow"}]"#;
+        inspector.incoming_json(batch);
+        // The request sent before setting the observer is not known.
+        inspector.incoming_json(r#"{"jsonrpc":"2.0","id":0,"result":9}"#);
+
+        let observed = observed.borrow();
+        assert_eq!(observed.len(), 4);
+        assert_eq!(observed[0].direction, Direction::Outgoing);
+        assert_eq!(observed[0].timestamp, 10.0);
+        assert_eq!(observed[0].method   , Some("pow".into()));
+        assert_eq!(observed[1].direction, Direction::Incoming);
+        assert_eq!(observed[1].id       , Some("1".into()));
+        assert_eq!(observed[1].method   , Some("pow".into()));
+        assert_eq!(observed[1].latency  , Some(15.0));
+        assert_eq!(observed[2].id       , None);
+        assert_eq!(observed[2].method   , Some("meow".into()));
+        assert_eq!(observed[3].method   , None);
+        assert_eq!(observed[3].latency  , None);
+        let expected = concat!(r#"[25.0ms] <-- #1 pow (after 15.0ms): "#,
+                               r#"{"id":1,"jsonrpc":"2.0","result":16}"#);
+        assert_eq!(observed[1].to_string(), expected);
+    }
+}
@@ -15,9 +15,11 @@
 pub mod api;
 pub mod error;
 pub mod handler;
+pub mod inspector;
 pub mod macros;
 pub mod messages;
 pub mod test_util;
+pub mod trace;
 pub mod transport;
 
 pub use api::RemoteMethodCall;
@@ -13,6 +13,9 @@
 /// }
 /// ```
 ///
+/// The default timeout of the method's requests may be overridden with an optional `timeout_secs`
+/// attribute, e.g. `#[MethodInput=CallMePleaseInput,rpc_name="callMePlease",timeout_secs=60]`.
+///
 /// This macro generates an `API` trait and creates two structs implementing `API`
 /// called `Client`, with the actual RPC methods, and `MockClient`, with mocked methods with
 /// return types setup by:
@@ -26,7 +29,8 @@ macro_rules! make_rpc_methods {
         $(#[doc = $impl_doc:expr])+
         trait API {
             $($(#[doc = $doc:expr])+
-            #[MethodInput=$method_input:ident,rpc_name=$rpc_name:expr]
+            #[MethodInput=$method_input:ident,rpc_name=$rpc_name:expr
+                $(,timeout_secs=$timeout_secs:expr)?]
             fn $method:ident(&self $(,$param_name:ident:$param_ty:ty)*) -> $result:ty;
             )*
         }
@@ -61,7 +65,12 @@ macro_rules! make_rpc_methods {
         impl Client {
             /// Create a new client that will use given transport.
             pub fn new(transport:impl json_rpc::Transport + 'static) -> Self {
-                let handler = RefCell::new(Handler::new(transport));
+                let handler = Handler::new(transport);
+                $($(
+                    let timeout = std::time::Duration::from_secs($timeout_secs);
+                    handler.set_method_timeout($rpc_name.into(),timeout);
+                )?)*
+                let handler = RefCell::new(handler);
                 Self { handler }
             }
 
@@ -83,6 +92,23 @@ macro_rules! make_rpc_methods {
             pub fn set_timeout(&mut self, timeout:std::time::Duration) {
                 self.handler.borrow().set_timeout(timeout);
             }
+
+            /// Set new timeout for future requests of method with given RPC name, overriding the
+            /// default one. Pending requests are not affected.
+            pub fn set_method_timeout(&self, rpc_name:&str, timeout:std::time::Duration) {
+                self.handler.borrow().set_method_timeout(rpc_name.into(),timeout);
+            }
+
+            /// Set the method of notification sent to the peer when a request is cancelled, by
+            /// dropping its future or because of timeout. If `None`, the peer is not notified.
+            pub fn set_cancel_method(&self, method:Option<String>) {
+                self.handler.borrow().set_cancel_method(method);
+            }
+
+            /// The hook observing the messages passed between the client and the peer.
+            pub fn inspector(&self) -> json_rpc::inspector::Inspector {
+                self.handler.borrow().inspector()
+            }
         }
 
         impl API for Client {
@@ -37,6 +37,9 @@ pub type RequestMessage<In> = Message<Request<MethodCall<In>>>;
 /// A response message.
 pub type ResponseMessage<Ret> = Message<Response<Ret>>;
 
+/// A response message to an invalid request.
+pub type InvalidRequestMessage = Message<InvalidRequestResponse>;
+
 /// A response message.
 pub type NotificationMessage<Ret> = Message<Notification<MethodCall<Ret>>>;
 
@@ -149,6 +152,25 @@ pub struct Response<Res> {
     pub result:Result<Res>
 }
 
+/// A response to a message which is not a valid request. As the id of such message cannot be
+/// determined, the `id` is always `null`, as required by the JSON-RPC 2.0 specification.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct InvalidRequestResponse {
+    /// Always `None`, serialized as `null`.
+    pub id    : Option<Id>,
+    /// The error with the `INVALID_REQUEST` code.
+    pub error : Error,
+}
+
+impl InvalidRequestResponse {
+    /// Construct the response bearing the error with given message.
+    pub fn new(message:impl Str) -> InvalidRequestResponse {
+        let id    = None;
+        let error = Error::new(error_code::INVALID_REQUEST,message);
+        InvalidRequestResponse {id,error}
+    }
+}
+
 /// Result of the remote call — either a returned value or en error.
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(untagged)]
@@ -186,7 +208,7 @@ pub struct Success<Ret> {
 }
 
 /// Error raised on a failed remote call.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Error {
     /// A number indicating what type of error occurred.
     pub code    : i64,
@@ -196,13 +218,37 @@ pub struct Error {
     pub data    : Option<serde_json::Value>
 }
 
-/// A message that can come from Server to Client — either a response or
+impl Error {
+    /// Construct an error that bears no optional data.
+    pub fn new(code:i64, message:impl Str) -> Error {
+        let message = message.into();
+        let data    = None;
+        Error {code,message,data}
+    }
+}
+
+/// Codes of the errors defined by the JSON-RPC 2.0 specification.
+#[allow(missing_docs)]
+pub mod error_code {
+    pub const PARSE_ERROR      : i64 = -32700;
+    pub const INVALID_REQUEST  : i64 = -32600;
+    pub const METHOD_NOT_FOUND : i64 = -32601;
+    pub const INVALID_PARAMS   : i64 = -32602;
+    pub const INTERNAL_ERROR   : i64 = -32603;
+}
+
+/// A message that can come from Server to Client — either a response, a request or
 /// notification.
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(untagged)]
 pub enum IncomingMessage {
     /// A response to a call made by client.
     Response    (Response    <serde_json::Value>),
+    /// A call initiated by the server, expecting a response.
+    ///
+    /// Must be tried before `Notification`, as requests differ from notifications only by the
+    /// `id` field.
+    Request     (Request     <MethodCall<serde_json::Value>>),
     /// A notification call (initiated by the server).
     Notification(Notification<serde_json::Value>),
 }
@@ -210,13 +256,31 @@ pub enum IncomingMessage {
 /// Partially decodes incoming message.
 ///
 /// This checks if has `jsonrpc` version string, and whether it is a
-/// response or a notification.
+/// response, a request or a notification.
 pub fn decode_incoming_message
 (message:&str) -> serde_json::Result<IncomingMessage> {
+    let message = serde_json::from_str::<serde_json::Value>(&message)?;
+    decode_incoming_value(message)
+}
+
+/// Partially decodes incoming text, being either a single message or a batch of messages.
+///
+/// The messages of a batch are decoded separately, so one malformed message does not prevent
+/// handling the others. Fails only if the text is not a valid JSON at all.
+pub fn decode_incoming_messages
+(text:&str) -> serde_json::Result<Vec<serde_json::Result<IncomingMessage>>> {
+    use serde_json::Value;
+    let messages = match serde_json::from_str::<Value>(&text)? {
+        Value::Array(messages) => messages,
+        message                => vec![message],
+    };
+    Ok(messages.into_iter().map(decode_incoming_value).collect())
+}
+
+fn decode_incoming_value(message:serde_json::Value) -> serde_json::Result<IncomingMessage> {
     use serde_json::Value;
-    use serde_json::from_str;
     use serde_json::from_value;
-    let message = from_str::<Message<Value>>(&message)?;
+    let message = from_value::<Message<Value>>(message)?;
     from_value::<IncomingMessage>(message.payload)
 }
 
@@ -377,4 +441,29 @@ mod tests {
             _ => panic!("Invalid decoding result of {}: {:?}", text, decoding_result),
         }
     }
+
+    #[test]
+    fn decode_incoming_request_and_batch() {
+        let request      = r#"{"jsonrpc":"2.0","id":3,"method":"confirm","params":{"x":1}}"#;
+        let notification = r#"{"jsonrpc":"2.0","method":"update","params":{"x":2}}"#;
+        let response     = r#"{"jsonrpc":"2.0","id":4,"result":null}"#;
+        match decode_incoming_message(request) {
+            Ok(IncomingMessage::Request(request)) => {
+                assert_eq!(request.id, Id(3));
+                assert_eq!(request.call.method, "confirm");
+            }
+            other => panic!("Invalid decoding result of {}: {:?}", request, other),
+        }
+
+        let batch    = format!("[{},{},{},\"garbage\"]",request,notification,response);
+        let messages = decode_incoming_messages(&batch).unwrap();
+        let kinds    = messages.iter().map(|message| match message {
+            Ok(IncomingMessage::Request(_))      => "request",
+            Ok(IncomingMessage::Notification(_)) => "notification",
+            Ok(IncomingMessage::Response(_))     => "response",
+            Err(_)                               => "error",
+        }).collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["request","notification","response","error"]);
+        assert!(decode_incoming_messages("[1,2").is_err());
+    }
 }
@@ -150,6 +150,13 @@ impl MockTransport {
         })
     }
 
+    /// Panics if the client has sent any text message not taken yet.
+    pub fn expect_no_text_message(&mut self) {
+        self.with_mut_data(|data| {
+            assert!(data.sent_text_msgs.is_empty(), "client should not have sent text message")
+        })
+    }
+
     /// Similar to `expect_message_text` but deserializes the message into
     /// given type `T` from JSON.
     pub fn expect_json_message<T:DeserializeOwned>(&mut self) -> T {
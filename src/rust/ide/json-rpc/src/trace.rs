@@ -0,0 +1,110 @@
+//! Recording and replaying the traffic of a `Transport`.
+//!
+//! The `RecordingTransport` wraps any transport and stores all the frames sent and received during
+//! a session in a `Trace`. The trace may be saved as JSON and later served back by the
+//! `ReplayTransport`, so scenarios recorded with a real server may be run offline, e.g. in tests
+//! or when reproducing bugs reported by users.
+
+pub mod recording;
+pub mod replay;
+
+pub use recording::RecordingTransport;
+pub use recording::Recorder;
+pub use replay::BinaryIds;
+pub use replay::ReplayTransport;
+
+use crate::prelude::*;
+
+use crate::transport::TransportEvent;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+
+
+// =============
+// === Frame ===
+// =============
+
+/// A single event of the recorded session.
+#[derive(Clone,Debug,Deserialize,Eq,PartialEq,Serialize)]
+#[serde(rename_all="camelCase", tag="kind", content="data")]
+#[allow(missing_docs)]
+pub enum Frame {
+    SentText       (String),
+    SentBinary     (Vec<u8>),
+    ReceivedText   (String),
+    ReceivedBinary (Vec<u8>),
+    Opened,
+    Closed,
+}
+
+impl Frame {
+    /// Create a frame describing the event received from the transport.
+    pub fn from_event(event:&TransportEvent) -> Self {
+        match event {
+            TransportEvent::TextMessage(text)   => Frame::ReceivedText(text.clone()),
+            TransportEvent::BinaryMessage(data) => Frame::ReceivedBinary(data.clone()),
+            TransportEvent::Opened              => Frame::Opened,
+            TransportEvent::Closed              => Frame::Closed,
+        }
+    }
+
+    /// Get the event received from the transport described by this frame. Returns `None` for
+    /// frames sent by the client.
+    pub fn to_event(&self) -> Option<TransportEvent> {
+        match self {
+            Frame::ReceivedText(text)   => Some(TransportEvent::TextMessage(text.clone())),
+            Frame::ReceivedBinary(data) => Some(TransportEvent::BinaryMessage(data.clone())),
+            Frame::Opened               => Some(TransportEvent::Opened),
+            Frame::Closed               => Some(TransportEvent::Closed),
+            _                           => None,
+        }
+    }
+
+    /// Check if the frame was sent by the client.
+    pub fn is_sent(&self) -> bool {
+        match self {
+            Frame::SentText(_) | Frame::SentBinary(_) => true,
+            _                                         => false,
+        }
+    }
+}
+
+
+
+// =============
+// === Trace ===
+// =============
+
+/// All frames of the recorded session, in order of their occurrence.
+#[derive(Clone,Debug,Default,Deserialize,Eq,PartialEq,Serialize)]
+pub struct Trace {
+    #[allow(missing_docs)]
+    pub frames : Vec<Frame>,
+}
+
+impl Trace {
+    /// Serialize the trace to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Deserialize the trace from JSON.
+    pub fn from_json(json:&str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Save the trace as JSON in the given file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path:impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path,self.to_json())
+    }
+
+    /// Load the trace from the JSON file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path:impl AsRef<std::path::Path>) -> Result<Self,failure::Error> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(Self::from_json(&json)?)
+    }
+}
@@ -0,0 +1,136 @@
+//! The transport recording the traffic of another transport.
+
+use crate::prelude::*;
+
+use crate::trace::Frame;
+use crate::trace::Trace;
+use crate::transport::Transport;
+use crate::transport::TransportEvent;
+
+use failure::Error;
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::channel::mpsc::UnboundedSender;
+use futures::channel::mpsc::unbounded;
+use futures::StreamExt;
+use std::future::Future;
+use utils::channel;
+
+
+
+// ================
+// === Recorder ===
+// ================
+
+/// The state shared between `RecordingTransport` and its `Recorder` handles.
+#[derive(Debug,Default)]
+struct RecorderData {
+    trace             : Trace,
+    event_transmitter : Option<UnboundedSender<TransportEvent>>,
+    /// Events received before setting the `event_transmitter`.
+    pending_events    : Vec<TransportEvent>,
+}
+
+/// A handle giving access to the trace of `RecordingTransport`. It may be kept after passing the
+/// transport to its user, e.g. the `Handler`.
+#[derive(Clone,CloneRef,Debug,Default)]
+pub struct Recorder {
+    data : Rc<RefCell<RecorderData>>,
+}
+
+impl Recorder {
+    /// Get the frames recorded so far.
+    pub fn trace(&self) -> Trace {
+        self.data.borrow().trace.clone()
+    }
+
+    /// Forget the frames recorded so far.
+    pub fn clear(&self) {
+        self.data.borrow_mut().trace.frames.clear()
+    }
+
+    fn record(&self, frame:Frame) {
+        self.data.borrow_mut().trace.frames.push(frame)
+    }
+
+    fn forward(&self, event:TransportEvent) {
+        self.record(Frame::from_event(&event));
+        let mut data = self.data.borrow_mut();
+        if let Some(transmitter) = data.event_transmitter.clone() {
+            channel::emit(&transmitter,event)
+        } else {
+            data.pending_events.push(event)
+        }
+    }
+
+    fn set_event_transmitter(&self, transmitter:UnboundedSender<TransportEvent>) {
+        let pending = std::mem::take(&mut self.data.borrow_mut().pending_events);
+        for event in pending {
+            channel::emit(&transmitter,event);
+        }
+        self.data.borrow_mut().event_transmitter = Some(transmitter);
+    }
+}
+
+
+
+// ==========================
+// === RecordingTransport ===
+// ==========================
+
+/// A transport passing all messages to the wrapped transport and recording them, together with
+/// all received events.
+///
+/// The received events are passed to the transport user by the future returned from `runner`,
+/// which must be spawned by the owner.
+#[derive(Debug)]
+pub struct RecordingTransport<T> {
+    inner          : T,
+    recorder       : Recorder,
+    event_receiver : Option<UnboundedReceiver<TransportEvent>>,
+}
+
+impl<T:Transport> RecordingTransport<T> {
+    /// Wrap the transport, starting recording.
+    pub fn new(mut inner:T) -> Self {
+        let (transmitter,receiver) = unbounded();
+        inner.set_event_transmitter(transmitter);
+        let recorder       = Recorder::default();
+        let event_receiver = Some(receiver);
+        RecordingTransport {inner,recorder,event_receiver}
+    }
+
+    /// Get the handle giving access to the recorded trace.
+    pub fn recorder(&self) -> Recorder {
+        self.recorder.clone_ref()
+    }
+
+    /// Returns a `Future` recording events received by the wrapped transport and passing them
+    /// to the user. It finishes when the wrapped transport's event stream finishes.
+    ///
+    /// May be called only once, the subsequent calls return futures finishing immediately.
+    pub fn runner(&mut self) -> impl Future<Output=()> {
+        let receiver = self.event_receiver.take();
+        let recorder = self.recorder.clone_ref();
+        let events   = futures::stream::iter(receiver).flatten();
+        events.for_each(move |event| {
+            recorder.forward(event);
+            futures::future::ready(())
+        })
+    }
+}
+
+impl<T:Transport> Transport for RecordingTransport<T> {
+    fn send_text(&mut self, message:&str) -> Result<(),Error> {
+        self.recorder.record(Frame::SentText(message.into()));
+        self.inner.send_text(message)
+    }
+
+    fn send_binary(&mut self, message:&[u8]) -> Result<(),Error> {
+        self.recorder.record(Frame::SentBinary(message.into()));
+        self.inner.send_binary(message)
+    }
+
+    fn set_event_transmitter(&mut self, transmitter:UnboundedSender<TransportEvent>) {
+        self.recorder.set_event_transmitter(transmitter)
+    }
+}
@@ -0,0 +1,316 @@
+//! The transport serving the recorded traffic back.
+
+use crate::prelude::*;
+
+use crate::trace::Frame;
+use crate::trace::Trace;
+use crate::transport::Transport;
+use crate::transport::TransportEvent;
+
+use failure::Error;
+use futures::channel::mpsc::UnboundedSender;
+use serde_json::Value;
+use utils::channel;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// Errors returned by the `ReplayTransport` when sending a message not matching the trace.
+#[derive(Clone,Debug,Fail)]
+#[allow(missing_docs)]
+pub enum ReplayError {
+    #[fail(display = "The sent message was not recorded in the trace: {}.", _0)]
+    UnexpectedText(String),
+    #[fail(display = "The sent binary message ({} bytes) was not recorded in the trace.", _0)]
+    UnexpectedBinary(usize),
+}
+
+
+
+// =================
+// === BinaryIds ===
+// =================
+
+/// Access to the ids of binary messages, which format is defined by the protocol using the
+/// transport.
+///
+/// Binary replies are correlated with their requests by ids generated anew in each session, so
+/// the `ReplayTransport` must rewrite them, just like the ids of text messages.
+pub trait BinaryIds : Debug {
+    /// Split the sent message into its content with the id cleared, used to match it with the
+    /// recorded messages, and the id itself. Returns `None` if the message cannot be decoded.
+    fn strip_id(&self, message:&[u8]) -> Option<(Vec<u8>,Vec<u8>)>;
+
+    /// Get the id of the request the received message replies to. Returns `None` if the message
+    /// cannot be decoded or is not a reply.
+    fn reply_id(&self, message:&[u8]) -> Option<Vec<u8>>;
+
+    /// Replace the id of the request the received message replies to, using `live_id` mapping the
+    /// recorded ids to the sent ones. Returns `None` if the message cannot be decoded or is not
+    /// a reply to any sent request.
+    fn rewrite_reply_id
+    (&self, message:&[u8], live_id:&dyn Fn(&[u8]) -> Option<Vec<u8>>) -> Option<Vec<u8>>;
+}
+
+
+
+// =================
+// === RequestId ===
+// =================
+
+/// The id of a request recorded in the trace, used to find the recorded replies to it.
+#[derive(Clone,Debug,PartialEq)]
+enum RequestId {
+    /// The id of a text request, serialized to JSON.
+    Text(String),
+    /// The id of a binary request.
+    Binary(Vec<u8>),
+}
+
+
+
+// =======================
+// === ReplayTransport ===
+// =======================
+
+/// A transport replying with the frames recorded in a `Trace`.
+///
+/// When a text message is sent, it is matched with the first not yet matched message recorded in
+/// the trace which differs at most by the `id` fields, i.e. has the same method and parameters.
+/// Then the recorded responses to that message are emitted, wherever they are in the trace, with
+/// their ids rewritten to the ids of the actually sent requests. Received frames not being
+/// responses, like notifications, are emitted after the recorded message they follow (up to the
+/// next sent one). The frames received before sending anything are emitted once the event
+/// transmitter is set.
+///
+/// Binary messages are matched the same way if the transport knows their format (see
+/// `with_binary_ids`), and by the exact content otherwise. Sending a message not matching any
+/// recorded one fails with `ReplayError`.
+#[derive(Debug)]
+pub struct ReplayTransport {
+    frames            : Vec<Frame>,
+    matched           : Vec<bool>,
+    emitted           : Vec<bool>,
+    /// The ids sent by the client, by the ids recorded in the trace (serialized to JSON).
+    ids               : HashMap<String,Value>,
+    binary_ids        : Option<Box<dyn BinaryIds>>,
+    /// The ids of binary messages sent by the client, by the recorded ids.
+    live_binary_ids   : HashMap<Vec<u8>,Vec<u8>>,
+    event_transmitter : Option<UnboundedSender<TransportEvent>>,
+    pending_events    : Vec<TransportEvent>,
+}
+
+impl ReplayTransport {
+    /// Create a transport replaying the given trace.
+    pub fn new(trace:Trace) -> Self {
+        let frames            = trace.frames;
+        let matched           = vec![false; frames.len()];
+        let emitted           = vec![false; frames.len()];
+        let ids               = default();
+        let binary_ids        = None;
+        let live_binary_ids   = default();
+        let event_transmitter = None;
+        let pending_events    = default();
+        let mut this          = Self {frames,matched,emitted,ids,binary_ids,live_binary_ids
+                                     ,event_transmitter,pending_events};
+        this.emit_replies(0,&[]);
+        this
+    }
+
+    /// Set the accessor of the binary messages' ids, so they are matched and rewritten like the
+    /// ids of text messages.
+    pub fn with_binary_ids(mut self, binary_ids:impl BinaryIds + 'static) -> Self {
+        self.binary_ids = Some(Box::new(binary_ids));
+        self
+    }
+
+    /// Check if all the messages recorded in the trace were sent.
+    pub fn is_finished(&self) -> bool {
+        self.frames.iter().zip(&self.matched).all(|(frame,matched)| !frame.is_sent() || *matched)
+    }
+
+    /// Emit the not yet emitted replies to the requests with given recorded ids, and the received
+    /// frames not being replies starting from `index` up to the next sent frame.
+    fn emit_replies(&mut self, index:usize, request_ids:&[RequestId]) {
+        let next_sent = self.frames.iter().skip(index).position(Frame::is_sent);
+        let end       = next_sent.map_or(self.frames.len(), |position| index + position);
+        let to_emit   = (0..self.frames.len()).filter(|i| !self.emitted[*i]).filter(|i| {
+            match self.reply_ids(&self.frames[*i]) {
+                Some(ids) => ids.iter().any(|id| request_ids.contains(id)),
+                None      => (index..end).contains(i),
+            }
+        }).collect_vec();
+        let events = to_emit.iter().filter_map(|i| match &self.frames[*i] {
+            Frame::ReceivedText(text) => Some(TransportEvent::TextMessage(self.rewrite_ids(text))),
+            Frame::ReceivedBinary(data) =>
+                Some(TransportEvent::BinaryMessage(self.rewrite_binary_ids(data))),
+            other => other.to_event(),
+        }).collect_vec();
+        for i in to_emit {
+            self.emitted[i] = true;
+        }
+        for event in events {
+            match &self.event_transmitter {
+                Some(transmitter) => channel::emit(transmitter,event),
+                None              => self.pending_events.push(event),
+            }
+        }
+    }
+
+    /// Get the recorded ids of the requests the received frame replies to. Returns `None` if the
+    /// frame is not a reply, or its format is unknown.
+    fn reply_ids(&self, frame:&Frame) -> Option<Vec<RequestId>> {
+        match frame {
+            Frame::ReceivedText(text) => {
+                let mut message = serde_json::from_str::<Value>(text).ok()?;
+                let mut ids     = Vec::new();
+                for_each_message(&mut message, |message| {
+                    let id = message.get("id").filter(|id| !id.is_null());
+                    if !message.contains_key("method") {
+                        ids.extend(id.map(|id| RequestId::Text(id.to_string())));
+                    }
+                });
+                if ids.is_empty() { None } else { Some(ids) }
+            }
+            Frame::ReceivedBinary(data) => {
+                let id = self.binary_ids.as_ref()?.reply_id(data)?;
+                Some(vec![RequestId::Binary(id)])
+            }
+            _ => None,
+        }
+    }
+
+    /// Replace the ids of the recorded responses with the ids of the actually sent requests.
+    fn rewrite_ids(&self, text:&str) -> String {
+        match serde_json::from_str::<Value>(text) {
+            Ok(mut message) => {
+                for_each_message(&mut message, |message| {
+                    let is_response = message.get("method").is_none();
+                    let id          = message.get_mut("id").filter(|_| is_response);
+                    if let Some(id) = id {
+                        if let Some(live_id) = self.ids.get(&id.to_string()) {
+                            *id = live_id.clone();
+                        }
+                    }
+                });
+                message.to_string()
+            }
+            Err(_) => text.to_string(),
+        }
+    }
+
+    /// Replace the request id the recorded binary reply refers to with the id actually sent.
+    fn rewrite_binary_ids(&self, data:&[u8]) -> Vec<u8> {
+        let live_id    = |id:&[u8]| self.live_binary_ids.get(id).cloned();
+        let binary_ids = self.binary_ids.as_ref();
+        let rewritten  = binary_ids.and_then(|ids| ids.rewrite_reply_id(data,&live_id));
+        rewritten.unwrap_or_else(|| data.to_vec())
+    }
+
+    /// Find the first not yet matched frame satisfying the predicate and mark it as matched.
+    fn match_frame(&mut self, predicate:impl Fn(&Frame) -> bool) -> Option<usize> {
+        let matched = &self.matched;
+        let index   = self.frames.iter().enumerate().position(|(index,frame)| {
+            !matched[index] && predicate(frame)
+        })?;
+        self.matched[index] = true;
+        Some(index)
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send_text(&mut self, message:&str) -> Result<(),Error> {
+        let (sent,sent_ids) = strip_ids(message);
+        let index = self.match_frame(|frame| match frame {
+            Frame::SentText(recorded) => strip_ids(recorded).0 == sent,
+            _                         => false,
+        });
+        let index = index.ok_or_else(|| ReplayError::UnexpectedText(message.into()))?;
+        let mut request_ids = Vec::new();
+        if let Frame::SentText(recorded) = &self.frames[index] {
+            let (_,recorded_ids) = strip_ids(recorded);
+            for (recorded_id,live_id) in recorded_ids.into_iter().zip(sent_ids) {
+                request_ids.push(RequestId::Text(recorded_id.to_string()));
+                self.ids.insert(recorded_id.to_string(),live_id);
+            }
+        }
+        self.emit_replies(index + 1,&request_ids);
+        Ok(())
+    }
+
+    fn send_binary(&mut self, message:&[u8]) -> Result<(),Error> {
+        let binary_ids     = self.binary_ids.take();
+        let (sent,live_id) = strip_binary_id(&binary_ids,message);
+        let index = self.match_frame(|frame| match frame {
+            Frame::SentBinary(recorded) => strip_binary_id(&binary_ids,recorded).0 == sent,
+            _                           => false,
+        });
+        let recorded_id = index.and_then(|index| match &self.frames[index] {
+            Frame::SentBinary(recorded) => strip_binary_id(&binary_ids,recorded).1,
+            _                           => None,
+        });
+        self.binary_ids = binary_ids;
+        let index = index.ok_or_else(|| ReplayError::UnexpectedBinary(message.len()))?;
+        let mut request_ids = Vec::new();
+        if let (Some(recorded_id),Some(live_id)) = (recorded_id,live_id) {
+            request_ids.push(RequestId::Binary(recorded_id.clone()));
+            self.live_binary_ids.insert(recorded_id,live_id);
+        }
+        self.emit_replies(index + 1,&request_ids);
+        Ok(())
+    }
+
+    fn set_event_transmitter(&mut self, transmitter:UnboundedSender<TransportEvent>) {
+        for event in std::mem::take(&mut self.pending_events) {
+            channel::emit(&transmitter,event);
+        }
+        self.event_transmitter = Some(transmitter);
+    }
+}
+
+
+
+// =============
+// === Utils ===
+// =============
+
+/// Call `f` for the message, or for each message of the batch.
+fn for_each_message(message:&mut Value, mut f:impl FnMut(&mut serde_json::Map<String,Value>)) {
+    match message {
+        Value::Object(object) => f(object),
+        Value::Array(batch)   => for message in batch {
+            if let Value::Object(object) = message { f(object) }
+        },
+        _ => {}
+    }
+}
+
+/// Parse the sent message and remove the ids of requests, returning them separately. Messages
+/// not being a valid JSON are returned as JSON strings.
+fn strip_ids(text:&str) -> (Value,Vec<Value>) {
+    match serde_json::from_str::<Value>(text) {
+        Ok(mut message) => {
+            let mut ids = Vec::new();
+            for_each_message(&mut message, |message| {
+                if message.contains_key("method") {
+                    ids.extend(message.remove("id"));
+                }
+            });
+            (message,ids)
+        }
+        Err(_) => (Value::String(text.into()),default()),
+    }
+}
+
+/// Split the binary message into its content with the id cleared and the id, if the message
+/// format is known. Otherwise the whole message is returned as its content.
+fn strip_binary_id
+(binary_ids:&Option<Box<dyn BinaryIds>>, message:&[u8]) -> (Vec<u8>,Option<Vec<u8>>) {
+    match binary_ids.as_ref().and_then(|ids| ids.strip_id(message)) {
+        Some((content,id)) => (content,Some(id)),
+        None               => (message.to_vec(),None),
+    }
+}
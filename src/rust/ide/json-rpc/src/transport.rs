@@ -32,6 +32,20 @@ pub trait Transport : Debug {
     }
 }
 
+impl<T:Transport + ?Sized> Transport for Box<T> {
+    fn send_text(&mut self, message:&str) -> Result<(), Error> {
+        (**self).send_text(message)
+    }
+
+    fn send_binary(&mut self, message:&[u8]) -> Result<(), Error> {
+        (**self).send_binary(message)
+    }
+
+    fn set_event_transmitter(&mut self, transmitter:UnboundedSender<TransportEvent>) {
+        (**self).set_event_transmitter(transmitter)
+    }
+}
+
 /// An event generated by the `Transport`.
 #[derive(Debug)]
 pub enum TransportEvent {
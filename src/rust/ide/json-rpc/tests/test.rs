@@ -300,3 +300,312 @@ fn test_handling_invalid_notification() {
         panic!("expected InvalidNotification error");
     }
 }
+
+#[test]
+fn test_replying_to_requests() {
+    let mut fixture = Fixture::new();
+    fixture.client.handler.set_request_handler("confirm", |params,responder| {
+        let confirmed = params["question"] == "Sure?";
+        responder.reply(Ok(serde_json::json!(confirmed)));
+    });
+    let request = r#"{"jsonrpc":"2.0","id":7,"method":"confirm","params":{"question":"Sure?"}}"#;
+    let unknown = r#"{"jsonrpc":"2.0","id":8,"method":"unknown","params":{}}"#;
+    fixture.transport.mock_peer_text_message(request);
+    fixture.transport.mock_peer_text_message(unknown);
+    fixture.pool.run_until_stalled();
+
+    type Reply = messages::ResponseMessage<serde_json::Value>;
+    let reply = fixture.transport.expect_json_message::<Reply>();
+    assert_eq!(reply.id, Id(7));
+    assert_eq!(reply.result, messages::Result::new_success(serde_json::json!(true)));
+    let reply = fixture.transport.expect_json_message::<Reply>();
+    assert_eq!(reply.id, Id(8));
+    if let messages::Result::Error {error} = &reply.result {
+        assert_eq!(error.code, messages::error_code::METHOD_NOT_FOUND);
+    } else {
+        panic!("Expected an error reply for unknown method.");
+    }
+}
+
+#[test]
+fn test_replying_to_batch() {
+    let mut fixture = Fixture::new();
+    let responders  = Rc::new(RefCell::new(Vec::new()));
+    let sink        = responders.clone();
+    fixture.client.handler.set_request_handler("confirm", move |_,responder| {
+        sink.borrow_mut().push(responder);
+    });
+    let first  = r#"{"jsonrpc":"2.0","id":7,"method":"confirm","params":{}}"#;
+    let second = r#"{"jsonrpc":"2.0","id":8,"method":"unknown","params":{}}"#;
+    fixture.transport.mock_peer_text_message(format!("[{},{}]",first,second));
+    fixture.pool.run_until_stalled();
+    // Nothing is sent until all the requests of the batch are replied.
+    fixture.transport.expect_no_text_message();
+
+    let responder = responders.borrow_mut().pop().unwrap();
+    responder.reply(Ok(serde_json::json!(true)));
+    type Reply = messages::ResponseMessage<serde_json::Value>;
+    let replies = fixture.transport.expect_json_message::<Vec<Reply>>();
+    let ids     = replies.iter().map(|reply| reply.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![Id(8),Id(7)]);
+}
+
+#[test]
+fn test_replying_to_invalid_batch() {
+    let mut fixture = Fixture::new();
+    fixture.transport.mock_peer_text_message("[]");
+    fixture.pool.run_until_stalled();
+    let reply = fixture.transport.expect_json_message::<messages::InvalidRequestMessage>();
+    assert_eq!(reply.id, None);
+    assert_eq!(reply.error.code, messages::error_code::INVALID_REQUEST);
+
+    let request = r#"{"jsonrpc":"2.0","id":8,"method":"unknown","params":{}}"#;
+    fixture.transport.mock_peer_text_message(format!("[1,{},{{\"foo\":2}}]",request));
+    fixture.pool.run_until_stalled();
+    let replies = fixture.transport.expect_json_message::<Vec<serde_json::Value>>();
+    let ids     = replies.iter().map(|reply| reply["id"].clone()).collect::<Vec<_>>();
+    let codes   = replies.iter().map(|reply| reply["error"]["code"].clone()).collect::<Vec<_>>();
+    let invalid = messages::error_code::INVALID_REQUEST;
+    let unknown = messages::error_code::METHOD_NOT_FOUND;
+    assert_eq!(ids, vec![serde_json::Value::Null,8.into(),serde_json::Value::Null]);
+    assert_eq!(codes, vec![invalid.into(),unknown.into(),invalid.into()]);
+}
+
+#[test]
+fn test_batch() {
+    let mut fixture = Fixture::new();
+    let mut batch   = fixture.client.handler.batch();
+    let mut first   = Box::pin(batch.request(MockRequest {i:2}));
+    let mut second  = Box::pin(batch.request(MockRequest {i:3}));
+    batch.notification("ping",&serde_json::json!({}));
+    first.expect_pending();
+    batch.send().unwrap();
+
+    let sent = fixture.transport.expect_json_message::<Vec<serde_json::Value>>();
+    assert_eq!(sent.len(), 3);
+    let requests = sent[..2].iter().map(|message| {
+        serde_json::from_value::<MockRequestMessage>(message.clone()).unwrap()
+    });
+    // The peer may reply in any order.
+    let replies = requests.rev().map(pow_impl).collect::<Vec<_>>();
+    fixture.transport.mock_peer_json_message(replies);
+    fixture.pool.run_until_stalled();
+    assert_eq!(first.expect_ok().result, 4);
+    assert_eq!(second.expect_ok().result, 9);
+
+    let mut batch   = fixture.client.handler.batch();
+    let mut dropped = Box::pin(batch.request(MockRequest {i:4}));
+    drop(batch);
+    if let RpcError::LostConnection = dropped.expect_err() {} else {
+        panic!("Expected an error to be LostConnection");
+    }
+}
+
+#[test]
+fn test_cancelling_dropped_request() {
+    let mut fixture = Fixture::new();
+    fixture.client.handler.set_cancel_method(Some("$/cancelRequest".into()));
+    let fut     = Box::pin(fixture.client.pow(8));
+    let req_msg = fixture.transport.expect_json_message::<MockRequestMessage>();
+    drop(fut);
+
+    let cancel = fixture.transport.expect_json_message::<serde_json::Value>();
+    assert_eq!(cancel["method"], "$/cancelRequest");
+    assert_eq!(cancel["params"]["id"], req_msg.id.0);
+
+    // The late reply is not matched with any request anymore.
+    fixture.transport.mock_peer_json_message(pow_impl(req_msg));
+    fixture.pool.run_until_stalled();
+    if let HandlingError::UnexpectedResponse(_) = fixture.client.expect_handling_error() {} else {
+        panic!("Expected an error to be UnexpectedResponse");
+    }
+}
+
+#[test]
+fn test_method_timeout() {
+    let mut fixture = Fixture::new();
+    let timeout     = Duration::from_millis(10);
+    fixture.client.handler.set_method_timeout(MockRequest::NAME.into(),timeout);
+    fixture.client.handler.set_cancel_method(Some("$/cancelRequest".into()));
+    let mut fut = Box::pin(fixture.client.pow(8));
+    let req_msg = fixture.transport.expect_json_message::<MockRequestMessage>();
+
+    fut.expect_pending();
+    sleep(Duration::from_millis(100));
+    if let RpcError::TimeoutError{millis} = fut.expect_err() {
+        assert_eq!(millis, 10);
+    } else {
+        panic!("Expected an error to be TimeoutError");
+    }
+    let cancel = fixture.transport.expect_json_message::<serde_json::Value>();
+    assert_eq!(cancel["params"]["id"], req_msg.id.0);
+
+    // The same timeout applies to requests sent as already serialized messages.
+    let message = Message::new_request(Id(100),MockRequest::NAME,MockRequest {i:8});
+    let message = serde_json::to_string(&message).unwrap();
+    let handler = &fixture.client.handler;
+    let mut fut = Box::pin(handler.open_request_with_message::<MockResponse>(Id(100),&message));
+    fixture.transport.expect_json_message::<MockRequestMessage>();
+    fut.expect_pending();
+    sleep(Duration::from_millis(100));
+    if let RpcError::TimeoutError{millis} = fut.expect_err() {
+        assert_eq!(millis, 10);
+    } else {
+        panic!("Expected an error to be TimeoutError");
+    }
+}
+
+#[test]
+fn test_recording_and_replaying() {
+    use json_rpc::trace::RecordingTransport;
+    use json_rpc::trace::ReplayTransport;
+    use json_rpc::trace::Trace;
+
+    // Record the session with a mocked peer.
+    let mut peer      = MockTransport::new();
+    let mut recording = RecordingTransport::new(peer.clone());
+    let recorder      = recording.recorder();
+    let mut pool      = futures::executor::LocalPool::new();
+    pool.spawner().spawn_local(recording.runner()).unwrap();
+    let mut client    = Client::new(recording);
+    pool.spawner().spawn_local(client.events_processor()).unwrap();
+    let mut fut       = Box::pin(client.pow(3));
+    let request       = peer.expect_json_message::<MockRequestMessage>();
+    peer.mock_peer_json_message(pow_impl(request));
+    peer.mock_peer_json_message(Message::new(MockNotification::Meow {text:"meow!".into()}));
+    pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(), 9);
+    let trace = Trace::from_json(&recorder.trace().to_json()).unwrap();
+    assert_eq!(trace.frames.len(), 3);
+
+    // Replay it, with requests having different ids than recorded.
+    let mut pool   = futures::executor::LocalPool::new();
+    let mut client = Client::new(ReplayTransport::new(trace));
+    pool.spawner().spawn_local(client.events_processor()).unwrap();
+    client.handler.generate_new_id();
+    let mut fut          = Box::pin(client.pow(3));
+    let mut not_recorded = Box::pin(client.pow(4));
+    pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(), 9);
+    assert_eq!(client.expect_notification(), MockNotification::Meow {text:"meow!".into()});
+    if let RpcError::LostConnection = not_recorded.expect_err() {} else {
+        panic!("Expected an error to be LostConnection");
+    }
+}
+
+#[test]
+fn test_replaying_replies_recorded_out_of_order() {
+    use json_rpc::trace::Frame;
+    use json_rpc::trace::ReplayTransport;
+    use json_rpc::trace::Trace;
+
+    let request = |id,i| {
+        let message = Message::new_request(Id(id),MockRequest::NAME,MockRequest {i});
+        Frame::SentText(serde_json::to_string(&message).unwrap())
+    };
+    let response = |id,result| {
+        let message = Message::new_success(Id(id),MockResponse {result});
+        Frame::ReceivedText(serde_json::to_string(&message).unwrap())
+    };
+    let meow   = Message::new(MockNotification::Meow {text:"meow!".into()});
+    let meow   = Frame::ReceivedText(serde_json::to_string(&meow).unwrap());
+    let frames = vec![request(0,2),request(1,3),meow,response(1,9),response(0,4)];
+
+    // Each request gets its own reply, even though the replies were recorded after both
+    // requests, and in the other order.
+    let mut pool   = futures::executor::LocalPool::new();
+    let mut client = Client::new(ReplayTransport::new(Trace {frames}));
+    pool.spawner().spawn_local(client.events_processor()).unwrap();
+    let mut first = Box::pin(client.pow(2));
+    pool.run_until_stalled();
+    assert_eq!(first.expect_ok(), 4);
+    client.expect_no_notification_yet();
+    let mut second = Box::pin(client.pow(3));
+    pool.run_until_stalled();
+    assert_eq!(second.expect_ok(), 9);
+    assert_eq!(client.expect_notification(), MockNotification::Meow {text:"meow!".into()});
+}
+
+/// Binary messages with the first byte being the id of the message or, in replies, the id of the
+/// request.
+#[derive(Debug)]
+struct MockBinaryIds;
+
+impl json_rpc::trace::BinaryIds for MockBinaryIds {
+    fn strip_id(&self, message:&[u8]) -> Option<(Vec<u8>,Vec<u8>)> {
+        let (id,content) = message.split_first()?;
+        Some((content.to_vec(),vec![*id]))
+    }
+
+    fn reply_id(&self, message:&[u8]) -> Option<Vec<u8>> {
+        message.first().map(|id| vec![*id])
+    }
+
+    fn rewrite_reply_id
+    (&self, message:&[u8], live_id:&dyn Fn(&[u8]) -> Option<Vec<u8>>) -> Option<Vec<u8>> {
+        let (id,content) = message.split_first()?;
+        let mut reply    = live_id(&[*id])?;
+        reply.extend_from_slice(content);
+        Some(reply)
+    }
+}
+
+#[test]
+fn test_replaying_binary_messages() {
+    use json_rpc::trace::Frame;
+    use json_rpc::trace::ReplayTransport;
+    use json_rpc::trace::Trace;
+
+    let frames = vec!
+        [ Frame::SentBinary(vec![1,10])
+        , Frame::ReceivedBinary(vec![1,20])
+        , Frame::SentBinary(vec![2,30])
+        , Frame::ReceivedBinary(vec![2,40])
+        ];
+    let trace         = Trace {frames};
+    let mut transport = ReplayTransport::new(trace.clone()).with_binary_ids(MockBinaryIds);
+    let (transmitter,mut receiver) = futures::channel::mpsc::unbounded();
+    transport.set_event_transmitter(transmitter);
+
+    // The requests are matched regardless of their ids, and replies refer to the sent ids.
+    transport.send_binary(&[8,30]).unwrap();
+    transport.send_binary(&[9,10]).unwrap();
+    let replies = std::iter::from_fn(|| receiver.try_next().ok().flatten());
+    let replies = replies.filter_map(|event| match event {
+        TransportEvent::BinaryMessage(data) => Some(data),
+        _                                   => None,
+    }).collect_vec();
+    assert_eq!(replies, vec![vec![8,40],vec![9,20]]);
+    assert!(transport.is_finished());
+    assert!(transport.send_binary(&[9,10]).is_err());
+
+    // Without knowing the message format, only the exact content is matched.
+    let mut transport = ReplayTransport::new(trace);
+    assert!(transport.send_binary(&[8,30]).is_err());
+    assert!(transport.send_binary(&[2,30]).is_ok());
+}
+
+#[test]
+fn test_inspecting_traffic() {
+    let mut fixture = Fixture::new();
+    let observed    = Rc::new(RefCell::new(Vec::new()));
+    let sink        = observed.clone();
+    let inspector   = fixture.client.handler.inspector();
+    inspector.set_clock(|| 0.0);
+    inspector.set_observer(move |traffic| sink.borrow_mut().push(traffic.clone()));
+
+    let mut fut = Box::pin(fixture.client.pow(8));
+    let req_msg = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_peer_json_message(pow_impl(req_msg));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(), 64);
+
+    let observed = observed.borrow();
+    assert_eq!(observed.len(), 2);
+    assert_eq!(observed[0].direction, inspector::Direction::Outgoing);
+    assert_eq!(observed[0].method   , Some(MockRequest::NAME.into()));
+    assert_eq!(observed[1].direction, inspector::Direction::Incoming);
+    assert_eq!(observed[1].id       , observed[0].id);
+    assert_eq!(observed[1].method   , Some(MockRequest::NAME.into()));
+    assert_eq!(observed[1].latency  , Some(0.0));
+}
@@ -2,3 +2,31 @@
 
 /// Visualization folder where IDE can look for user-defined visualizations per project.
 pub const PROJECT_VISUALIZATION_FOLDER:&str = "visualization";
+
+/// File in the project's root directory with the project-specific keymap.
+pub const PROJECT_KEYMAP_FILE:&str = "keymap";
+
+/// Key of the browser's local storage item with the global user keymap.
+pub const GLOBAL_KEYMAP_STORAGE_KEY:&str = "enso.keymap";
+
+/// File in the project's root directory with the project-specific themes.
+pub const PROJECT_THEME_FILE:&str = "themes";
+
+/// Key of the browser's local storage item with the name of the theme selected by the user.
+pub const THEME_STORAGE_KEY:&str = "enso.theme";
+
+/// Key of the browser's local storage item enabling logging of the Language Server protocol
+/// traffic, when set to `true`.
+pub const INSPECT_PROTOCOL_STORAGE_KEY:&str = "enso.inspectProtocol";
+
+/// Key of the browser's local storage item enabling recording of the protocol traffic, when set to
+/// `true`. The recorded traces are written to the console with ctrl+alt+p.
+pub const RECORD_PROTOCOL_STORAGE_KEY:&str = "enso.recordProtocol";
+
+/// Key of the browser's local storage item with the log filter, e.g.
+/// `warning,IDE.Project=info,IDE.Project.Module=debug`. See `logger::filter::Filter`.
+pub const LOG_FILTER_STORAGE_KEY:&str = "enso.logFilter";
+
+/// Key of the browser's local storage item with the most verbose level of the log entries kept for
+/// crash reports, e.g. `info`. All levels are kept by default. See `logger::history`.
+pub const LOG_HISTORY_LEVEL_STORAGE_KEY:&str = "enso.logHistoryLevel";
@@ -2,7 +2,9 @@
 //!
 //! This controller provides access to a specific graph. It lives under a module controller, as
 //! each graph belongs to some module.
+pub mod clipboard;
 pub mod executed;
+pub mod layout;
 
 use crate::prelude::*;
 
@@ -14,7 +16,11 @@ use crate::double_representation::graph::GraphInfo;
 pub use crate::double_representation::graph::LocationHint;
 use crate::double_representation::node;
 use crate::double_representation::node::NodeInfo;
+use crate::model::module::NodeColor;
 use crate::model::module::NodeMetadata;
+use crate::model::module::Position;
+use crate::model::module::VisualizationMetadata;
+use crate::model::module::VisualizationPath;
 
 use parser::Parser;
 use span_tree::action::Actions;
@@ -514,12 +520,20 @@ impl Handle {
             let is_relevant = name.item.starts_with(base_name.as_str());
             is_relevant.then(name.item)
         }).collect::<HashSet<_>>();
-        let name = (1..).find_map(|i| {
+        Ok(Self::variable_name_avoiding(node,&unavailable))
+    }
+
+    /// Suggests a variable name for storing results of the given node, which is not any of the
+    /// `unavailable` names.
+    fn variable_name_avoiding
+    (node:&NodeInfo, unavailable:&HashSet<NormalizedName>) -> ast::known::Var {
+        let base_name = Self::variable_name_base_for(node);
+        let name      = (1..).find_map(|i| {
             let candidate              = NormalizedName::new(iformat!("{base_name}{i}"));
             let available              = !unavailable.contains(&candidate);
             available.and_option_from(|| Some(candidate.deref().clone()))
         }).unwrap(); // It always return a value.
-        Ok(ast::known::Var::new(ast::Var {name}, None))
+        ast::known::Var::new(ast::Var {name}, None)
     }
 
     /// Converts node to an assignment, where the whole value is bound to a single identifier.
@@ -718,6 +732,203 @@ impl Handle {
         Ok(())
     }
 
+    /// Modifies the metadata of the given node. Fails if there is no such node in the graph.
+    fn update_node_metadata
+    (&self, id:ast::Id, f:impl FnOnce(&mut NodeMetadata)) -> FallibleResult<()> {
+        self.node_info(id)?;
+        self.module.with_node_metadata(id,f);
+        Ok(())
+    }
+
+    /// Sets the position of the given node.
+    pub fn set_node_position(&self, id:ast::Id, position:Position) -> FallibleResult<()> {
+        self.update_node_metadata(id,|md| md.position = Some(position))
+    }
+
+    /// Sets the comment of the given node. `None` removes the comment.
+    pub fn set_node_comment(&self, id:ast::Id, comment:Option<String>) -> FallibleResult<()> {
+        info!(self.logger, "Setting node {id} comment to {comment:?}");
+        self.update_node_metadata(id,|md| md.comment = comment)
+    }
+
+    /// Sets the colour of the given node. `None` removes the colour tag.
+    pub fn set_node_color(&self, id:ast::Id, color:Option<NodeColor>) -> FallibleResult<()> {
+        info!(self.logger, "Setting node {id} colour to {color:?}");
+        self.update_node_metadata(id,|md| md.color = color)
+    }
+
+    /// Collapses or expands the given node.
+    pub fn set_node_collapsed(&self, id:ast::Id, collapsed:bool) -> FallibleResult<()> {
+        info!(self.logger, "Setting node {id} collapsed state to {collapsed}");
+        self.update_node_metadata(id,|md| md.collapsed = collapsed)
+    }
+
+    /// Sets the visualization selected for the given node. The preprocessor is kept if the same
+    /// visualization was selected before, otherwise the visualization's default one is used.
+    pub fn set_node_visualization
+    (&self, id:ast::Id, path:Option<VisualizationPath>) -> FallibleResult<()> {
+        info!(self.logger, "Setting node {id} visualization to {path:?}");
+        self.update_node_metadata(id,|md| {
+            let same_path = md.visualization.as_ref().map(|vis| &vis.path) == path.as_ref();
+            if !same_path {
+                md.visualization = path.map(VisualizationMetadata::new);
+            }
+        })
+    }
+
+    /// Sets the preprocessor of the visualization selected for the given node. Does nothing if
+    /// there is no visualization selected.
+    pub fn set_node_visualization_preprocessor
+    (&self, id:ast::Id, preprocessor:Option<String>) -> FallibleResult<()> {
+        info!(self.logger, "Setting node {id} visualization preprocessor to {preprocessor:?}");
+        self.update_node_metadata(id,|md| {
+            if let Some(visualization) = &mut md.visualization {
+                visualization.preprocessor = preprocessor;
+            }
+        })
+    }
+
+    /// Describes the given nodes for putting them in the clipboard.
+    ///
+    /// The connections between copied nodes are preserved, while the inputs connected to the nodes
+    /// outside the selection are replaced with placeholders.
+    pub fn copy_nodes(&self, ids:&[node::Id]) -> FallibleResult<clipboard::Content> {
+        use double_representation::alias_analysis::analyse_node;
+        let nodes = self.nodes()?;
+        if let Some(missing) = ids.iter().find(|id| !nodes.iter().any(|n| n.info.id() == **id)) {
+            return Err(NodeNotFound(*missing).into());
+        }
+        let (selected,others) : (Vec<_>,Vec<_>) = nodes.into_iter().partition(|node| {
+            ids.contains(&node.info.id())
+        });
+        let external_names = others.iter().flat_map(|node| analyse_node(&node.info).introduced);
+        let external_names = external_names.map(|name| name.item).collect();
+        let selected       = selected.into_iter().map(|node| {
+            let position = node.metadata.and_then(|metadata| metadata.position);
+            (node.info,position)
+        }).collect_vec();
+        clipboard::Content::from_nodes(&selected,&external_names)
+    }
+
+    /// Adds the nodes from clipboard to the graph. The nodes positions are relative to the given
+    /// `position`. Returns the ids of added nodes.
+    ///
+    /// Each pasted node which had a variable name gets a new one, generated like in
+    /// `variable_name_for`, and the connections between pasted nodes are updated accordingly.
+    ///
+    /// All nodes are added in a single module edit, so the paste either succeeds as a whole or
+    /// leaves the graph intact.
+    pub fn paste_nodes
+    (&self, content:&clipboard::Content, position:Position) -> FallibleResult<Vec<node::Id>> {
+        info!(self.logger, "Pasting {content.nodes.len()} nodes.");
+        let used_names    = self.used_names()?.into_iter().map(|name| name.item);
+        let mut taken     = used_names.collect::<HashSet<_>>();
+        let mut new_names = HashMap::<usize,ast::known::Var>::new();
+        let mut new_nodes = Vec::new();
+        for (index,node) in content.nodes.iter().enumerate() {
+            let expression = self.parse_node_expression(&node.expression)?;
+            let renames    = content.connections_to(index).filter_map(|connection| {
+                let old_name = content.nodes.get(connection.source)?.name.as_ref()?;
+                let new_name = new_names.get(&connection.source)?;
+                Some((NormalizedName::new(old_name),new_name.name.clone()))
+            }).collect::<HashMap<_,_>>();
+            let expression = clipboard::rename_used_identifiers(&expression,&renames)?;
+            let mut info   = NodeInfo::from_line_ast(&expression).ok_or(FailedToCreateNode)?;
+            let metadata   = node.position.map(|relative| {
+                NodeMetadata::new(Some(Position {vector:position.vector + relative.vector}))
+            });
+            if node.name.is_some() {
+                let name = Self::variable_name_avoiding(&info,&taken);
+                taken.insert(NormalizedName::new(name.name.as_str()));
+                info.set_pattern(name.ast().clone());
+                new_names.insert(index,name);
+            }
+            new_nodes.push((info,metadata));
+        }
+
+        self.update_definition_ast(|definition| {
+            let mut graph = GraphInfo::from_definition(definition);
+            for (info,_) in &new_nodes {
+                graph.add_node(info.ast().clone(),LocationHint::End)?;
+            }
+            Ok(graph.source)
+        })?;
+        let ids = new_nodes.into_iter().map(|(info,metadata)| {
+            if let Some(metadata) = metadata {
+                self.module.set_node_metadata(info.id(),metadata);
+            }
+            info.id()
+        }).collect();
+        Ok(ids)
+    }
+
+    /// Adds the nodes from the system clipboard text to the graph. The text may be either the
+    /// serialized clipboard content or plain Enso code. See also `paste_nodes`.
+    pub fn paste_text(&self, text:&str, position:Position) -> FallibleResult<Vec<node::Id>> {
+        let content = clipboard::Content::from_text(&self.parser,text)?;
+        self.paste_nodes(&content,position)
+    }
+
+    /// Assigns positions to all nodes which have no position in metadata, e.g. the nodes added in
+    /// the text editor. Nodes with positions are treated as pinned.
+    ///
+    /// Returns the ids of nodes which received positions.
+    pub fn place_unpositioned_nodes(&self) -> FallibleResult<Vec<node::Id>> {
+        let nodes  = self.nodes()?;
+        let pinned = nodes.iter().filter_map(|node| {
+            let position = node.metadata.as_ref().and_then(|metadata| metadata.position)?;
+            Some((node.info.id(),position.vector))
+        }).collect::<HashMap<_,_>>();
+        if pinned.len() == nodes.len() {
+            return Ok(default());
+        }
+        let ids = nodes.iter().map(|node| node.info.id()).collect_vec();
+        self.apply_layout(ids,pinned,None)
+    }
+
+    /// Arranges the given nodes using the automatic layout. The other nodes are not moved.
+    ///
+    /// The arranged nodes are placed around their current location, if they have any.
+    pub fn arrange_nodes(&self, ids:&[node::Id]) -> FallibleResult<Vec<node::Id>> {
+        let nodes = self.nodes()?;
+        let mut pinned  = HashMap::new();
+        let mut current = Vec::new();
+        for node in &nodes {
+            let id       = node.info.id();
+            let position = node.metadata.as_ref().and_then(|metadata| metadata.position);
+            match (ids.contains(&id),position) {
+                (true ,Some(position)) => current.push(position.vector),
+                (false,Some(position)) => { pinned.insert(id,position.vector); }
+                _                      => {}
+            }
+        }
+        let origin = (!current.is_empty()).as_some_from(|| {
+            let mean_x = current.iter().map(|pos| pos.x).sum::<f32>() / current.len() as f32;
+            let max_y  = current.iter().map(|pos| pos.y).fold(f32::NEG_INFINITY,f32::max);
+            Vector2::new(mean_x,max_y)
+        });
+        let ids = nodes.iter().map(|node| node.info.id()).filter(|id| ids.contains(id));
+        self.apply_layout(ids.collect(),pinned,origin)
+    }
+
+    fn apply_layout
+    (&self, nodes:Vec<node::Id>, pinned:HashMap<node::Id,Vector2<f32>>, origin:Option<Vector2<f32>>)
+    -> FallibleResult<Vec<node::Id>> {
+        let connections = self.connections()?.connections;
+        let edges       = connections.iter().map(|c| (c.source.node,c.destination.node)).collect();
+        let input       = layout::Input {nodes,edges,pinned,origin};
+        let positions   = layout::layout(&input);
+        let mut placed  = Vec::new();
+        for id in input.nodes {
+            if let Some(position) = positions.get(&id) {
+                let position = Position {vector:*position};
+                self.module.with_node_metadata(id, |md| md.position = Some(position));
+                placed.push(id);
+            }
+        }
+        Ok(placed)
+    }
+
     /// Subscribe to updates about changes in this graph.
     pub fn subscribe(&self) -> impl Stream<Item=Notification> {
         let module_sub = self.module.subscribe();
@@ -876,6 +1087,38 @@ main =
         })
     }
 
+    #[wasm_bindgen_test]
+    fn graph_controller_node_metadata() {
+        use crate::model::module::ColorTag;
+
+        let mut test = GraphControllerFixture::set_up();
+        let program  = r"main =
+    foo = 2";
+        test.run_graph_for_main(program, "main", |module, graph| async move {
+            let (node,) = graph.nodes().unwrap().expect_tuple();
+            let id      = node.info.id();
+            let path    = VisualizationPath {library:"builtin".into(), name:"Graph".into()};
+            graph.set_node_comment(id,Some("Comment".into())).unwrap();
+            graph.set_node_color(id,Some(ColorTag::Blue.into())).unwrap();
+            graph.set_node_collapsed(id,true).unwrap();
+            graph.set_node_visualization(id,Some(path.clone())).unwrap();
+            graph.set_node_visualization_preprocessor(id,Some("x -> x".into())).unwrap();
+            graph.set_node_visualization(id,Some(path.clone())).unwrap();
+
+            let metadata      = module.model.node_metadata(id).unwrap();
+            let visualization = metadata.visualization.unwrap();
+            assert_eq!(metadata.comment.as_deref(), Some("Comment"));
+            assert_eq!(metadata.color.and_then(|color| color.tag()), Some(ColorTag::Blue));
+            assert!(metadata.collapsed);
+            assert_eq!(visualization.path, path);
+            assert_eq!(visualization.preprocessor.as_deref(), Some("x -> x"));
+
+            graph.set_node_visualization(id,None).unwrap();
+            assert!(module.model.node_metadata(id).unwrap().visualization.is_none());
+            assert!(graph.set_node_collapsed(ast::Id::new_v4(),true).is_err());
+        })
+    }
+
     #[wasm_bindgen_test]
     fn graph_controller_used_names_in_inline_def() {
         let mut test  = GraphControllerFixture::set_up();
@@ -943,7 +1186,7 @@ main =
             // === Add node ===
             let id       = ast::Id::new_v4();
             let position = Some(model::module::Position::new(10.0,20.0));
-            let metadata = NodeMetadata {position};
+            let metadata = NodeMetadata::new(position);
             let info     = NewNodeInfo {
                 expression    : "a+b".into(),
                 metadata      : Some(metadata),
@@ -1223,4 +1466,121 @@ main =
             case.run();
         }
     }
+
+    #[wasm_bindgen_test]
+    fn copying_and_pasting_nodes() {
+        let mut test = GraphControllerFixture::set_up();
+        const PROGRAM:&str = r"
+main =
+    foo = 2
+    bar = foo + 1
+    baz = bar + foo";
+        test.run_graph_for_main(PROGRAM, "main", |module, graph| async move {
+            let nodes = graph.nodes().unwrap();
+            let (_,bar,baz) = nodes.expect_tuple();
+            graph.module.with_node_metadata(bar.info.id(), |md| {
+                md.position = Some(Position::new(10.0,20.0));
+            });
+            graph.module.with_node_metadata(baz.info.id(), |md| {
+                md.position = Some(Position::new(30.0,10.0));
+            });
+
+            let content = graph.copy_nodes(&[bar.info.id(),baz.info.id()]).unwrap();
+            let text    = content.to_text().unwrap();
+            let pasted  = graph.paste_text(&text,Position::new(100.0,100.0)).unwrap();
+            let expected_program = r"
+main =
+    foo = 2
+    bar = foo + 1
+    baz = bar + foo
+    sum1 = _ + 1
+    sum2 = sum1 + _";
+            module.expect_code(expected_program);
+
+            let (first,second) = pasted.expect_tuple();
+            let first_position  = graph.module.node_metadata(first).unwrap().position;
+            let second_position = graph.module.node_metadata(second).unwrap().position;
+            assert_eq!(first_position , Some(Position::new(100.0,100.0)));
+            assert_eq!(second_position, Some(Position::new(120.0,90.0)));
+        })
+    }
+
+    #[wasm_bindgen_test]
+    fn pasting_plain_code() {
+        let mut test = GraphControllerFixture::set_up();
+        const PROGRAM:&str = r"
+main =
+    foo = 2";
+        test.run_graph_for_main(PROGRAM, "main", |module, graph| async move {
+            let code = "a = 5\nb = a + foo";
+            graph.paste_text(code,Position::new(0.0,0.0)).unwrap();
+            let expected_program = r"
+main =
+    foo = 2
+    number1 = 5
+    sum1 = number1 + foo";
+            module.expect_code(expected_program);
+        })
+    }
+
+    #[wasm_bindgen_test]
+    fn pasting_nodes_leaves_graph_intact_on_failure() {
+        use clipboard::CopiedNode;
+
+        let mut test = GraphControllerFixture::set_up();
+        const PROGRAM:&str = r"
+main =
+    foo = 2";
+        test.run_graph_for_main(PROGRAM, "main", |module, graph| async move {
+            let valid   = CopiedNode {name:Some("a".into()), expression:"5".into(), position:None};
+            let invalid = CopiedNode {name:None, expression:"b = 6".into(), position:None};
+            let nodes   = vec![valid,invalid];
+            let content = clipboard::Content {nodes, connections:default()};
+            assert!(graph.paste_nodes(&content,Position::new(0.0,0.0)).is_err());
+            module.expect_code(PROGRAM);
+        })
+    }
+
+    #[wasm_bindgen_test]
+    fn placing_unpositioned_nodes() {
+        use layout::LAYER_GAP;
+        use layout::NODE_GAP;
+        let mut test = GraphControllerFixture::set_up();
+        const PROGRAM:&str = r"
+main =
+    foo = 2
+    bar = foo + 1
+    baz = foo + 2";
+        test.run_graph_for_main(PROGRAM, "main", |_, graph| async move {
+            let placed = graph.place_unpositioned_nodes().unwrap();
+            let (foo,bar,baz) = placed.expect_tuple();
+            let position = |id| graph.module.node_metadata(id).unwrap().position.unwrap();
+            assert_eq!(position(foo), Position::new(0.0,0.0));
+            assert_eq!(position(bar), Position::new(-NODE_GAP/2.0,-LAYER_GAP));
+            assert_eq!(position(baz), Position::new(NODE_GAP/2.0,-LAYER_GAP));
+            assert!(graph.place_unpositioned_nodes().unwrap().is_empty());
+        })
+    }
+
+    #[wasm_bindgen_test]
+    fn arranging_nodes_around_pinned_ones() {
+        use layout::LAYER_GAP;
+        use layout::NODE_GAP;
+        let mut test = GraphControllerFixture::set_up();
+        const PROGRAM:&str = r"
+main =
+    foo = 2
+    bar = foo + 1
+    baz = foo + 2";
+        test.run_graph_for_main(PROGRAM, "main", |_, graph| async move {
+            let (foo,bar,baz) = graph.nodes().unwrap().expect_tuple();
+            let (foo,bar,baz) = (foo.info.id(),bar.info.id(),baz.info.id());
+            graph.module.with_node_metadata(foo, |md| md.position = Some(Position::new(10.0,10.0)));
+            graph.arrange_nodes(&[bar,baz]).unwrap();
+            let position = |id| graph.module.node_metadata(id).unwrap().position.unwrap();
+            assert_eq!(position(foo), Position::new(10.0,10.0));
+            assert_eq!(position(bar), Position::new(10.0-NODE_GAP/2.0,10.0-LAYER_GAP));
+            assert_eq!(position(baz), Position::new(10.0+NODE_GAP/2.0,10.0-LAYER_GAP));
+        })
+    }
 }
@@ -0,0 +1,214 @@
+//! A module with the graph clipboard content: a description of copied nodes, independent of the
+//! graph they were copied from.
+//!
+//! The content may be created either from nodes selected in some graph, or from plain Enso code
+//! copied from a text editor. The pasting itself is implemented by the graph controller.
+use crate::prelude::*;
+
+use crate::double_representation::alias_analysis::analyse_ast;
+use crate::double_representation::alias_analysis::NormalizedName;
+use crate::double_representation::node::NodeInfo;
+use crate::model::module::Position;
+
+use parser::Parser;
+use serde::Deserialize;
+use serde::Serialize;
+
+
+
+// ==================
+// === CopiedNode ===
+// ==================
+
+/// A single node stored in the clipboard.
+#[derive(Clone,Debug,Deserialize,PartialEq,Serialize)]
+pub struct CopiedNode {
+    /// The variable name bound to the node's value in the source graph. `None` if the node had no
+    /// pattern or its pattern was not a single identifier.
+    pub name : Option<String>,
+    /// The node's expression. Usages of nodes not being copied are replaced with placeholders.
+    pub expression : String,
+    /// Node position relative to the top-left corner of the copied selection.
+    pub position : Option<Position>,
+}
+
+/// A connection between two copied nodes, identified by their indices in `Content::nodes`.
+#[allow(missing_docs)]
+#[derive(Clone,Copy,Debug,Deserialize,Eq,PartialEq,Serialize)]
+pub struct CopiedConnection {
+    pub source      : usize,
+    pub destination : usize,
+}
+
+
+
+// ===============
+// === Content ===
+// ===============
+
+/// The content of the graph clipboard.
+///
+/// Nodes are stored in the order they should be added to the graph, so each connection's source
+/// goes before its destination.
+#[allow(missing_docs)]
+#[derive(Clone,Debug,Default,Deserialize,PartialEq,Serialize)]
+pub struct Content {
+    pub nodes       : Vec<CopiedNode>,
+    pub connections : Vec<CopiedConnection>,
+}
+
+impl Content {
+    /// Describes given nodes with their positions. Nodes should be given in their graph order.
+    ///
+    /// The `external_names` are identifiers introduced by the graph's nodes which are not
+    /// copied. Their usages in copied expressions are replaced with placeholders (`_`).
+    pub fn from_nodes
+    (nodes:&[(NodeInfo,Option<Position>)], external_names:&HashSet<NormalizedName>)
+    -> FallibleResult<Self> {
+        let origin = Self::top_left_corner(nodes.iter().filter_map(|(_,position)| *position));
+        let mut internal_names : HashMap<NormalizedName,usize> = default();
+        let mut content = Content::default();
+        for (index,(node,position)) in nodes.iter().enumerate() {
+            let mut expression = node.expression().clone();
+            for used in analyse_ast(&expression).used {
+                if let Some(source) = internal_names.get(&used.item) {
+                    let connection = CopiedConnection {source:*source, destination:index};
+                    if !content.connections.contains(&connection) {
+                        content.connections.push(connection);
+                    }
+                } else if external_names.contains(&used.item) {
+                    expression = expression.set_traversing(&used.crumbs,Ast::blank())?;
+                }
+            }
+            let name = node.pattern().and_then(ast::identifier::name).map(ToString::to_string);
+            if let Some(name) = &name {
+                internal_names.insert(NormalizedName::new(name),index);
+            }
+            let expression = expression.repr();
+            let position   = position.map(|position| {
+                Position {vector:position.vector - origin.vector}
+            });
+            content.nodes.push(CopiedNode {name,expression,position});
+        }
+        Ok(content)
+    }
+
+    /// Describes nodes from plain Enso code, e.g. copied from the text editor. Each non-empty line
+    /// becomes a node; identifiers not introduced by the code are left intact.
+    pub fn from_code(parser:&Parser, code:&str) -> FallibleResult<Self> {
+        let module = parser.parse_module(code,default())?;
+        let lines  = module.lines.iter().filter_map(|line| line.elem.as_ref());
+        let nodes  = lines.filter_map(NodeInfo::from_line_ast).map(|node| (node,None));
+        let nodes  = nodes.collect_vec();
+        Self::from_nodes(&nodes,&default())
+    }
+
+    /// Serializes content to the text which can be put in the system clipboard.
+    pub fn to_text(&self) -> FallibleResult<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Reads the content from the system clipboard text. The text is either the content
+    /// serialized with `to_text`, or plain Enso code.
+    pub fn from_text(parser:&Parser, text:&str) -> FallibleResult<Self> {
+        match serde_json::from_str(text) {
+            Ok(content) => Ok(content),
+            Err(_)      => Self::from_code(parser,text),
+        }
+    }
+
+    /// Connections which have the node with given index as destination.
+    pub fn connections_to(&self, index:usize) -> impl Iterator<Item=&CopiedConnection> {
+        self.connections.iter().filter(move |connection| connection.destination == index)
+    }
+
+    fn top_left_corner(positions:impl Iterator<Item=Position>) -> Position {
+        let corner = positions.fold(None, |corner:Option<Position>,position| {
+            Some(match corner {
+                Some(corner) => {
+                    let x = corner.vector.x.min(position.vector.x);
+                    let y = corner.vector.y.max(position.vector.y);
+                    Position::new(x,y)
+                }
+                None => position,
+            })
+        });
+        corner.unwrap_or_else(|| Position::new(0.0,0.0))
+    }
+}
+
+
+
+// =================
+// === Utilities ===
+// =================
+
+/// Replaces all usages of the identifiers being the keys of `renames` with the mapped names.
+///
+/// All identifiers are replaced in a single pass over the original expression, so the renames
+/// never chain: with `a → b` and `b → c`, the usages of `a` become `b`, not `c`.
+pub fn rename_used_identifiers
+(expression:&Ast, renames:&HashMap<NormalizedName,String>) -> FallibleResult<Ast> {
+    let mut expression = expression.clone();
+    for used in analyse_ast(&expression).used {
+        if let Some(new_name) = renames.get(&used.item) {
+            expression = expression.set_traversing(&used.crumbs,Ast::var(new_name))?;
+        }
+    }
+    Ok(expression)
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn content_from_code() {
+        let parser  = Parser::new_or_panic();
+        let content = Content::from_code(&parser,"a = 5\nb = a + foo\nprint b").unwrap();
+        let names   = content.nodes.iter().map(|node| node.name.clone()).collect_vec();
+        let exprs   = content.nodes.iter().map(|node| node.expression.as_str()).collect_vec();
+        assert_eq!(names, vec![Some("a".into()),Some("b".into()),None]);
+        assert_eq!(exprs, vec!["5","a + foo","print b"]);
+        let expected_connections = vec![
+            CopiedConnection {source:0, destination:1},
+            CopiedConnection {source:1, destination:2},
+        ];
+        assert_eq!(content.connections, expected_connections);
+    }
+
+    #[wasm_bindgen_test]
+    fn text_roundtrip() {
+        let parser      = Parser::new_or_panic();
+        let mut content = Content::from_code(&parser,"a = 5\nb = a + 1").unwrap();
+        content.nodes[1].position = Some(Position::new(10.0,-20.0));
+        let text     = content.to_text().unwrap();
+        let restored = Content::from_text(&parser,&text).unwrap();
+        assert_eq!(content,restored);
+    }
+
+    #[wasm_bindgen_test]
+    fn renaming_identifiers() {
+        let parser     = Parser::new_or_panic();
+        let expression = parser.parse_line("foo + bar.baz foo").unwrap();
+        let renames    = vec![(NormalizedName::new("foo"),"sum1".to_string())];
+        let renamed    = rename_used_identifiers(&expression,&renames.into_iter().collect());
+        assert_eq!(renamed.unwrap().repr(), "sum1 + bar.baz sum1");
+
+        let expression = parser.parse_line("a + b").unwrap();
+        let renames    = vec!
+            [ (NormalizedName::new("a"),"b".to_string())
+            , (NormalizedName::new("b"),"c".to_string())
+            ];
+        let renamed    = rename_used_identifiers(&expression,&renames.into_iter().collect());
+        assert_eq!(renamed.unwrap().repr(), "b + c");
+    }
+}
@@ -0,0 +1,235 @@
+//! Automatic layout of the graph nodes.
+//!
+//! The algorithm is a simplified layered (Sugiyama-style) layout: nodes are assigned to layers
+//! following the data flow, the order in each layer is refined with a barycenter heuristic to
+//! reduce edge crossings, and finally each node gets its coordinates. Dummy nodes for edges
+//! spanning several layers are not introduced.
+//!
+//! The layout respects pinned nodes: they are never moved, but are taken into account when
+//! choosing where the laid out nodes should be placed.
+use crate::prelude::*;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The vertical distance between subsequent layers.
+pub const LAYER_GAP:f32 = 80.0;
+
+/// The horizontal distance between nodes in the same layer.
+pub const NODE_GAP:f32 = 240.0;
+
+/// The number of barycenter sweeps performed when reducing edge crossings.
+const ORDERING_SWEEPS:usize = 4;
+
+
+
+// =============
+// === Input ===
+// =============
+
+/// The graph to be laid out.
+#[derive(Clone,Debug)]
+pub struct Input<Id> {
+    /// Nodes to be placed, in the order they appear in the graph's code.
+    pub nodes  : Vec<Id>,
+    /// Connections between nodes: pairs of source and destination.
+    pub edges  : Vec<(Id,Id)>,
+    /// The pinned nodes with their positions. They will not be moved.
+    pub pinned : HashMap<Id,Vector2<f32>>,
+    /// The position of the center of the first layer. If not given, it is chosen so the laid out
+    /// nodes appear below the pinned nodes they are connected to, or below all pinned nodes.
+    pub origin : Option<Vector2<f32>>,
+}
+
+impl<Id> Default for Input<Id> {
+    fn default() -> Self {
+        Input {nodes:default(), edges:default(), pinned:default(), origin:default()}
+    }
+}
+
+
+
+// ==============
+// === Layout ===
+// ==============
+
+/// Computes positions for all the `nodes` of the given input, excluding the pinned ones.
+pub fn layout<Id:Copy+Debug+Eq+Hash>(input:&Input<Id>) -> HashMap<Id,Vector2<f32>> {
+    let free_nodes = input.nodes.iter().filter(|id| !input.pinned.contains_key(*id)).copied();
+    let free_nodes = free_nodes.collect_vec();
+    let free_set   = free_nodes.iter().copied().collect::<HashSet<_>>();
+    let free_edges = input.edges.iter().filter(|(source,destination)| {
+        free_set.contains(source) && free_set.contains(destination) && source != destination
+    }).copied().collect_vec();
+
+    let layers = order_layers(assign_layers(&free_nodes,&free_edges),&free_edges);
+    let origin = input.origin.unwrap_or_else(|| choose_origin(input,&free_set));
+    let mut positions = HashMap::new();
+    for (layer_index,layer) in layers.iter().enumerate() {
+        let center_offset = (layer.len() as f32 - 1.0) / 2.0;
+        for (index,id) in layer.iter().enumerate() {
+            let x = origin.x + (index as f32 - center_offset) * NODE_GAP;
+            let y = origin.y - layer_index as f32 * LAYER_GAP;
+            positions.insert(*id,Vector2::new(x,y));
+        }
+    }
+    positions
+}
+
+/// Assigns nodes to layers using the longest path from the graph's sources. Nodes are processed
+/// in topological order; if there is a cycle, the remaining nodes are processed in their code
+/// order, so the cycle is broken.
+fn assign_layers<Id:Copy+Eq+Hash>(nodes:&[Id], edges:&[(Id,Id)]) -> Vec<Vec<Id>> {
+    let mut in_degree : HashMap<Id,usize> = nodes.iter().map(|id| (*id,0)).collect();
+    for (_,destination) in edges {
+        *in_degree.entry(*destination).or_default() += 1;
+    }
+    let mut layer_of  : HashMap<Id,usize> = default();
+    let mut processed : HashSet<Id>       = default();
+    while processed.len() < nodes.len() {
+        let ready = nodes.iter().find(|id| !processed.contains(*id) && in_degree[*id] == 0);
+        let next  = ready.or_else(|| nodes.iter().find(|id| !processed.contains(*id)));
+        let next  = *next.expect("There is always an unprocessed node inside the loop.");
+        let layer = layer_of.get(&next).copied().unwrap_or(0);
+        layer_of.insert(next,layer);
+        processed.insert(next);
+        let outgoing = edges.iter().filter(|(source,_)| *source == next);
+        for (_,destination) in outgoing {
+            if !processed.contains(destination) {
+                let degree = in_degree.get_mut(destination).expect("Edges connect known nodes.");
+                *degree = degree.saturating_sub(1);
+                let destination_layer = layer_of.entry(*destination).or_insert(0);
+                *destination_layer = std::cmp::max(*destination_layer,layer + 1);
+            }
+        }
+    }
+    let layers_count = layer_of.values().max().map_or(0,|max| max + 1);
+    let mut layers   = vec![Vec::new();layers_count];
+    for id in nodes {
+        layers[layer_of[id]].push(*id);
+    }
+    layers
+}
+
+/// Reorders nodes in each layer to reduce edge crossings, using barycenter heuristic with
+/// alternating downward and upward sweeps.
+fn order_layers<Id:Copy+Eq+Hash>(mut layers:Vec<Vec<Id>>, edges:&[(Id,Id)]) -> Vec<Vec<Id>> {
+    for sweep in 0..ORDERING_SWEEPS {
+        let downward = sweep % 2 == 0;
+        let indices  = (1..layers.len()).collect_vec();
+        let indices  = if downward {indices} else {indices.into_iter().rev().collect()};
+        for index in indices {
+            let (layer,neighbour) = if downward {(index,index-1)} else {(index-1,index)};
+            let neighbour_positions : HashMap<Id,usize> = layers[neighbour].iter().enumerate()
+                .map(|(position,id)| (*id,position)).collect();
+            let barycenter = |id:&Id| {
+                let adjacent = edges.iter().filter_map(|(source,destination)| {
+                    let other = if downward {
+                        (destination == id).as_some(source)
+                    } else {
+                        (source == id).as_some(destination)
+                    };
+                    other.and_then(|other| neighbour_positions.get(other))
+                }).collect_vec();
+                let sum = adjacent.iter().map(|position| **position as f32).sum::<f32>();
+                (!adjacent.is_empty()).as_some(sum / adjacent.len() as f32)
+            };
+            let current = layers[layer].iter().enumerate();
+            let mut keyed = current.map(|(position,id)| {
+                let key = barycenter(id).unwrap_or(position as f32);
+                (key,*id)
+            }).collect_vec();
+            keyed.sort_by(|(left,_),(right,_)| {
+                left.partial_cmp(right).unwrap_or(std::cmp::Ordering::Equal)
+            });
+            layers[layer] = keyed.into_iter().map(|(_,id)| id).collect();
+        }
+    }
+    layers
+}
+
+/// Chooses the origin for laid out nodes if not given explicitly: below the pinned nodes which are
+/// sources of connections to the laid out nodes, or below all pinned nodes if there are no such.
+fn choose_origin<Id:Copy+Eq+Hash>(input:&Input<Id>, free:&HashSet<Id>) -> Vector2<f32> {
+    let connected_sources = input.edges.iter().filter(|(_,destination)| free.contains(destination));
+    let connected_sources = connected_sources.filter_map(|(source,_)| input.pinned.get(source));
+    let connected_sources = connected_sources.copied().collect_vec();
+    if !connected_sources.is_empty() {
+        let mean_x = connected_sources.iter().map(|pos| pos.x).sum::<f32>();
+        let mean_x = mean_x / connected_sources.len() as f32;
+        let min_y  = connected_sources.iter().map(|pos| pos.y).fold(f32::INFINITY,f32::min);
+        Vector2::new(mean_x,min_y - LAYER_GAP)
+    } else if !input.pinned.is_empty() {
+        let min_x = input.pinned.values().map(|pos| pos.x).fold(f32::INFINITY,f32::min);
+        let min_y = input.pinned.values().map(|pos| pos.y).fold(f32::INFINITY,f32::min);
+        Vector2::new(min_x,min_y - LAYER_GAP)
+    } else {
+        Vector2::new(0.0,0.0)
+    }
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(nodes:&[usize], edges:&[(usize,usize)]) -> Input<usize> {
+        let nodes = nodes.to_vec();
+        let edges = edges.to_vec();
+        Input {nodes,edges,..default()}
+    }
+
+    #[test]
+    fn chain_is_laid_out_vertically() {
+        let positions = layout(&input(&[1,2,3],&[(1,2),(2,3)]));
+        assert_eq!(positions[&1], Vector2::new(0.0,0.0));
+        assert_eq!(positions[&2], Vector2::new(0.0,-LAYER_GAP));
+        assert_eq!(positions[&3], Vector2::new(0.0,-2.0 * LAYER_GAP));
+    }
+
+    #[test]
+    fn diamond_is_laid_out_in_three_layers() {
+        let positions = layout(&input(&[1,2,3,4],&[(1,2),(1,3),(2,4),(3,4)]));
+        assert_eq!(positions[&1].y, 0.0);
+        assert_eq!(positions[&2].y, -LAYER_GAP);
+        assert_eq!(positions[&3].y, -LAYER_GAP);
+        assert_eq!(positions[&4].y, -2.0 * LAYER_GAP);
+        assert_eq!(positions[&2].x, -NODE_GAP / 2.0);
+        assert_eq!(positions[&3].x, NODE_GAP / 2.0);
+    }
+
+    #[test]
+    fn crossings_are_reduced() {
+        // Node 4 is connected to 1 and node 3 is connected to 2, so they should swap places to
+        // avoid crossing edges.
+        let positions = layout(&input(&[1,2,3,4],&[(1,4),(2,3)]));
+        assert!(positions[&1].x < positions[&2].x);
+        assert!(positions[&4].x < positions[&3].x);
+    }
+
+    #[test]
+    fn pinned_nodes_are_respected() {
+        let mut input = input(&[1,2,3],&[(1,2),(1,3)]);
+        input.pinned.insert(1,Vector2::new(100.0,50.0));
+        let positions = layout(&input);
+        assert!(!positions.contains_key(&1));
+        assert_eq!(positions[&2].y, 50.0 - LAYER_GAP);
+        assert_eq!(positions[&3].y, 50.0 - LAYER_GAP);
+        assert_eq!((positions[&2].x + positions[&3].x) / 2.0, 100.0);
+    }
+
+    #[test]
+    fn cycles_do_not_break_layout() {
+        let positions = layout(&input(&[1,2],&[(1,2),(2,1)]));
+        assert_eq!(positions.len(), 2);
+        assert_ne!(positions[&1], positions[&2]);
+    }
+}
@@ -5,6 +5,10 @@
 
 use crate::prelude::*;
 
+use crate::config::PROJECT_KEYMAP_FILE;
+use crate::config::PROJECT_THEME_FILE;
+use crate::constants::LANGUAGE_FILE_EXTENSION;
+use crate::constants::SOURCE_DIRECTORY;
 use crate::controller::FilePath;
 use crate::controller::Visualization;
 use crate::model::execution_context::VisualizationId;
@@ -13,9 +17,12 @@ use crate::model::module::QualifiedName as ModuleQualifiedName;
 use crate::model::module::Path          as ModulePath;
 use crate::model::synchronized::ExecutionContext;
 
+use data::text::Index;
+use data::text::TextChange;
 use enso_protocol::binary;
 use enso_protocol::binary::message::VisualisationContext;
 use enso_protocol::language_server;
+use enso_protocol::language_server::FileSystemObject;
 use parser::Parser;
 use uuid::Uuid;
 
@@ -76,6 +83,40 @@ impl ExecutionContextsRegistry {
 
 
 
+// =======================
+// === Module Renaming ===
+// =======================
+
+// === Errors ===
+
+#[allow(missing_docs)]
+#[derive(Clone,Debug,Fail)]
+#[fail(display="Cannot rename module to {}, because such file already exists.", _0)]
+pub struct ModuleAlreadyExists(ModulePath);
+
+
+// === Report ===
+
+/// The outcome of the module renaming refactoring. If renaming returned the report, the module
+/// is available under the new path and all references to it were updated.
+#[derive(Debug)]
+pub struct ModuleRenameReport {
+    /// The qualified name of the module after renaming.
+    pub new_name : ModuleQualifiedName,
+    /// Modules which had their references updated.
+    pub updated  : Vec<ModulePath>,
+}
+
+/// A module whose references to the renamed module were rewritten, with the code to restore if
+/// renaming fails.
+#[derive(Debug)]
+struct RewrittenModule {
+    module   : controller::Module,
+    old_code : String,
+}
+
+
+
 // ==========================
 // === Project Controller ===
 // ==========================
@@ -203,6 +244,153 @@ impl Handle {
         ModuleQualifiedName::from_path(path,self.project_name.deref())
     }
 
+    /// Lists paths of all modules in this project, i.e. all language files in the sources
+    /// directory and its subdirectories.
+    pub async fn module_paths(&self) -> FallibleResult<Vec<ModulePath>> {
+        let root_id         = self.content_root_id();
+        let mut directories = vec![FilePath::new(root_id,&[SOURCE_DIRECTORY])];
+        let mut modules     = Vec::new();
+        while let Some(directory) = directories.pop() {
+            let listing = self.language_server_rpc.client.file_list(&directory).await?;
+            for object in listing.paths {
+                match object {
+                    FileSystemObject::Directory {name,path} =>
+                        directories.push(path.append_im(name)),
+                    FileSystemObject::File {name,path} => {
+                        let file_path = path.append_im(name);
+                        if file_path.extension() == Some(LANGUAGE_FILE_EXTENSION) {
+                            if let Ok(module_path) = ModulePath::from_file_path(file_path) {
+                                modules.push(module_path);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(modules)
+    }
+
+    /// Reads the project-specific keymap from the file in the project's root directory. Returns
+    /// `None` if the project does not define its keymap.
+    pub async fn read_keymap(&self) -> FallibleResult<Option<String>> {
+        self.read_optional_root_file(PROJECT_KEYMAP_FILE).await
+    }
+
+    /// Reads the project-specific themes from the file in the project's root directory. Returns
+    /// `None` if the project does not define any themes.
+    pub async fn read_themes(&self) -> FallibleResult<Option<String>> {
+        self.read_optional_root_file(PROJECT_THEME_FILE).await
+    }
+
+    /// Reads the file from the project's root directory, or returns `None` if it does not exist.
+    async fn read_optional_root_file(&self, name:&str) -> FallibleResult<Option<String>> {
+        let path   = FilePath::new(self.content_root_id(),&[name]);
+        let client = &self.language_server_rpc.client;
+        if client.file_exists(&path).await?.exists {
+            Ok(Some(client.read_file(&path).await?.contents))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Renames (moves) the module file and updates all references to it in other modules of the
+    /// project: the import lines and fully-qualified method calls.
+    ///
+    /// The references are rewritten through each module's synchronized model before the module
+    /// file is moved. If any module cannot be updated or the file cannot be moved, the rewritten
+    /// modules are restored and an error is returned. If the renamed module is open, its model is
+    /// reopened and registered under the new path.
+    pub async fn rename_module
+    (&self, old_path:ModulePath, new_path:ModulePath) -> FallibleResult<ModuleRenameReport> {
+        let old_name = self.qualified_module_name(&old_path);
+        let new_name = self.qualified_module_name(&new_path);
+        info!(self.logger,"Renaming module {old_name.deref()} to {new_name.deref()}.");
+        let client   = &self.language_server_rpc.client;
+        let modules  = self.module_paths().await?;
+        let exists   = client.file_exists(new_path.file_path()).await?.exists;
+        if exists {
+            return Err(ModuleAlreadyExists(new_path).into());
+        }
+        let other_modules = modules.into_iter().filter(|path| *path != old_path);
+        let rewritten     = self.rename_references(other_modules,&old_name,&new_name).await?;
+        if let Err(error) = self.move_module(&old_path,&new_path).await {
+            error!(self.logger,"Failed to move module {old_path}: {error}");
+            self.restore_modules(rewritten);
+            return Err(error)
+        }
+        let updated = rewritten.iter().map(|rewritten| rewritten.module.path.deref().clone());
+        let updated = updated.collect();
+        Ok(ModuleRenameReport {new_name,updated})
+    }
+
+    /// Rewrites all references to module `old_name` in the modules under given `paths`. Returns
+    /// the modules which had any reference updated. If any module cannot be updated, all of them
+    /// are restored.
+    async fn rename_references
+    ( &self
+    , paths    : impl IntoIterator<Item=ModulePath>
+    , old_name : &ModuleQualifiedName
+    , new_name : &ModuleQualifiedName
+    ) -> FallibleResult<Vec<RewrittenModule>> {
+        use double_representation::module::rename_module_references;
+        let mut rewritten = Vec::new();
+        for path in paths {
+            let module = match self.module_controller(path.clone()).await {
+                Ok(module) => module,
+                Err(error) => {
+                    error!(self.logger,"Failed to open module {path}: {error}");
+                    self.restore_modules(rewritten);
+                    return Err(error)
+                }
+            };
+            let changes = rename_module_references(&module.model.ast(),old_name,new_name);
+            if !changes.is_empty() {
+                let old_code = module.code();
+                rewritten.push(RewrittenModule {module:module.clone_ref(),old_code});
+                let applied = changes.into_iter().try_for_each(|ch| module.apply_code_change(ch));
+                if let Err(error) = applied {
+                    error!(self.logger,"Failed to update references in module {path}: {error}");
+                    self.restore_modules(rewritten);
+                    return Err(error)
+                }
+            }
+        }
+        Ok(rewritten)
+    }
+
+    /// Restores the code of modules rewritten by `rename_references`.
+    fn restore_modules(&self, rewritten:Vec<RewrittenModule>) {
+        for RewrittenModule {module,old_code} in rewritten.into_iter().rev() {
+            let end    = Index::new(module.code().chars().count());
+            let change = TextChange::replace(Index::new(0)..end,old_code);
+            if let Err(error) = module.apply_code_change(change) {
+                error!(self.logger,"Failed to restore module {module.path}: {error}");
+            }
+        }
+    }
+
+    /// Moves the module file. If the module is open, its file is closed for the time of moving and
+    /// then reopened under the new path.
+    async fn move_module(&self, old_path:&ModulePath, new_path:&ModulePath) -> FallibleResult<()> {
+        let client = &self.language_server_rpc.client;
+        let opened = self.module_registry.get_loaded(old_path);
+        if let Some(module) = &opened {
+            module.close_file().await?;
+        }
+        let moved = client.move_file(old_path.file_path(),new_path.file_path()).await;
+        if let Some(module) = &opened {
+            let path = if moved.is_ok() {new_path} else {old_path};
+            if let Err(error) = module.reopen(path.clone()).await {
+                error!(self.logger,"Failed to reopen module {path}: {error}");
+            }
+            if moved.is_ok() {
+                self.module_registry.rekey(old_path,new_path.clone());
+            }
+        }
+        Ok(moved?)
+    }
+
     fn module_controller_with_model
     (&self, path:ModulePath, model:Rc<model::synchronized::Module>)
     -> controller::Module {
@@ -340,6 +528,76 @@ mod test {
         });
     }
 
+    #[wasm_bindgen_test]
+    fn renaming_module() {
+        let mut test = TestWithLocalPoolExecutor::set_up();
+        test.run_task(async move {
+            let main_path    = ModulePath::from_mock_module_name("Main");
+            let old_path     = ModulePath::from_mock_module_name("Foo");
+            let new_path     = ModulePath::from_mock_module_name("Bar");
+            let src_path     = FilePath::new(default(),&[SOURCE_DIRECTORY]);
+            let main_code    = "import Project.Foo\nmain = Project.Foo.baz 2";
+            let project_ctrl = setup_mock_project(|ls_json| {
+                let main_file = FileSystemObject::new_file(main_path.file_path().clone());
+                let foo_file  = FileSystemObject::new_file(old_path.file_path().clone());
+                let paths     = vec![main_file.unwrap(),foo_file.unwrap()];
+                mock_calls_for_opening_text_file(ls_json,main_path.file_path().clone(),main_code);
+                // Two more edits are expected: one per each updated reference.
+                ls_json.expect.apply_text_file_edit(|_| Ok(()));
+                ls_json.expect.apply_text_file_edit(|_| Ok(()));
+                expect_call!(ls_json.file_list(path=src_path) => Ok(response::FileList{paths}));
+                let exists_response = response::FileExists {exists:false};
+                expect_call!(ls_json.file_exists(path=new_path.file_path().clone())
+                    => Ok(exists_response));
+                expect_call!(ls_json.move_file(from=old_path.file_path().clone(),
+                    to=new_path.file_path().clone()) => Ok(()));
+            }, |_| {});
+            let main_module = project_ctrl.module_controller(main_path.clone()).await.unwrap();
+            let report      = project_ctrl.rename_module(old_path,new_path).await.unwrap();
+
+            assert_eq!(report.new_name.to_string(), "Project.Bar");
+            assert_eq!(report.updated, vec![main_path]);
+            assert_eq!(main_module.code(), "import Project.Bar\nmain = Project.Bar.baz 2");
+        });
+    }
+
+    #[wasm_bindgen_test]
+    fn restoring_references_when_module_cannot_be_moved() {
+        use enso_protocol::language_server::error::code;
+        use json_rpc::error::RpcError;
+
+        let mut test = TestWithLocalPoolExecutor::set_up();
+        test.run_task(async move {
+            let main_path    = ModulePath::from_mock_module_name("Main");
+            let old_path     = ModulePath::from_mock_module_name("Foo");
+            let new_path     = ModulePath::from_mock_module_name("Bar");
+            let src_path     = FilePath::new(default(),&[SOURCE_DIRECTORY]);
+            let main_code    = "import Project.Foo\nmain = Project.Foo.baz 2";
+            let project_ctrl = setup_mock_project(|ls_json| {
+                let main_file = FileSystemObject::new_file(main_path.file_path().clone());
+                let foo_file  = FileSystemObject::new_file(old_path.file_path().clone());
+                let paths     = vec![main_file.unwrap(),foo_file.unwrap()];
+                let error     = RpcError::new_remote_error(code::FILE_NOT_FOUND,"File not found");
+                mock_calls_for_opening_text_file(ls_json,main_path.file_path().clone(),main_code);
+                // Two edits updating the references and one restoring the original code.
+                ls_json.expect.apply_text_file_edit(|_| Ok(()));
+                ls_json.expect.apply_text_file_edit(|_| Ok(()));
+                ls_json.expect.apply_text_file_edit(|_| Ok(()));
+                expect_call!(ls_json.file_list(path=src_path) => Ok(response::FileList{paths}));
+                let exists_response = response::FileExists {exists:false};
+                expect_call!(ls_json.file_exists(path=new_path.file_path().clone())
+                    => Ok(exists_response));
+                expect_call!(ls_json.move_file(from=old_path.file_path().clone(),
+                    to=new_path.file_path().clone()) => Err(error));
+            }, |_| {});
+            let main_module = project_ctrl.module_controller(main_path.clone()).await.unwrap();
+            let result      = project_ctrl.rename_module(old_path,new_path).await;
+
+            assert!(result.is_err());
+            assert_eq!(main_module.code(), main_code);
+        });
+    }
+
     fn mock_calls_for_opening_text_file
     (client:&language_server::MockClient, path:language_server::Path, content:&str) {
         let content          = content.to_string();
@@ -9,8 +9,8 @@ use crate::prelude::*;
 use crate::controller::FilePath;
 
 use data::text::TextChange;
+use enso_protocol::common::error::RemoteError;
 use enso_protocol::language_server;
-use json_rpc::error::RpcError;
 use std::pin::Pin;
 
 
@@ -75,13 +75,24 @@ impl Handle {
         }
     }
 
+    /// Check if the handled file is an Enso module.
+    pub fn is_module(&self) -> bool {
+        match self.file {
+            FileHandle::PlainText{..} => false,
+            FileHandle::Module{..}    => true,
+        }
+    }
+
     /// Read file's content.
-    pub async fn read_content(&self) -> Result<String,RpcError> {
+    ///
+    /// The errors reported by Language Server are returned as `language_server::Error`.
+    pub async fn read_content(&self) -> FallibleResult<String> {
         use FileHandle::*;
         match &self.file {
             PlainText {path,language_server} => {
                 let response = language_server.read_file(&path).await;
-                response.map(|response| response.contents)
+                let response = response.map_err(language_server::Error::typed)?;
+                Ok(response.contents)
             },
             Module{controller} => Ok(controller.code())
         }
@@ -93,7 +104,8 @@ impl Handle {
         async move {
             match file_handle {
                 FileHandle::PlainText {path,language_server} => {
-                    language_server.write_file(&path,&content).await?
+                    let result = language_server.write_file(&path,&content).await;
+                    result.map_err(language_server::Error::typed)?
                 },
                 FileHandle::Module {controller} => {
                     controller.check_code_sync(content)?;
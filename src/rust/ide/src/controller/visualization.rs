@@ -7,6 +7,7 @@ use crate::prelude::*;
 
 use crate::config::PROJECT_VISUALIZATION_FOLDER;
 
+use enso_protocol::common::error::RemoteError;
 use enso_protocol::language_server;
 use graph_editor::data;
 use graph_editor::component::visualization::definition;
@@ -94,7 +95,14 @@ impl Handle {
         let path      = language_server::Path::new(root_id,&[PROJECT_VISUALIZATION_FOLDER]);
         let folder    = self.language_server_rpc.file_exists(&path).await?;
         let file_list = if folder.exists {
-            self.language_server_rpc.file_list(&path).await?.paths
+            match self.language_server_rpc.file_list(&path).await {
+                Ok(response) => response.paths,
+                Err(error)   => match language_server::Error::from_rpc_error(&error) {
+                    // A file named as the visualization folder contains no visualizations.
+                    Some(language_server::Error::NotDirectory) => default(),
+                    _ => return Err(language_server::Error::typed(error)),
+                }
+            }
         } else {
             default()
         };
@@ -134,7 +142,16 @@ impl Handle {
                 result.cloned().ok_or_else(error)
             },
             VisualizationPath::File(path) => {
-                let js_code    = self.language_server_rpc.read_file(&path).await?.contents;
+                let js_code    = match self.language_server_rpc.read_file(&path).await {
+                    Ok(response) => response.contents,
+                    Err(error)   => match language_server::Error::from_rpc_error(&error) {
+                        Some(language_server::Error::FileNotFound) => {
+                            let identifier = visualization.clone();
+                            return Err(VisualizationError::NotFound{identifier}.into())
+                        }
+                        _ => return Err(language_server::Error::typed(error)),
+                    }
+                };
                 let identifier = visualization.clone();
                 let error      = |_| VisualizationError::InstantiationError {identifier}.into();
                 let module     = data::builtin_library(); // FIXME: provide real library name.
@@ -239,4 +256,26 @@ mod tests {
             assert_eq!(loaded_signature,expected_signature);
         }
     }
+
+    #[wasm_bindgen_test(async)]
+    async fn load_missing_file() {
+        use enso_protocol::language_server::error::code;
+        use json_rpc::error::RpcError;
+
+        let mock_client = language_server::MockClient::default();
+        let path        = Path::new(uuid::Uuid::default(),&["visualization","removed.js"]);
+        let error       = RpcError::new_remote_error(code::FILE_NOT_FOUND,"File not found");
+        expect_call!(mock_client.read_file(path=path.clone()) => Err(error));
+
+        let language_server = language_server::Connection::new_mock_rc(mock_client);
+        let vis_controller  = Handle::new(language_server,default());
+        let visualization   = VisualizationPath::File(path);
+        let error           = vis_controller.load_visualization(&visualization).await.unwrap_err();
+        let error           = error.downcast_ref::<VisualizationError>();
+        if let Some(VisualizationError::NotFound{identifier}) = error {
+            assert_eq!(*identifier, visualization);
+        } else {
+            panic!("Unexpected error: {:?}", error);
+        }
+    }
 }
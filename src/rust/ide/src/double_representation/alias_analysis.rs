@@ -380,8 +380,14 @@ impl AliasAnalyzer {
 /// Describes identifiers that nodes introduces into the graph and identifiers from graph's scope
 /// that node uses. This logic serves as a base for connection discovery.
 pub fn analyse_node(node:&NodeInfo) -> IdentifierUsage {
+    analyse_ast(node.ast())
+}
+
+/// Describes identifiers that given AST introduces and uses. The identifier locations are relative
+/// to the given AST.
+pub fn analyse_ast(ast:&Ast) -> IdentifierUsage {
     let mut analyzer = AliasAnalyzer::new();
-    analyzer.process_ast(node.ast());
+    analyzer.process_ast(ast);
     analyzer.root_scope.symbols
 }
 
@@ -0,0 +1,108 @@
+//! Code for module-level double representation processing.
+
+use crate::prelude::*;
+
+use ast::known;
+use data::text::Index;
+use data::text::Size;
+use data::text::Span;
+use data::text::TextChange;
+
+
+
+// =========================
+// === Module References ===
+// =========================
+
+/// Checks if given character may be a part of an identifier. Used to make sure that we don't
+/// match a prefix of some other, longer name.
+fn is_identifier_char(c:char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '\''
+}
+
+/// Finds all occurrences of module's qualified name in the module's code. Both import lines
+/// (e.g. `import Project.Foo`) and qualified method calls (e.g. `Project.Foo.bar`) are found.
+///
+/// Only the occurrences that start at some constructor identifier are considered, so text
+/// literals and comments containing the name are not reported.
+pub fn qualified_name_occurrences(module:&known::Module, name:&str) -> Vec<Span> {
+    let code            = module.repr();
+    let first_segment   = name.split('.').next().unwrap_or_default();
+    let mut occurrences = Vec::new();
+    ast::traverse_with_span(module, |span,ast| {
+        let is_first_segment = match ast.shape() {
+            ast::Shape::Cons(cons) => cons.name == first_segment,
+            _                      => false,
+        };
+        if is_first_segment {
+            let tail         = &code[span.index.value..];
+            let next_char    = tail.get(name.len()..).and_then(|rest| rest.chars().next());
+            let is_name_end  = !next_char.contains_if(|c| is_identifier_char(*c));
+            let is_occurence = tail.starts_with(name) && is_name_end;
+            if is_occurence {
+                occurrences.push(Span::new(span.index,Size::new(name.len())));
+            }
+        }
+    });
+    occurrences
+}
+
+/// Generates text changes which replace all references to module `old_name` with `new_name`.
+///
+/// The changes are returned in the reverse order of their position in the code, so they can be
+/// applied one by one without invalidating indices of the next ones.
+pub fn rename_module_references
+(module:&known::Module, old_name:&str, new_name:&str) -> Vec<TextChange> {
+    let occurrences = qualified_name_occurrences(module,old_name);
+    occurrences.into_iter().rev().map(|span| {
+        let range = span.index..Index::new(span.end().value);
+        TextChange::replace(range,new_name.to_string())
+    }).collect()
+}
+
+
+
+// ============
+// === Test ===
+// ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use parser::Parser;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn apply_rename(parser:&Parser, code:&str, old_name:&str, new_name:&str) -> String {
+        let module  = parser.parse_module(code,default()).unwrap();
+        let changes = rename_module_references(&module,old_name,new_name);
+        changes.iter().fold(code.to_string(), |code,change| change.applied(&code))
+    }
+
+    #[wasm_bindgen_test]
+    fn renaming_imports_and_qualified_calls() {
+        let parser = Parser::new_or_panic();
+        let code = "import Project.Foo
+main =
+    a = Project.Foo.bar 2
+    b = Project.FooBar.baz a
+    c = \"Project.Foo\"";
+        let expected = "import Project.Sub.Qux
+main =
+    a = Project.Sub.Qux.bar 2
+    b = Project.FooBar.baz a
+    c = \"Project.Foo\"";
+        let result = apply_rename(&parser,code,"Project.Foo","Project.Sub.Qux");
+        assert_eq!(result,expected);
+    }
+
+    #[wasm_bindgen_test]
+    fn no_references_no_changes() {
+        let parser  = Parser::new_or_panic();
+        let module  = parser.parse_module("main = Project.Bar.baz 2",default()).unwrap();
+        let changes = rename_module_references(&module,"Project.Foo","Project.Qux");
+        assert!(changes.is_empty());
+    }
+}
@@ -61,6 +61,10 @@ pub mod prelude {
 
 use crate::prelude::*;
 
+use crate::config::INSPECT_PROTOCOL_STORAGE_KEY;
+use crate::config::LOG_FILTER_STORAGE_KEY;
+use crate::config::LOG_HISTORY_LEVEL_STORAGE_KEY;
+use crate::transport::recording::record_if_enabled;
 use crate::transport::web::ConnectingError;
 use crate::transport::web::WebSocket;
 use crate::view::project::ProjectView;
@@ -70,6 +74,10 @@ use enso_protocol::language_server;
 use enso_protocol::project_manager;
 use enso_protocol::project_manager::ProjectMetadata;
 use enso_protocol::project_manager::ProjectName;
+use ensogl::system::web;
+use json_rpc::inspector::Inspector;
+use logger::entry::Level;
+use logger::filter::Filter;
 use uuid::Uuid;
 
 
@@ -175,8 +183,14 @@ pub async fn open_project
     let client_id     = Uuid::new_v4();
     let json_ws       = new_opened_ws(logger.clone_ref(), json_endpoint).await?;
     let binary_ws     = new_opened_ws(logger.clone_ref(), binary_endpoint).await?;
+    let json_ws       = record_if_enabled("language-server-json",json_ws);
+    let binary_ws     = record_if_enabled("language-server-binary",binary_ws);
     let client_json   = language_server::Client::new(json_ws);
     let client_binary = binary::Client::new(logger,binary_ws);
+    if is_protocol_inspection_enabled() {
+        log_protocol_traffic(logger,"json-protocol-traffic",&client_json.inspector());
+        log_protocol_traffic(logger,"binary-protocol-traffic",&client_binary.inspector());
+    }
     crate::executor::global::spawn(client_json.runner());
     crate::executor::global::spawn(client_binary.runner());
     let connection_json   = language_server::Connection::new(client_json,client_id).await?;
@@ -216,15 +230,63 @@ pub async fn open_most_recent_project_or_create_new
 pub async fn setup_project_view(logger:&Logger,config:SetupConfig)
 -> Result<ProjectView,failure::Error> {
     let transport    = connect_to_project_manager(logger.clone_ref(),config).await?;
+    let transport    = record_if_enabled("project-manager",transport);
     let pm           = setup_project_manager(transport);
     let project      = open_most_recent_project_or_create_new(logger,&pm).await?;
     let project_view = ProjectView::new(logger,project).await?;
     Ok(project_view)
 }
 
+/// Checks if the protocol traffic logging was enabled by the user. See
+/// `config::INSPECT_PROTOCOL_STORAGE_KEY`.
+pub fn is_protocol_inspection_enabled() -> bool {
+    let stored = web::try_local_storage().ok().and_then(|storage| {
+        storage.get_item(INSPECT_PROTOCOL_STORAGE_KEY).ok().flatten()
+    });
+    stored.contains(&"true")
+}
+
+/// Logs all messages observed by the protocol inspector, with their timestamps and latencies.
+pub fn log_protocol_traffic(logger:&Logger, name:&str, inspector:&Inspector) {
+    let logger = Logger::sub(logger,name);
+    inspector.set_observer(move |traffic| info!(logger,"{traffic}"));
+}
+
+/// Sets the log filter stored by the user. See `config::LOG_FILTER_STORAGE_KEY`.
+pub fn setup_log_filter(logger:&Logger) {
+    let stored = web::try_local_storage().ok().and_then(|storage| {
+        storage.get_item(LOG_FILTER_STORAGE_KEY).ok().flatten()
+    });
+    if let Some(text) = stored {
+        match text.parse::<Filter>() {
+            Ok(filter) => logger::config::set_filter(filter),
+            Err(err)   => warning!(logger,"Invalid log filter `{text}`: {err}"),
+        }
+    }
+}
+
+/// Starts keeping the log history for crash reports, with the level stored by the user. See
+/// `config::LOG_HISTORY_LEVEL_STORAGE_KEY`.
+pub fn setup_log_history(logger:&Logger) {
+    let stored = web::try_local_storage().ok().and_then(|storage| {
+        storage.get_item(LOG_HISTORY_LEVEL_STORAGE_KEY).ok().flatten()
+    });
+    let mut level = logger::history::DEFAULT_LEVEL;
+    if let Some(text) = stored {
+        match text.parse::<Level>() {
+            Ok(stored_level) => level = stored_level,
+            Err(err)         => warning!(logger,"Invalid log history level `{text}`: {err}"),
+        }
+    }
+    logger::history::enable(logger::history::DEFAULT_CAPACITY,level);
+}
+
 /// This function is the IDE entry point responsible for setting up all views and controllers.
 pub fn run_ide() {
     let logger          = Logger::new("IDE");
+    setup_log_filter(&logger);
+    setup_log_history(&logger);
+    logger::history::set_panic_hook();
     let global_executor = setup_global_executor();
     // We want global executor to live indefinitely.
     std::mem::forget(global_executor);
@@ -269,11 +269,137 @@ pub struct IdeMetadata {
     node : HashMap<ast::Id,NodeMetadata>
 }
 
+/// The version of `NodeMetadata` schema written by this IDE version.
+///
+/// Version `0` denotes the legacy metadata, that contained only the node position.
+pub const NODE_METADATA_VERSION:u32 = 1;
+
 /// Metadata of specific node.
-#[derive(Debug,Clone,Copy,Default,Serialize,Deserialize,Shrinkwrap)]
+///
+/// The schema is versioned and forward-compatible: all fields are optional and the fields not
+/// known to this IDE version are kept in `unknown_fields`, so they will be written back unchanged.
+#[derive(Clone,Debug,Deserialize,PartialEq,Serialize,Shrinkwrap)]
 pub struct NodeMetadata {
     /// Position in x,y coordinates.
-    pub position: Option<Position>
+    #[shrinkwrap(main_field)]
+    #[serde(default)]
+    pub position : Option<Position>,
+    /// Version of the schema these metadata were written with. Metadata of older versions are
+    /// written back using the current version.
+    #[serde(default,serialize_with="serialize_version")]
+    pub version : u32,
+    /// The comment added to the node by user.
+    #[serde(default,skip_serializing_if="Option::is_none")]
+    pub comment : Option<String>,
+    /// The colour tag assigned to the node by user.
+    #[serde(default,skip_serializing_if="Option::is_none")]
+    pub color : Option<NodeColor>,
+    /// Whether the node is collapsed in the graph editor.
+    #[serde(default,skip_serializing_if="std::ops::Not::not")]
+    pub collapsed : bool,
+    /// The visualization selected for this node.
+    #[serde(default,skip_serializing_if="Option::is_none")]
+    pub visualization : Option<VisualizationMetadata>,
+    /// Fields not recognized by this IDE version, most likely written by a newer one.
+    #[serde(flatten)]
+    unknown_fields : serde_json::Map<String,serde_json::Value>,
+}
+
+impl Default for NodeMetadata {
+    fn default() -> Self {
+        NodeMetadata {
+            position       : default(),
+            version        : NODE_METADATA_VERSION,
+            comment        : default(),
+            color          : default(),
+            collapsed      : default(),
+            visualization  : default(),
+            unknown_fields : default(),
+        }
+    }
+}
+
+impl NodeMetadata {
+    /// Creates metadata of a node placed at given position.
+    pub fn new(position:Option<Position>) -> Self {
+        NodeMetadata {position,..default()}
+    }
+
+    /// Checks if these metadata were written by a newer IDE version, so they possibly contain
+    /// information not understood by us.
+    pub fn is_from_newer_version(&self) -> bool {
+        self.version > NODE_METADATA_VERSION
+    }
+
+    /// Checks if there are any fields not recognized by this IDE version.
+    pub fn has_unknown_fields(&self) -> bool {
+        !self.unknown_fields.is_empty()
+    }
+}
+
+/// Serializes the metadata version, upgrading legacy versions to the current one.
+fn serialize_version<S:serde::Serializer>(version:&u32, serializer:S) -> Result<S::Ok,S::Error> {
+    serializer.serialize_u32(std::cmp::max(*version,NODE_METADATA_VERSION))
+}
+
+/// The colour tag which user may assign to a node.
+#[allow(missing_docs)]
+#[derive(Clone,Copy,Debug,Deserialize,Eq,Hash,PartialEq,Serialize)]
+#[serde(rename_all="snake_case")]
+pub enum ColorTag {Red,Orange,Yellow,Green,Blue,Purple,Gray}
+
+/// The colour of a node as stored in its metadata. Newer IDE versions may add new colour tags, so
+/// the values not recognized by this version are kept as raw JSON and written back unchanged.
+#[allow(missing_docs)]
+#[derive(Clone,Debug,Deserialize,PartialEq,Serialize)]
+#[serde(untagged)]
+pub enum NodeColor {
+    Tag(ColorTag),
+    Unknown(serde_json::Value),
+}
+
+impl NodeColor {
+    /// The colour tag, if it is known to this IDE version.
+    pub fn tag(&self) -> Option<ColorTag> {
+        match self {
+            NodeColor::Tag(tag)   => Some(*tag),
+            NodeColor::Unknown(_) => None,
+        }
+    }
+}
+
+impl From<ColorTag> for NodeColor {
+    fn from(tag:ColorTag) -> Self {
+        NodeColor::Tag(tag)
+    }
+}
+
+/// The visualization pinned to the node by user.
+#[derive(Clone,Debug,Default,Deserialize,Eq,PartialEq,Serialize)]
+pub struct VisualizationMetadata {
+    /// The path of the visualization definition.
+    pub path : VisualizationPath,
+    /// The preprocessor code set by the visualization, if it differs from the default one.
+    #[serde(default,skip_serializing_if="Option::is_none")]
+    pub preprocessor : Option<String>,
+}
+
+impl VisualizationMetadata {
+    /// Creates metadata of the visualization with given path and the default preprocessor.
+    pub fn new(path:VisualizationPath) -> Self {
+        let preprocessor = None;
+        VisualizationMetadata {path,preprocessor}
+    }
+}
+
+/// The path of a visualization definition, made of the library containing the definition and
+/// the visualization name in that library.
+#[derive(Clone,Debug,Default,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct VisualizationPath {
+    /// The name of library containing the visualization definition.
+    pub library : String,
+    /// The name of visualization in its library.
+    pub name    : String,
 }
 
 /// Used for storing node position.
@@ -491,7 +617,7 @@ mod test {
 
             // Metadata update
             let id            = Uuid::new_v4();
-            let node_metadata = NodeMetadata {position:Some(Position::new(1.0, 2.0))};
+            let node_metadata = NodeMetadata::new(Some(Position::new(1.0, 2.0)));
             module.set_node_metadata(id.clone(),node_metadata.clone());
             assert_eq!(Some(Notification::MetadataChanged), subscription.next().await);
             module.remove_node_metadata(id.clone()).unwrap();
@@ -521,7 +647,7 @@ mod test {
             let initial_md = module.node_metadata(id.clone());
             assert!(initial_md.is_err());
 
-            let md_to_set = NodeMetadata {position:Some(Position::new(1.0, 2.0))};
+            let md_to_set = NodeMetadata::new(Some(Position::new(1.0, 2.0)));
             module.set_node_metadata(id.clone(),md_to_set.clone());
             assert_eq!(md_to_set.position, module.node_metadata(id.clone()).unwrap().position);
 
@@ -534,6 +660,54 @@ mod test {
         });
     }
 
+    #[test]
+    fn reading_legacy_node_metadata() {
+        let json     = r#"{"position":{"vector":[1.0,2.0]}}"#;
+        let metadata = serde_json::from_str::<NodeMetadata>(json).unwrap();
+        assert_eq!(metadata.position, Some(Position::new(1.0,2.0)));
+        assert_eq!(metadata.version, 0);
+        assert!(!metadata.collapsed);
+        assert!(!metadata.has_unknown_fields());
+
+        let serialized = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(serialized["version"], NODE_METADATA_VERSION);
+    }
+
+    #[test]
+    fn node_metadata_roundtrip() {
+        let library       = "Builtin".into();
+        let name          = "Scatter Plot".into();
+        let visualization = VisualizationMetadata {
+            path         : VisualizationPath {library,name},
+            preprocessor : Some("x -> x.json_serialize".into()),
+        };
+        let metadata = NodeMetadata {
+            comment       : Some("Important node".into()),
+            color         : Some(ColorTag::Green.into()),
+            collapsed     : true,
+            visualization : Some(visualization),
+            ..NodeMetadata::new(Some(Position::new(4.0,5.0)))
+        };
+        let json     = serde_json::to_string(&metadata).unwrap();
+        let restored = serde_json::from_str::<NodeMetadata>(&json).unwrap();
+        assert_eq!(metadata, restored);
+    }
+
+    #[test]
+    fn preserving_unknown_node_metadata_fields() {
+        let json     = r#"{"version":7,"color":"ultraviolet","comment":"c","future":[1,2]}"#;
+        let metadata = serde_json::from_str::<NodeMetadata>(json).unwrap();
+        assert!(metadata.is_from_newer_version());
+        assert!(metadata.has_unknown_fields());
+        assert_eq!(metadata.color.as_ref().and_then(NodeColor::tag), None);
+        assert_eq!(metadata.comment.as_deref(), Some("c"));
+
+        let serialized = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(serialized["version"], 7);
+        assert_eq!(serialized["color"], "ultraviolet");
+        assert_eq!(serialized["future"], serde_json::json!([1,2]));
+    }
+
     #[test]
     fn module_path_conversion() {
         let path = FilePath::new(default(), &["src","Main.enso"]);
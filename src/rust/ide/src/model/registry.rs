@@ -114,6 +114,25 @@ where K : Clone + Eq + Hash {
         }
     }
 
+    /// Get item under the key if it is already loaded. Does not wait for the item being loaded by
+    /// another task.
+    pub fn get_loaded(&self, key:&K) -> Option<Rc<V>> {
+        match self.registry.borrow_mut().get(key) {
+            Some(Entry::Loaded(state)) => Some(state),
+            _                          => None,
+        }
+    }
+
+    /// Move the loaded item to the new key, e.g. after the module file was moved. Does nothing if
+    /// no item under `old_key` is loaded.
+    pub fn rekey(&self, old_key:&K, new_key:K) {
+        let mut registry = self.registry.borrow_mut();
+        if let Some(Entry::Loaded(state)) = registry.get(old_key) {
+            registry.remove(old_key);
+            registry.insert(new_key,Entry::Loaded(state));
+        }
+    }
+
     async fn get(&self, key:&K) -> Result<Option<Rc<V>>,LoadingError> {
         loop {
             let entry = self.registry.borrow_mut().get(&key);
@@ -5,6 +5,7 @@ use crate::prelude::*;
 use crate::model::module::Notification;
 
 use enso_protocol::types::Sha3_224;
+use enso_protocol::common::error::RemoteError;
 use enso_protocol::language_server;
 use data::text::TextLocation;
 use parser::api::SourceFile;
@@ -87,7 +88,7 @@ impl LanguageServerContent {
 /// [https://github.com/luna/enso/blob/main/docs/language-server/protocol-language-server.md].
 #[derive(Debug)]
 pub struct Module {
-    path            : model::module::Path,
+    path            : RefCell<model::module::Path>,
     /// The module handle.
     pub model       : model::Module,
     language_server : Rc<language_server::Connection>,
@@ -110,7 +111,8 @@ impl Module {
         let logger        = Logger::new(iformat!("Module {path}"));
         let file_path     = path.file_path().clone();
         info!(logger, "Opening module {file_path}");
-        let opened = language_server.client.open_text_file(&file_path).await?;
+        let opened = language_server.client.open_text_file(&file_path).await;
+        let opened = opened.map_err(language_server::Error::typed)?;
         info!(logger, "Read content of the module {path}, digest is {opened.current_version:?}");
         let end_of_file = TextLocation::at_document_end(&opened.content);
         // TODO[ao] We should not fail here when metadata are malformed, but discard them and set
@@ -119,6 +121,7 @@ impl Module {
         let digest  = opened.current_version;
         let summary = ContentSummary {digest,end_of_file};
         let model   = model::Module::new(source.ast,source.metadata);
+        let path    = RefCell::new(path);
         let this    = Rc::new(Module {path,model,language_server,logger});
         executor::global::spawn(Self::runner(this.clone_ref(),summary));
         Ok(this)
@@ -132,8 +135,34 @@ impl Module {
         client.expect.close_text_file(|_| Ok(()));
         // We don't expect any other call, because we don't execute `runner()`.
         let language_server = language_server::Connection::new_mock_rc(client);
+        let path            = RefCell::new(path);
         Rc::new(Module{path,model,language_server,logger})
     }
+
+    /// The path of the module file.
+    pub fn path(&self) -> model::module::Path {
+        self.path.borrow().clone()
+    }
+
+    /// Close the module file in Language Server, e.g. before the file is moved. The module remains
+    /// usable, but its changes are not synchronized until the file is opened again with `reopen`.
+    pub async fn close_file(&self) -> FallibleResult<()> {
+        let file_path = self.path.borrow().file_path().clone();
+        info!(self.logger, "Closing module {file_path}");
+        let result = self.language_server.client.close_text_file(&file_path).await;
+        Ok(result.map_err(language_server::Error::typed)?)
+    }
+
+    /// Open the module file under `path` in Language Server, after it was closed by `close_file`.
+    /// If the file was moved, the module changes are sent to the new location from now on.
+    pub async fn reopen(&self, path:model::module::Path) -> FallibleResult<()> {
+        let file_path = path.file_path().clone();
+        info!(self.logger, "Reopening module under {file_path}");
+        let opened = self.language_server.client.open_text_file(&file_path).await;
+        opened.map_err(language_server::Error::typed)?;
+        *self.path.borrow_mut() = path;
+        Ok(())
+    }
 }
 
 
@@ -173,6 +202,10 @@ impl Module {
         match new_content {
             Ok(new_content) => LanguageServerContent::Synchronized(new_content),
             Err(err)        => {
+                let write_denied = Some(language_server::Error::WriteDenied);
+                if language_server::Error::from_failure(&err) == write_denied {
+                    warning!(self.logger,"Another client holds the write lock of this module.");
+                }
                 error!(self.logger,"Error during sending text change to Language Server: {err}");
                 LanguageServerContent::Desynchronized(old_content)
             }
@@ -233,26 +266,33 @@ impl Module {
         let content = self.model.serialized_content()?;
         let summary = ParsedContentSummary::from_source(&content);
         let edit    = language_server::types::FileEdit {
-            path        : self.path.file_path().clone(),
+            path        : self.path.borrow().file_path().clone(),
             edits       : edits_constructor(content),
             old_version : ls_content.digest.clone(),
             new_version : summary.digest.clone()
         };
         debug!(self.logger,"Notifying LS with edit: {edit:?}.");
-        self.language_server.client.apply_text_file_edit(&edit).await?;
+        let result = self.language_server.client.apply_text_file_edit(&edit).await;
+        result.map_err(language_server::Error::typed)?;
         Ok(summary)
     }
 }
 
 impl Drop for Module {
     fn drop(&mut self) {
-        let file_path       = self.path.file_path().clone();
+        let file_path       = self.path.borrow().file_path().clone();
         let language_server = self.language_server.clone_ref();
         let logger          = self.logger.clone_ref();
         executor::global::spawn(async move {
             let result = language_server.client.close_text_file(&file_path).await;
             if let Err(err) = result {
-                error!(logger,"Error when closing module file {file_path}: {err}");
+                let not_opened = Some(language_server::Error::FileNotOpened);
+                if language_server::Error::from_rpc_error(&err) == not_opened {
+                    debug!(logger,"Module file {file_path} was already closed.");
+                } else {
+                    let err = language_server::Error::typed(err);
+                    error!(logger,"Error when closing module file {file_path}: {err}");
+                }
             }
         });
     }
@@ -1,5 +1,6 @@
 //! Transport implementations used by the IDE.
 
+pub mod recording;
 pub mod web;
 
 #[cfg(test)]
@@ -0,0 +1,56 @@
+//! Recording the protocol traffic of the IDE session, so it can be attached to bug reports or
+//! replayed in tests with `json_rpc::trace::ReplayTransport`.
+
+use crate::prelude::*;
+
+use crate::config::RECORD_PROTOCOL_STORAGE_KEY;
+
+use ensogl::system::web;
+use json_rpc::Transport;
+use json_rpc::trace::Recorder;
+use json_rpc::trace::RecordingTransport;
+
+
+
+// =================
+// === Recorders ===
+// =================
+
+thread_local! {
+    /// Recorders of all the connections made during the session, with the connection names.
+    static RECORDERS : RefCell<Vec<(String,Recorder)>> = default();
+}
+
+/// Checks if the protocol traffic recording was enabled by the user. See
+/// `config::RECORD_PROTOCOL_STORAGE_KEY`.
+pub fn is_enabled() -> bool {
+    let stored = web::try_local_storage().ok().and_then(|storage| {
+        storage.get_item(RECORD_PROTOCOL_STORAGE_KEY).ok().flatten()
+    });
+    stored.contains(&"true")
+}
+
+/// Wraps the transport so its traffic is recorded under the given connection name, if the
+/// recording is enabled. The recording is run by the global executor.
+pub fn record_if_enabled
+(name:impl Str, transport:impl Transport + 'static) -> Box<dyn Transport> {
+    if is_enabled() {
+        let mut recording = RecordingTransport::new(transport);
+        executor::global::spawn(recording.runner());
+        let recorder = recording.recorder();
+        RECORDERS.with(|recorders| recorders.borrow_mut().push((name.into(),recorder)));
+        Box::new(recording)
+    } else {
+        Box::new(transport)
+    }
+}
+
+/// Writes the traces recorded so far to the console, each preceded by its connection name.
+pub fn dump() {
+    RECORDERS.with(|recorders| {
+        for (name,recorder) in recorders.borrow().iter() {
+            let text = format!("=== {} ===\n{}",name,recorder.trace().to_json());
+            web::console::info_1(&text.into());
+        }
+    })
+}
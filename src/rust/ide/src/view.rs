@@ -1,8 +1,10 @@
 //! A module containing view components.
 
+pub mod clipboard;
 pub mod node_editor;
 pub mod layout;
 pub mod node_searcher;
 pub mod project;
 pub mod temporary_panel;
 pub mod text_editor;
+pub mod theme;
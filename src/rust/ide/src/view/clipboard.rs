@@ -0,0 +1,40 @@
+//! Access to the system clipboard.
+
+use wasm_bindgen::prelude::*;
+
+
+
+// ===========================
+// === JavaScript Bindings ===
+// ===========================
+
+mod js {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(module = "/src/view/clipboard/clipboard.js")]
+    extern "C" {
+        #[allow(unsafe_code)]
+        pub fn write_text(text:String);
+
+        #[allow(unsafe_code)]
+        pub fn read_text(callback:JsValue);
+    }
+}
+
+
+
+// =================
+// === Clipboard ===
+// =================
+
+/// Puts the text in the system clipboard. Does nothing if the clipboard is not available.
+pub fn write_text(text:impl Into<String>) {
+    js::write_text(text.into())
+}
+
+/// Reads the text from the system clipboard and passes it to the `callback`. The text is empty if
+/// the clipboard is not available, e.g. when the user denied the permission.
+pub fn read_text(callback:impl FnOnce(String) + 'static) {
+    let callback = Closure::once_into_js(callback);
+    js::read_text(callback)
+}
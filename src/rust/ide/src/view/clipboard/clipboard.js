@@ -0,0 +1,26 @@
+// System clipboard access through the asynchronous Clipboard API.
+//
+// The API may be missing (e.g. in older browsers or insecure contexts) or the user may deny the
+// permission. In such cases writing does nothing and reading yields an empty string, so the caller
+// may fall back to its own buffer.
+
+// Put the text in the system clipboard.
+export function write_text(text) {
+    if (navigator.clipboard && navigator.clipboard.writeText) {
+        navigator.clipboard.writeText(text).catch(error => {
+            console.warn("Failed to write the system clipboard:", error)
+        })
+    }
+}
+
+// Read the text from the system clipboard and pass it to the callback.
+export function read_text(callback) {
+    if (navigator.clipboard && navigator.clipboard.readText) {
+        navigator.clipboard.readText().then(callback, error => {
+            console.warn("Failed to read the system clipboard:", error)
+            callback("")
+        })
+    } else {
+        callback("")
+    }
+}
@@ -6,6 +6,9 @@ use crate::controller::graph::NodeTrees;
 use crate::model::execution_context::Visualization;
 use crate::model::execution_context::VisualizationId;
 use crate::model::execution_context::VisualizationUpdateData;
+use crate::model::module::ColorTag;
+use crate::model::module::VisualizationPath;
+use crate::view::clipboard;
 
 use bimap::BiMap;
 use enso_frp as frp;
@@ -13,6 +16,7 @@ use enso_frp::stream::EventEmitter;
 use ensogl::display;
 use ensogl::display::traits::*;
 use ensogl::application::Application;
+use ensogl::data::color;
 use graph_editor::component::visualization;
 use graph_editor::EdgeTarget;
 use graph_editor::GraphEditor;
@@ -109,6 +113,31 @@ impl<Parameter:frp::Data> FencedAction<Parameter> {
 /// node - possible when node was added by editing text).
 const DEFAULT_GAP_BETWEEN_NODES:f32 = 44.0;
 
+/// The colour displayed for the given node colour tag.
+fn color_tag_rgba(tag:ColorTag) -> color::Rgba {
+    match tag {
+        ColorTag::Red    => color::Rgba::new(0.86,0.27,0.27,1.0),
+        ColorTag::Orange => color::Rgba::new(0.93,0.55,0.20,1.0),
+        ColorTag::Yellow => color::Rgba::new(0.93,0.80,0.25,1.0),
+        ColorTag::Green  => color::Rgba::new(0.35,0.71,0.35,1.0),
+        ColorTag::Blue   => color::Rgba::new(0.30,0.55,0.85,1.0),
+        ColorTag::Purple => color::Rgba::new(0.60,0.40,0.80,1.0),
+        ColorTag::Gray   => color::Rgba::new(0.55,0.55,0.55,1.0),
+    }
+}
+
+/// Convert the visualization path stored in node metadata to the graph editor's one.
+fn displayed_visualization_path(path:&VisualizationPath) -> visualization::Path {
+    visualization::Path::new(path.library.as_str(),path.name.as_str())
+}
+
+/// Convert the graph editor's visualization path to the one stored in node metadata.
+fn controller_visualization_path(path:&visualization::Path) -> VisualizationPath {
+    let library = path.library.to_string();
+    let name    = path.name.to_string();
+    VisualizationPath {library,name}
+}
+
 /// A structure which handles integration between controller and graph_editor EnsoGl control.
 /// All changes made by user in view are reflected in controller, and all controller notifications
 /// update view accordingly.
@@ -134,8 +163,10 @@ struct GraphEditorIntegratedWithControllerModel {
     controller       : controller::ExecutedGraph,
     node_views       : RefCell<BiMap<ast::Id,graph_editor::NodeId>>,
     expression_views : RefCell<HashMap<graph_editor::NodeId,String>>,
+    vis_path_views   : RefCell<HashMap<graph_editor::NodeId,Option<VisualizationPath>>>,
     connection_views : RefCell<BiMap<controller::graph::Connection,graph_editor::EdgeId>>,
     visualizations   : SharedHashMap<graph_editor::NodeId,VisualizationId>,
+    clipboard        : Rc<RefCell<Option<controller::graph::clipboard::Content>>>,
 }
 
 
@@ -163,12 +194,28 @@ impl GraphEditorIntegratedWithController {
             GraphEditorIntegratedWithControllerModel::connection_removed_in_ui,&invalidate.trigger);
         let node_moved = Self::ui_action(&model,
             GraphEditorIntegratedWithControllerModel::node_moved_in_ui,&invalidate.trigger);
+        let nodes_arranged = Self::ui_action(&model,
+            |model,ids:&Vec<graph_editor::NodeId>| model.nodes_arrangement_requested_in_ui(ids),
+            &invalidate.trigger);
+        let nodes_copied = Self::ui_action(&model,
+            |model,ids:&Vec<graph_editor::NodeId>| model.nodes_copied_in_ui(ids),
+            &invalidate.trigger);
+        let nodes_pasted = Self::ui_action(&model,
+            GraphEditorIntegratedWithControllerModel::nodes_pasted_in_ui,&invalidate.trigger);
         let visualization_enabled = Self::ui_action(&model,
             GraphEditorIntegratedWithControllerModel::visualization_enabled_in_ui,
             &invalidate.trigger);
         let visualization_disabled = Self::ui_action(&model,
             GraphEditorIntegratedWithControllerModel::visualization_disabled_in_ui,
             &invalidate.trigger);
+        let node_collapsed = Self::ui_action(&model,
+            GraphEditorIntegratedWithControllerModel::node_collapsed_in_ui,&invalidate.trigger);
+        let visualization_chosen = Self::ui_action(&model,
+            GraphEditorIntegratedWithControllerModel::visualization_chosen_in_ui,
+            &invalidate.trigger);
+        let preprocessor_changed = Self::ui_action(&model,
+            GraphEditorIntegratedWithControllerModel::visualization_preprocessor_changed_in_ui,
+            &invalidate.trigger);
         frp::extend! {network
             // Notifications from controller
             let handle_notification = FencedAction::fence(&network,
@@ -185,6 +232,13 @@ impl GraphEditorIntegratedWithController {
             def _action = editor_outs.visualization_disabled   .map2(&is_hold,visualization_disabled);
             def _action = editor_outs.connection_removed       .map2(&is_hold,connection_removed);
             def _action = editor_outs.node_position_set_batched.map2(&is_hold,node_moved);
+            def _action = editor_outs.nodes_arrangement_requested.map2(&is_hold,nodes_arranged);
+            def _action = editor_outs.nodes_copy_requested     .map2(&is_hold,nodes_copied);
+            def _action = editor_outs.nodes_paste_requested    .map2(&is_hold,nodes_pasted);
+            def _action = editor_outs.node_collapsed_set       .map2(&is_hold,node_collapsed);
+            def _action = editor_outs.visualization_path_set   .map2(&is_hold,visualization_chosen);
+            def _action = editor_outs.visualization_set_preprocessor
+                .map2(&is_hold,preprocessor_changed);
         }
         Self::connect_frp_to_controller_notifications(&model,handle_notification.trigger);
         Self {model,network}
@@ -233,9 +287,11 @@ impl GraphEditorIntegratedWithControllerModel {
         let node_views       = default();
         let connection_views = default();
         let expression_views = default();
+        let vis_path_views   = default();
         let visualizations   = default();
+        let clipboard        = default();
         let this = GraphEditorIntegratedWithControllerModel {editor,controller,node_views,
-            expression_views,connection_views,logger,visualizations};
+            expression_views,vis_path_views,connection_views,logger,visualizations,clipboard};
 
         if let Err(err) = this.update_graph_view() {
             error!(this.logger,"Error while initializing graph editor: {err}");
@@ -249,14 +305,23 @@ impl GraphEditorIntegratedWithControllerModel {
 
 impl GraphEditorIntegratedWithControllerModel {
     /// Reload whole displayed content to be up to date with module state.
+    ///
+    /// The nodes without position, e.g. added in the text editor, are placed first.
     pub fn update_graph_view(&self) -> FallibleResult<()> {
         use controller::graph::Connections;
+        self.place_unpositioned_nodes();
         let Connections{trees,connections} = self.controller.graph.connections()?;
         self.update_node_views(trees)?;
         self.update_connection_views(connections)?;
         Ok(())
     }
 
+    fn place_unpositioned_nodes(&self) {
+        if let Err(err) = self.controller.graph.place_unpositioned_nodes() {
+            error!(self.logger,"Error while placing nodes without position: {err}");
+        }
+    }
+
     fn update_node_views
     (&self, mut trees:HashMap<double_representation::node::Id,NodeTrees>) -> FallibleResult<()> {
         let nodes = self.controller.graph.nodes()?;
@@ -285,6 +350,8 @@ impl GraphEditorIntegratedWithControllerModel {
         for (id,displayed_id) in to_remove {
             self.editor.frp.inputs.remove_node.emit_event(&displayed_id);
             self.node_views.borrow_mut().remove_by_left(&id);
+            self.expression_views.borrow_mut().remove(&displayed_id);
+            self.vis_path_views.borrow_mut().remove(&displayed_id);
         }
     }
 
@@ -294,7 +361,7 @@ impl GraphEditorIntegratedWithControllerModel {
         let displayed_id = self.editor.add_node();
         self.update_node_view(displayed_id,info,trees);
         // If position wasn't present in metadata, we must initialize it.
-        if info.metadata.and_then(|md| md.position).is_none() {
+        if info.metadata.as_ref().and_then(|md| md.position).is_none() {
             self.editor.frp.inputs.set_node_position.emit_event(&(displayed_id,default_pos));
         }
         self.node_views.borrow_mut().insert(id, displayed_id);
@@ -333,7 +400,7 @@ impl GraphEditorIntegratedWithControllerModel {
 
     fn update_node_view
     (&self, node:graph_editor::NodeId, info:&controller::graph::Node, trees:NodeTrees) {
-        let position = info.metadata.and_then(|md| md.position);
+        let position = info.metadata.as_ref().and_then(|md| md.position);
         if let Some(pos) = position {
             self.editor.frp.inputs.set_node_position.emit_event(&(node,pos.vector));
         }
@@ -347,6 +414,24 @@ impl GraphEditorIntegratedWithControllerModel {
             self.editor.frp.inputs.set_node_expression.emit_event(&(node,code_and_trees));
             self.expression_views.borrow_mut().insert(node, expression);
         }
+        self.update_node_metadata_view(node,info.metadata.as_ref());
+    }
+
+    fn update_node_metadata_view
+    (&self, node:graph_editor::NodeId, metadata:Option<&model::module::NodeMetadata>) {
+        let inputs    = &self.editor.frp.inputs;
+        let comment   = metadata.and_then(|md| md.comment.clone()).unwrap_or_default();
+        let color     = metadata.and_then(|md| md.color.as_ref()?.tag()).map(color_tag_rgba);
+        let collapsed = metadata.map_or(false,|md| md.collapsed);
+        let vis_path  = metadata.and_then(|md| Some(md.visualization.as_ref()?.path.clone()));
+        inputs.set_node_comment.emit_event(&(node,comment));
+        inputs.set_node_color_tag.emit_event(&(node,color));
+        inputs.set_node_collapsed.emit_event(&(node,collapsed));
+        if Some(&vis_path) != self.vis_path_views.borrow().get(&node) {
+            let displayed = vis_path.as_ref().map(displayed_visualization_path);
+            inputs.set_visualization.emit_event(&(node,displayed));
+            self.vis_path_views.borrow_mut().insert(node,vis_path);
+        }
     }
 
     fn update_connection_views
@@ -426,8 +511,68 @@ impl GraphEditorIntegratedWithControllerModel {
     fn node_moved_in_ui(&self, param:&(graph_editor::NodeId, Vector2)) -> FallibleResult<()> {
         let (displayed_id,pos) = param;
         let id                 = self.get_controller_node_id(*displayed_id)?;
-        self.controller.graph.module.with_node_metadata(id, |md| {
-            md.position = Some(model::module::Position::new(pos.x,pos.y));
+        let position           = model::module::Position::new(pos.x,pos.y);
+        self.controller.graph.set_node_position(id,position)
+    }
+
+    fn node_collapsed_in_ui(&self, param:&(graph_editor::NodeId,bool)) -> FallibleResult<()> {
+        let (displayed_id,collapsed) = param;
+        let id                       = self.get_controller_node_id(*displayed_id)?;
+        self.controller.graph.set_node_collapsed(id,*collapsed)
+    }
+
+    fn visualization_chosen_in_ui
+    (&self, param:&(graph_editor::NodeId,Option<visualization::Path>)) -> FallibleResult<()> {
+        let (displayed_id,path) = param;
+        let id                  = self.get_controller_node_id(*displayed_id)?;
+        let path                = path.as_ref().map(controller_visualization_path);
+        self.vis_path_views.borrow_mut().insert(*displayed_id,path.clone());
+        self.controller.graph.set_node_visualization(id,path)
+    }
+
+    fn visualization_preprocessor_changed_in_ui
+    (&self, param:&(graph_editor::NodeId,graph_editor::data::EnsoCode)) -> FallibleResult<()> {
+        let (displayed_id,code) = param;
+        let id                  = self.get_controller_node_id(*displayed_id)?;
+        self.controller.graph.set_node_visualization_preprocessor(id,Some(code.to_string()))
+    }
+
+    fn nodes_arrangement_requested_in_ui
+    (&self, displayed_ids:&[graph_editor::NodeId]) -> FallibleResult<()> {
+        let ids = displayed_ids.iter().map(|id| self.get_controller_node_id(*id));
+        let ids = ids.collect::<FallibleResult<Vec<_>>>()?;
+        self.controller.graph.arrange_nodes(&ids)?;
+        Ok(())
+    }
+
+    /// Puts the copied nodes both in the system clipboard and in the internal buffer. The latter is
+    /// used when the system clipboard is not available.
+    fn nodes_copied_in_ui(&self, displayed_ids:&[graph_editor::NodeId]) -> FallibleResult<()> {
+        let ids     = displayed_ids.iter().map(|id| self.get_controller_node_id(*id));
+        let ids     = ids.collect::<FallibleResult<Vec<_>>>()?;
+        let content = self.controller.graph.copy_nodes(&ids)?;
+        clipboard::write_text(content.to_text()?);
+        *self.clipboard.borrow_mut() = Some(content);
+        Ok(())
+    }
+
+    /// Pastes the system clipboard text, which may be copied nodes or plain Enso code. If the
+    /// system clipboard is empty or not available, the internal buffer is pasted instead.
+    fn nodes_pasted_in_ui(&self, position:&Vector2) -> FallibleResult<()> {
+        let graph    = self.controller.graph.clone_ref();
+        let buffer   = self.clipboard.clone_ref();
+        let logger   = self.logger.clone_ref();
+        let position = model::module::Position::new(position.x,position.y);
+        clipboard::read_text(move |text| {
+            let result = if text.trim().is_empty() {
+                let content = buffer.borrow().clone();
+                content.map_or(Ok(default()), |content| graph.paste_nodes(&content,position))
+            } else {
+                graph.paste_text(&text,position)
+            };
+            if let Err(err) = result {
+                error!(logger,"Failed to paste nodes: {err}");
+            }
         });
         Ok(())
     }
@@ -470,8 +615,11 @@ impl GraphEditorIntegratedWithControllerModel {
         let module_name          = crate::view::project::INITIAL_MODULE_NAME;
         let visualisation_module = QualifiedName::from_module_segments(&[module_name],project_name);
         let id                   = VisualizationId::new_v4();
-        let expression           = crate::constants::SERIALIZE_TO_JSON_EXPRESSION.into();
         let ast_id               = self.get_controller_node_id(*node_id)?;
+        let metadata             = self.controller.graph.module.node_metadata(ast_id).ok();
+        let preprocessor         = metadata.and_then(|md| md.visualization?.preprocessor);
+        let default_expression   = crate::constants::SERIALIZE_TO_JSON_EXPRESSION;
+        let expression           = preprocessor.unwrap_or_else(|| default_expression.into());
         Ok(Visualization{ast_id,expression,id,visualisation_module})
     }
 
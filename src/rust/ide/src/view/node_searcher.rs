@@ -9,6 +9,7 @@ use crate::model::module::NodeMetadata;
 use crate::model::module::Position;
 use crate::view::node_editor::NodeEditor;
 
+use ensogl::application::shortcut;
 use ensogl::data::color;
 use ensogl::display::shape::text::glyph::font;
 use ensogl::display::shape::text::text_field::TextField;
@@ -18,12 +19,16 @@ use ensogl::display;
 use ensogl::traits::*;
 
 
+/// The shortcut context flag set while the searcher is visible.
+pub const OPEN_FLAG:&str = graph_editor::SEARCHER_OPEN_FLAG;
+
 #[derive(Clone,Debug,CloneRef)]
 pub struct NodeSearcher {
     display_object : display::object::Instance,
     node_editor    : NodeEditor,
     text_field     : TextField,
     controller     : controller::graph::Handle,
+    context        : shortcut::Context,
     logger         : Logger,
 }
 
@@ -33,6 +38,7 @@ impl NodeSearcher {
     , logger      : impl AnyLogger
     , node_editor : NodeEditor
     , controller  : controller::graph::Handle
+    , context     : &shortcut::Context
     , fonts       : &mut font::Registry)
     -> Self {
         let scene          = world.scene();
@@ -47,8 +53,10 @@ impl NodeSearcher {
             size       : Vector2::new(screen.width,16.0),
         };
         let text_field = TextField::new(world,properties);
+        let context    = context.clone_ref();
         display_object.add_child(&text_field.display_object());
-        let searcher = NodeSearcher{node_editor,display_object,text_field,controller,logger};
+        let searcher   = NodeSearcher{node_editor,display_object,text_field,controller,context,
+            logger};
         searcher.initialize()
     }
 
@@ -62,7 +70,7 @@ impl NodeSearcher {
                 let position      = node_searcher.display_object.position();
                 let position      = position - node_searcher.node_editor.position();
                 let position      = Some(Position{vector:Vector2::new(position.x,position.y)});
-                let metadata      = Some(NodeMetadata{position});
+                let metadata      = Some(NodeMetadata::new(position));
                 let id            = None;
                 let location_hint = LocationHint::End;
                 let expression    = expression.to_string();
@@ -82,12 +90,15 @@ impl NodeSearcher {
         self.display_object.add_child(&self.text_field.display_object());
         self.text_field.clear_content();
         self.text_field.set_focus();
+        self.context.set(OPEN_FLAG,true);
     }
 
     /// Hide NodeSearcher if it is visible.
     pub fn hide(&mut self) {
         self.text_field.clear_content();
+        self.text_field.lose_focus();
         self.display_object.remove_child(&self.text_field.display_object());
+        self.context.set(OPEN_FLAG,false);
     }
 }
 
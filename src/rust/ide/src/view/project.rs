@@ -3,21 +3,23 @@
 
 use crate::prelude::*;
 
+use crate::config::GLOBAL_KEYMAP_STORAGE_KEY;
 use crate::double_representation::definition::DefinitionName;
 use crate::model::module::Path as ModulePath;
 use crate::view::layout::ViewLayout;
+use crate::view::theme;
 
 use ensogl::application::Application;
+use ensogl::application::shortcut::Keymap;
 use ensogl::control::callback;
 use ensogl::control::io::keyboard::listener::KeyboardFrpBindings;
-use ensogl::data::color;
 use ensogl::display::shape::text::glyph::font;
-use ensogl::display::style::theme;
 use ensogl::system::web;
 use enso_frp::io::keyboard::Keyboard;
 use enso_frp::io::keyboard;
 use nalgebra::Vector2;
 use shapely::shared;
+use std::str::FromStr;
 
 
 
@@ -60,7 +62,12 @@ shared! { ProjectView
         controller        : controller::Project,
         keyboard          : Keyboard,
         keyboard_bindings : KeyboardFrpBindings,
-        keyboard_actions  : keyboard::Actions
+        keyboard_actions  : keyboard::Actions,
+        theme_switching   : callback::Handle,
+        log_dumping       : callback::Handle,
+        keymap_dumping    : callback::Handle,
+        trace_dumping     : callback::Handle,
+        frp_tracing       : callback::Handle,
     }
 
     impl {
@@ -88,21 +95,29 @@ impl ProjectView {
         let graph_controller     = module_controller.executed_graph_controller_unchecked(graph_id,&controller);
         let graph_controller     = graph_controller.await?;
         let application          = Application::new(&web::get_html_element_by_id("root").unwrap());
+        let logger               = Logger::sub(logger,"ProjectView");
         Self::setup_components(&application);
-        Self::setup_theme(&application);
+        theme::setup(&logger,&application,&controller).await;
         let _world               = &application.display;
         // graph::register_shapes(&world);
-        let logger                   = Logger::sub(logger,"ProjectView");
         let keyboard                 = Keyboard::default();
         let keyboard_bindings        = KeyboardFrpBindings::new(&logger,&keyboard);
         let mut keyboard_actions     = keyboard::Actions::new(&keyboard);
+        let theme_switching          =
+            Self::setup_theme_switching(&logger,&application,&keyboard_actions);
+        let log_dumping              = Self::setup_log_dumping(&keyboard_actions);
+        let keymap_dumping           = Self::setup_keymap_dumping(&application,&keyboard_actions);
+        let trace_dumping            = Self::setup_trace_dumping(&keyboard_actions);
+        let frp_tracing              = Self::setup_frp_tracing(&keyboard_actions);
         let resize_callback          = None;
         let mut fonts                = font::Registry::new();
         let visualization_controller = controller.visualization.clone();
         let layout = ViewLayout::new(&logger,&mut keyboard_actions,&application, text_controller,
             graph_controller,visualization_controller,&mut fonts).await?;
+        Self::setup_keymap(&logger,&application,&controller).await;
         let data = ProjectViewData {application,layout,resize_callback,controller,keyboard,
-            keyboard_bindings,keyboard_actions};
+            keyboard_bindings,keyboard_actions,theme_switching,log_dumping,keymap_dumping,
+            trace_dumping,frp_tracing};
         Ok(Self::new_from_data(data).init())
     }
 
@@ -124,20 +139,91 @@ impl ProjectView {
         app.views.register::<graph_editor::GraphEditor>();
     }
 
-    fn setup_theme(app:&Application) {
-        let mut dark = theme::Theme::new();
-        dark.insert("application.background.color", color::Lcha::new(0.13,0.013,0.18,1.0));
-        dark.insert("graph_editor.node.background.color", color::Lcha::new(0.2,0.013,0.18,1.0));
-        dark.insert("graph_editor.node.selection.color", color::Lcha::new(0.72,0.5,0.22,1.0));
-        dark.insert("graph_editor.node.selection.size", 7.0);
-        //    dark.insert("graph_editor.node.selection.color", color::Lcha::new(0.7,0.59,0.18,1.0));
-        dark.insert("animation.duration", 0.5);
-        dark.insert("graph.node.shadow.color", 5.0);
-        dark.insert("graph.node.shadow.size", 5.0);
-        dark.insert("mouse.pointer.color", color::Rgba::new(0.3,0.3,0.3,1.0));
-
-        app.themes.register("dark",dark);
-        app.themes.set_enabled(&["dark"]);
+    /// Binds ctrl+alt+t to switching to the next theme.
+    fn setup_theme_switching(logger:&Logger, app:&Application, actions:&keyboard::Actions)
+    -> callback::Handle {
+        let switch = keyboard::Key::Character("t".into());
+        let keys   = &[keyboard::Key::Control,keyboard::Key::Alt,switch];
+        let logger = logger.clone_ref();
+        let app    = app.clone_ref();
+        actions.add_action(keys, move || theme::select_next(&logger,&app))
+    }
+
+    /// Binds ctrl+alt+l to writing the recent log entries to the console, to be attached to bug
+    /// reports.
+    fn setup_log_dumping(actions:&keyboard::Actions) -> callback::Handle {
+        let dump = keyboard::Key::Character("l".into());
+        let keys = &[keyboard::Key::Control,keyboard::Key::Alt,dump];
+        actions.add_action(keys, || logger::history::report(&logger::history::dump_text()))
+    }
+
+    /// Binds ctrl+alt+k to writing the effective keymap, i.e. all shortcuts in effect, both the
+    /// default and user-defined ones, to the console. The output uses the keymap file syntax, so
+    /// it may be used as a starting point for the user keymap.
+    fn setup_keymap_dumping(app:&Application, actions:&keyboard::Actions) -> callback::Handle {
+        let dump = keyboard::Key::Character("k".into());
+        let keys = &[keyboard::Key::Control,keyboard::Key::Alt,dump];
+        let app  = app.clone_ref();
+        actions.add_action(keys, move || {
+            let keymap = app.shortcuts.effective_keymap();
+            web::console::info_1(&keymap.to_string().into());
+        })
+    }
+
+    /// Binds ctrl+alt+p to writing the recorded protocol traffic to the console, if the recording
+    /// is enabled. See `config::RECORD_PROTOCOL_STORAGE_KEY`.
+    fn setup_trace_dumping(actions:&keyboard::Actions) -> callback::Handle {
+        let dump = keyboard::Key::Character("p".into());
+        let keys = &[keyboard::Key::Control,keyboard::Key::Alt,dump];
+        actions.add_action(keys, crate::transport::recording::dump)
+    }
+
+    /// Binds ctrl+alt+f to switching the FRP events tracing on and off. The recorded emissions may
+    /// be then inspected with the `enso_frp::tracing` queries, e.g. `Network::traced_emissions`.
+    fn setup_frp_tracing(actions:&keyboard::Actions) -> callback::Handle {
+        let switch = keyboard::Key::Character("f".into());
+        let keys   = &[keyboard::Key::Control,keyboard::Key::Alt,switch];
+        actions.add_action(keys, || {
+            if enso_frp::tracing::is_enabled() {
+                enso_frp::tracing::disable();
+                web::console::info_1(&"FRP tracing disabled.".into());
+            } else {
+                enso_frp::tracing::enable(enso_frp::tracing::DEFAULT_CAPACITY);
+                web::console::info_1(&"FRP tracing enabled.".into());
+            }
+        })
+    }
+
+    /// Installs the user keymap: the global one stored in the browser's local storage, overridden
+    /// by the project-specific one. Invalid keymaps and the entries naming unknown commands are
+    /// reported and skipped.
+    async fn setup_keymap(logger:&Logger, app:&Application, project:&controller::Project) {
+        let global_text  = web::try_local_storage().ok().and_then(|storage| {
+            storage.get_item(GLOBAL_KEYMAP_STORAGE_KEY).ok().flatten()
+        });
+        let project_text = project.read_keymap().await.unwrap_or_else(|err| {
+            warning!(logger,"Cannot read the project keymap: {err}");
+            None
+        });
+        let parse = |name:&str, text:Option<String>| match text.map(|t| Keymap::from_str(&t)) {
+            Some(Ok(keymap)) => keymap,
+            Some(Err(err))   => {
+                error!(logger,"Invalid {name} keymap: {err}");
+                default()
+            }
+            None => default(),
+        };
+        let global  = parse("global",global_text);
+        let project = parse("project",project_text);
+        let mut keymap = global.overridden_by(project);
+        for err in keymap.retain_valid(&app.commands) {
+            warning!(logger,"Skipping keymap entry: {err}");
+        }
+        // Only the user bindings are checked, as they replace the defaults of their commands.
+        for conflict in keymap.conflicts() {
+            warning!(logger,"{conflict}");
+        }
+        app.shortcuts.set_keymap(&keymap);
     }
 
     /// Forgets ProjectView, so it won't get dropped when it goes out of scope.
@@ -1,9 +1,12 @@
 //! This module contains TextEditor, an UiComponent to edit Enso Modules or Text Files.
 
+pub mod highlight;
+
 use crate::prelude::*;
 
 use crate::view::temporary_panel::TemporaryPadding;
 use crate::view::temporary_panel::TemporaryPanel;
+use crate::view::text_editor::highlight::EnsoHighlighter;
 
 use data::text::TextChange;
 use enso_frp::io::keyboard::KeyMask;
@@ -12,6 +15,7 @@ use ensogl::data::color;
 use ensogl::display::shape::text::glyph::font;
 use ensogl::display::shape::text::text_field::TextField;
 use ensogl::display::shape::text::text_field::TextFieldProperties;
+use ensogl::display::shape::text::text_field::highlight::Highlighting;
 use ensogl::display::world::*;
 use ensogl::display;
 use ensogl::system::web::platform::Platform;
@@ -27,8 +31,8 @@ use utils::channel::process_stream_with_handle;
 
 shared! { TextEditor
 
-/// TextEditor allows us to edit text files or Enso Modules. Extensible code highlighting is
-/// planned to be implemented for it.
+/// TextEditor allows us to edit text files or Enso Modules. The code of Enso Modules is
+/// highlighted with `EnsoHighlighter`.
 #[derive(Debug)]
 pub struct TextEditorData {
     text_field : TextField,
@@ -46,9 +50,9 @@ impl {
         let text       = self.text_field.get_content();
         let logger     = self.logger.clone();
         executor::global::spawn(async move {
-            if controller.store_content(text).await.is_err() {
+            if let Err(err) = controller.store_content(text).await {
                 let file_path  = controller.file_path();
-                let message:&str = &format!("Failed to save file: {}", file_path);
+                let message:&str = &format!("Failed to save file {}: {}", file_path, err);
                 logger.error(message);
             } else {
                 logger.info("File saved");
@@ -83,6 +87,10 @@ impl TextEditor {
         let text_size  = 16.0;
         let properties = TextFieldProperties {font,text_size,base_color,size};
         let text_field = TextField::new(&world,properties);
+        if controller.is_module() {
+            let highlighting = Highlighting::new(EnsoHighlighter,default());
+            text_field.set_highlighting(Some(highlighting));
+        }
         // world.add_child(&text_field); // FIXME !!!
 
         let data = TextEditorData {controller,text_field,padding,position,size,logger};
@@ -157,11 +165,12 @@ impl TextEditor {
             (data.logger.clone(),data.controller.clone_ref()));
         let weak  = self.downgrade();
         async move {
-            if let Ok(content) = controller.read_content().await {
-                if let Some(this) = weak.upgrade() {
+            match controller.read_content().await {
+                Ok(content) => if let Some(this) = weak.upgrade() {
                     this.with_borrowed(|data| data.text_field.set_content(&content));
                     logger.info("File loaded");
-                }
+                },
+                Err(err) => logger.error(|| format!("Cannot load the file: {}", err)),
             }
         }
     }
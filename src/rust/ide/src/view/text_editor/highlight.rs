@@ -0,0 +1,168 @@
+//! Syntax highlighting of Enso code displayed in the TextEditor.
+//!
+//! The highlighter is a simple line-based lexer. It does not need the module to be parsable, so
+//! the code is highlighted properly also while being edited, and the characters which cannot
+//! appear in Enso code at all are marked as invalid.
+
+use crate::prelude::*;
+
+use ensogl::display::shape::text::text_field::highlight::Highlighter;
+use ensogl::display::shape::text::text_field::highlight::Span;
+use ensogl::display::shape::text::text_field::highlight::SpanKind;
+
+
+
+// =======================
+// === EnsoHighlighter ===
+// =======================
+
+/// Characters which may build operators.
+const OPERATOR_CHARS:&str = "!$%&*+-/<>?^~|:\\=.,@";
+
+/// Characters delimiting text literals.
+const QUOTES:&str = "\"'`";
+
+/// Brackets, left in the base color.
+const BRACKETS:&str = "()[]{}";
+
+/// The highlighter of Enso code.
+#[derive(Clone,Copy,Debug,Default)]
+pub struct EnsoHighlighter;
+
+impl Highlighter for EnsoHighlighter {
+    fn highlight_line(&self, line:&[char]) -> Vec<Span> {
+        let mut lexer = Lexer {line, offset:0, spans:default()};
+        lexer.run();
+        lexer.spans
+    }
+}
+
+
+// === Lexer ===
+
+#[derive(Debug)]
+struct Lexer<'a> {
+    line   : &'a [char],
+    offset : usize,
+    spans  : Vec<Span>,
+}
+
+impl<'a> Lexer<'a> {
+    fn run(&mut self) {
+        while let Some(&current) = self.line.get(self.offset) {
+            let start = self.offset;
+            let kind  = match current {
+                '#'                                   => self.comment(),
+                c if QUOTES.contains(c)               => self.text(c),
+                c if c.is_ascii_digit()               => self.number(),
+                c if c.is_uppercase()                 => self.identifier(SpanKind::Constructor),
+                c if c.is_alphabetic() || c == '_'    => self.identifier(SpanKind::Identifier),
+                c if OPERATOR_CHARS.contains(c)       => self.operator(),
+                c if c == ' ' || BRACKETS.contains(c) => self.single_char(None),
+                _                                     => self.single_char(Some(SpanKind::Invalid)),
+            };
+            if let Some(kind) = kind {
+                self.spans.push(Span::new(start..self.offset,kind));
+            }
+        }
+    }
+
+    fn single_char(&mut self, kind:Option<SpanKind>) -> Option<SpanKind> {
+        self.offset += 1;
+        kind
+    }
+
+    fn comment(&mut self) -> Option<SpanKind> {
+        self.offset = self.line.len();
+        Some(SpanKind::Comment)
+    }
+
+    fn text(&mut self, quote:char) -> Option<SpanKind> {
+        self.offset += 1;
+        while let Some(&current) = self.line.get(self.offset) {
+            self.offset += 1;
+            match current {
+                '\\'            => self.offset = (self.offset + 1).min(self.line.len()),
+                c if c == quote => break,
+                _               => {}
+            }
+        }
+        Some(SpanKind::Text)
+    }
+
+    fn number(&mut self) -> Option<SpanKind> {
+        self.skip_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        let next_is_digit = self.line.get(self.offset + 1).map_or(false, |c| c.is_ascii_digit());
+        if self.line.get(self.offset) == Some(&'.') && next_is_digit {
+            self.offset += 1;
+            self.skip_while(|c| c.is_ascii_digit() || c == '_');
+        }
+        Some(SpanKind::Number)
+    }
+
+    fn identifier(&mut self, kind:SpanKind) -> Option<SpanKind> {
+        self.skip_while(|c| c.is_alphanumeric() || c == '_');
+        self.skip_while(|c| c == '\'');
+        Some(kind)
+    }
+
+    fn operator(&mut self) -> Option<SpanKind> {
+        self.skip_while(|c| OPERATOR_CHARS.contains(c));
+        Some(SpanKind::Operator)
+    }
+
+    fn skip_while(&mut self, predicate:impl Fn(char) -> bool) {
+        while self.line.get(self.offset).map_or(false, |c| predicate(*c)) {
+            self.offset += 1;
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlight(line:&str) -> Vec<(String,SpanKind)> {
+        let chars = line.chars().collect_vec();
+        let spans = EnsoHighlighter.highlight_line(&chars);
+        spans.into_iter().map(|span| {
+            let text = chars[span.columns].iter().collect();
+            (text,span.kind)
+        }).collect()
+    }
+
+    #[test]
+    fn highlighting_enso_code() {
+        use SpanKind::*;
+        let line     = "main = Foo.bar' (-12.5) \"a \\\" b\" # comment";
+        let expected = vec!
+            [ ("main"         , Identifier)
+            , ("="            , Operator)
+            , ("Foo"          , Constructor)
+            , ("."            , Operator)
+            , ("bar'"         , Identifier)
+            , ("-"            , Operator)
+            , ("12.5"         , Number)
+            , ("\"a \\\" b\"" , Text)
+            , ("# comment"    , Comment)
+            ];
+        let expected = expected.into_iter().map(|(text,kind)| (text.to_string(),kind));
+        let expected = expected.collect_vec();
+        assert_eq!(highlight(line), expected);
+    }
+
+    #[test]
+    fn highlighting_invalid_characters() {
+        use SpanKind::*;
+        let expected = vec![("x".to_string(),Identifier), ("\t".to_string(),Invalid)];
+        assert_eq!(highlight("x\t"), expected);
+        let expected = vec![("'unterminated".to_string(),Text)];
+        assert_eq!(highlight("'unterminated"), expected);
+    }
+}
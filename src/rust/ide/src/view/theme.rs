@@ -0,0 +1,87 @@
+//! The IDE color themes.
+//!
+//! The built-in themes are embedded theme files (see `ensogl::display::style::theme` docs for the
+//! format). Each project may define additional themes, or override the built-in ones, in the
+//! theme file in its root directory. The theme selected by the user is remembered in the
+//! browser's local storage.
+
+use crate::prelude::*;
+
+use crate::config::THEME_STORAGE_KEY;
+
+use ensogl::application::Application;
+use ensogl::system::web;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The theme enabled when the user has not selected any.
+pub const DEFAULT_THEME:&str = "dark";
+
+/// The built-in theme files.
+const BUILTIN_THEME_FILES:&[&str] =
+    &[ include_str!("theme/dark.theme")
+     , include_str!("theme/light.theme")
+     , include_str!("theme/high_contrast.theme")
+     ];
+
+
+
+// =============
+// === Setup ===
+// =============
+
+/// Registers the built-in themes and the project themes, and enables the theme selected by the
+/// user. Invalid theme files are reported and skipped.
+pub async fn setup(logger:&Logger, app:&Application, project:&controller::Project) {
+    for file in BUILTIN_THEME_FILES {
+        if let Err(err) = app.themes.load(file) {
+            error!(logger,"Invalid built-in theme: {err}");
+        }
+    }
+    match project.read_themes().await {
+        Ok(Some(text)) => if let Err(err) = app.themes.load(&text) {
+            error!(logger,"Invalid project theme file: {err}");
+        }
+        Ok(None)       => {}
+        Err(err)       => warning!(logger,"Cannot read the project theme file: {err}"),
+    }
+    let stored   = web::try_local_storage().ok().and_then(|storage| {
+        storage.get_item(THEME_STORAGE_KEY).ok().flatten()
+    });
+    let names    = app.themes.names();
+    let selected = stored.filter(|name| names.contains(name));
+    app.themes.set_enabled(&[selected.as_deref().unwrap_or(DEFAULT_THEME)]);
+}
+
+
+
+// =================
+// === Selection ===
+// =================
+
+/// Enables the theme with the given name and remembers it as the user's choice.
+pub fn select(logger:&Logger, app:&Application, name:&str) {
+    info!(logger,"Switching to the {name} theme.");
+    app.themes.set_enabled(&[name]);
+    let stored = web::try_local_storage().ok().and_then(|storage| {
+        storage.set_item(THEME_STORAGE_KEY,name).ok()
+    });
+    if stored.is_none() {
+        warning!(logger,"Cannot store the selected theme in the local storage.");
+    }
+}
+
+/// Selects the theme following the currently enabled one, in alphabetical order.
+pub fn select_next(logger:&Logger, app:&Application) {
+    let names   = app.themes.names();
+    let current = app.themes.enabled().into_iter().next();
+    let current = current.and_then(|current| names.iter().position(|name| *name == current));
+    let next    = current.map_or(0, |index| (index + 1) % names.len());
+    if let Some(name) = names.get(next) {
+        select(logger,app,name);
+    }
+}
@@ -1,14 +1,23 @@
 //! Language Server integration tests.
 //!
-//! They are disabled by default, as there is no CI infrastructure to run them with Lanaguage
-//! Server. To run tests manually, uncomment the `#[wasm_bindgen_test::wasm_bindgen_test(async)]`
-//! attributes and use wasm-bindgen test.
+//! The Language Server API tests replay the traces stored in the `traces` directory. These are
+//! synthetic fixtures written by hand after the protocol specification, not sessions recorded with
+//! a real server, so they check the client's side of the protocol only. The same scenarios may be
+//! run against a real server with the `_live` variants of the tests, disabled by default. They
+//! print the recorded session, which may replace the fixture. Note that the recorded client and
+//! visualisation ids must be the ones defined in this file.
 //!
 //! Note that running Lanugage Server is expected at `SERVER_ENDPOINT` (by default localhost:30616).
 //! To run the language server manually run in the `enso` repository e.g.
 //! ```
 //! sbt "runner/run --server --root-id 6f7d58dd-8ee8-44cf-9ab7-9f0454033641 --path $HOME/ensotmp --rpc-port 30616"
 //! ```
+//!
+//! The tests opening a project through the Project Manager are disabled by default, as there is no
+//! CI infrastructure to run them with Lanaguage Server. To run them manually, uncomment the
+//! `#[wasm_bindgen_test::wasm_bindgen_test(async)]` attributes and use wasm-bindgen test.
+//!
+//! Note that running Project Manager is expected at `ide::PROJECT_MANAGER_ENDPOINT`.
 
 use ide::prelude::*;
 
@@ -18,6 +27,10 @@ use ide::controller::Project;
 use ide::model::execution_context::Visualization;
 use ide::transport::web::WebSocket;
 use ide::view::project::INITIAL_MODULE_NAME;
+use json_rpc::trace::Recorder;
+use json_rpc::trace::RecordingTransport;
+use json_rpc::trace::ReplayTransport;
+use json_rpc::trace::Trace;
 use std::time::Duration;
 #[allow(unused_imports)]
 use wasm_bindgen_test::wasm_bindgen_test;
@@ -26,6 +39,12 @@ use wasm_bindgen_test::wasm_bindgen_test_configure;
 /// The endpoint at which the Language Server should be accepting WS connections.
 const SERVER_ENDPOINT:&str = "ws://localhost:30616";
 
+/// The id of the client used in the `file_operations` trace.
+const CLIENT_ID:&str = "4b7e0c9a-2f13-4d58-8a6e-1c3b5d7f9e20";
+
+/// The id of the visualisation attached in the `file_operations` trace.
+const VISUALISATION_ID:&str = "c2d4e6f8-0a1b-4c3d-9e5f-6a7b8c9d0e1f";
+
 const PACKAGE_YAML:&str = r#"
 maintainer: ''
 license: ''
@@ -60,17 +79,44 @@ const VISUALISATION_CODE:&str = r#"
 
 wasm_bindgen_test_configure!(run_in_browser);
 
-//#[wasm_bindgen_test::wasm_bindgen_test(async)]
-#[allow(dead_code)]
+/// Creates the Language Server client replaying the recorded trace.
+fn replaying_client(trace:&str) -> Client {
+    let trace = Trace::from_json(trace).expect("Couldn't parse the recorded trace.");
+    Client::new(ReplayTransport::new(trace))
+}
+
+/// Creates the Language Server client connected to the server running at `SERVER_ENDPOINT`,
+/// recording its traffic. Must be called after setting up the global executor.
+async fn recording_live_client() -> (Client,Recorder) {
+    ensogl_system_web::set_stdout();
+    let ws            = WebSocket::new_opened(default(),SERVER_ENDPOINT).await;
+    let ws            = ws.expect("Couldn't connect to WebSocket server.");
+    let mut recording = RecordingTransport::new(ws);
+    let recorder      = recording.recorder();
+    executor::global::spawn(recording.runner());
+    (Client::new(recording),recorder)
+}
+
+#[wasm_bindgen_test::wasm_bindgen_test(async)]
 async fn file_operations() {
-    let ws        = WebSocket::new_opened(default(),SERVER_ENDPOINT).await;
-    let ws        = ws.expect("Couldn't connect to WebSocket server.");
-    let client    = Client::new(ws);
     let _executor = ide::setup_global_executor();
+    file_operations_scenario(replaying_client(include_str!("traces/file_operations.json"))).await
+}
 
+//#[wasm_bindgen_test::wasm_bindgen_test(async)]
+#[allow(dead_code)]
+async fn file_operations_live() {
+    let _executor         = ide::setup_global_executor();
+    let (client,recorder) = recording_live_client().await;
+    file_operations_scenario(client).await;
+    println!("Recorded trace: {}",recorder.trace().to_json());
+}
+
+/// Tests the file operations, execution contexts and visualisations.
+async fn file_operations_scenario(client:Client) {
     executor::global::spawn(client.runner());
 
-    let client_id = uuid::Uuid::new_v4();
+    let client_id = uuid::Uuid::parse_str(CLIENT_ID).expect("Couldn't parse client id.");
     let session   = client.init_protocol_connection(&client_id).await;
     let session   = session.expect("Couldn't initialize session.");
     let root_id   = session.content_roots[0];
@@ -108,7 +154,8 @@ async fn file_operations() {
     let response = client.pop_from_execution_context(&execution_context_id).await;
     response.expect("Couldn't pop execution context.");
 
-    let visualisation_id     = uuid::Uuid::new_v4();
+    let visualisation_id     = uuid::Uuid::parse_str(VISUALISATION_ID);
+    let visualisation_id     = visualisation_id.expect("Couldn't parse visualisation id.");
     let expression_id        = uuid::Uuid::parse_str("c553533e-a2b9-4305-9f12-b8fe7781f933");
     let expression_id        = expression_id.expect("Couldn't parse expression id.");
     let expression           = "x -> here.encode x".to_string();
@@ -202,15 +249,24 @@ async fn file_operations() {
     assert_eq!("Hello, world!".to_string(),read.contents);
 }
 
+#[wasm_bindgen_test::wasm_bindgen_test(async)]
+async fn file_events() {
+    let _executor = ide::setup_global_executor();
+    file_events_scenario(replaying_client(include_str!("traces/file_events.json"))).await
+}
+
 //#[wasm_bindgen_test::wasm_bindgen_test(async)]
 #[allow(dead_code)]
-async fn file_events() {
-    let ws         = WebSocket::new_opened(default(),SERVER_ENDPOINT).await;
-    let ws         = ws.expect("Couldn't connect to WebSocket server.");
-    let client     = Client::new(ws);
-    let mut stream = client.events();
+async fn file_events_live() {
+    let _executor         = ide::setup_global_executor();
+    let (client,recorder) = recording_live_client().await;
+    file_events_scenario(client).await;
+    println!("Recorded trace: {}",recorder.trace().to_json());
+}
 
-    let _executor = ide::setup_global_executor();
+/// Tests receiving the file events after acquiring the `receivesTreeUpdates` capability.
+async fn file_events_scenario(client:Client) {
+    let mut stream = client.events();
 
     executor::global::spawn(client.runner());
 
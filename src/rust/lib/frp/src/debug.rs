@@ -1,15 +1,44 @@
 //! This module defines FRP Graphviz bindings. It allows visualizing the FRP network as Graphviz
-//! diagram.
+//! diagram, including the links between nodes, bridge networks and, optionally, the number of
+//! emitted events and the last emitted values.
 
-#![allow(missing_docs)]
+use crate::prelude::*;
 
-//! WARNING
-//! THIS MODULE IS IN A VERY WORK-IN-PROGRESS STATE. IT WILL BE CHANGED SOON.
+use crate::network::LinkType;
 
+use std::collections::BTreeSet;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Maximum number of characters of the last emitted value shown in the diagram.
+pub const MAX_VALUE_LENGTH : usize = 40;
+
+
+
+// =======================
+// === Value Recording ===
+// =======================
+
+thread_local! {
+    static RECORD_VALUES : Cell<bool> = Cell::new(false);
+}
+
+/// Enable or disable recording the debug representation of the last value emitted by every node
+/// of the current thread, shown in the annotated diagrams. It requires formatting every emitted
+/// value, so it is disabled by default.
+pub fn record_values(enabled:bool) {
+    RECORD_VALUES.with(|record| record.set(enabled))
+}
+
+/// Checks whether the last emitted values are recorded. See `record_values` to learn more.
+pub fn is_recording_values() -> bool {
+    RECORD_VALUES.with(|record| record.get())
+}
 
-use crate::prelude::*;
-//use crate::DataType;
-//
 
 
 // ================
@@ -19,14 +48,18 @@ use crate::prelude::*;
 /// Visualization data for a nodes.
 #[derive(Debug,Clone)]
 pub struct VizNode {
-    variant    : String,
-    label      : String,
+    variant     : String,
+    label       : String,
+    output_type : Option<String>,
+    annotation  : Option<String>,
 }
 
 impl VizNode {
     /// Constructor
     pub fn new(variant:String, label:String) -> Self {
-        VizNode {variant,label}
+        let output_type = default();
+        let annotation  = default();
+        VizNode {variant,label,output_type,annotation}
     }
 }
 
@@ -36,26 +69,24 @@ impl VizNode {
 pub struct VizLink {
     source_display_id : usize,
     target_display_id : usize,
-//    message_type      : DataType,
-    data_type         : String,
+    link_type         : LinkType,
 }
 
 impl VizLink {
     /// Constructor.
-    pub fn new
-    (source_display_id:usize, target_display_id:usize, data_type:String)
-     -> Self {
-        Self {source_display_id,target_display_id,data_type}
+    pub fn new(source_display_id:usize, target_display_id:usize, link_type:LinkType) -> Self {
+        Self {source_display_id,target_display_id,link_type}
     }
 }
 
 
-/// Graphviz FRP system visualizer.
+/// Graphviz FRP system visualizer. The nested visualizers are drawn as clusters, which is used to
+/// visualize bridge networks.
 #[derive(Debug,Default)]
 pub struct Graphviz {
-    nodes  : HashMap<usize,VizNode>,
-    labels : HashMap<usize,String>,
-    links  : Vec<VizLink>,
+    nodes    : HashMap<usize,VizNode>,
+    links    : Vec<VizLink>,
+    clusters : Vec<(String,Graphviz)>,
 }
 
 impl Graphviz {
@@ -64,79 +95,87 @@ impl Graphviz {
     (&mut self, id:usize, tp:Tp, label:Label) {
         let tp    = tp.into();
         let label = label.into();
-        self.nodes.insert(id,VizNode::new(tp,label.clone()));
-        self.labels.insert(id,label);
-    }
-//
-//    /// Defines a new link between nodes.
-//    pub fn add_link<S:Str>
-//    (&mut self, source:usize, target:usize, message_type:DataType, data_type:S) {
-//        let link = VizLink::new(source,target,message_type,data_type.into());
-//        self.links.push(link);
-//    }
-//
-//    /// Checks if a node with the given id is already registered in the node map.
-//    pub fn contains(&mut self, id:usize) -> bool {
-//        self.nodes.contains_key(&id)
-//    }
-//
-//    /// Takes a set of nodes and outputs a map from `display_id` to a particular node. In case the
-//    /// `display_id` points to several nodes, the node types `Hold` and `Recursive` has weaker
-//    /// preference.
-//    fn create_node_map(&self) -> HashMap<usize,VizNode> {
-//        let mut node_map : HashMap<usize,VizNode> = default();
-//        for node in self.nodes.values() {
-//            let entry        = node_map.entry(node.display_id);
-//            let merged_entry = entry.and_modify(|node2|{
-//                let variant = &node2.variant;
-//                if variant == "Hold" || variant == "Recursive" {
-//                    *node2 = node.clone();
-//                }
-//            });
-//            merged_entry.or_insert_with(|| node.clone());
-//        }
-//        node_map
-//    }
-//
-//    /// Outputs a Graphviz Dot code.
+        self.nodes.insert(id,VizNode::new(tp,label));
+    }
+
+    /// Sets the name of the type of the values emitted by the node. It is shown in the node and
+    /// on its outgoing links, unless it is `()`.
+    pub fn set_output_type<S:Str>(&mut self, id:usize, output_type:S) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.output_type = Some(output_type.into())
+        }
+    }
+
+    /// Annotates the node with the number of emitted events and the last emitted value.
+    pub fn annotate(&mut self, id:usize, emission_count:usize, last_value:Option<String>) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            let events = if emission_count == 1 { "event" } else { "events" };
+            let mut annotation = iformat!("{emission_count} {events}");
+            if let Some(mut value) = last_value {
+                if value.chars().count() > MAX_VALUE_LENGTH {
+                    value = value.chars().take(MAX_VALUE_LENGTH).collect::<String>() + "…";
+                }
+                annotation.push_str(&iformat!(", last: {value}"));
+            }
+            node.annotation = Some(annotation);
+        }
+    }
+
+    /// Defines a new link between nodes. Links of different types between the same nodes are
+    /// merged into a single `Mixed` link.
+    pub fn add_link(&mut self, source:usize, target:usize, link_type:LinkType) {
+        let same_nodes = |link:&&mut VizLink| {
+            link.source_display_id == source && link.target_display_id == target
+        };
+        match self.links.iter_mut().find(same_nodes) {
+            Some(link) => if link.link_type != link_type { link.link_type = LinkType::Mixed },
+            None       => self.links.push(VizLink::new(source,target,link_type)),
+        }
+    }
+
+    /// Defines a nested cluster of nodes.
+    pub fn add_cluster<S:Str>(&mut self, label:S, cluster:Graphviz) {
+        self.clusters.push((label.into(),cluster))
+    }
+
+    /// Checks if a node with the given id is already registered in the node map.
+    pub fn contains(&self, id:usize) -> bool {
+        self.nodes.contains_key(&id)
+    }
+
+    /// All nodes, including the ones of the nested clusters.
+    fn all_nodes(&self) -> Vec<(usize,&VizNode)> {
+        let nodes    = self.nodes.iter().map(|(id,node)| (*id,node));
+        let clusters = self.clusters.iter().flat_map(|(_,cluster)| cluster.all_nodes());
+        nodes.chain(clusters).collect()
+    }
+
+    /// All links, including the ones of the nested clusters.
+    fn all_links(&self) -> Vec<&VizLink> {
+        let clusters = self.clusters.iter().flat_map(|(_,cluster)| cluster.all_links());
+        self.links.iter().chain(clusters).collect()
+    }
+
+    /// Outputs a Graphviz Dot code.
     pub fn to_code(&self) -> String {
-        let mut code = String::default();
-//        let node_map = self.create_node_map();
-//
-        for (idx,node) in self.nodes.iter() {
-            let color = match node.variant.as_str() {
-                "Toggle"  => "534666",
-                "Gate"    => "e69d45",
-                "Hold"    => "308695",
-                "Lambda"  => "d45769",
-                "Lambda2" => "d45769",
-                _         => "455054",
-            };
-            let fill    = iformat!("[fillcolor=\"#{color}\"]");
-            let spacing = "<br/><FONT POINT-SIZE=\"5\"> </FONT><br/>";
-            let variant = iformat!("<FONT POINT-SIZE=\"9\">{node.variant}</FONT>");
-            let label   = iformat!("[label=< {node.label} {spacing} {variant} >]");
-            let line    = iformat!("\n{idx} {fill} {label}");
+        let nodes        = self.all_nodes();
+        let output_types = nodes.iter().filter_map(|(id,node)| {
+            node.output_type.as_ref().map(|tp| (*id,tp.as_str()))
+        }).collect::<HashMap<_,_>>();
+        let mut code          = String::default();
+        let mut cluster_count = 0;
+        self.write_code(&mut code,&output_types,&mut cluster_count);
+
+        // Nodes outside of the visualized networks, receiving the events from them.
+        let known    = nodes.iter().map(|(id,_)| *id).collect::<HashSet<_>>();
+        let links    = self.all_links().into_iter();
+        let ends     = links.flat_map(|link| vec![link.source_display_id,link.target_display_id]);
+        let external = ends.filter(|id| !known.contains(id)).collect::<BTreeSet<_>>();
+        for id in external {
+            let line = iformat!("\n{id} [fillcolor=\"#aaaaaa\"] [label=\"external\"]");
             code.push_str(&line);
         }
-//
-//        for link in &self.links {
-//            let source    = &link.source_display_id;
-//            let target    = &link.target_display_id;
-//            let data_type = &link.data_type;
-//            let not_loop  = source != target;
-//            if not_loop {
-//                let style = match link.message_type {
-//                    DataType::Behavior => "[style=\"dashed\"]",
-//                    _ => ""
-//                };
-//                let label = if data_type == "()" { "" } else { &data_type };
-//                let label = iformat!("[label=\"  {label}\"]");
-//                let line  = iformat!("\n{source} -> {target} {style} {label}");
-//                code.push_str(&line);
-//            }
-//        }
-//
+
         let fonts      = "[fontname=\"Helvetica Neue\" fontsize=11]";
         let node_shape = "[shape=box penwidth=0 margin=0.12 style=\"rounded,filled\"]";
         let node_style = "[fontcolor=white fillcolor=\"#5397dc\"]";
@@ -145,7 +184,62 @@ impl Graphviz {
         let nodes_cfg  = iformat!("node {fonts} {node_shape} {node_style};");
         let edges_cfg  = iformat!("edge {fonts} {edge_style};");
         iformat!("digraph G {{ \n{graph_cfg} \n{nodes_cfg} \n{edges_cfg} \n{code} \n}}")
-//        todo!()
+    }
+
+    fn write_code
+    (&self, code:&mut String, output_types:&HashMap<usize,&str>, cluster_count:&mut usize) {
+        let mut nodes = self.nodes.iter().collect_vec();
+        nodes.sort_by_key(|(id,_)| **id);
+        for (idx,node) in nodes {
+            let color = match node.variant.as_str() {
+                "Toggle"  => "534666",
+                "Gate"    => "e69d45",
+                "Hold"    => "308695",
+                "Lambda"  => "d45769",
+                "Lambda2" => "d45769",
+                _         => "455054",
+            };
+            let fill       = iformat!("[fillcolor=\"#{color}\"]");
+            let spacing    = "<br/><FONT POINT-SIZE=\"5\"> </FONT><br/>";
+            let name       = escape_html(&node.label);
+            let tp         = node.output_type.as_deref().map(escape_html);
+            let tp         = tp.map(|tp| iformat!(" : {tp}")).unwrap_or_default();
+            let variant    = iformat!("<FONT POINT-SIZE=\"9\">{node.variant}{tp}</FONT>");
+            let annotation = node.annotation.as_deref().map(escape_html);
+            let annotation = annotation.map(|text| {
+                iformat!("{spacing}<FONT POINT-SIZE=\"8\">{text}</FONT>")
+            }).unwrap_or_default();
+            let label      = iformat!("[label=< {name} {spacing} {variant} {annotation} >]");
+            let line       = iformat!("\n{idx} {fill} {label}");
+            code.push_str(&line);
+        }
+
+        for link in &self.links {
+            let source   = &link.source_display_id;
+            let target   = &link.target_display_id;
+            let not_loop = source != target;
+            if not_loop {
+                let style = match link.link_type {
+                    LinkType::Event    => "",
+                    LinkType::Behavior => "[style=\"dashed\"]",
+                    LinkType::Mixed    => "[style=\"dashed\" penwidth=2]",
+                };
+                let data_type = output_types.get(source).copied().unwrap_or("()");
+                let label     = if data_type == "()" { "" } else { data_type };
+                let label     = iformat!("[label=\"  {label}\"]");
+                let line      = iformat!("\n{source} -> {target} {style} {label}");
+                code.push_str(&line);
+            }
+        }
+
+        for (label,cluster) in &self.clusters {
+            let index = *cluster_count;
+            let style = "style=\"rounded,dashed\" color=\"#999999\" fontcolor=\"#555555\"";
+            *cluster_count += 1;
+            code.push_str(&iformat!("\nsubgraph cluster_{index} {{ \nlabel=\"{label}\" {style};"));
+            cluster.write_code(code,output_types,cluster_count);
+            code.push_str("\n}");
+        }
     }
 }
 
@@ -155,6 +249,33 @@ impl From<Graphviz> for String {
     }
 }
 
+/// Escapes the text to be used in Graphviz HTML-like labels.
+fn escape_html(text:&str) -> String {
+    text.replace('&',"&amp;").replace('<',"&lt;").replace('>',"&gt;").replace('"',"&quot;")
+}
+
+/// Strips the module paths from the type name, like `core::option::Option<alloc::string::String>`
+/// to `Option<String>`.
+pub fn short_type_name(name:&str) -> String {
+    let mut result  = String::new();
+    let mut segment = String::new();
+    let mut chars   = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            segment.clear();
+        } else if c.is_alphanumeric() || c == '_' {
+            segment.push(c);
+        } else {
+            result.push_str(&segment);
+            result.push(c);
+            segment.clear();
+        }
+    }
+    result.push_str(&segment);
+    result
+}
+
 
 
 // =======================
@@ -182,6 +303,7 @@ pub trait GraphvizBuilder {
     }
 }
 
+/// Displays the visualization in a new tab in a web browser.
 pub fn display_graphviz(viz:Graphviz) {
     let code : String = viz.into();
     let url  = percent_encoding::utf8_percent_encode(&code,percent_encoding::NON_ALPHANUMERIC);
@@ -196,3 +318,21 @@ where T:ContentRef, Content<T>:GraphvizBuilder {
         self.content().graphviz_build(builder)
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortening_type_names() {
+        let name = "core::option::Option<(alloc::string::String, usize)>";
+        assert_eq!(short_type_name(name), "Option<(String, usize)>");
+        assert_eq!(short_type_name("()"), "()");
+    }
+}
@@ -167,6 +167,8 @@ pub mod network;
 pub mod node;
 pub mod nodes;
 pub mod stream;
+pub mod time;
+pub mod tracing;
 
 pub use network::*;
 pub use node::*;
@@ -203,6 +205,38 @@ mod network_mode_tests {
         source.emit(());
         assert_eq!(sampler.value(),0);
     }
+
+    #[test]
+    fn exporting_graph() {
+        use frp::HasId;
+        frp::debug::record_values(true);
+        frp::new_network! { network
+            def source  = source::<usize>();
+            def enabled = source::<bool>();
+            def gate    = source.gate(&enabled);
+            def count   = gate.count();
+        }
+        frp::new_bridge_network! { [network]
+            def bridged = count.map(|count| count.to_string());
+        }
+        enabled.emit(true);
+        source.emit(7);
+
+        fn id(node:&impl HasId) -> usize { node.id().into() }
+        let dot              = network.to_dot(true);
+        let source_to_gate   = format!("{} -> {}  [label=\"  usize\"]",id(&source),id(&gate));
+        let enabled_to_gate  = format!("{} -> {} [style=\"dashed\"]",id(&enabled),id(&gate));
+        let count_to_bridged = format!("{} -> {}  [label=\"  usize\"]",id(&count),id(&bridged));
+        assert!(dot.contains(&source_to_gate));
+        assert!(dot.contains(&enabled_to_gate));
+        assert!(dot.contains(&count_to_bridged));
+        assert!(dot.contains("subgraph cluster_0"));
+        assert!(dot.contains("Count : usize"));
+        assert!(dot.contains("1 event, last: 7"));
+        assert!(dot.contains("1 event, last: &quot;1&quot;"));
+        assert!(!network.to_dot(false).contains("1 event"));
+        frp::debug::record_values(false);
+    }
 }
 
 #[cfg(test)]
@@ -5,6 +5,7 @@ use crate::stream;
 use crate::stream::Stream;
 use crate::node::*;
 use crate::debug;
+use crate::tracing;
 
 
 
@@ -29,8 +30,9 @@ pub struct WeakNetwork {
 }
 
 /// Network item.
-pub trait Item : HasId + HasLabel + stream::HasOutputTypeLabel {}
-impl<T> Item for T where T : HasId + HasLabel + stream::HasOutputTypeLabel {}
+pub trait Item : HasId + HasLabel + stream::HasOutputTypeLabel + stream::HasDebugInfo {}
+impl<T> Item for T
+where T : HasId + HasLabel + stream::HasOutputTypeLabel + stream::HasDebugInfo {}
 
 /// Internal data of `Network`.
 #[derive(Derivative)]
@@ -38,7 +40,7 @@ impl<T> Item for T where T : HasId + HasLabel + stream::HasOutputTypeLabel {}
 pub struct NetworkData {
     #[derivative(Debug="ignore")]
     nodes   : RefCell<Vec<Box<dyn Item>>>,
-    links   : RefCell<HashMap<Id,Link>>,
+    links   : RefCell<Vec<(Id,Link)>>,
     bridges : RefCell<Vec<BridgeNetwork>>,
 }
 
@@ -87,9 +89,10 @@ impl Network {
         stream
     }
 
-    /// Register a new link between nodes. Visualization purposes only.
+    /// Register a new link between nodes, in addition to the ones known by the nodes themselves.
+    /// Visualization purposes only.
     pub fn register_link(&self, target:Id, link:Link) {
-        self.data.links.borrow_mut().insert(target,link);
+        self.data.links.borrow_mut().push((target,link));
     }
 
     /// Registers the provided bridge network as child of this network.
@@ -97,13 +100,54 @@ impl Network {
         self.data.bridges.borrow_mut().push(sub_network.clone_ref())
     }
 
-    /// Draw the network using GraphViz.
+    /// Draw the network using GraphViz, annotating the nodes with their emissions.
     pub fn draw(&self) {
+        debug::display_graphviz(self.graphviz(true));
+    }
+
+    /// The GraphViz visualization of the network, including all of its bridge networks. If
+    /// `annotate` is set, the nodes are annotated with the number of emitted events and the last
+    /// emitted value, if values are recorded (see `debug::record_values`).
+    pub fn graphviz(&self, annotate:bool) -> debug::Graphviz {
         let mut viz = debug::Graphviz::default();
-        self.data.nodes.borrow().iter().for_each(|node| {
-            viz.add_node(node.id().into(),node.output_type_label(),node.label());
-        });
-        debug::display_graphviz(viz);
+        self.graphviz_build(&mut viz,annotate);
+        viz
+    }
+
+    /// The network in the GraphViz Dot syntax. See `graphviz` to learn more.
+    pub fn to_dot(&self, annotate:bool) -> String {
+        self.graphviz(annotate).to_code()
+    }
+
+    /// The emissions of the nodes of this network recorded so far, from the oldest one. See the
+    /// `tracing` module to learn more.
+    pub fn traced_emissions(&self) -> Vec<tracing::Emission> {
+        let nodes = self.data.nodes.borrow().iter().map(|node| node.id()).collect::<HashSet<_>>();
+        tracing::emissions().into_iter().filter(|e| nodes.contains(&e.node)).collect()
+    }
+
+    fn graphviz_build(&self, viz:&mut debug::Graphviz, annotate:bool) {
+        let nodes = self.data.nodes.borrow();
+        for node in nodes.iter() {
+            let id = node.id().into();
+            viz.add_node(id,node.output_type_label(),node.label());
+            viz.set_output_type(id,node.output_type_name());
+            if annotate {
+                viz.annotate(id,node.emission_count(),node.last_value());
+            }
+        }
+        let node_links = nodes.iter().flat_map(|node| node.links());
+        let links      = node_links.chain(self.data.links.borrow().iter().copied()).collect_vec();
+        for (target,link) in links {
+            viz.add_link(link.source.into(),target.into(),link.tp);
+        }
+        for bridge in self.data.bridges.borrow().iter() {
+            if let Some(network) = &*bridge.data.borrow() {
+                let mut sub_viz = debug::Graphviz::default();
+                network.graphviz_build(&mut sub_viz,annotate);
+                viz.add_cluster("BridgeNetwork",sub_viz);
+            }
+        }
     }
 }
 
@@ -112,6 +156,11 @@ impl WeakNetwork {
     pub fn upgrade(&self) -> Option<Network> {
         self.data.upgrade().map(|data| Network {data})
     }
+
+    /// Checks whether both weak references point to the same network.
+    pub fn ptr_eq(&self, other:&WeakNetwork) -> bool {
+        self.data.ptr_eq(&other.data)
+    }
 }
 
 
@@ -197,6 +246,6 @@ impl Link {
 }
 
 /// Type of the link between FRP nodes.
-#[derive(Debug,Clone,Copy)]
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
 #[allow(missing_docs)]
 pub enum LinkType {Event,Behavior,Mixed}
@@ -17,8 +17,12 @@ use crate::stream::ValueProvider;
 use crate::stream::Stream;
 use crate::stream::OwnedStream;
 use crate::stream;
+use crate::time::Clock;
+use crate::time::TickConsumer;
+use crate::time::Time;
 use enso_generics as generics;
 use enso_generics::traits::*;
+use std::collections::VecDeque;
 
 
 
@@ -361,6 +365,37 @@ impl Network {
           F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>,&Output<T4>)->T {
         self.register(OwnedAllWith4::new(label,t1,t2,t3,t4,f))
     }
+
+
+    // === Time ===
+
+    /// Emits the last incoming event once no new events arrived for `duration` milliseconds of the
+    /// clock. Useful for reacting to the user input only after the user stopped typing.
+    pub fn debounce<T:EventOutput>
+    (&self, label:Label, src:&T, clock:&Clock, duration:Time) -> Stream<Output<T>> {
+        self.register(OwnedDebounce::new(label,src,clock,duration))
+    }
+
+    /// Passes at most one event per `interval` milliseconds of the clock. The first event passes
+    /// immediately, while the last event received during the interval is emitted at its end.
+    pub fn throttle<T:EventOutput>
+    (&self, label:Label, src:&T, clock:&Clock, interval:Time) -> Stream<Output<T>> {
+        self.register(OwnedThrottle::new(label,src,clock,interval))
+    }
+
+    /// Emits every incoming event on the first clock tick at least `duration` milliseconds after
+    /// the event arrived.
+    pub fn delay<T:EventOutput>
+    (&self, label:Label, src:&T, clock:&Clock, duration:Time) -> Stream<Output<T>> {
+        self.register(OwnedDelay::new(label,src,clock,duration))
+    }
+
+    /// Emits the last event received since the previous clock tick on every tick. If the clock is
+    /// ticked by the animation loop, it passes at most one event per frame.
+    pub fn per_frame<T:EventOutput>
+    (&self, label:Label, src:&T, clock:&Clock) -> Stream<Output<T>> {
+        self.register(OwnedPerFrame::new(label,src,clock))
+    }
 }
 
 
@@ -589,6 +624,29 @@ impl DynamicNetwork {
           F:'static+Fn(&Output<T1>,&Output<T2>,&Output<T3>,&Output<T4>)->T {
         OwnedAllWith4::new(label,t1,t2,t3,t4,f).into()
     }
+
+
+    // === Time ===
+
+    pub fn debounce<T:EventOutput>
+    (self, label:Label, src:&T, clock:&Clock, duration:Time) -> OwnedStream<Output<T>> {
+        OwnedDebounce::new(label,src,clock,duration).into()
+    }
+
+    pub fn throttle<T:EventOutput>
+    (self, label:Label, src:&T, clock:&Clock, interval:Time) -> OwnedStream<Output<T>> {
+        OwnedThrottle::new(label,src,clock,interval).into()
+    }
+
+    pub fn delay<T:EventOutput>
+    (self, label:Label, src:&T, clock:&Clock, duration:Time) -> OwnedStream<Output<T>> {
+        OwnedDelay::new(label,src,clock,duration).into()
+    }
+
+    pub fn per_frame<T:EventOutput>
+    (self, label:Label, src:&T, clock:&Clock) -> OwnedStream<Output<T>> {
+        OwnedPerFrame::new(label,src,clock).into()
+    }
 }
 
 
@@ -1990,3 +2048,201 @@ impl<T1,T2,T3,T4,F> Debug for AllWith4Data<T1,T2,T3,T4,F> {
         write!(f,"AllWith4Data")
     }
 }
+
+
+
+// ================
+// === Debounce ===
+// ================
+
+#[derive(Debug)]
+pub struct DebounceData  <T:EventOutput>
+    { _src:T, clock:Clock, duration:Time, pending:RefCell<Option<(Time,Output<T>)>> }
+pub type   OwnedDebounce <T> = stream::Node     <DebounceData<T>>;
+pub type   Debounce      <T> = stream::WeakNode <DebounceData<T>>;
+
+impl<T:EventOutput> HasOutput for DebounceData<T> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput> OwnedDebounce<T> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock:&Clock, duration:Time) -> Self {
+        let _src    = src1.clone_ref();
+        let clock   = clock.clone_ref();
+        let pending = default();
+        let def     = DebounceData {_src,clock,duration,pending};
+        let this    = Self::construct_and_connect(label,src1,def);
+        this.clock.register(&this.downgrade());
+        this
+    }
+}
+
+impl<T:EventOutput> stream::EventConsumer<Output<T>> for OwnedDebounce<T> {
+    fn on_event(&self, event:&Output<T>) {
+        *self.pending.borrow_mut() = Some((self.clock.now(),event.clone()));
+    }
+}
+
+impl<T:EventOutput> TickConsumer for OwnedDebounce<T> {
+    fn on_tick(&self, time:Time) {
+        let is_due = |(arrival,_):&(Time,Output<T>)| time - arrival >= self.duration;
+        let is_due = self.pending.borrow().as_ref().map_or(false,is_due);
+        if is_due {
+            let pending = mem::take(&mut *self.pending.borrow_mut());
+            pending.for_each(|(_,event)| self.emit_event(&event));
+        }
+    }
+}
+
+
+
+// ================
+// === Throttle ===
+// ================
+
+#[derive(Debug)]
+pub struct ThrottleData  <T:EventOutput>
+    { _src:T, clock:Clock, interval:Time, last:Cell<Option<Time>>
+    , pending:RefCell<Option<Output<T>>> }
+pub type   OwnedThrottle <T> = stream::Node     <ThrottleData<T>>;
+pub type   Throttle      <T> = stream::WeakNode <ThrottleData<T>>;
+
+impl<T:EventOutput> HasOutput for ThrottleData<T> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput> ThrottleData<T> {
+    fn is_open(&self, time:Time) -> bool {
+        self.last.get().map_or(true,|last| time - last >= self.interval)
+    }
+}
+
+impl<T:EventOutput> OwnedThrottle<T> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock:&Clock, interval:Time) -> Self {
+        let _src    = src1.clone_ref();
+        let clock   = clock.clone_ref();
+        let last    = default();
+        let pending = default();
+        let def     = ThrottleData {_src,clock,interval,last,pending};
+        let this    = Self::construct_and_connect(label,src1,def);
+        this.clock.register(&this.downgrade());
+        this
+    }
+}
+
+impl<T:EventOutput> stream::EventConsumer<Output<T>> for OwnedThrottle<T> {
+    fn on_event(&self, event:&Output<T>) {
+        let now = self.clock.now();
+        if self.is_open(now) {
+            self.last.set(Some(now));
+            self.emit_event(event);
+        } else {
+            *self.pending.borrow_mut() = Some(event.clone());
+        }
+    }
+}
+
+impl<T:EventOutput> TickConsumer for OwnedThrottle<T> {
+    fn on_tick(&self, time:Time) {
+        if self.pending.borrow().is_some() && self.is_open(time) {
+            self.last.set(Some(time));
+            let pending = mem::take(&mut *self.pending.borrow_mut());
+            pending.for_each(|event| self.emit_event(&event));
+        }
+    }
+}
+
+
+
+// =============
+// === Delay ===
+// =============
+
+#[derive(Debug)]
+pub struct DelayData  <T:EventOutput>
+    { _src:T, clock:Clock, duration:Time, queue:RefCell<VecDeque<(Time,Output<T>)>> }
+pub type   OwnedDelay <T> = stream::Node     <DelayData<T>>;
+pub type   Delay      <T> = stream::WeakNode <DelayData<T>>;
+
+impl<T:EventOutput> HasOutput for DelayData<T> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput> DelayData<T> {
+    /// Takes the oldest queued event, if it is due at the given time.
+    fn pop_due(&self, time:Time) -> Option<Output<T>> {
+        let mut queue = self.queue.borrow_mut();
+        let is_due    = queue.front().map_or(false,|(due,_)| *due <= time);
+        if is_due { queue.pop_front().map(|(_,event)| event) } else { None }
+    }
+}
+
+impl<T:EventOutput> OwnedDelay<T> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock:&Clock, duration:Time) -> Self {
+        let _src  = src1.clone_ref();
+        let clock = clock.clone_ref();
+        let queue = default();
+        let def   = DelayData {_src,clock,duration,queue};
+        let this  = Self::construct_and_connect(label,src1,def);
+        this.clock.register(&this.downgrade());
+        this
+    }
+}
+
+impl<T:EventOutput> stream::EventConsumer<Output<T>> for OwnedDelay<T> {
+    fn on_event(&self, event:&Output<T>) {
+        let due = self.clock.now() + self.duration;
+        self.queue.borrow_mut().push_back((due,event.clone()));
+    }
+}
+
+impl<T:EventOutput> TickConsumer for OwnedDelay<T> {
+    fn on_tick(&self, time:Time) {
+        while let Some(event) = self.pop_due(time) {
+            self.emit_event(&event);
+        }
+    }
+}
+
+
+
+// ================
+// === PerFrame ===
+// ================
+
+#[derive(Debug)]
+pub struct PerFrameData  <T:EventOutput> { _src:T, pending:RefCell<Option<Output<T>>> }
+pub type   OwnedPerFrame <T> = stream::Node     <PerFrameData<T>>;
+pub type   PerFrame      <T> = stream::WeakNode <PerFrameData<T>>;
+
+impl<T:EventOutput> HasOutput for PerFrameData<T> {
+    type Output = Output<T>;
+}
+
+impl<T:EventOutput> OwnedPerFrame<T> {
+    /// Constructor.
+    pub fn new(label:Label, src1:&T, clock:&Clock) -> Self {
+        let _src    = src1.clone_ref();
+        let pending = default();
+        let def     = PerFrameData {_src,pending};
+        let this    = Self::construct_and_connect(label,src1,def);
+        clock.register(&this.downgrade());
+        this
+    }
+}
+
+impl<T:EventOutput> stream::EventConsumer<Output<T>> for OwnedPerFrame<T> {
+    fn on_event(&self, event:&Output<T>) {
+        *self.pending.borrow_mut() = Some(event.clone());
+    }
+}
+
+impl<T:EventOutput> TickConsumer for OwnedPerFrame<T> {
+    fn on_tick(&self, _:Time) {
+        let pending = mem::take(&mut *self.pending.borrow_mut());
+        pending.for_each(|event| self.emit_event(&event));
+    }
+}
@@ -4,6 +4,8 @@ use crate::prelude::*;
 use crate::network::*;
 use crate::node::*;
 use crate::data::watch;
+use crate::debug;
+use crate::tracing;
 
 
 
@@ -21,6 +23,26 @@ pub trait HasOutputTypeLabel {
 
 
 
+// ====================
+// === HasDebugInfo ===
+// ====================
+
+/// Information about the FRP node used for visualization purposes only.
+pub trait HasDebugInfo {
+    /// Name of the type of the emitted values, without module paths.
+    fn output_type_name(&self) -> String;
+    /// Links to the targets of events emitted by this node and from the behaviors it samples. Each
+    /// link is paired with the id of its target.
+    fn links(&self) -> Vec<(Id,Link)>;
+    /// Number of events emitted by this node so far.
+    fn emission_count(&self) -> usize;
+    /// Debug representation of the last emitted value, if values are recorded. See
+    /// `debug::record_values` to learn more.
+    fn last_value(&self) -> Option<String>;
+}
+
+
+
 // ======================
 // === InputBehaviors ===
 // ======================
@@ -98,22 +120,27 @@ pub trait ValueProvider : HasOutput {
 /// A generalization of any stream input which consumes events of the provided type. This is the
 /// slowest bit of the whole FRP network as it uses an trait object, however, we can refactor it
 /// in the future to an enum-based trait if needed.
+///
+/// The input remembers the id of the consuming node, so the links between nodes can be visualized.
 #[derive(Clone)]
 pub struct EventInput<Input> {
-    data : Rc<dyn WeakEventConsumer<Input>>
+    data   : Rc<dyn WeakEventConsumer<Input>>,
+    target : Id,
 }
 
 impl<Def,Input> From<WeakNode<Def>> for EventInput<Input>
 where Def:HasOutputStatic, Node<Def>:EventConsumer<Input> {
     fn from(node:WeakNode<Def>) -> Self {
-        Self {data:Rc::new(node)}
+        let target = node.id();
+        let data   = Rc::new(node);
+        Self {data,target}
     }
 }
 
 impl<Def,Input> From<&WeakNode<Def>> for EventInput<Input>
 where Def:HasOutputStatic, Node<Def>:EventConsumer<Input> {
     fn from(node:&WeakNode<Def>) -> Self {
-        Self {data:Rc::new(node.clone_ref())}
+        node.clone_ref().into()
     }
 }
 
@@ -138,24 +165,30 @@ impl<Input> Debug for EventInput<Input> {
 /// event targets (the `targets` field), but are watching this node and can ask it for the last
 /// value any time. If the number of such nodes is zero, the value propagated trough this node does
 /// not need to be cached, and it will not be cloned. This minimizes the amount of clones in FRP
-/// networks drastically.
+/// networks drastically. The `emission_count` and `last_value` fields are used for visualization
+/// purposes only. The latter is filled only if recording values was enabled with
+/// `debug::record_values`.
 #[derive(Debug)]
 pub struct NodeData<Out=()> {
-    label         : Label,
-    targets       : RefCell<Vec<EventInput<Out>>>,
-    value_cache   : RefCell<Out>,
-    during_call   : Cell<bool>,
-    watch_counter : watch::Counter,
+    label          : Label,
+    targets        : RefCell<Vec<EventInput<Out>>>,
+    value_cache    : RefCell<Out>,
+    during_call    : Cell<bool>,
+    watch_counter  : watch::Counter,
+    emission_count : Cell<usize>,
+    last_value     : RefCell<Option<String>>,
 }
 
 impl<Out:Default> NodeData<Out> {
     /// Constructor.
     pub fn new(label:Label) -> Self {
-        let targets       = default();
-        let value_cache   = default();
-        let during_call   = default();
-        let watch_counter = default();
-        Self {label,targets,value_cache,during_call,watch_counter}
+        let targets        = default();
+        let value_cache    = default();
+        let during_call    = default();
+        let watch_counter  = default();
+        let emission_count = default();
+        let last_value     = default();
+        Self {label,targets,value_cache,during_call,watch_counter,emission_count,last_value}
     }
 
     fn use_caching(&self) -> bool {
@@ -171,10 +204,16 @@ impl<Out:Data> EventEmitter for NodeData<Out> {
     fn emit_event(&self, value:&Out) {
         if !self.during_call.get() {
             self.during_call.set(true);
+            let emission = tracing::begin_emission(self.id(),self.label,value);
             if self.use_caching() {
                 *self.value_cache.borrow_mut() = value.clone();
             }
+            self.emission_count.set(self.emission_count.get() + 1);
+            if debug::is_recording_values() {
+                *self.last_value.borrow_mut() = Some(format!("{:?}",value));
+            }
             self.targets.borrow_mut().retain(|target| target.data.on_event_if_exists(value));
+            tracing::end_emission(emission);
             self.during_call.set(false);
         }
     }
@@ -453,6 +492,13 @@ impl<Def:HasOutputStatic> ValueProvider for WeakNode<Def> {
 
 // === HasId ===
 
+impl<Out> HasId for NodeData<Out> {
+    fn id(&self) -> Id {
+        let raw:*const Self = self;
+        (raw as *const() as usize).into()
+    }
+}
+
 impl<Out> HasId for Stream<Out> {
     fn id(&self) -> Id {
         let raw = self.data.as_raw() as *const() as usize;
@@ -505,15 +551,40 @@ impl<Def> HasOutputTypeLabel for Node<Def>
 impl<Def:HasOutputStatic> InputBehaviors for Node<Def>
 where Def:InputBehaviors {
     fn input_behaviors(&self) -> Vec<Link> {
-        vec![] // FIXME
-//        self.data.input_behaviors()
+        self.definition.input_behaviors()
     }
 }
 
 impl<Def:HasOutputStatic> InputBehaviors for WeakNode<Def>
     where Def:InputBehaviors {
     fn input_behaviors(&self) -> Vec<Link> {
-        self.stream.input_behaviors()
+        self.definition.upgrade().map(|def| def.input_behaviors()).unwrap_or_default()
+    }
+}
+
+
+// === HasDebugInfo ===
+
+impl<Def:HasOutputStatic> HasDebugInfo for Node<Def>
+where Def:InputBehaviors {
+    fn output_type_name(&self) -> String {
+        debug::short_type_name(type_name::<Output<Def>>())
+    }
+
+    fn links(&self) -> Vec<(Id,Link)> {
+        let id        = self.id();
+        let targets   = self.stream.data.targets.borrow();
+        let mut links = targets.iter().map(|t| (t.target,Link::event(self))).collect_vec();
+        links.extend(self.input_behaviors().into_iter().map(|link| (id,link)));
+        links
+    }
+
+    fn emission_count(&self) -> usize {
+        self.stream.data.emission_count.get()
+    }
+
+    fn last_value(&self) -> Option<String> {
+        self.stream.data.last_value.borrow().clone()
     }
 }
 
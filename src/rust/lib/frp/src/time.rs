@@ -0,0 +1,171 @@
+//! The clock driving the time-based FRP nodes, like `debounce`, `throttle`, `delay`, or
+//! `per_frame`. The clock does not measure the time on its own. Instead, it is ticked from the
+//! outside, usually once per animation frame, which makes the time-based nodes fully deterministic
+//! in tests.
+
+use crate::prelude::*;
+
+use crate::node::*;
+use crate::stream;
+use crate::tracing;
+
+
+
+// ============
+// === Time ===
+// ============
+
+/// Time in milliseconds, as provided by the animation loops.
+pub type Time = f32;
+
+
+
+// ====================
+// === TickConsumer ===
+// ====================
+
+/// Implementors of this trait have to know how to react to the clock ticks.
+pub trait TickConsumer {
+    /// Callback for a new clock tick, with the current time.
+    fn on_tick(&self, time:Time);
+}
+
+
+
+// =============
+// === Clock ===
+// =============
+
+/// Shared clock ticking the registered nodes. The nodes are referenced weakly, so they are
+/// unregistered automatically after being dropped.
+#[derive(Clone,CloneRef,Default)]
+pub struct Clock {
+    data : Rc<ClockData>,
+}
+
+#[derive(Default)]
+struct ClockData {
+    time      : Cell<Time>,
+    consumers : RefCell<Vec<Box<dyn Fn(Time)->bool>>>,
+}
+
+impl Clock {
+    /// Constructor. The clock starts at time zero.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// The time of the last tick.
+    pub fn now(&self) -> Time {
+        self.data.time.get()
+    }
+
+    /// Set the current time and notify all registered nodes. The tick is traced as an emission of
+    /// the clock, so the events emitted by the time-based nodes can be tracked back to it.
+    pub fn tick(&self, time:Time) {
+        self.data.time.set(time);
+        let emission = tracing::begin_emission(self.id(),"clock",&time);
+        // The consumers are taken out, so the nodes may register new ones while being ticked.
+        let mut consumers = mem::take(&mut *self.data.consumers.borrow_mut());
+        consumers.retain(|consumer| consumer(time));
+        let mut registered = self.data.consumers.borrow_mut();
+        consumers.extend(registered.drain(..));
+        *registered = consumers;
+        tracing::end_emission(emission);
+    }
+
+    /// Tick the clock with the time advanced by `delta`.
+    pub fn advance(&self, delta:Time) {
+        self.tick(self.now() + delta)
+    }
+
+    /// Register the node to be ticked by this clock as long as it is alive.
+    pub fn register<Def>(&self, node:&stream::WeakNode<Def>)
+    where Def:HasOutputStatic, stream::Node<Def>:TickConsumer {
+        let node     = node.clone_ref();
+        let consumer = move |time| node.upgrade().map(|node| node.on_tick(time)).is_some();
+        self.data.consumers.borrow_mut().push(Box::new(consumer));
+    }
+}
+
+impl HasId for Clock {
+    fn id(&self) -> Id {
+        let raw:*const ClockData = &*self.data;
+        (raw as *const() as usize).into()
+    }
+}
+
+impl Debug for Clock {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"Clock({})",self.now())
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate as frp;
+
+    #[test]
+    fn time_based_nodes() {
+        let clock  = Clock::new();
+        let events = Rc::new(RefCell::new(Vec::<(&str,usize)>::new()));
+        let record = |name:&'static str| {
+            let events = events.clone();
+            move |value:&usize| events.borrow_mut().push((name,*value))
+        };
+        frp::new_network! { network
+            def src       = source::<usize>();
+            def debounced = src.debounce(&clock,100.0);
+            def throttled = src.throttle(&clock,100.0);
+            def delayed   = src.delay(&clock,50.0);
+            def per_frame = src.per_frame(&clock);
+            def _debounced = debounced.map(record("debounce"));
+            def _throttled = throttled.map(record("throttle"));
+            def _delayed   = delayed.map(record("delay"));
+            def _per_frame = per_frame.map(record("per_frame"));
+        }
+        src.emit(1);
+        clock.tick(10.0);
+        src.emit(2);
+        src.emit(3);
+        clock.tick(60.0);
+        clock.advance(50.0);
+        clock.advance(10.0);
+        let expected = vec!
+            [ ("throttle",1), ("per_frame",1)
+            , ("delay",1), ("delay",2), ("delay",3), ("per_frame",3)
+            , ("debounce",3), ("throttle",3)
+            ];
+        assert_eq!(*events.borrow(), expected);
+
+        drop(network);
+        clock.tick(1000.0);
+        assert!(clock.data.consumers.borrow().is_empty());
+    }
+
+    #[test]
+    fn tracing_ticks() {
+        let clock = Clock::new();
+        frp::new_network! { network
+            def src       = source::<usize>();
+            def debounced = src.debounce(&clock,100.0);
+        }
+        tracing::enable(100);
+        src.emit(1);
+        clock.tick(100.0);
+        let chain = tracing::why(&debounced);
+        let text  = chain.iter().map(|emission| emission.label).collect_vec();
+        assert_eq!(text, vec!["clock","debounced"]);
+        assert_eq!(chain[0].node, clock.id());
+        tracing::disable();
+        drop(network);
+    }
+}
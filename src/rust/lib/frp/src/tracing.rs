@@ -0,0 +1,297 @@
+//! Network-wide tracing of FRP events. Once enabled, every event emitted by any node is recorded
+//! in a bounded buffer, together with the emission which caused it. As events propagate trough the
+//! network synchronously, the cause of an emission is the emission being propagated at the time.
+//!
+//! The recorded emissions may be queried to reconstruct why a given node fired (see `why` and
+//! `explain`), what a given emission caused (see `effects`), or what were the last values of all
+//! nodes at any recorded point in time (see `snapshot`).
+//!
+//! The tracing is thread-local, like everything running in the browser.
+
+use crate::prelude::*;
+
+use crate::node::*;
+
+use std::collections::VecDeque;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The number of emissions kept by default.
+pub const DEFAULT_CAPACITY : usize = 10_000;
+
+
+
+// ================
+// === Emission ===
+// ================
+
+/// Sequential identifier of a recorded emission.
+#[derive(Clone,Copy,Debug,Display,Eq,From,Hash,Into,Ord,PartialEq,PartialOrd)]
+pub struct EmissionId {
+    raw : usize
+}
+
+/// A single event emitted by an FRP node.
+#[derive(Clone,Debug)]
+#[allow(missing_docs)]
+pub struct Emission {
+    pub id     : EmissionId,
+    /// The emission being propagated when this one occurred, if any.
+    pub parent : Option<EmissionId>,
+    pub node   : Id,
+    pub label  : Label,
+    /// The debug representation of the emitted value.
+    pub value  : String,
+}
+
+impl Display for Emission {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,"#{} {} = {}",self.id,self.label,self.value)
+    }
+}
+
+
+
+// ==============
+// === Tracer ===
+// ==============
+
+#[derive(Debug)]
+struct Tracer {
+    emissions   : VecDeque<Emission>,
+    capacity    : usize,
+    next_id     : usize,
+    propagating : Vec<EmissionId>,
+}
+
+impl Tracer {
+    fn new(capacity:usize) -> Self {
+        let emissions   = VecDeque::with_capacity(capacity);
+        let next_id     = default();
+        let propagating = default();
+        Self {emissions,capacity,next_id,propagating}
+    }
+
+    fn find(&self, id:EmissionId) -> Option<&Emission> {
+        let first = self.emissions.front()?.id;
+        let index = id.raw.checked_sub(first.raw)?;
+        self.emissions.get(index)
+    }
+}
+
+thread_local! {
+    static TRACER : RefCell<Option<Tracer>> = default();
+    /// Whether the `TRACER` is set. Checked on every emission, so it must be cheap to read.
+    static ENABLED : Cell<bool> = Cell::new(false);
+}
+
+fn with_tracer<T>(f:impl FnOnce(&mut Tracer) -> T) -> Option<T> {
+    TRACER.with(|tracer| tracer.borrow_mut().as_mut().map(f))
+}
+
+fn with_emissions<T>(f:impl FnOnce(&VecDeque<Emission>) -> T) -> T {
+    let emissions = default();
+    TRACER.with(|tracer| f(tracer.borrow().as_ref().map_or(&emissions,|t| &t.emissions)))
+}
+
+
+
+// ================
+// === Enabling ===
+// ================
+
+/// Start recording the last `capacity` emissions. The emissions recorded so far are dropped.
+pub fn enable(capacity:usize) {
+    TRACER.with(|tracer| *tracer.borrow_mut() = Some(Tracer::new(capacity)));
+    ENABLED.with(|enabled| enabled.set(true))
+}
+
+/// Stop recording the emissions, dropping the ones recorded so far.
+pub fn disable() {
+    TRACER.with(|tracer| *tracer.borrow_mut() = None);
+    ENABLED.with(|enabled| enabled.set(false))
+}
+
+/// Checks whether the emissions are recorded.
+pub fn is_enabled() -> bool {
+    ENABLED.with(|enabled| enabled.get())
+}
+
+/// Drop the emissions recorded so far.
+pub fn clear() {
+    with_tracer(|tracer| tracer.emissions.clear());
+}
+
+
+
+// =================
+// === Recording ===
+// =================
+
+/// Record the beginning of the propagation of an event. Must be followed by `end_emission` with the
+/// returned id once the event stops propagating. Returns `None` if the tracing is disabled.
+pub fn begin_emission<T:Debug>(node:Id, label:Label, value:&T) -> Option<EmissionId> {
+    if !is_enabled() {
+        return None
+    }
+    with_tracer(|tracer| {
+        let id     = EmissionId::from(tracer.next_id);
+        let parent = tracer.propagating.last().copied();
+        let value  = format!("{:?}",value);
+        tracer.next_id += 1;
+        tracer.propagating.push(id);
+        if tracer.capacity > 0 {
+            if tracer.emissions.len() == tracer.capacity {
+                tracer.emissions.pop_front();
+            }
+            tracer.emissions.push_back(Emission {id,parent,node,label,value});
+        }
+        id
+    })
+}
+
+/// Record the end of the propagation of an event begun with `begin_emission`. The emissions must
+/// end in the reverse order of beginning.
+///
+/// The emissions begun before the tracing was enabled anew are unknown to the new tracer, so their
+/// ends are ignored.
+pub fn end_emission(id:Option<EmissionId>) {
+    if let Some(id) = id {
+        with_tracer(|tracer| {
+            if let Some(ended) = tracer.propagating.pop() {
+                debug_assert_eq!(ended, id, "Emissions must end in the reverse order of beginning.");
+            }
+        });
+    }
+}
+
+
+
+// ===============
+// === Queries ===
+// ===============
+
+/// All recorded emissions, from the oldest one.
+pub fn emissions() -> Vec<Emission> {
+    with_emissions(|emissions| emissions.iter().cloned().collect())
+}
+
+/// The recorded emission with the given id, unless it was already evicted.
+pub fn emission(id:EmissionId) -> Option<Emission> {
+    TRACER.with(|tracer| tracer.borrow().as_ref().and_then(|t| t.find(id).cloned()))
+}
+
+/// The recorded emissions of the given node, from the oldest one.
+pub fn emissions_of(node:&impl HasId) -> Vec<Emission> {
+    let node = node.id();
+    with_emissions(|emissions| emissions.iter().filter(|e| e.node == node).cloned().collect())
+}
+
+/// The most recent emission of the given node.
+pub fn last_emission_of(node:&impl HasId) -> Option<Emission> {
+    let node = node.id();
+    with_emissions(|emissions| emissions.iter().rev().find(|e| e.node == node).cloned())
+}
+
+/// The chain of emissions which caused the given one, starting with the root cause and ending with
+/// the given emission. The chain is cut at the emissions which were already evicted.
+pub fn causes(id:EmissionId) -> Vec<Emission> {
+    let chain = TRACER.with(|tracer| {
+        let tracer    = tracer.borrow();
+        let mut chain = Vec::new();
+        if let Some(tracer) = tracer.as_ref() {
+            let mut current = tracer.find(id);
+            while let Some(emission) = current {
+                chain.push(emission.clone());
+                current = emission.parent.and_then(|parent| tracer.find(parent));
+            }
+        }
+        chain
+    });
+    chain.into_iter().rev().collect()
+}
+
+/// The emissions directly caused by the given one.
+pub fn effects(id:EmissionId) -> Vec<Emission> {
+    let parent = Some(id);
+    with_emissions(|emissions| emissions.iter().filter(|e| e.parent == parent).cloned().collect())
+}
+
+/// The chain of emissions which caused the most recent emission of the given node. See `causes` to
+/// learn more.
+pub fn why(node:&impl HasId) -> Vec<Emission> {
+    last_emission_of(node).map(|emission| causes(emission.id)).unwrap_or_default()
+}
+
+/// Human readable version of `why`, one emission per line.
+pub fn explain(node:&impl HasId) -> String {
+    let chain = why(node);
+    if chain.is_empty() {
+        return "No emission recorded.".into()
+    }
+    chain.iter().enumerate().map(|(depth,emission)| {
+        format!("{}{}\n"," ".repeat(4*depth),emission)
+    }).collect()
+}
+
+/// The most recent emission of every node, as of the given emission (inclusive). Allows inspecting
+/// the state of the network at any recorded point in time.
+pub fn snapshot(at:EmissionId) -> HashMap<Id,Emission> {
+    with_emissions(|emissions| {
+        let past = emissions.iter().take_while(|emission| emission.id <= at);
+        past.map(|emission| (emission.node,emission.clone())).collect()
+    })
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate as frp;
+
+    #[test]
+    fn reconstructing_causes() {
+        enable(100);
+        frp::new_network! { network
+            def source  = source::<usize>();
+            def other   = source::<usize>();
+            def doubled = source.map(|value| value * 2);
+            def sum     = doubled.map2(&other,|a,b| a + b);
+        }
+        other.emit(1);
+        source.emit(3);
+        source.emit(4);
+
+        let chain = why(&sum);
+        let text  = chain.iter().map(ToString::to_string).collect_vec();
+        assert_eq!(text, vec!["#4 source = 4","#5 doubled = 8","#6 sum = 9"]);
+        assert_eq!(effects(chain[0].id).len(), 1);
+        assert_eq!(emissions_of(&sum).len(), 2);
+        assert!(explain(&sum).ends_with("        #6 sum = 9\n"));
+        assert!(why(&other)[0].parent.is_none());
+
+        let before = snapshot(chain[0].id);
+        assert_eq!(before[&sum.id()].value, "7");
+        assert_eq!(before[&source.id()].value, "4");
+
+        enable(2);
+        source.emit(5);
+        assert_eq!(emissions().len(), 2);
+        assert_eq!(why(&sum).len(), 2);
+        assert_eq!(network.traced_emissions().len(), 2);
+        disable();
+        source.emit(6);
+        assert!(emissions().is_empty());
+        drop(network);
+    }
+}
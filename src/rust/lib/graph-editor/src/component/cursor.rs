@@ -7,6 +7,7 @@ use ensogl::data::color;
 use ensogl::display::Buffer;
 use ensogl::display::scene::Scene;
 use ensogl::display::shape::*;
+use ensogl::display::style::data::DataMatch;
 use ensogl::display::{Sprite, Attribute};
 use ensogl::display;
 use ensogl::gui::component::Animation;
@@ -24,6 +25,7 @@ pub const DEFAULT_RADIUS : f32 = 8.0;
     const PADDING        : f32 = 2.0;
     const SIDES_PADDING  : f32 = PADDING * 2.0;
     const DEFAULT_COLOR  : color::Lcha = color::Lcha::new(1.0,0.0,0.0,0.2);
+    const COLOR_STYLE    : &str = "graph_editor.cursor.color";
     const FADE_OUT_TIME  : f32 = 3000.0;
 
 #[allow(non_snake_case)]
@@ -278,6 +280,7 @@ pub struct CursorModel {
     pub frp    : FrpInputs,
     pub view   : component::ShapeView<shape::Shape>,
     pub style  : Rc<RefCell<Style>>,
+    pub theme  : StyleWatch,
 }
 
 impl CursorModel {
@@ -288,13 +291,20 @@ impl CursorModel {
         let frp    = FrpInputs::new(network);
         let view   = component::ShapeView::<shape::Shape>::new(&logger,&scene);
         let style  = default();
+        let theme  = StyleWatch::new(&scene.style_sheet);
 
         let shape_system = scene.shapes.shape_system(PhantomData::<shape::Shape>);
         shape_system.shape_system.set_pointer_events(false);
         scene.views.main.remove(&shape_system.shape_system.symbol);
         scene.views.cursor.add(&shape_system.shape_system.symbol);
 
-        Self {logger,scene,frp,view,style}
+        Self {logger,scene,frp,view,style,theme}
+    }
+
+    /// The cursor color used when the style does not define it, read from the current theme.
+    pub fn default_color(&self) -> color::Lcha {
+        self.theme.reset();
+        self.theme.get(COLOR_STYLE).color().unwrap_or(DEFAULT_COLOR)
     }
 }
 
@@ -353,8 +363,9 @@ impl Cursor {
         let host_attached_weight = Tween     :: new(&network);
 
         host_attached_weight.set_duration(300.0);
-        color_lab.set_target_value(DEFAULT_COLOR.opaque.into());
-        color_alpha.set_target_value(DEFAULT_COLOR.alpha);
+        let default_color = model.default_color();
+        color_lab.set_target_value(default_color.opaque.into());
+        color_alpha.set_target_value(default_color.alpha);
         radius.set_target_value(DEFAULT_RADIUS);
         size.set_target_value(DEFAULT_SIZE());
 
@@ -362,6 +373,8 @@ impl Cursor {
         let fade_in_spring  = inactive_fade.spring();
 
         frp::extend! { network
+            def theme_changed = source::<()>();
+
             eval press.value  ((v) model.view.shape.press.set(*v));
             eval radius.value ((v) model.view.shape.radius.set(*v));
             eval size.value   ([model] (v) {
@@ -375,6 +388,14 @@ impl Cursor {
                 |lab,alpha| color::Rgba::from(color::Laba::new(lab.x,lab.y,lab.z,*alpha))
             );
 
+            eval_ theme_changed ([model,color_lab,color_alpha] {
+                if model.style.borrow().color.is_none() {
+                    let default_color = model.default_color();
+                    color_lab.set_target_value(default_color.opaque.into());
+                    color_alpha.set_target_value(default_color.alpha);
+                }
+            });
+
             eval input.set_style([host_attached_weight,size,offset,model] (new_style) {
                 host_attached_weight.stop_and_rewind();
                 if new_style.host.is_some() { host_attached_weight.start() }
@@ -393,11 +414,12 @@ impl Cursor {
 
                 match &new_style.color {
                     None => {
-                        color_lab.set_target_value(DEFAULT_COLOR.opaque.into());
-                        color_alpha.set_target_value(DEFAULT_COLOR.alpha);
+                        let default_color = model.default_color();
+                        color_lab.set_target_value(default_color.opaque.into());
+                        color_alpha.set_target_value(default_color.alpha);
                     }
                     Some(t) => {
-                        let value = t.value.unwrap_or(DEFAULT_COLOR);
+                        let value = t.value.unwrap_or_else(|| model.default_color());
                         let lab = color::Laba::from(value);
                         color_lab.set_target_value(Vector3::new(lab.lightness,lab.a,lab.b));
                         color_alpha.set_target_value(lab.alpha);
@@ -476,17 +498,16 @@ impl Cursor {
 
             // === Fade-out when not moved ===
 
-            move_time            <- scene.frp.frame_time.sample(&mouse.position);
-            time_since_last_move <- scene.frp.frame_time.map2(&move_time,|t,s|t-s);
-            check_fade_time      <- time_since_last_move.gate(&is_not_hosted).gate(&mouse.ever_moved);
-            eval check_fade_time ([inactive_fade](time) {
-                if *time > FADE_OUT_TIME {
-                    inactive_fade.set_spring(fade_out_spring);
-                    inactive_fade.set_target_value(0.0)
-                } else {
-                    inactive_fade.set_spring(fade_in_spring);
-                    inactive_fade.set_target_value(1.0)
-                }
+            moved      <- mouse.position.gate(&is_not_hosted).gate(&mouse.ever_moved);
+            idle_moved <- moved.debounce(&scene.frp.clock,FADE_OUT_TIME);
+            idle       <- idle_moved.gate(&is_not_hosted);
+            eval_ moved ([inactive_fade] {
+                inactive_fade.set_spring(fade_in_spring);
+                inactive_fade.set_target_value(1.0)
+            });
+            eval_ idle ([inactive_fade] {
+                inactive_fade.set_spring(fade_out_spring);
+                inactive_fade.set_target_value(0.0)
             });
 
 
@@ -497,6 +518,8 @@ impl Cursor {
             eval position     ((t) model.view.set_position(*t));
         }
 
+        model.theme.set_on_style_change(f_!(theme_changed.emit(())));
+
         // Hide on init.
         inactive_fade.set_target_value(0.0);
         inactive_fade.skip();
@@ -11,6 +11,7 @@ use ensogl::display::Buffer;
 use ensogl::display::Sprite;
 use ensogl::display::scene::Scene;
 use ensogl::display::shape::*;
+use ensogl::display::style::data::DataMatch;
 use ensogl::display::traits::*;
 use ensogl::display;
 use ensogl::gui::component;
@@ -46,18 +47,30 @@ const RIGHT_ANGLE        : f32 = std::f32::consts::PI / 2.0;
 
 const INFINITE : f32 = 99999.0;
 
+/// Path of the edge color in the style sheet.
+pub const COLOR_STYLE : &str = "graph_editor.edge.color";
+
+/// The edge color used when the current theme does not define it.
+pub const DEFAULT_COLOR : color::Lcha = color::Lcha::new(0.6,0.5,0.76,1.0);
+
+/// The edge color defined by the current theme.
+pub fn current_color(scene:&Scene) -> color::Lcha {
+    scene.style_sheet.value(COLOR_STYLE).color().unwrap_or(DEFAULT_COLOR)
+}
+
 
 
 // =========================
 // === Shape Definitions ===
 // =========================
 
-macro_rules! define_corner_start {($($color:tt)*) => {
+macro_rules! define_corner_start {() => {
     /// Shape definition.
     pub mod corner {
         use super::*;
         ensogl::define_shape_system! {
-            (radius:f32, angle:f32, start_angle:f32, pos:Vector2<f32>, dim:Vector2<f32>) {
+            ( style:Style, radius:f32, angle:f32, start_angle:f32, pos:Vector2<f32>
+            , dim:Vector2<f32>) {
                 let radius = 1.px() * radius;
                 let width  = LINE_WIDTH.px();
                 let width2 = width / 2.0;
@@ -76,7 +89,8 @@ macro_rules! define_corner_start {($($color:tt)*) => {
                 let n_shape  = n_shape.translate((tx,ty));
 
                 let shape = shape - n_shape;
-                let shape = shape.fill(color::Rgba::from($($color)*));
+                let edge_color = style.get(COLOR_STYLE).color().unwrap_or(DEFAULT_COLOR);
+                let shape = shape.fill(color::Rgba::from(edge_color));
 
                 shape.into()
             }
@@ -84,12 +98,13 @@ macro_rules! define_corner_start {($($color:tt)*) => {
     }
 }}
 
-macro_rules! define_corner_end {($($color:tt)*) => {
+macro_rules! define_corner_end {() => {
     /// Shape definition.
     pub mod corner {
         use super::*;
         ensogl::define_shape_system! {
-            (radius:f32, angle:f32, start_angle:f32, pos:Vector2<f32>, dim:Vector2<f32>) {
+            ( style:Style, radius:f32, angle:f32, start_angle:f32, pos:Vector2<f32>
+            , dim:Vector2<f32>) {
                 let radius = 1.px() * radius;
                 let width  = LINE_WIDTH.px();
                 let width2 = width / 2.0;
@@ -108,7 +123,8 @@ macro_rules! define_corner_end {($($color:tt)*) => {
                 let n_shape  = n_shape.translate((tx,ty));
 
                 let shape = shape * n_shape;
-                let shape = shape.fill(color::Rgba::from($($color)*));
+                let edge_color = style.get(COLOR_STYLE).color().unwrap_or(DEFAULT_COLOR);
+                let shape = shape.fill(color::Rgba::from(edge_color));
 
                 shape.into()
             }
@@ -116,28 +132,29 @@ macro_rules! define_corner_end {($($color:tt)*) => {
     }
 }}
 
-macro_rules! define_line {($($color:tt)*) => {
+macro_rules! define_line {() => {
     /// Shape definition.
     pub mod line {
         use super::*;
         ensogl::define_shape_system! {
-            () {
+            (style:Style) {
                 let width  = LINE_WIDTH.px();
                 let height : Var<Distance<Pixels>> = "input_size.y".into();
                 let shape  = Rect((width,height));
-                let shape  = shape.fill(color::Rgba::from($($color)*));
+                let edge_color = style.get(COLOR_STYLE).color().unwrap_or(DEFAULT_COLOR);
+                let shape  = shape.fill(color::Rgba::from(edge_color));
                 shape.into()
             }
         }
     }
 }}
 
-macro_rules! define_arrow {($($color:tt)*) => {
+macro_rules! define_arrow {() => {
     /// Shape definition.
     pub mod arrow {
         use super::*;
         ensogl::define_shape_system! {
-            () {
+            (style:Style) {
                 let width  : Var<Distance<Pixels>> = "input_size.x".into();
                 let height : Var<Distance<Pixels>> = "input_size.y".into();
                 let width      = width  - (2.0 * PADDING).px();
@@ -147,7 +164,8 @@ macro_rules! define_arrow {($($color:tt)*) => {
                 let triangle_l = triangle.translate_x(-&offset);
                 let triangle_r = triangle.translate_x(&offset);
                 let shape      = triangle_l + triangle_r;
-                let shape      = shape.fill(color::Rgba::from($($color)*));
+                let edge_color = style.get(COLOR_STYLE).color().unwrap_or(DEFAULT_COLOR);
+                let shape      = shape.fill(color::Rgba::from(edge_color));
                 shape.into()
             }
         }
@@ -230,17 +248,17 @@ impl LayoutLine for component::ShapeView<back::line::Shape> {
 /// Shape definitions which will be rendered in the front layer (on top of nodes).
 pub mod front {
     use super::*;
-    define_corner_start!(color::Lcha::new(0.6,0.5,0.76,1.0));
-    define_line!(color::Lcha::new(0.6,0.5,0.76,1.0));
-    define_arrow!(color::Lcha::new(0.6,0.5,0.76,1.0));
+    define_corner_start!();
+    define_line!();
+    define_arrow!();
 }
 
 /// Shape definitions which will be rendered in the bottom layer (below nodes).
 pub mod back {
     use super::*;
-    define_corner_end!(color::Lcha::new(0.6,0.5,0.76,1.0));
-    define_line!(color::Lcha::new(0.6,0.5,0.76,1.0));
-    define_arrow!(color::Lcha::new(0.6,0.5,0.76,1.0));
+    define_corner_end!();
+    define_line!();
+    define_arrow!();
 }
 
 
@@ -35,6 +35,7 @@ use crate::component::node::port::output::OutputPorts;
 
 pub const NODE_SHAPE_PADDING : f32 = 40.0;
 pub const NODE_SHAPE_RADIUS  : f32 = 14.0;
+pub const COLOR_TAG_SIZE     : f32 = 3.0;
 
 
 
@@ -47,7 +48,7 @@ pub mod shape {
     use super::*;
 
     ensogl::define_shape_system! {
-        (style:Style, selection:f32) {
+        (style:Style, selection:f32, tag_color:Vector4<f32>) {
             let bg_color = style.get("graph_editor.node.background.color").color().unwrap_or_else(|| color::Rgba::new(1.0,0.0,0.0,1.0).into());
             let selection_color = style.get("graph_editor.node.selection.color").color().unwrap_or_else(|| color::Rgba::new(1.0,0.0,0.0,1.0).into());
             let _selection_size = style.get("graph_editor.node.selection.size").number().unwrap_or(8.0);
@@ -63,6 +64,16 @@ pub mod shape {
             let shape  = shape.fill(color::Rgba::from(bg_color));
 
 
+            // === Colour Tag ===
+
+            let tag_size   = COLOR_TAG_SIZE.px();
+            let tag_width  = &width  + &tag_size * 2.0;
+            let tag_height = &height + &tag_size * 2.0;
+            let tag_radius = (NODE_SHAPE_RADIUS + COLOR_TAG_SIZE).px();
+            let tag        = Rect((tag_width,tag_height)).corners_radius(tag_radius);
+            let tag        = tag.fill("srgba(input_tag_color)");
+
+
             // === Shadow ===
 
             let shadow_size   = SHADOW_SIZE.px();
@@ -70,9 +81,14 @@ pub mod shape {
             let shadow_height = &height + &shadow_size * 2.0;
             let shadow_radius = &shadow_height / 2.0;
             let shadow        = Rect((shadow_width,shadow_height)).corners_radius(shadow_radius);
+            let shadow_color  = style.get("graph_editor.node.shadow.color").color();
+            let shadow_color  = shadow_color.map(color::Rgba::from);
+            let shadow_color  = shadow_color.unwrap_or_else(|| color::Rgba::new(0.0,0.0,0.0,0.20));
+            let (red,green,blue) = (shadow_color.red,shadow_color.green,shadow_color.blue);
+            let shadow_start  = color::Rgba::new(red,green,blue,0.0);
             let shadow_color  = color::LinearGradient::new()
-                .add(0.0,color::Rgba::new(0.0,0.0,0.0,0.0).into_linear())
-                .add(1.0,color::Rgba::new(0.0,0.0,0.0,0.20).into_linear());
+                .add(0.0,shadow_start.into_linear())
+                .add(1.0,shadow_color.into_linear());
             let shadow_color  = color::SdfSampler::new(shadow_color).max_distance(border_size_f).slope(color::Slope::Exponent(2.0));
             let shadow        = shadow.fill(shadow_color);
 
@@ -94,7 +110,7 @@ pub mod shape {
             let select         = select2 - select;
             let select         = select.fill(color::Rgba::from(selection_color));
 
-            let out = select + shadow + shape;
+            let out = select + shadow + tag + shape;
             out.into()
         }
     }
@@ -137,6 +153,12 @@ pub struct InputEvents {
     pub deselect          : frp::Source,
     pub set_expression    : frp::Source<Expression>,
     pub set_visualization : frp::Source<Option<visualization::Instance>>,
+    /// Sets the comment displayed above the node. Empty comment is not displayed.
+    pub set_comment       : frp::Source<String>,
+    /// Sets the colour of the tag around the node. `None` hides the tag.
+    pub set_color_tag     : frp::Source<Option<color::Rgba>>,
+    /// Collapses the node, hiding its visualization, or expands it back.
+    pub set_collapsed     : frp::Source<bool>,
 }
 
 impl InputEvents {
@@ -146,8 +168,12 @@ impl InputEvents {
             def deselect          = source();
             def set_expression    = source();
             def set_visualization = source();
+            def set_comment       = source();
+            def set_color_tag     = source();
+            def set_collapsed     = source();
         }
-        Self {select,deselect,set_expression,set_visualization}
+        Self {select,deselect,set_expression,set_visualization,set_comment,set_color_tag
+             ,set_collapsed}
     }
 }
 
@@ -207,12 +233,15 @@ pub struct NodeModel {
     pub ports          : port::Manager,
     pub visualization  : visualization::Container,
     pub output_ports   : OutputPorts,
+    pub comment        : component::ShapeView<port::label::Shape>,
+    collapsed          : Rc<Cell<bool>>,
 }
 
 pub const CORNER_RADIUS : f32 = 14.0;
 pub const NODE_HEIGHT   : f32 = 28.0;
 pub const TEXT_OFF      : f32 = 10.0;
 pub const SHADOW_SIZE   : f32 = 10.0;
+pub const COMMENT_OFF   : f32 = 16.0;
 
 
 impl NodeModel {
@@ -257,6 +286,15 @@ impl NodeModel {
         });
         display_object.add_child(&ports);
 
+        let comment_logger = Logger::sub(&logger,"comment");
+        let comment        = component::ShapeView::<port::label::Shape>::new(&comment_logger,&scene);
+        comment.mod_position(|t| {
+            t.x = TEXT_OFF;
+            t.y = NODE_HEIGHT + COMMENT_OFF;
+        });
+        display_object.add_child(&comment);
+        let collapsed = default();
+
         let frp = Frp{input};
 
 
@@ -266,7 +304,7 @@ impl NodeModel {
 
 
         Self {scene,display_object,logger,frp,main_area,drag_area,output_ports,ports
-             ,visualization} . init()
+             ,visualization,comment,collapsed} . init()
     }
 
     fn init(self) -> Self {
@@ -305,6 +343,30 @@ impl NodeModel {
     pub fn visualization(&self) -> &visualization::Container {
         &self.visualization
     }
+
+    fn set_comment(&self, comment:&str) {
+        self.comment.shape.label.set_text(comment);
+    }
+
+    fn set_color_tag(&self, color:Option<color::Rgba>) {
+        let color = color.unwrap_or_else(|| color::Rgba::new(0.0,0.0,0.0,0.0));
+        self.main_area.shape.tag_color.set(color.into());
+    }
+
+    fn set_collapsed(&self, collapsed:bool) {
+        if collapsed != self.collapsed.replace(collapsed) {
+            if collapsed {
+                self.display_object.remove_child(&self.visualization);
+            } else {
+                self.display_object.add_child(&self.visualization);
+            }
+        }
+    }
+
+    /// Checks whether the node is collapsed, with its visualization hidden.
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed.get()
+    }
 }
 
 impl Node {
@@ -325,6 +387,10 @@ impl Node {
             eval inputs.set_visualization ((content)
                 model.visualization.frp.set_visualization.emit(content)
             );
+
+            eval inputs.set_comment   ((comment) model.set_comment(comment));
+            eval inputs.set_color_tag ((color)   model.set_color_tag(*color));
+            eval inputs.set_collapsed ((value)   model.set_collapsed(*value));
         }
 
         Self {frp_network,model}
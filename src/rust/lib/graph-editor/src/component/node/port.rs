@@ -15,6 +15,9 @@ use ensogl::display::Buffer;
 use ensogl::display::scene::Scene;
 use ensogl::display::shape::*;
 use ensogl::display::shape::text::glyph::system::GlyphSystem;
+use ensogl::control::callback;
+use ensogl::display::style::data::DataMatch;
+use ensogl::display::style;
 use ensogl::display::Sprite;
 use ensogl::display::traits::*;
 use ensogl::display;
@@ -22,6 +25,7 @@ use ensogl::gui::component;
 
 
 use crate::component::cursor;
+use crate::component::edge;
 use super::super::node;
 use span_tree::SpanTree;
 
@@ -55,9 +59,10 @@ pub mod label {
     #[derive(Clone,CloneRef,Debug)]
     #[allow(missing_docs)]
     pub struct Shape {
-        pub label : ensogl::display::shape::text::glyph::system::Line,
-        pub obj   : display::object::Instance,
-
+        pub label     : ensogl::display::shape::text::glyph::system::Line,
+        pub obj       : display::object::Instance,
+        _color_var    : style::Var,
+        _color_handle : callback::Handle,
     }
     impl ensogl::display::shape::system::Shape for Shape {
         type System = ShapeSystem;
@@ -73,34 +78,44 @@ pub mod label {
     #[derive(Clone, CloneRef, Debug)]
     #[allow(missing_docs)]
     pub struct ShapeSystem {
-        pub glyph_system: GlyphSystem,
-        style_manager: StyleWatch,
-
+        pub glyph_system : GlyphSystem,
+        style_sheet      : style::Sheet,
     }
     impl ShapeSystemInstance for ShapeSystem {
         type Shape = Shape;
 
         fn new(scene:&Scene) -> Self {
-            let style_manager = StyleWatch::new(&scene.style_sheet);
+            let style_sheet   = scene.style_sheet.clone_ref();
             let font          = scene.fonts.get_or_load_embedded_font("DejaVuSansMono").unwrap();
             let glyph_system  = GlyphSystem::new(scene,font);
             let symbol        = &glyph_system.sprite_system().symbol;
             scene.views.main.remove(symbol);
             scene.views.label.add(symbol);
-            Self {glyph_system,style_manager} // .init_refresh_on_style_change()
+            Self {glyph_system,style_sheet}
         }
 
         fn new_instance(&self) -> Self::Shape {
-            let color = color::Rgba::new(1.0, 1.0, 1.0, 0.7);
-            let obj   = display::object::Instance::new(Logger::new("test"));
-            let label = self.glyph_system.new_line();
+            let color_var = self.style_sheet.var(COLOR_STYLE);
+            let obj       = display::object::Instance::new(Logger::new("test"));
+            let label     = self.glyph_system.new_line();
             label.set_font_size(12.0);
-            label.set_font_color(color);
+            label.set_font_color(text_color(&color_var.value()));
             label.set_text("");
             obj.add_child(&label);
-            Shape {label,obj}
+            // The color follows the style, as it may be changed by switching the theme.
+            let color_handle = color_var.on_change(f!([label] (color:&Option<style::Data>)
+                label.set_font_color(text_color(color))
+            ));
+            Shape {label,obj,_color_var:color_var,_color_handle:color_handle}
         }
     }
+
+    const COLOR_STYLE : &str = "graph_editor.node.text.color";
+
+    /// The label color for the given style value, with a fallback when the style is not set.
+    fn text_color(color:&Option<style::Data>) -> color::Rgba {
+        color.color().map_or(color::Rgba::new(1.0,1.0,1.0,0.7),color::Rgba::from)
+    }
 }
 
 
@@ -241,12 +256,20 @@ impl Manager {
 //                        let network = &port.events.network;
                         let hover   = &port.shape.hover;
                         let crumbs  = node.crumbs.clone();
+                        let scene   = &self.scene;
+                        let host    = port.display_object();
                         frp::new_network! { port_network
                             def _foo = port.events.mouse_over . map(f_!(hover.set(1.0);));
                             def _foo = port.events.mouse_out  . map(f_!(hover.set(0.0);));
 
                             def out  = port.events.mouse_out.constant(cursor::Style::default());
-                            def over = port.events.mouse_over.constant(cursor::Style::new_highlight(&port,Vector2::new(width2,height),Some(color::Lcha::new(0.6,0.5,0.76,1.0))));
+                            // The color is read on each hover, as it may be changed by switching
+                            // the theme.
+                            def over = port.events.mouse_over.map(f_!([scene,host] {
+                                let color = edge::current_color(&scene);
+                                let size  = Vector2::new(width2,height);
+                                cursor::Style::new_highlight(&host,size,Some(color))
+                            }));
                             // FIXME: the following lines leak memory in the current FRP
                             // implementation because self.frp does not belong to this network and
                             // we are attaching node there. Nothing bad should happen though.
@@ -47,13 +47,13 @@ const CORNER_RADIUS : f32       = super::super::node::CORNER_RADIUS;
 pub mod background {
     use super::*;
 
-    // TODO use style
     ensogl::define_shape_system! {
-        (selected:f32,radius:f32,roundness:f32) {
+        (style:Style,selected:f32,radius:f32,roundness:f32) {
             let width  : Var<Distance<Pixels>> = "input_size.x".into();
             let height : Var<Distance<Pixels>> = "input_size.y".into();
             let radius        = 1.px() * &radius;
-            let color_bg      = color::Lcha::new(0.2,0.013,0.18,1.0);
+            let color_bg      = style.get("graph_editor.visualization.background.color").color();
+            let color_bg      = color_bg.unwrap_or_else(|| color::Lcha::new(0.2,0.013,0.18,1.0));
             let corner_radius = &radius * &roundness;
             let background    = Rect((&width,&height)).corners_radius(&corner_radius);
             let background    = background.fill(color::Rgba::from(color_bg));
@@ -70,13 +70,13 @@ pub mod background {
 pub mod fullscreen_background {
     use super::*;
 
-    // TODO use style
     ensogl::define_shape_system! {
-        (selected:f32,radius:f32,roundness:f32) {
+        (style:Style,selected:f32,radius:f32,roundness:f32) {
             let width  : Var<Distance<Pixels>> = "input_size.x".into();
             let height : Var<Distance<Pixels>> = "input_size.y".into();
             let radius        = 1.px() * &radius;
-            let color_bg      = color::Lcha::new(0.2,0.013,0.18,1.0);
+            let color_bg      = style.get("graph_editor.visualization.background.color").color();
+            let color_bg      = color_bg.unwrap_or_else(|| color::Lcha::new(0.2,0.013,0.18,1.0));
             let corner_radius = &radius * &roundness;
             let background    = Rect((&width,&height)).corners_radius(&corner_radius);
             let background    = background.fill(color::Rgba::from(color_bg));
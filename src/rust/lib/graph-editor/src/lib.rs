@@ -68,6 +68,10 @@ use ensogl::gui::component::Tween;
 const SNAP_DISTANCE_THRESHOLD         : f32 = 10.0;
 const VIZ_PREVIEW_MODE_TOGGLE_TIME_MS : f32 = 300.0;
 
+/// The shortcut context flag set while the node searcher is visible. The typing shortcuts of the
+/// graph editor are disabled then.
+pub const SEARCHER_OPEN_FLAG : &str = "searcher_open";
+
 
 
 #[derive(Clone,CloneRef,Debug,Derivative)]
@@ -310,6 +314,14 @@ ensogl::def_command_api! { Commands
     remove_selected_nodes,
     /// Remove all nodes from the graph.
     remove_all_nodes,
+    /// Arrange the selected nodes using the automatic layout.
+    arrange_selected_nodes,
+    /// Copy the selected nodes to the clipboard.
+    copy_selected_nodes,
+    /// Paste the nodes from the clipboard at the mouse cursor position.
+    paste_nodes,
+    /// Collapse the selected nodes, hiding their visualizations, or expand the collapsed ones.
+    toggle_selected_nodes_collapsed,
     /// Toggle the visibility of the selected visualizations.
     toggle_visualization_visibility,
     /// Simulates a visualization open press event. In case the event will be shortly followed by `release_visualization_visibility`, the visualization will be shown permanently. In other case, it will be disabled as soon as the `release_visualization_visibility` is emitted.
@@ -369,6 +381,10 @@ impl Commands {
             add_node_at_cursor                    <- source();
             remove_selected_nodes                 <- source();
             remove_all_nodes                      <- source();
+            arrange_selected_nodes                <- source();
+            copy_selected_nodes                   <- source();
+            paste_nodes                           <- source();
+            toggle_selected_nodes_collapsed       <- source();
             toggle_visualization_visibility       <- source();
             press_visualization_visibility        <- source();
             double_press_visualization_visibility <- source();
@@ -398,6 +414,7 @@ impl Commands {
             cancel <- source();
         }
         Self {add_node,add_node_at_cursor,remove_selected_nodes,remove_all_nodes
+             ,arrange_selected_nodes,copy_selected_nodes,paste_nodes,toggle_selected_nodes_collapsed
              ,toggle_visualization_visibility,press_visualization_visibility
              ,double_press_visualization_visibility,release_visualization_visibility
              ,enable_node_multi_select,disable_node_multi_select,toggle_node_multi_select
@@ -437,6 +454,9 @@ pub struct FrpInputs {
     pub remove_node                  : frp::Source<NodeId>,
     pub set_node_expression          : frp::Source<(NodeId,node::Expression)>,
     pub set_node_position            : frp::Source<(NodeId,Vector2)>,
+    pub set_node_comment             : frp::Source<(NodeId,String)>,
+    pub set_node_color_tag           : frp::Source<(NodeId,Option<color::Rgba>)>,
+    pub set_node_collapsed           : frp::Source<(NodeId,bool)>,
     pub cycle_visualization          : frp::Source<NodeId>,
     pub set_visualization            : frp::Source<(NodeId,Option<visualization::Path>)>,
     pub register_visualization : frp::Source<Option<visualization::Definition>>,
@@ -444,6 +464,7 @@ pub struct FrpInputs {
 
     hover_node_input           : frp::Source<Option<EdgeTarget>>,
     hover_node_output          : frp::Source<Option<EdgeTarget>>,
+    set_node_preprocessor      : frp::Source<(NodeId,data::EnsoCode)>,
     some_edge_targets_detached : frp::Source,
     some_edge_sources_detached : frp::Source,
     all_edge_targets_attached  : frp::Source,
@@ -473,6 +494,9 @@ impl FrpInputs {
             def remove_node                  = source();
             def set_node_expression          = source();
             def set_node_position            = source();
+            def set_node_comment             = source();
+            def set_node_color_tag           = source();
+            def set_node_collapsed           = source();
             def set_visualization_data       = source();
             def cycle_visualization          = source();
             def set_visualization            = source();
@@ -480,6 +504,7 @@ impl FrpInputs {
 
             def hover_node_input           = source();
             def hover_node_output          = source();
+            def set_node_preprocessor      = source();
             def some_edge_targets_detached = source();
             def some_edge_sources_detached = source();
             def all_edge_targets_attached  = source();
@@ -492,6 +517,7 @@ impl FrpInputs {
              ,set_detached_edge_targets,set_edge_source,set_edge_target
              ,unset_edge_source,unset_edge_target
              ,set_node_position,select_node,remove_node,set_node_expression
+             ,set_node_comment,set_node_color_tag,set_node_collapsed,set_node_preprocessor
              ,connect_nodes,deselect_all_nodes,cycle_visualization,set_visualization
              ,register_visualization,some_edge_targets_detached,some_edge_sources_detached
              ,all_edge_targets_attached,hover_node_input,all_edge_sources_attached
@@ -575,6 +601,10 @@ generate_frp_outputs! {
     node_position_set         : (NodeId,Vector2),
     node_position_set_batched : (NodeId,Vector2),
     node_expression_set       : (NodeId,node::Expression),
+    node_collapsed_set        : (NodeId,bool),
+    nodes_arrangement_requested : Vec<NodeId>,
+    nodes_copy_requested        : Vec<NodeId>,
+    nodes_paste_requested       : Vector2,
 
     edge_added        : EdgeId,
     edge_removed      : EdgeId,
@@ -596,6 +626,7 @@ generate_frp_outputs! {
     visualization_disabled : NodeId,
     visualization_enable_fullscreen : NodeId,
     visualization_set_preprocessor  : (NodeId,data::EnsoCode),
+    visualization_path_set          : (NodeId,Option<visualization::Path>),
 
 }
 
@@ -984,6 +1015,10 @@ impl GraphEditorModelWithNetwork {
              });
 
              eval_ node.view.output_ports.frp.port_mouse_out ( model.frp.hover_node_output.emit(None));
+
+             eval node.visualization.frp.preprocessor ([model](code)
+                model.frp.set_node_preprocessor.emit((node_id,code.clone()))
+             );
         }
 
 //        self.visualizations.push(node.visualization().clone_ref());
@@ -1279,6 +1314,34 @@ impl GraphEditorModel {
 }
 
 
+// === Comments, Colour Tags And Collapsing ===
+
+impl GraphEditorModel {
+    fn set_node_comment(&self, node_id:impl Into<NodeId>, comment:&str) {
+        if let Some(node) = self.nodes.get_cloned_ref(&node_id.into()) {
+            node.frp.set_comment.emit(comment.to_string());
+        }
+    }
+
+    fn set_node_color_tag(&self, node_id:impl Into<NodeId>, color:Option<color::Rgba>) {
+        if let Some(node) = self.nodes.get_cloned_ref(&node_id.into()) {
+            node.frp.set_color_tag.emit(color);
+        }
+    }
+
+    fn set_node_collapsed(&self, node_id:impl Into<NodeId>, collapsed:bool) {
+        if let Some(node) = self.nodes.get_cloned_ref(&node_id.into()) {
+            node.frp.set_collapsed.emit(collapsed);
+        }
+    }
+
+    fn is_node_collapsed(&self, node_id:impl Into<NodeId>) -> bool {
+        let node = self.nodes.get_cloned_ref(&node_id.into());
+        node.map_or(false,|node| node.is_collapsed())
+    }
+}
+
+
 // === Position ===
 
 impl GraphEditorModel {
@@ -1404,8 +1467,20 @@ impl application::command::Provider for GraphEditor {
 impl application::shortcut::DefaultShortcutProvider for GraphEditor {
     fn default_shortcuts() -> Vec<application::shortcut::Shortcut> {
         use keyboard::Key;
-        vec! [ Self::self_shortcut(shortcut::Action::press        (&[Key::Character("n".into())])               , "add_node_at_cursor")
-             , Self::self_shortcut(shortcut::Action::press        (&[Key::Backspace])                           , "remove_selected_nodes")
+        use shortcut::Condition;
+        // Keys used for typing must not trigger graph actions while the user types in any text
+        // field, e.g. the code in the text editor, a node expression in the searcher or a query in
+        // the command palette.
+        let text_editing  = || Condition::flag(shortcut::TEXT_EDITING_FLAG);
+        let searcher_open = || Condition::flag(SEARCHER_OPEN_FLAG);
+        let palette_open  = || Condition::flag(application::command_palette::OPEN_FLAG);
+        let not_typing    = || !text_editing() & !searcher_open() & !palette_open();
+        vec! [ Self::self_shortcut_when(shortcut::Action::press        (&[Key::Character("n".into())])          , "add_node_at_cursor", not_typing())
+             , Self::self_shortcut_when(shortcut::Action::press        (&[Key::Backspace])                      , "remove_selected_nodes", not_typing())
+             , Self::self_shortcut(shortcut::Action::press        (&[Key::Control,Key::Character("l".into())])  , "arrange_selected_nodes")
+             , Self::self_shortcut_when(shortcut::Action::press        (&[Key::Control,Key::Character("c".into())])  , "copy_selected_nodes", not_typing())
+             , Self::self_shortcut_when(shortcut::Action::press        (&[Key::Control,Key::Character("v".into())])  , "paste_nodes", not_typing())
+             , Self::self_shortcut_when(shortcut::Action::press        (&[Key::Character("h".into())])          , "toggle_selected_nodes_collapsed", not_typing())
              , Self::self_shortcut(shortcut::Action::press        (&[Key::Control,Key::Character(" ".into())])  , "press_visualization_visibility")
              , Self::self_shortcut(shortcut::Action::double_press (&[Key::Control,Key::Character(" ".into())])  , "double_press_visualization_visibility")
              , Self::self_shortcut(shortcut::Action::release      (&[Key::Control,Key::Character(" ".into())])  , "release_visualization_visibility")
@@ -1419,8 +1494,8 @@ impl application::shortcut::DefaultShortcutProvider for GraphEditor {
              , Self::self_shortcut(shortcut::Action::release      (&[Key::Alt])                                 , "toggle_node_subtract_select")
              , Self::self_shortcut(shortcut::Action::press        (&[Key::Shift,Key::Alt])                      , "toggle_node_inverse_select")
              , Self::self_shortcut(shortcut::Action::release      (&[Key::Shift,Key::Alt])                      , "toggle_node_inverse_select")
-             , Self::self_shortcut(shortcut::Action::press        (&[Key::Character("d".into())])               , "set_test_visualization_data_for_selected_node")
-             , Self::self_shortcut(shortcut::Action::press        (&[Key::Character("f".into())]) , "cycle_visualization_for_selected_node")
+             , Self::self_shortcut_when(shortcut::Action::press        (&[Key::Character("d".into())])          , "set_test_visualization_data_for_selected_node", not_typing())
+             , Self::self_shortcut_when(shortcut::Action::press        (&[Key::Character("f".into())])          , "cycle_visualization_for_selected_node", not_typing())
              ]
     }
 }
@@ -1551,11 +1626,15 @@ fn new_graph_editor(world:&World) -> GraphEditor {
     // === Cursor Color ===
     frp::extend! { network
 
-    let style = cursor::Style::new_color_no_animation(color::Lcha::new(0.6,0.5,0.76,1.0)).press();
-    cursor_style_source_drag       <- outputs.some_edge_sources_detached.constant(style.clone());
-    cursor_style_target_drag       <- outputs.some_edge_targets_detached.constant(style);
+    // The edge color is read on each drag, as it may be changed by switching the theme.
+    edge_drag_start                <- any_ (outputs.some_edge_sources_detached,
+                                            outputs.some_edge_targets_detached);
+    cursor_style_on_edge_drag      <- edge_drag_start.map(f_!([scene] {
+        let edge_color = component::edge::current_color(&scene);
+        cursor::Style::new_color_no_animation(edge_color).press()
+    }));
     cursor_style_on_edge_drag_stop <- outputs.all_edges_attached.constant(default());
-    cursor_style_edge_drag         <- any (cursor_style_source_drag,cursor_style_target_drag,
+    cursor_style_edge_drag         <- any (cursor_style_on_edge_drag,
                                            cursor_style_on_edge_drag_stop);
 
     }
@@ -1747,12 +1826,41 @@ fn new_graph_editor(world:&World) -> GraphEditor {
     }
 
 
+    // === Arrange Nodes ===
+    frp::extend! { network
+
+    nodes_to_arrange <- inputs.arrange_selected_nodes.map(f_!(model.selected_nodes()));
+    outputs.nodes_arrangement_requested <+ nodes_to_arrange;
+    }
+
+
+    // === Copy And Paste Nodes ===
+    frp::extend! { network
+
+    nodes_to_copy <- inputs.copy_selected_nodes.map(f_!(model.selected_nodes()));
+    outputs.nodes_copy_requested <+ nodes_to_copy;
+    paste_position <- mouse.position.sample(&inputs.paste_nodes);
+    outputs.nodes_paste_requested <+ paste_position;
+    }
+
+
     // === Set Node Expression ===
     frp::extend! { network
 
     outputs.node_expression_set <+ inputs.set_node_expression;
 
 
+    // === Node Comments, Colour Tags And Collapsing ===
+
+    eval inputs.set_node_comment   (((id,comment)) model.set_node_comment(id,comment));
+    eval inputs.set_node_color_tag (((id,color))   model.set_node_color_tag(id,*color));
+
+    nodes_to_toggle <= inputs.toggle_selected_nodes_collapsed.map(f_!(model.selected_nodes()));
+    toggled_nodes   <- nodes_to_toggle.map(f!((id) (*id,!model.is_node_collapsed(id))));
+    outputs.node_collapsed_set <+ inputs.set_node_collapsed;
+    outputs.node_collapsed_set <+ toggled_nodes;
+
+
 
     // ==================
     // === Move Nodes ===
@@ -1951,6 +2059,9 @@ fn new_graph_editor(world:&World) -> GraphEditor {
 //
    // === Vis Set ===
 
+   outputs.visualization_path_set <+ inputs.set_visualization;
+   outputs.visualization_set_preprocessor <+ inputs.set_node_preprocessor;
+
    def _update_vis_data = inputs.set_visualization.map(f!([logger,nodes,scene,visualizations]((node_id,vis_path)) {
        match (&nodes.get_cloned_ref(node_id), vis_path) {
             (Some(node), Some(vis_path)) => {
@@ -1997,21 +2108,27 @@ fn new_graph_editor(world:&World) -> GraphEditor {
      node_to_cycle  <- any(nodes_to_cycle,inputs.cycle_visualization);
 
      let cycle_count = Rc::new(Cell::new(0));
-     def _cycle_visualization = node_to_cycle.map(f!([scene,nodes,visualizations,logger](node_id) {
+     cycled_visualization <- node_to_cycle.map(f!([scene,nodes,visualizations,logger](node_id) {
         let visualizations = visualizations.valid_sources(&"Any".into());
         cycle_count.set(cycle_count.get() % visualizations.len());
         let vis  = &visualizations[cycle_count.get()];
+        let path = vis.signature.path.clone_ref();
         let vis  = vis.new_instance(&scene);
         let node = nodes.get_cloned_ref(node_id);
+        cycle_count.set(cycle_count.get() + 1);
         match (vis, node) {
             (Ok(vis), Some(node))  => {
                 node.visualization.frp.set_visualization.emit(Some(vis));
+                Some((*node_id,Some(path)))
             },
-            (Err(e), _) => logger.warning(|| format!("Failed to cycle visualization: {:?}", e)),
-            _           => {}
-        };
-        cycle_count.set(cycle_count.get() + 1);
-    }));
+            (Err(e), _) => {
+                logger.warning(|| format!("Failed to cycle visualization: {:?}", e));
+                None
+            },
+            _ => None
+        }
+    })).unwrap();
+    outputs.visualization_path_set <+ cycled_visualization;
 
 
     // === Visualization toggle ===
@@ -2077,6 +2194,7 @@ fn new_graph_editor(world:&World) -> GraphEditor {
     eval outputs.edge_removed           ((id) model.remove_edge(id));
     eval outputs.node_removed           ((id) model.remove_node(id));
     eval outputs.node_expression_set    (((id,expr)) model.set_node_expression(id,expr));
+    eval outputs.node_collapsed_set     (((id,value)) model.set_node_collapsed(id,*value));
     eval outputs.visualization_enabled  ((id) model.enable_visualization(id));
     eval outputs.visualization_disabled ((id) model.disable_visualization(id));
     eval outputs.visualization_enable_fullscreen ((id) model.enable_visualization_fullscreen(id));
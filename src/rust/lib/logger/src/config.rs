@@ -0,0 +1,81 @@
+//! The global configuration of logging: the filter and the sinks receiving the entries. By default
+//! all entries are written to the console.
+//!
+//! The configuration is thread-local, like everything running in the browser.
+
+use enso_prelude::*;
+
+use crate::entry::Level;
+use crate::filter::Filter;
+use crate::sink::ConsoleSink;
+use crate::sink::Sink;
+
+
+
+// ==============
+// === Config ===
+// ==============
+
+#[derive(Debug)]
+struct Config {
+    filter : Filter,
+    sinks  : Vec<Rc<dyn Sink>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let filter = default();
+        let sinks  = vec![Rc::new(ConsoleSink) as Rc<dyn Sink>];
+        Self {filter,sinks}
+    }
+}
+
+thread_local! {
+    static CONFIG : RefCell<Config> = default();
+}
+
+
+
+// ==============
+// === Filter ===
+// ==============
+
+/// The filter deciding which entries are logged.
+pub fn filter() -> Filter {
+    CONFIG.with(|config| config.borrow().filter.clone())
+}
+
+/// Replace the filter deciding which entries are logged.
+pub fn set_filter(filter:Filter) {
+    CONFIG.with(|config| config.borrow_mut().filter = filter)
+}
+
+/// Check if the entry with given level logged by the logger with given path passes the filter.
+pub fn is_enabled(path:&str, level:Level) -> bool {
+    CONFIG.with(|config| config.borrow().filter.is_enabled(path,level))
+}
+
+
+
+// =============
+// === Sinks ===
+// =============
+
+/// Register a new sink. The entries are written to all the registered sinks.
+pub fn add_sink(sink:impl Sink + 'static) {
+    CONFIG.with(|config| config.borrow_mut().sinks.push(Rc::new(sink)))
+}
+
+/// Replace all the registered sinks, e.g. to stop writing to the console.
+pub fn set_sinks(sinks:Vec<Rc<dyn Sink>>) {
+    CONFIG.with(|config| config.borrow_mut().sinks = sinks)
+}
+
+/// Call `f` for each of the registered sinks. The configuration is not borrowed during the calls,
+/// so the sinks may log on their own.
+pub fn for_each_sink(f:impl Fn(&dyn Sink)) {
+    let sinks = CONFIG.with(|config| config.borrow().sinks.clone());
+    for sink in sinks {
+        f(&*sink)
+    }
+}
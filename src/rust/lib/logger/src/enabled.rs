@@ -4,14 +4,23 @@ use enso_prelude::*;
 
 use crate::AnyLogger;
 use crate::Message;
+use crate::config;
+use crate::entry;
+use crate::entry::Entry;
+use crate::entry::Level;
+use crate::history;
 
 use shapely::CloneRef;
 use std::fmt::Debug;
 
-#[cfg(target_arch = "wasm32")]
-use web_sys::console;
-#[cfg(target_arch = "wasm32")]
-use wasm_bindgen::JsValue;
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Level of the messages beginning groups.
+pub const GROUP_LEVEL:Level = Level::Debug;
 
 
 
@@ -19,72 +28,80 @@ use wasm_bindgen::JsValue;
 // === Logger ===
 // ==============
 
-/// Default Logger implementation.
+/// Default Logger implementation. Passes the messages to the sinks registered in `config`, if they
+/// pass its filter, and to the `history`, if it keeps their level. The messages are formatted only
+/// if passed anywhere.
 #[derive(Clone,CloneRef,Debug,Default)]
 pub struct Logger {
     /// Path that is used as an unique identifier of this logger.
-    path : ImString,
-    #[cfg(not(target_arch="wasm32"))]
-    indent : Rc<Cell<usize>>,
+    path   : ImString,
+    /// Whether each of the currently open groups passed the filter.
+    groups : Rc<RefCell<Vec<bool>>>,
 }
 
-#[cfg(not(target_arch="wasm32"))]
 impl Logger {
-    fn format(&self, msg:impl Message) -> String {
-        let indent = " ".repeat(4*self.indent.get());
-        msg.with(|s|iformat!("{indent}[{self.path}] {s}"))
+    fn entry(&self, level:Level, msg:impl Message) -> Entry {
+        let timestamp = entry::now();
+        let path      = self.path.clone_ref();
+        let message   = msg.with(|s| s.to_string());
+        let fields    = msg.fields();
+        let depth     = self.groups.borrow().len();
+        Entry {timestamp,level,path,message,fields,depth}
     }
 
-    fn inc_indent(&self) {
-        self.indent.update(|t|t+1);
+    /// The number of the currently open groups which passed the filter.
+    fn shown_depth(&self) -> usize {
+        self.groups.borrow().iter().filter(|enabled| **enabled).count()
     }
 
-    fn dec_indent(&self) {
-        self.indent.update(|t|t-1);
+    fn log(&self, level:Level, msg:impl Message) {
+        let shown = config::is_enabled(&self.path,level);
+        let kept  = history::keeps(level);
+        if shown || kept {
+            let mut entry = self.entry(level,msg);
+            history::record(&entry);
+            if shown {
+                entry.depth = self.shown_depth();
+                config::for_each_sink(|sink| sink.write(&entry));
+            }
+        }
     }
 }
 
-#[cfg(target_arch="wasm32")]
-impl Logger {
-    fn format(&self, msg:impl Message) -> JsValue {
-        msg.with(|s|iformat!("[{self.path}] {s}")).into()
-    }
-}
-
-#[cfg(not(target_arch="wasm32"))]
 impl AnyLogger for Logger {
     type Owned = Self;
     fn new(path:impl Into<ImString>) -> Self {
         let path   = path.into();
-        let indent = default();
-        Self {path,indent}
+        let groups = default();
+        Self {path,groups}
+    }
+    fn path    (&self) -> &str { &self.path }
+    fn trace   (&self, msg:impl Message) { self.log(Level::Trace   ,msg) }
+    fn debug   (&self, msg:impl Message) { self.log(Level::Debug   ,msg) }
+    fn info    (&self, msg:impl Message) { self.log(Level::Info    ,msg) }
+    fn warning (&self, msg:impl Message) { self.log(Level::Warning ,msg) }
+    fn error   (&self, msg:impl Message) { self.log(Level::Error   ,msg) }
+
+    fn group_begin(&self, msg:impl Message) {
+        let enabled = config::is_enabled(&self.path,GROUP_LEVEL);
+        let kept    = history::keeps(GROUP_LEVEL);
+        if enabled || kept {
+            let mut entry = self.entry(GROUP_LEVEL,msg);
+            history::record(&entry);
+            if enabled {
+                entry.depth = self.shown_depth();
+                config::for_each_sink(|sink| sink.group_begin(&entry));
+            }
+        }
+        self.groups.borrow_mut().push(enabled);
     }
-    fn path        (&self) -> &str { &self.path }
-    fn trace       (&self, msg:impl Message) { println!("{}",self.format(msg)) }
-    fn debug       (&self, msg:impl Message) { println!("{}",self.format(msg)) }
-    fn info        (&self, msg:impl Message) { println!("{}",self.format(msg)) }
-    fn warning     (&self, msg:impl Message) { println!("[WARNING] {}",self.format(msg)) }
-    fn error       (&self, msg:impl Message) { println!("[ERROR] {}",self.format(msg)) }
-    fn group_begin (&self, msg:impl Message) { println!("{}",self.format(msg)); self.inc_indent() }
-    fn group_end   (&self)                  { self.dec_indent() }
-}
-
 
-#[cfg(target_arch="wasm32")]
-impl AnyLogger for Logger {
-    type Owned = Self;
-    fn new(path:impl Into<ImString>) -> Self {
-        let path = path.into();
-        Self {path}
+    fn group_end(&self) {
+        let enabled = self.groups.borrow_mut().pop();
+        if enabled == Some(true) {
+            config::for_each_sink(|sink| sink.group_end());
+        }
     }
-    fn path        (&self) -> &str { &self.path }
-    fn trace       (&self, msg:impl Message) { console::trace_1 (&self.format(msg)) }
-    fn debug       (&self, msg:impl Message) { console::debug_1 (&self.format(msg)) }
-    fn info        (&self, msg:impl Message) { console::info_1  (&self.format(msg)) }
-    fn warning     (&self, msg:impl Message) { console::warn_1  (&self.format(msg)) }
-    fn error       (&self, msg:impl Message) { console::error_1 (&self.format(msg)) }
-    fn group_begin (&self, msg:impl Message) { console::group_1 (&self.format(msg)) }
-    fn group_end   (&self)                  { console::group_end() }
 }
 
 
@@ -94,3 +111,51 @@ impl AnyLogger for Logger {
 // ===================
 
 impls!{ From + &From <crate::disabled::Logger> for Logger { |logger| Self::new(logger.path()) }}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::filter::Filter;
+    use crate::sink::MemorySink;
+
+    #[test]
+    fn filtering_and_routing() {
+        let sink = MemorySink::new(10);
+        config::set_sinks(default());
+        config::add_sink(sink.clone_ref());
+        config::set_filter("info,IDE.Project=debug".parse::<Filter>().unwrap());
+        history::enable(10,Level::Trace);
+        let ide     = Logger::new("IDE");
+        let project = Logger::sub(&ide,"Project");
+        let path    = "Main.enso";
+        crate::debug!(ide,"Hidden.");
+        crate::debug!(project,"Opening module."; path=path, attempt=1);
+        crate::group!(ide,"Hidden group.", {
+            crate::info!(ide,"Shown.");
+        });
+
+        let entries = sink.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].level, Level::Debug);
+        assert_eq!(&*entries[0].path, "IDE.Project");
+        assert_eq!(entries[0].message, "Opening module.");
+        let fields = entries[0].fields.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(fields, vec!["path=Main.enso","attempt=1"]);
+        assert_eq!(entries[1].message, "Shown.");
+        assert_eq!(entries[1].depth, 0);
+
+        // The history keeps the hidden entries, with the depth counting the hidden groups.
+        let kept = history::entries();
+        assert_eq!(kept.len(), 4);
+        assert_eq!(kept[3].message, "Shown.");
+        assert_eq!(kept[3].depth, 1);
+        history::disable();
+    }
+}
@@ -0,0 +1,201 @@
+//! Log entries passed to the sinks, together with their levels and structured fields.
+
+use enso_prelude::*;
+
+use std::str::FromStr;
+
+
+
+// =============
+// === Level ===
+// =============
+
+/// Verbosity level of the log entry, from the most verbose one.
+#[derive(Clone,Copy,Debug,Eq,Hash,Ord,PartialEq,PartialOrd)]
+#[allow(missing_docs)]
+pub enum Level {Trace,Debug,Info,Warning,Error}
+
+impl Level {
+    /// All levels, from the most verbose one.
+    pub const ALL : [Level;5] = [Level::Trace,Level::Debug,Level::Info,Level::Warning,Level::Error];
+
+    /// The lowercase name of the level, as used in filter directives.
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Trace   => "trace",
+            Level::Debug   => "debug",
+            Level::Info    => "info",
+            Level::Warning => "warning",
+            Level::Error   => "error",
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.name().to_uppercase())
+    }
+}
+
+impl FromStr for Level {
+    type Err = ParseError;
+    fn from_str(s:&str) -> Result<Self,Self::Err> {
+        let name = s.trim().to_lowercase();
+        match name.as_str() {
+            "warn" => Ok(Level::Warning),
+            _      => Self::ALL.iter().find(|level| level.name() == name).copied().ok_or_else(||
+                ParseError::new(iformat!("Unknown log level `{s}`.")))
+        }
+    }
+}
+
+
+// === ParseError ===
+
+/// Error of parsing a level or a filter.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct ParseError {
+    /// Description of the problem.
+    pub message : String,
+}
+
+impl ParseError {
+    /// Constructor.
+    pub fn new(message:impl Into<String>) -> Self {
+        let message = message.into();
+        Self {message}
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+
+
+// =============
+// === Field ===
+// =============
+
+/// A key/value pair attached to the log message, e.g. the path of the processed module.
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
+#[allow(missing_docs)]
+pub struct Field {
+    pub key   : String,
+    pub value : String,
+}
+
+impl Field {
+    /// Constructor.
+    pub fn new(key:impl Into<String>, value:impl Display) -> Self {
+        let key   = key.into();
+        let value = value.to_string();
+        Self {key,value}
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}={}",self.key,self.value)
+    }
+}
+
+
+
+// =============
+// === Entry ===
+// =============
+
+/// A single logged message.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Entry {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp : f64,
+    #[allow(missing_docs)]
+    pub level     : Level,
+    /// Path of the logger which logged the message.
+    pub path      : ImString,
+    #[allow(missing_docs)]
+    pub message   : String,
+    #[allow(missing_docs)]
+    pub fields    : Vec<Field>,
+    /// Number of the groups opened by the logger when logging the message. The entries kept in
+    /// the `history` count all of them, while the ones written to sinks count only the shown ones.
+    pub depth     : usize,
+}
+
+impl Display for Entry {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        let time  = format_time(self.timestamp);
+        let level = self.level.to_string();
+        write!(f,"{} {:7} [{}] {}",time,level,self.path,self.message)?;
+        for field in &self.fields {
+            write!(f," {}",field)?;
+        }
+        Ok(())
+    }
+}
+
+
+
+// ============
+// === Time ===
+// ============
+
+/// Milliseconds since the Unix epoch.
+#[cfg(target_arch="wasm32")]
+pub fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Milliseconds since the Unix epoch.
+#[cfg(not(target_arch="wasm32"))]
+pub fn now() -> f64 {
+    let since_epoch = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH);
+    since_epoch.map(|duration| duration.as_secs_f64() * 1000.0).unwrap_or_default()
+}
+
+/// Formats the timestamp as UTC time of the day, e.g. `13:05:42.017`.
+pub fn format_time(timestamp:f64) -> String {
+    let millis  = timestamp as u64;
+    let seconds = millis / 1000;
+    let minutes = seconds / 60;
+    let hours   = minutes / 60 % 24;
+    format!("{:02}:{:02}:{:02}.{:03}",hours,minutes % 60,seconds % 60,millis % 1000)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_levels() {
+        assert_eq!("debug".parse(), Ok(Level::Debug));
+        assert_eq!(" WARN ".parse(), Ok(Level::Warning));
+        assert!("verbose".parse::<Level>().is_err());
+    }
+
+    #[test]
+    fn formatting_entries() {
+        let entry = Entry {
+            timestamp : 47_142_017.0,
+            level     : Level::Info,
+            path      : "IDE.Project".into(),
+            message   : "Module opened.".into(),
+            fields    : vec![Field::new("path","Main.enso"),Field::new("size",12)],
+            depth     : 0,
+        };
+        let expected = "13:05:42.017 INFO    [IDE.Project] Module opened. path=Main.enso size=12";
+        assert_eq!(entry.to_string(), expected);
+    }
+}
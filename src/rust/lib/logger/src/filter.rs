@@ -0,0 +1,118 @@
+//! Runtime filtering of the log entries by their level and the path of their logger.
+
+use enso_prelude::*;
+
+use crate::entry::Level;
+use crate::entry::ParseError;
+
+use std::str::FromStr;
+
+
+
+// ==============
+// === Filter ===
+// ==============
+
+/// Decides which entries are logged. Each logger path gets the minimum level of the most specific
+/// directive with matching path prefix, or the default level if there is none.
+///
+/// The prefixes match whole path segments, so `IDE.Project` matches `IDE.Project.Module` but not
+/// `IDE.ProjectView`.
+///
+/// Filters may be parsed from comma-separated directives, where a bare level sets the default
+/// one, e.g. `warning,IDE.Project=info,IDE.Project.Module=debug`.
+#[derive(Clone,Debug,Eq,PartialEq)]
+pub struct Filter {
+    default    : Level,
+    directives : Vec<(String,Level)>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::new(Level::Trace)
+    }
+}
+
+impl Filter {
+    /// Create a filter passing all entries with at least the given level.
+    pub fn new(default:Level) -> Self {
+        let directives = default();
+        Self {default,directives}
+    }
+
+    /// Set the minimum level of the entries logged by loggers with given path prefix.
+    pub fn set_level(&mut self, prefix:impl Into<String>, level:Level) {
+        let prefix = prefix.into();
+        self.directives.retain(|(other,_)| *other != prefix);
+        self.directives.push((prefix,level));
+    }
+
+    /// Builder-style version of `set_level`.
+    pub fn with_level(mut self, prefix:impl Into<String>, level:Level) -> Self {
+        self.set_level(prefix,level);
+        self
+    }
+
+    /// The minimum level of the entries logged by the logger with given path.
+    pub fn level(&self, path:&str) -> Level {
+        let matching = self.directives.iter().filter(|(prefix,_)| is_path_prefix(prefix,path));
+        let specific = matching.max_by_key(|(prefix,_)| prefix.len());
+        specific.map(|(_,level)| *level).unwrap_or(self.default)
+    }
+
+    /// Check if the entry with given level logged by the logger with given path passes the filter.
+    pub fn is_enabled(&self, path:&str, level:Level) -> bool {
+        level >= self.level(path)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ParseError;
+    fn from_str(s:&str) -> Result<Self,Self::Err> {
+        let mut filter = Self::default();
+        for directive in s.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+            let mut parts = directive.splitn(2,'=');
+            let first     = parts.next().unwrap_or_default();
+            match parts.next() {
+                Some(level) => filter.set_level(first.trim(),level.parse()?),
+                None        => filter.default = first.parse()?,
+            }
+        }
+        Ok(filter)
+    }
+}
+
+fn is_path_prefix(prefix:&str, path:&str) -> bool {
+    let is_parent = path.starts_with(prefix) && path[prefix.len()..].starts_with('.');
+    prefix.is_empty() || path == prefix || is_parent
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_paths() {
+        let filter:Filter = "warning, IDE.Project=info, IDE.Project.Module=debug".parse().unwrap();
+        assert_eq!(filter.level("IDE")                     , Level::Warning);
+        assert_eq!(filter.level("IDE.ProjectView")         , Level::Warning);
+        assert_eq!(filter.level("IDE.Project")             , Level::Info);
+        assert_eq!(filter.level("IDE.Project.Text")        , Level::Info);
+        assert_eq!(filter.level("IDE.Project.Module.Main") , Level::Debug);
+        assert!( filter.is_enabled("IDE.Project",Level::Error));
+        assert!(!filter.is_enabled("IDE.Project",Level::Debug));
+    }
+
+    #[test]
+    fn parsing_invalid_filters() {
+        assert!("IDE=loud".parse::<Filter>().is_err());
+        assert!("IDE".parse::<Filter>().is_err());
+        assert_eq!("".parse::<Filter>(), Ok(Filter::default()));
+    }
+}
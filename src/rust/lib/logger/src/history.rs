@@ -0,0 +1,264 @@
+//! The history of the recently logged entries, kept for crash reports.
+//!
+//! Once enabled, the last entries of all loggers up to the given level of verbosity are kept in a
+//! ring buffer, no matter if they passed the filter set in `config`. The more verbose messages are
+//! not even formatted. The history may be dumped as text or JSON, together with the hierarchy of
+//! the loggers, e.g. from the panic hook installed by `set_panic_hook`.
+
+use enso_prelude::*;
+
+use crate::entry::Entry;
+use crate::entry::Level;
+use crate::entry::format_time;
+use crate::sink::MemorySink;
+use crate::sink::Sink;
+
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[cfg(target_arch = "wasm32")]
+use web_sys::console;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The number of entries kept in the history by default.
+pub const DEFAULT_CAPACITY:usize = 1000;
+
+/// The most verbose level of the entries kept in the history by default, i.e. all of them are kept.
+pub const DEFAULT_LEVEL:Level = Level::Trace;
+
+
+
+// ===============
+// === History ===
+// ===============
+
+/// The kept entries, together with the most verbose level of them.
+#[derive(Clone,Debug)]
+struct History {
+    sink  : MemorySink,
+    level : Level,
+}
+
+thread_local! {
+    static HISTORY : RefCell<Option<History>> = default();
+}
+
+/// Start keeping the last `capacity` entries of `level` or less verbose. The entries kept so far
+/// are dropped.
+pub fn enable(capacity:usize, level:Level) {
+    let sink = MemorySink::new(capacity);
+    HISTORY.with(|history| *history.borrow_mut() = Some(History {sink,level}))
+}
+
+/// Stop keeping the entries, dropping the ones kept so far.
+pub fn disable() {
+    HISTORY.with(|history| *history.borrow_mut() = None)
+}
+
+/// Check if the history is kept.
+pub fn is_enabled() -> bool {
+    HISTORY.with(|history| history.borrow().is_some())
+}
+
+/// Check if the entries of the given level are kept. Loggers should format their messages only if
+/// they are kept or shown.
+pub fn keeps(level:Level) -> bool {
+    history().map_or(false, |history| level >= history.level)
+}
+
+/// Put the entry in the history, if it keeps the entries of its level.
+pub fn record(entry:&Entry) {
+    if let Some(history) = history().filter(|history| entry.level >= history.level) {
+        history.sink.write(entry)
+    }
+}
+
+/// The kept entries, from the oldest one.
+pub fn entries() -> Vec<Entry> {
+    sink().map(|history| history.entries()).unwrap_or_default()
+}
+
+/// Drop the entries kept so far.
+pub fn clear() {
+    if let Some(history) = sink() {
+        history.clear()
+    }
+}
+
+/// The sink keeping the history.
+fn sink() -> Option<MemorySink> {
+    history().map(|history| history.sink)
+}
+
+/// The kept history. The access does not fail even when panicking in the middle of changing the
+/// configuration.
+fn history() -> Option<History> {
+    let history = HISTORY.try_with(|history| history.try_borrow().ok().and_then(|h| h.clone()));
+    history.ok().flatten()
+}
+
+
+
+// ============
+// === Dump ===
+// ============
+
+/// The kept entries as text, one per line, preceded by the hierarchy of their loggers.
+pub fn dump_text() -> String {
+    let entries = entries();
+    let mut out = String::new();
+    out.push_str("=== Loggers ===\n");
+    LoggerTree::new(&entries).write_text(&mut out,0);
+    out.push_str(&format!("=== Last {} log entries ===\n",entries.len()));
+    for entry in &entries {
+        out.push_str(&format!("{}{}\n"," ".repeat(4*entry.depth),entry));
+    }
+    out
+}
+
+/// The kept entries as JSON object with `loggers` hierarchy and the list of `entries`.
+pub fn dump_json() -> String {
+    let entries = entries();
+    let loggers = LoggerTree::new(&entries).to_json();
+    let entries = entries.iter().map(|entry| {
+        let fields = entry.fields.iter().map(|field| (field.key.clone(),json!(field.value)));
+        json!({
+            "timestamp" : entry.timestamp,
+            "time"      : format_time(entry.timestamp),
+            "level"     : entry.level.name(),
+            "path"      : &*entry.path,
+            "message"   : entry.message,
+            "fields"    : fields.collect::<serde_json::Map<_,_>>(),
+            "depth"     : entry.depth,
+        })
+    }).collect_vec();
+    json!({"loggers":loggers, "entries":entries}).to_string()
+}
+
+/// Write the text to the browser console as an error, or to the standard error natively. It
+/// bypasses the sinks, so the text does not get to the history.
+pub fn report(text:&str) {
+    #[cfg(target_arch = "wasm32")]
+    console::error_1(&text.into());
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}",text);
+}
+
+/// Install the panic hook reporting the `dump_text` after calling the previously installed hook.
+pub fn set_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        if is_enabled() {
+            report(&dump_text());
+        }
+    }));
+}
+
+
+
+// ==================
+// === LoggerTree ===
+// ==================
+
+/// Hierarchy of the loggers, built from their dot-separated paths.
+#[derive(Clone,Debug,Default)]
+struct LoggerTree {
+    children : BTreeMap<String,LoggerTree>,
+}
+
+impl LoggerTree {
+    fn new(entries:&[Entry]) -> Self {
+        let mut tree = Self::default();
+        for entry in entries {
+            let segments = entry.path.split('.').filter(|segment| !segment.is_empty());
+            segments.fold(&mut tree, |node,segment| {
+                node.children.entry(segment.to_string()).or_default()
+            });
+        }
+        tree
+    }
+
+    fn write_text(&self, out:&mut String, depth:usize) {
+        for (name,child) in &self.children {
+            out.push_str(&format!("{}{}\n"," ".repeat(4*depth),name));
+            child.write_text(out,depth+1);
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let children = self.children.iter().map(|(name,child)| (name.clone(),child.to_json()));
+        serde_json::Value::Object(children.collect())
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::AnyLogger;
+    use crate::config;
+    use crate::enabled::Logger;
+    use crate::filter::Filter;
+
+    #[test]
+    fn keeping_filtered_entries() {
+        config::set_sinks(default());
+        config::set_filter(Filter::new(Level::Error));
+        enable(2,Level::Trace);
+        let ide     = Logger::new("IDE");
+        let project = Logger::sub(&ide,"Project");
+        crate::info!(ide,"Evicted.");
+        crate::debug!(project,"Kept."; id=1);
+        crate::warning!(ide,"Kept too.");
+
+        let kept = entries();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].message, "Kept.");
+        assert_eq!(kept[1].message, "Kept too.");
+
+        let text   = dump_text();
+        let header = "=== Loggers ===\nIDE\n    Project\n=== Last 2 log entries ===\n";
+        assert!(text.starts_with(header));
+        assert!(text.contains("DEBUG   [IDE.Project] Kept. id=1\n"));
+
+        let json:serde_json::Value = serde_json::from_str(&dump_json()).unwrap();
+        assert_eq!(json["loggers"], json!({"IDE":{"Project":{}}}));
+        assert_eq!(json["entries"][0]["level"], "debug");
+        assert_eq!(json["entries"][0]["fields"], json!({"id":"1"}));
+
+        disable();
+        crate::error!(ide,"Not kept.");
+        assert!(entries().is_empty());
+    }
+
+    #[test]
+    fn not_formatting_skipped_entries() {
+        config::set_sinks(default());
+        config::set_filter(Filter::new(Level::Error));
+        enable(10,Level::Info);
+        let ide       = Logger::new("IDE");
+        let formatted = Cell::new(0);
+        let message   = || {
+            formatted.set(formatted.get() + 1);
+            "Message."
+        };
+        ide.debug(message);
+        assert_eq!(formatted.get(), 0);
+        ide.info(message);
+        assert_eq!(formatted.get(), 1);
+        assert_eq!(entries().len(), 1);
+        disable();
+    }
+}
@@ -1,4 +1,11 @@
 //! This crate contains implementation of logging interface.
+//!
+//! The messages are logged on one of the `entry::Level`s, optionally with structured key/value
+//! fields, e.g. `debug!(logger,"Module opened."; path=path)`. Which of them are logged is decided
+//! at runtime by the `filter::Filter` set in `config`, by the level and the path of the logger. The
+//! logged entries are passed to the `sink::Sink`s registered in `config`. Independently of the
+//! filter, the recent entries may be kept in the `history`, to be dumped when the application
+//! crashes.
 
 #![feature(cell_update)]
 
@@ -11,11 +18,18 @@
 #![warn(unsafe_code)]
 #![warn(unused_import_braces)]
 
+pub mod config;
 pub mod disabled;
 pub mod enabled;
+pub mod entry;
+pub mod filter;
+pub mod history;
+pub mod sink;
 
 use enso_prelude::*;
 
+use crate::entry::Field;
+
 
 
 // ==============
@@ -26,6 +40,11 @@ use enso_prelude::*;
 pub trait Message {
     /// Turns message into `&str` and passes it to input function.
     fn with<T,F:FnOnce(&str)->T>(&self, f:F) -> T;
+
+    /// Structured fields attached to the message.
+    fn fields(&self) -> Vec<Field> {
+        default()
+    }
 }
 
 impl Message for &str {
@@ -41,6 +60,28 @@ impl<G:Fn()->S, S:AsRef<str>> Message for G {
 }
 
 
+// === WithFields ===
+
+/// Message with structured fields. Both are computed only if the message is logged.
+#[derive(Clone,Copy,Debug)]
+pub struct WithFields<M,F> {
+    /// The message text.
+    pub message : M,
+    /// Function returning the fields.
+    pub fields  : F,
+}
+
+impl<M:Message, F:Fn()->Vec<Field>> Message for WithFields<M,F> {
+    fn with<T,G:FnOnce(&str)->T>(&self, f:G) -> T {
+        self.message.with(f)
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        (self.fields)()
+    }
+}
+
+
 
 // =================
 // === AnyLogger ===
@@ -137,9 +178,16 @@ macro_rules! group {
     }};
 }
 
-/// Logs a message on on given level.
+/// Logs a message on on given level. The message may be followed by the structured fields, e.g.
+/// `info!(logger,"Module opened."; path=path, size=size)`.
 #[macro_export]
 macro_rules! log_template {
+    ($method:ident $logger:expr, $message:tt; $($key:ident = $value:expr),+ $(,)?) => {{
+        $logger.$method($crate::WithFields {
+            message : || iformat!($message),
+            fields  : || vec![$($crate::entry::Field::new(stringify!($key),&$value)),+],
+        });
+    }};
     ($method:ident $logger:expr, $message:tt $($rest:tt)*) => {
         $crate::log_template_impl! {$method $logger, iformat!($message) $($rest)*}
     };
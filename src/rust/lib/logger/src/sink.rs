@@ -0,0 +1,167 @@
+//! Sinks receiving the log entries which passed the filter.
+
+use enso_prelude::*;
+
+use crate::entry::Entry;
+#[cfg(target_arch="wasm32")]
+use crate::entry::Level;
+
+use shapely::CloneRef;
+use std::collections::VecDeque;
+
+#[cfg(target_arch = "wasm32")]
+use web_sys::console;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsValue;
+
+
+
+// ============
+// === Sink ===
+// ============
+
+/// Destination of the log entries.
+pub trait Sink : Debug {
+    /// Write a single entry.
+    fn write(&self, entry:&Entry);
+
+    /// Write the entry beginning a group. The entries logged until the matching `group_end` have
+    /// greater `depth`.
+    fn group_begin(&self, entry:&Entry) {
+        self.write(entry)
+    }
+
+    /// End the most recently begun group.
+    fn group_end(&self) {}
+}
+
+
+
+// ===================
+// === ConsoleSink ===
+// ===================
+
+/// Writes the entries to the standard output, or to the browser console on wasm.
+#[derive(Clone,Copy,Debug,Default)]
+pub struct ConsoleSink;
+
+#[cfg(not(target_arch="wasm32"))]
+impl Sink for ConsoleSink {
+    fn write(&self, entry:&Entry) {
+        println!("{}{}",indent(entry.depth),entry)
+    }
+}
+
+#[cfg(target_arch="wasm32")]
+impl Sink for ConsoleSink {
+    fn write(&self, entry:&Entry) {
+        let text = JsValue::from(entry.to_string());
+        match entry.level {
+            Level::Trace   => console::trace_1 (&text),
+            Level::Debug   => console::debug_1 (&text),
+            Level::Info    => console::info_1  (&text),
+            Level::Warning => console::warn_1  (&text),
+            Level::Error   => console::error_1 (&text),
+        }
+    }
+
+    fn group_begin(&self, entry:&Entry) {
+        console::group_1(&entry.to_string().into())
+    }
+
+    fn group_end(&self) {
+        console::group_end()
+    }
+}
+
+
+
+// ==================
+// === MemorySink ===
+// ==================
+
+/// Keeps the most recent entries in memory, dropping the oldest ones when full. It is a handle,
+/// so it may be registered in the configuration and still read.
+#[derive(Clone,CloneRef,Debug)]
+pub struct MemorySink {
+    entries  : Rc<RefCell<VecDeque<Entry>>>,
+    capacity : usize,
+}
+
+impl MemorySink {
+    /// Create a sink keeping at most `capacity` entries.
+    pub fn new(capacity:usize) -> Self {
+        let entries = Rc::new(RefCell::new(VecDeque::with_capacity(capacity)));
+        Self {entries,capacity}
+    }
+
+    /// The maximum number of entries kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The kept entries, from the oldest one.
+    pub fn entries(&self) -> Vec<Entry> {
+        self.entries.borrow().iter().cloned().collect()
+    }
+
+    /// Drop all kept entries.
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear()
+    }
+}
+
+impl Sink for MemorySink {
+    fn write(&self, entry:&Entry) {
+        let mut entries = self.entries.borrow_mut();
+        if self.capacity > 0 {
+            if entries.len() == self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(entry.clone());
+        }
+    }
+}
+
+
+
+// ================
+// === FileSink ===
+// ================
+
+/// Appends the entries to a file, one per line.
+#[cfg(not(target_arch="wasm32"))]
+#[derive(Debug)]
+pub struct FileSink {
+    file : RefCell<std::fs::File>,
+}
+
+#[cfg(not(target_arch="wasm32"))]
+impl FileSink {
+    /// Open the file for appending, creating it if it does not exist.
+    pub fn new(path:impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let file = RefCell::new(file);
+        Ok(Self {file})
+    }
+}
+
+#[cfg(not(target_arch="wasm32"))]
+impl Sink for FileSink {
+    fn write(&self, entry:&Entry) {
+        use std::io::Write;
+        // There is no better place to report the failure of logging.
+        writeln!(self.file.borrow_mut(),"{}{}",indent(entry.depth),entry).ok();
+    }
+}
+
+
+
+// =============
+// === Utils ===
+// =============
+
+#[cfg(not(target_arch="wasm32"))]
+fn indent(depth:usize) -> String {
+    " ".repeat(4*depth)
+}
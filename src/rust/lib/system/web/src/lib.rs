@@ -33,6 +33,7 @@ pub use web_sys::HtmlElement;
 pub use web_sys::MouseEvent;
 pub use web_sys::Node;
 pub use web_sys::Performance;
+pub use web_sys::Storage;
 pub use web_sys::WebGl2RenderingContext;
 pub use web_sys::Window;
 pub use std::time::Duration;
@@ -130,6 +131,12 @@ pub fn window() -> Window {
     try_window().unwrap()
 }
 
+/// Access the `window.localStorage` object if exists.
+pub fn try_local_storage() -> Result<Storage> {
+    let error = || Error("Cannot access 'window.localStorage'.");
+    try_window().and_then(|w| w.local_storage().ok().flatten().ok_or_else(error))
+}
+
 /// Access the `window.document` object if exists.
 pub fn try_document() -> Result<Document> {
     try_window().and_then(|w| w.document().ok_or_else(|| Error("Cannot access 'window.document'.")))
@@ -222,6 +229,32 @@ pub fn cancel_animation_frame(id:i32) {
     window().cancel_animation_frame(id).unwrap();
 }
 
+/// Handle returned from `try_set_timeout`. The timeout is cancelled when the handle is dropped.
+#[derive(Debug)]
+pub struct TimeoutHandle {
+    id       : i32,
+    _closure : Closure<dyn FnMut()>
+}
+
+impl Drop for TimeoutHandle {
+    fn drop(&mut self) {
+        if let Ok(window) = try_window() {
+            window.clear_timeout_with_handle(self.id);
+        }
+    }
+}
+
+/// Calls the function after the given number of milliseconds, unless the returned handle is
+/// dropped earlier.
+pub fn try_set_timeout<F:FnMut()+'static>(f:F, timeout_ms:i32) -> Result<TimeoutHandle> {
+    let closure  = Closure::wrap(Box::new(f) as Box<dyn FnMut()>);
+    let callback : &Function = closure.as_ref().unchecked_ref();
+    let id       = try_window()?
+        .set_timeout_with_callback_and_timeout_and_arguments_0(callback,timeout_ms)
+        .map_err(|_| Error("Cannot access 'setTimeout'."))?;
+    Ok(TimeoutHandle {id,_closure:closure})
+}
+
 
 
 // =====================